@@ -4,6 +4,9 @@
 //!   cargo run --bin gen_license -- --hours 24
 //!   cargo run --bin gen_license -- --until "2025-02-03 00:00:00"
 //!   cargo run --bin gen_license -- --hours 24 --out license.key
+//!   cargo run --bin gen_license -- --hours 24 --grace-hours 12
+//!   cargo run --bin gen_license -- --hours 24 --machine-id <用户提供的机器标识>
+//!   cargo run --bin gen_license -- --print-machine-id
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
@@ -17,6 +20,8 @@ fn main() -> Result<()> {
     let mut hours: Option<u64> = None;
     let mut until: Option<String> = None;
     let mut out_path: Option<PathBuf> = None;
+    let mut machine_id: Option<String> = None;
+    let mut grace_hours: u64 = 0;
 
     let mut i = 1;
     while i < args.len() {
@@ -40,6 +45,28 @@ fn main() -> Result<()> {
                 );
                 i += 1;
             }
+            "--grace-hours" => {
+                i += 1;
+                grace_hours = args
+                    .get(i)
+                    .context("--grace-hours 需要参数")?
+                    .parse()
+                    .context("--grace-hours 必须为非负整数")?;
+                i += 1;
+            }
+            "--machine-id" => {
+                i += 1;
+                machine_id = Some(
+                    args.get(i)
+                        .context("--machine-id 需要参数")?
+                        .clone(),
+                );
+                i += 1;
+            }
+            "--print-machine-id" => {
+                println!("{}", poly_5min_bot::trial::local_machine_id()?);
+                return Ok(());
+            }
             "--out" => {
                 i += 1;
                 out_path = Some(
@@ -50,9 +77,12 @@ fn main() -> Result<()> {
                 i += 1;
             }
             _ => {
-                eprintln!("用法: gen_license --hours <N> | --until \"<datetime>\" [--out license.key]");
+                eprintln!("用法: gen_license --hours <N> | --until \"<datetime>\" [--grace-hours N] [--machine-id ID] [--out license.key]");
                 eprintln!("  --hours N    从当前起 N 小时后过期");
                 eprintln!("  --until \"...\" 指定过期时间（UTC），格式如 2025-02-03 00:00:00");
+                eprintln!("  --grace-hours N  过期后宽限 N 小时（继续运行并持续告警），默认 0");
+                eprintln!("  --machine-id ID  绑定到指定机器标识（目标机器运行 --print-machine-id 获取），不指定则不绑定");
+                eprintln!("  --print-machine-id  输出本机标识后退出");
                 eprintln!("  --out FILE   写入文件，不指定则输出到 stdout");
                 std::process::exit(1);
             }
@@ -78,7 +108,7 @@ fn main() -> Result<()> {
         anyhow::bail!("请指定 --hours <N> 或 --until \"<datetime>\"");
     };
 
-    let license = poly_5min_bot::trial::create_license(expiry_secs)?;
+    let license = poly_5min_bot::trial::create_license(expiry_secs, grace_hours * 3600, machine_id.as_deref())?;
 
     if let Some(path) = out_path {
         fs::write(&path, &license).context("写入许可证文件失败")?;
@@ -0,0 +1,87 @@
+//! 时钟抽象：窗口边界、收尾触发与定时任务经 Clock 取当前时间与等待，便于以 MockClock 驱动时间逻辑，无需真实等待。
+//! 生产环境使用 SystemClock（Utc::now + tokio::time::sleep），行为与直接调用一致。
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 当前时间与等待的来源
+pub trait Clock: Send + Sync {
+    /// 当前时间
+    fn now(&self) -> DateTime<Utc>;
+    /// 等待 duration
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// 共享时钟
+pub type SharedClock = Arc<dyn Clock>;
+
+/// 系统时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// 手动推进的时钟：now 返回设定的时间，sleep 立即将时间前移 duration 后返回
+#[derive(Debug)]
+pub struct MockClock {
+    now_ms: AtomicI64, // Unix 毫秒
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now_ms: AtomicI64::new(start.timestamp_millis()),
+        }
+    }
+
+    /// 设置当前时间
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now_ms.store(now.timestamp_millis(), Ordering::SeqCst);
+    }
+
+    /// 将当前时间前移 duration
+    pub fn advance(&self, duration: Duration) {
+        self.now_ms.fetch_add(duration.as_millis() as i64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.now_ms.load(Ordering::SeqCst)).unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mock_clock_sleep_advances_without_waiting() {
+        let start = DateTime::from_timestamp(1_770_972_300, 0).unwrap();
+        let clock = MockClock::new(start);
+        let real_start = std::time::Instant::now();
+        clock.sleep(Duration::from_secs(300)).await;
+        assert!(real_start.elapsed() < Duration::from_secs(1));
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(300));
+
+        clock.set(start);
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(clock.now(), start + chrono::Duration::milliseconds(1500));
+    }
+}
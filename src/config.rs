@@ -1,8 +1,23 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use polymarket_client_sdk::clob::types::OrderType;
+use std::collections::HashMap;
 use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use tracing::warn;
 
-use polymarket_client_sdk::types::Address;
+use polymarket_client_sdk::types::{Address, Decimal};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+
+use crate::market::WindowSize;
+use crate::trading::fees::FeeParams;
+use crate::trading::signer::{self, BotSigner};
+use crate::risk::position_balancer::BalanceTargetMode;
+use crate::trading::executor::SlippageMode;
+use crate::utils::arbitrage_logger::TradeLogFormat;
 
 /// 解析套利订单类型：GTC、GTD、FOK、FAK，大小写不敏感，无效或未知值默认 GTD。
 fn parse_arbitrage_order_type(s: &str) -> OrderType {
@@ -11,161 +26,1122 @@ fn parse_arbitrage_order_type(s: &str) -> OrderType {
         "GTD" => OrderType::GTD,
         "FOK" => OrderType::FOK,
         "FAK" => OrderType::FAK,
-        _ => OrderType::GTD,
+        other => {
+            warn!(key = "ARBITRAGE_ORDER_TYPE", value = %other, "配置值无法解析，使用默认值 GTD");
+            OrderType::GTD
+        }
     }
 }
 
+/// 滑点绝对值上限：价格在 0~1 之间，超过该值的滑点基本是配置错误
+const MAX_SLIPPAGE: Decimal = dec!(0.2);
+
+/// 数量精度上限：份额以 6 位小数计（与 USDC 相同），更细的精度交易所不接受
+const MAX_SIZE_DECIMALS: u32 = 6;
+
 /// 解析滑点数组：逗号分隔，如 "-0.02,0.0"。
 /// 索引 0=上涨/持平侧滑点，1=仅下降侧滑点。只写一个值时用于两项。默认 "0,0.01"。
-fn parse_slippage(s: &str) -> [f64; 2] {
-    let parts: Vec<f64> = s
+fn parse_slippage(s: &str) -> [Decimal; 2] {
+    let parts: Vec<Decimal> = s
         .split(',')
-        .map(|x| x.trim().parse().unwrap_or(0.0))
+        .map(|x| {
+            x.trim().parse().unwrap_or_else(|_| {
+                warn!(key = "SLIPPAGE", value = %x, "滑点值无法解析，按 0 处理");
+                dec!(0)
+            })
+        })
         .collect();
     match parts.len() {
-        0 => [0.0, 0.01],
+        0 => [dec!(0), dec!(0.01)],
         1 => [parts[0], parts[0]],
         _ => [parts[0], parts[1]],
     }
 }
 
+/// 解析可接受的二元结果标签对：逗号分隔，每对以 `/` 分隔，如 "Up/Down,Yes/No"。
+/// 每对的第一个标签映射为 YES、第二个映射为 NO；格式无效的项忽略。
+fn parse_outcome_labels(s: &str) -> Vec<(String, String)> {
+    s.split(',')
+        .filter(|x| !x.trim().is_empty())
+        .filter_map(|pair| match pair.split_once('/') {
+            Some((yes, no)) if !yes.trim().is_empty() && !no.trim().is_empty() => {
+                Some((yes.trim().to_string(), no.trim().to_string()))
+            }
+            _ => {
+                warn!(key = "MARKET_OUTCOME_LABELS", value = %pair, "结果标签对格式无效（应为 YES标签/NO标签），已忽略");
+                None
+            }
+        })
+        .collect()
+}
+
+/// TOML 配置文件键名（与 Config 字段同名）到环境变量名的映射
+const FILE_KEYS: &[(&str, &str)] = &[
+    ("private_key", "POLYMARKET_PRIVATE_KEY"),
+    ("keystore_path", "KEYSTORE_PATH"),
+    ("proxy_address", "POLYMARKET_PROXY_ADDRESS"),
+    ("extra_accounts", "EXTRA_ACCOUNTS"),
+    ("min_profit_threshold", "MIN_PROFIT_THRESHOLD"),
+    ("max_order_size_usdc", "MAX_ORDER_SIZE_USDC"),
+    ("size_decimals", "SIZE_DECIMALS"),
+    ("crypto_symbols", "CRYPTO_SYMBOLS"),
+    ("max_markets_per_window", "MAX_MARKETS_PER_WINDOW"),
+    ("market_priority", "MARKET_PRIORITY"),
+    ("carry_price_direction", "CARRY_PRICE_DIRECTION"),
+    ("trade_symbols", "TRADE_SYMBOLS"),
+    ("market_window", "MARKET_WINDOW"),
+    ("market_outcome_labels", "MARKET_OUTCOME_LABELS"),
+    ("market_refresh_advance_secs", "MARKET_REFRESH_ADVANCE_SECS"),
+    ("discovery_retry_base_secs", "DISCOVERY_RETRY_BASE_SECS"),
+    ("discovery_retry_max_secs", "DISCOVERY_RETRY_MAX_SECS"),
+    ("risk_max_exposure_usdc", "RISK_MAX_EXPOSURE_USDC"),
+    ("max_inflight_trades", "MAX_INFLIGHT_TRADES"),
+    ("per_market_tasks", "PER_MARKET_TASKS"),
+    ("profit_prioritize", "PROFIT_PRIORITIZE"),
+    ("profit_prioritize_window_ms", "PROFIT_PRIORITIZE_WINDOW_MS"),
+    ("max_position_per_market_usdc", "MAX_POSITION_PER_MARKET_USDC"),
+    ("market_inventory_target", "MARKET_INVENTORY_TARGET"),
+    ("dynamic_sizing", "DYNAMIC_SIZING"),
+    ("dynamic_sizing_fraction", "DYNAMIC_SIZING_FRACTION"),
+    ("dynamic_sizing_floor_usdc", "DYNAMIC_SIZING_FLOOR_USDC"),
+    ("risk_imbalance_threshold", "RISK_IMBALANCE_THRESHOLD"),
+    ("hedge_take_profit_pct", "HEDGE_TAKE_PROFIT_PCT"),
+    ("hedge_stop_loss_pct", "HEDGE_STOP_LOSS_PCT"),
+    ("enable_hedge", "ENABLE_HEDGE"),
+    ("enable_user_ws", "ENABLE_USER_WS"),
+    ("hedge_trailing_pct", "HEDGE_TRAILING_PCT"),
+    ("hedge_sell_offset", "HEDGE_SELL_OFFSET"),
+    ("fee_c", "FEE_C"),
+    ("fee_rate", "FEE_RATE"),
+    ("fee_exponent", "FEE_EXPONENT"),
+    ("arbitrage_execution_spread", "ARBITRAGE_EXECUTION_SPREAD"),
+    ("spread_ramp_start", "SPREAD_RAMP_START"),
+    ("spread_ramp_end", "SPREAD_RAMP_END"),
+    ("arbitrage_multi_level", "ARBITRAGE_MULTI_LEVEL"),
+    ("slippage", "SLIPPAGE"),
+    ("slippage_mode", "SLIPPAGE_MODE"),
+    ("gtd_expiration_secs", "GTD_EXPIRATION_SECS"),
+    ("gtd_anchor_to_window", "GTD_ANCHOR_TO_WINDOW"),
+    ("gtd_window_buffer_secs", "GTD_WINDOW_BUFFER_SECS"),
+    ("arbitrage_order_type", "ARBITRAGE_ORDER_TYPE"),
+    ("post_only", "POST_ONLY"),
+    ("price_tick", "PRICE_TICK"),
+    ("stop_arbitrage_before_end_minutes", "STOP_ARBITRAGE_BEFORE_END_MINUTES"),
+    ("trade_start_delay_secs", "TRADE_START_DELAY_SECS"),
+    ("min_trade_interval_secs", "MIN_TRADE_INTERVAL_SECS"),
+    ("global_trade_rate_per_sec", "GLOBAL_TRADE_RATE_PER_SEC"),
+    ("order_jitter_pct", "ORDER_JITTER_PCT"),
+    ("complete_pair_retries", "COMPLETE_PAIR_RETRIES"),
+    ("atomic_pair", "ATOMIC_PAIR"),
+    ("atomic_pair_window_ms", "ATOMIC_PAIR_WINDOW_MS"),
+    ("trade_failure_limit", "TRADE_FAILURE_LIMIT"),
+    ("trade_failure_cooldown_secs", "TRADE_FAILURE_COOLDOWN_SECS"),
+    ("daily_loss_limit_usdc", "DAILY_LOSS_LIMIT_USDC"),
+    ("order_jitter_seed", "ORDER_JITTER_SEED"),
+    ("merge_interval_minutes", "MERGE_INTERVAL_MINUTES"),
+    ("merge_max_retries", "MERGE_MAX_RETRIES"),
+    ("merge_retry_base_secs", "MERGE_RETRY_BASE_SECS"),
+    ("merge_min_size", "MERGE_MIN_SIZE"),
+    ("auto_merge_on_fill", "AUTO_MERGE_ON_FILL"),
+    ("merge_spacing_secs", "MERGE_SPACING_SECS"),
+    ("merge_initial_delay_secs", "MERGE_INITIAL_DELAY_SECS"),
+    ("wind_down_delay_after_cancel_secs", "WIND_DOWN_DELAY_AFTER_CANCEL_SECS"),
+    ("cancel_batch_size", "CANCEL_BATCH_SIZE"),
+    ("cancel_max_retries", "CANCEL_MAX_RETRIES"),
+    ("polygon_rpc_urls", "POLYGON_RPC_URLS"),
+    ("min_yes_price_threshold", "MIN_YES_PRICE_THRESHOLD"),
+    ("min_no_price_threshold", "MIN_NO_PRICE_THRESHOLD"),
+    ("min_valid_ask", "MIN_VALID_ASK"),
+    ("max_plausible_profit_pct", "MAX_PLAUSIBLE_PROFIT_PCT"),
+    ("min_arbitrage_size", "MIN_ARBITRAGE_SIZE"),
+    ("arbitrage_dedup_cooldown_secs", "ARBITRAGE_DEDUP_COOLDOWN_SECS"),
+    ("arbitrage_dedup_ticks", "ARBITRAGE_DEDUP_TICKS"),
+    ("min_spread_persist_ms", "MIN_SPREAD_PERSIST_MS"),
+    ("min_spread_persist_updates", "MIN_SPREAD_PERSIST_UPDATES"),
+    ("position_sync_interval_secs", "POSITION_SYNC_INTERVAL_SECS"),
+    ("pair_refresh_interval_secs", "PAIR_REFRESH_INTERVAL_SECS"),
+    ("position_sync_tolerance", "POSITION_SYNC_TOLERANCE"),
+    ("position_sync_warn_threshold", "POSITION_SYNC_WARN_THRESHOLD"),
+    ("position_reconcile_grace_secs", "POSITION_RECONCILE_GRACE_SECS"),
+    ("position_balance_interval_secs", "POSITION_BALANCE_INTERVAL_SECS"),
+    ("position_balance_threshold", "POSITION_BALANCE_THRESHOLD"),
+    ("position_balance_min_total", "POSITION_BALANCE_MIN_TOTAL"),
+    ("balance_target_mode", "BALANCE_TARGET_MODE"),
+    ("wind_down_before_window_end_minutes", "WIND_DOWN_BEFORE_WINDOW_END_MINUTES"),
+    ("cancel_orders_on_window_change", "CANCEL_ORDERS_ON_WINDOW_CHANGE"),
+    ("wind_down_sell_price", "WIND_DOWN_SELL_PRICE"),
+    ("wind_down_use_book_price", "WIND_DOWN_USE_BOOK_PRICE"),
+    ("wind_down_book_price_offset", "WIND_DOWN_BOOK_PRICE_OFFSET"),
+    ("wind_down_sell_retries", "WIND_DOWN_SELL_RETRIES"),
+    ("wind_down_sell_price_step", "WIND_DOWN_SELL_PRICE_STEP"),
+    ("max_position_age_secs", "MAX_POSITION_AGE_SECS"),
+    ("metrics_port", "METRICS_PORT"),
+    ("status_port", "STATUS_PORT"),
+    ("display_tz", "DISPLAY_TZ"),
+    ("dry_run", "DRY_RUN"),
+    ("auth_max_retries", "AUTH_MAX_RETRIES"),
+    ("auth_retry_delay_secs", "AUTH_RETRY_DELAY_SECS"),
+    ("pending_pairs_path", "PENDING_PAIRS_PATH"),
+    ("hedge_positions_path", "HEDGE_POSITIONS_PATH"),
+    ("orderbook_record_path", "ORDERBOOK_RECORD_PATH"),
+    ("trade_log_path", "TRADE_LOG_PATH"),
+    ("trading_halt_file", "TRADING_HALT_FILE"),
+    ("trade_log_format", "TRADE_LOG_FORMAT"),
+    ("skipped_log_path", "SKIPPED_LOG_PATH"),
+    ("book_staleness_ms", "BOOK_STALENESS_MS"),
+    ("book_missing_warn_secs", "BOOK_MISSING_WARN_SECS"),
+    ("ws_stall_timeout_secs", "WS_STALL_TIMEOUT_SECS"),
+    ("orderbook_log_depth", "ORDERBOOK_LOG_DEPTH"),
+    ("enable_sell_arbitrage", "ENABLE_SELL_ARBITRAGE"),
+    ("telegram_bot_token", "TELEGRAM_BOT_TOKEN"),
+    ("telegram_chat_id", "TELEGRAM_CHAT_ID"),
+];
+
+/// 按币种覆盖的变量前缀（后接币种大写，如 MAX_ORDER_SIZE_USDC_BTC）
+const PER_SYMBOL_PREFIXES: &[&str] = &["MAX_ORDER_SIZE_USDC_", "MIN_PROFIT_THRESHOLD_", "SIZE_DECIMALS_"];
+
+/// 将 TOML 值转为与环境变量相同的字符串形式，使文件与环境变量共用同一套解析器；
+/// 数组以逗号拼接（如 slippage = [0.0, 0.01] → "0,0.01"）
+fn toml_value_to_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        toml::Value::Array(items) => items
+            .iter()
+            .map(toml_value_to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        other => other.to_string(),
+    }
+}
+
+/// 主账户的账户 ID
+pub const PRIMARY_ACCOUNT_ID: &str = "main";
+
+/// EXTRA_ACCOUNTS 中的一个附加账户
+#[derive(Debug, Clone)]
+pub struct AccountCredentials {
+    pub id: String,
+    /// 该账户的签名器（已绑定 Polygon 链 ID）
+    pub signer: BotSigner,
+    pub proxy_address: Option<Address>,
+    /// 持仓所在地址：设置了 proxy_address 时为代理钱包，否则为私钥推导的 EOA 地址
+    pub wallet_address: Address,
+}
+
+/// 解析 EXTRA_ACCOUNTS：逗号分隔，每项为 `ID:私钥` 或 `ID:私钥:代理地址`。
+/// 格式错误时报错退出（错误信息只含序号与 ID，不输出私钥）
+fn parse_extra_accounts(raw: &str) -> Result<Vec<AccountCredentials>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .enumerate()
+        .map(|(i, entry)| {
+            let mut parts = entry.split(':').map(str::trim);
+            let id = parts.next().unwrap_or_default().to_string();
+            let private_key = parts.next().unwrap_or_default().to_string();
+            if id.is_empty() || private_key.is_empty() {
+                bail!("EXTRA_ACCOUNTS 第 {} 项格式应为 ID:私钥[:代理地址]", i + 1);
+            }
+            let proxy_address = match parts.next().filter(|p| !p.is_empty()) {
+                Some(addr) => Some(
+                    addr.parse::<Address>()
+                        .map_err(|_| anyhow::anyhow!("EXTRA_ACCOUNTS 账户 {} 的代理地址无法解析", id))?,
+                ),
+                None => None,
+            };
+            let signer = signer::build_signer(&signer::RawKey(private_key))
+                .with_context(|| format!("EXTRA_ACCOUNTS 账户 {} 的私钥格式无效", id))?;
+            let wallet_address = proxy_address.unwrap_or_else(|| alloy::signers::Signer::address(&signer));
+            Ok(AccountCredentials { id, signer, proxy_address, wallet_address })
+        })
+        .collect()
+}
+
+/// 在文件名（扩展名前）加上账户 ID 后缀：pending_pairs.json → pending_pairs.acct2.json
+fn with_account_suffix(path: &str, account_id: &str) -> String {
+    let p = Path::new(path);
+    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or(path);
+    let name = match p.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}.{}.{}", stem, account_id, ext),
+        None => format!("{}.{}", stem, account_id),
+    };
+    p.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// 解析布尔开关：1/true/yes/on 为 true，0/false/no/off 为 false（大小写不敏感），其余无法识别
+pub fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// 将 f64 配置值转为 Decimal：NaN、无穷等无法表示的值返回带配置名的错误（由 validate 在启动时汇总）
+pub fn to_decimal(name: &str, value: f64) -> Result<Decimal> {
+    Decimal::try_from(value).map_err(|_| anyhow::anyhow!("{} 无法转换为十进制数，当前 {}", name, value))
+}
+
+/// 读取按 f64 书写的配置项并在加载时一次性经 to_decimal 转为 Decimal，下游直接使用 Decimal 字段。
+/// NaN、无穷等无法表示的值记入 invalid（由 validate 在启动时报错），此时字段暂取 0
+fn parse_decimal_var(var: &impl Fn(&str) -> Option<String>, key: &str, default: f64, invalid: &mut Vec<String>) -> Decimal {
+    to_decimal(key, parse_var(var, key, default)).unwrap_or_else(|e| {
+        invalid.push(e.to_string());
+        Decimal::ZERO
+    })
+}
+
+/// 读取并解析配置项：未设置时用默认值；设置了但无法解析时告警后用默认值，避免拼写错误被静默吞掉
+fn parse_var<T>(var: &impl Fn(&str) -> Option<String>, key: &str, default: T) -> T
+where
+    T: FromStr + Display,
+{
+    match var(key) {
+        Some(raw) => raw.trim().parse().unwrap_or_else(|_| {
+            warn!(key = %key, value = %raw, default = %default, "配置值无法解析，使用默认值");
+            default
+        }),
+        None => default,
+    }
+}
+
+/// 读取布尔开关，规则同 parse_var
+fn parse_bool_var(var: &impl Fn(&str) -> Option<String>, key: &str, default: bool) -> bool {
+    match var(key) {
+        Some(raw) => parse_bool(&raw).unwrap_or_else(|| {
+            warn!(key = %key, value = %raw, default = %default, "配置值无法解析，使用默认值");
+            default
+        }),
+        None => default,
+    }
+}
+
+/// 按币种覆盖：对每个已配置币种查找 {前缀}{币种大写}，如 MAX_ORDER_SIZE_USDC_BTC=200；键为小写币种。
+/// 无法解析的项不生效，以 (键, 原始值) 记入 invalid，由 validate 报错
+fn parse_per_symbol<T: FromStr>(
+    var: &impl Fn(&str) -> Option<String>,
+    symbols: &[String],
+    prefix: &str,
+    invalid: &mut Vec<(String, String)>,
+) -> HashMap<String, T> {
+    let mut overrides = HashMap::new();
+    for sym in symbols {
+        let key = format!("{}{}", prefix, sym.to_uppercase());
+        let Some(value) = var(&key) else {
+            continue;
+        };
+        match value.trim().parse() {
+            Ok(v) => {
+                overrides.insert(sym.clone(), v);
+            }
+            Err(_) => invalid.push((key, value)),
+        }
+    }
+    overrides
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub private_key: String,
+    /// 签名器（已绑定 Polygon 链 ID）：由 POLYMARKET_PRIVATE_KEY 创建，或设置 KEYSTORE_PATH 时由 keystore 解密得到
+    pub signer: BotSigner,
     pub proxy_address: Option<Address>, // Polymarket Proxy地址（如果使用Email/Magic或Browser Wallet登录）
-    pub min_profit_threshold: f64,
-    pub max_order_size_usdc: f64,
+    /// 持仓所在地址（持仓查询与 Merge 使用）：设置了 proxy_address 时为代理钱包，否则为私钥推导的 EOA 地址
+    pub wallet_address: Address,
+    /// 当前配置所属账户的 ID：主账户为 "main"，附加账户为 EXTRA_ACCOUNTS 中的 ID（见 account_configs）
+    pub account_id: String,
+    /// 附加账户：与主账户在同一进程中共用市场发现与订单簿订阅，按账户各自下单、记账、Merge 与收尾，默认无
+    pub extra_accounts: Vec<AccountCredentials>,
+    /// 以下金额、阈值与滑点在加载时即解析为 Decimal，下游直接使用，不再各自转换
+    pub min_profit_threshold: Decimal,
+    pub max_order_size_usdc: Decimal,
+    /// 按币种覆盖的最大下单金额（键为小写币种，如 btc），来自 MAX_ORDER_SIZE_USDC_BTC 等；未覆盖时用全局值
+    pub max_order_size_overrides: HashMap<String, Decimal>,
+    /// 按币种覆盖的最小利润阈值，来自 MIN_PROFIT_THRESHOLD_BTC 等；未覆盖时用全局值
+    pub min_profit_threshold_overrides: HashMap<String, Decimal>,
+    /// 下单数量精度（小数位数），数量向下截断到该精度，默认 2（0.01 份）
+    pub size_decimals: u32,
+    /// 按币种覆盖的数量精度，来自 SIZE_DECIMALS_BTC 等；未覆盖时用全局值
+    pub size_decimals_overrides: HashMap<String, u32>,
+    /// 无法解析的按币种覆盖项 (键, 原始值)，由 validate 报错
+    pub invalid_symbol_overrides: Vec<(String, String)>,
+    /// 无法转换为 Decimal 的配置项（NaN、无穷等）的错误说明，由 validate 报错
+    pub invalid_decimals: Vec<String>,
     pub crypto_symbols: Vec<String>,
+    /// 每个窗口最多订阅的市场数，超出时按 MARKET_PRIORITY 选择，0 表示不限制，默认0
+    pub max_markets_per_window: usize,
+    /// 市场数量超过上限时的币种优先级（靠前优先），默认与 CRYPTO_SYMBOLS 顺序相同
+    pub market_priority: Vec<String>,
+    /// 涨跌方向按币种跨窗口延续：新窗口首拍与同币种上一窗口的最后卖一价比较，默认 false（每窗口首拍无箭头）
+    pub carry_price_direction: bool,
+    /// 实际下单的币种（CRYPTO_SYMBOLS 的子集），其余币种仅订阅与记录订单簿；默认与 CRYPTO_SYMBOLS 相同
+    pub trade_symbols: Vec<String>,
+    /// 市场窗口大小：1m、5m、15m、1h，默认 5m；决定窗口对齐、slug 后缀与收尾时间
+    pub market_window: WindowSize,
+    /// 可接受的二元结果标签对（YES 标签, NO 标签），如 Up/Down、Yes/No；默认仅 Up/Down
+    pub market_outcome_labels: Vec<(String, String)>,
     pub market_refresh_advance_secs: u64,
-    pub risk_max_exposure_usdc: f64,
-    pub risk_imbalance_threshold: f64,
-    pub hedge_take_profit_pct: f64, // 对冲止盈百分比（例如0.05表示5%）
-    pub hedge_stop_loss_pct: f64,   // 对冲止损百分比（例如0.05表示5%）
-    pub arbitrage_execution_spread: f64, // 套利执行价差：yes+no <= 1 - 套利执行价差时，执行套利
+    /// 市场查询出错（网络错误、5xx）时的首次退避秒数，之后每次翻倍，默认1
+    pub discovery_retry_base_secs: u64,
+    /// 市场查询出错时的退避上限秒数，默认30
+    pub discovery_retry_max_secs: u64,
+    pub risk_max_exposure_usdc: Decimal,
+    /// 单个市场的敞口上限（USDC），与全局 RISK_MAX_EXPOSURE_USDC 同时生效；0 表示不限制，默认0
+    pub max_position_per_market_usdc: Decimal,
+    /// 单市场持仓目标（份）：套利数量按 目标 - max(YES持仓, NO持仓) 截断，剩余不足最小套利数量时不再执行，0 表示不限制，默认0
+    pub market_inventory_target: Decimal,
+    /// 按剩余敞口额度动态下单：每笔成本取 (RISK_MAX_EXPOSURE_USDC - 当前敞口) × DYNAMIC_SIZING_FRACTION，
+    /// 不低于 DYNAMIC_SIZING_FLOOR_USDC（且不超过剩余额度与盘口可用数量），默认 false
+    pub dynamic_sizing: bool,
+    /// 动态下单时每笔占剩余敞口额度的比例，默认0.25
+    pub dynamic_sizing_fraction: Decimal,
+    /// 动态下单时每笔成本下限（USDC），默认5.0
+    pub dynamic_sizing_floor_usdc: Decimal,
+    /// 同时执行中的套利任务上限，达到上限时跳过新机会，默认2
+    pub max_inflight_trades: usize,
+    /// 每个市场在独立任务中处理订单簿对（按 market_id 分发），避免单个市场的处理阻塞其他市场；默认 false（主循环串行处理）
+    pub per_market_tasks: bool,
+    /// 把 PROFIT_PRIORITIZE_WINDOW_MS 内各市场的机会缓冲起来，按利润率从高到低执行；默认 false（逐个立即执行），不可与 PER_MARKET_TASKS 同时启用
+    pub profit_prioritize: bool,
+    /// 利润优先模式下的缓冲时长（毫秒），默认300
+    pub profit_prioritize_window_ms: u64,
+    pub risk_imbalance_threshold: Decimal,
+    pub hedge_take_profit_pct: Decimal, // 对冲止盈百分比（例如0.05表示5%）
+    pub hedge_stop_loss_pct: Decimal,   // 对冲止损百分比（例如0.05表示5%）
+    /// 单边成交时启用对冲监测（按买一价止盈止损卖出），默认关闭
+    pub enable_hedge: bool,
+    /// 订阅用户频道（认证 WebSocket），按推送的订单成交实时更新订单对与持仓，默认 false
+    pub enable_user_ws: bool,
+    /// 对冲追踪止损：买一价较最高点回落该比例时卖出（例如0.05表示5%），0 表示使用固定止盈止损
+    pub hedge_trailing_pct: Decimal,
+    /// 对冲止盈止损卖单相对买一价下调的 tick 数（正数更快成交，负数高于买一价挂单），默认0（按买一价卖出）
+    pub hedge_sell_offset: i32,
+    /// 手续费公式 fee = c * fee_rate * (p*(1-p))^exponent 中的 c，默认100.0
+    pub fee_c: Decimal,
+    /// 手续费公式 fee = c * fee_rate * (p*(1-p))^exponent 中的 fee_rate，默认0.25
+    pub fee_rate: Decimal,
+    /// 手续费公式中的 exponent，默认2.0
+    pub fee_exponent: Decimal,
+    pub arbitrage_execution_spread: Decimal, // 套利执行价差：yes+no <= 1 - 套利执行价差时，执行套利
+    /// 窗口开始时的套利执行价差：执行价差按窗口内时间从此值线性过渡到 spread_ramp_end（早期更挑剔、后期更积极），默认等于 arbitrage_execution_spread
+    pub spread_ramp_start: Decimal,
+    /// 停止套利时（窗口结束前 stop_arbitrage_before_end_minutes，未设置时为窗口结束）的套利执行价差，默认等于 arbitrage_execution_spread
+    pub spread_ramp_end: Decimal,
+    /// 多档深度聚合：沿卖单阶梯累计可成交数量并以 VWAP 定价，默认 true；false 时仅使用卖一档（旧行为）
+    pub arbitrage_multi_level: bool,
     /// 滑点 [first, second]：仅下降侧用 second，上涨与持平用 first。如 "-0.02,0.0"
-    pub slippage: [f64; 2],
+    pub slippage: [Decimal; 2],
+    /// 滑点分配方式：directional=按涨跌方向（默认，见 slippage 说明），symmetric=不看方向，两腿都用 max(first, second)
+    pub slippage_mode: SlippageMode,
     pub gtd_expiration_secs: u64, // GTD订单过期时间（秒），默认300秒（5分钟）；仅当 arbitrage_order_type=GTD 时有效
+    /// GTD 订单过期时间不晚于当前窗口结束前 gtd_window_buffer_secs，避免订单在窗口结束后才成交，默认 false
+    pub gtd_anchor_to_window: bool,
+    /// gtd_anchor_to_window 开启时，过期时间距窗口结束的提前量（秒），默认10
+    pub gtd_window_buffer_secs: u64,
     /// 套利下单时的订单类型：GTC（一直有效）、GTD（配合 gtd_expiration_secs）、FOK（立即全部成交否则取消）、FAK（立即部分成交其余取消）
     pub arbitrage_order_type: OrderType,
+    /// 挂单模式：套利双边以买一价上方一个 tick 的 post-only 挂单（maker）提交，按挂价评估利润，
+    /// 会穿价时跳过机会；须配合 ARBITRAGE_ORDER_TYPE=GTC/GTD，挂单可能部分或完全不成交，默认 false
+    pub post_only: bool,
+    /// 最小价格单位：下单价格买入向上、卖出向下取整到该单位（交易所拒绝不在 tick 上的价格），默认0.01
+    pub price_tick: Decimal,
     pub stop_arbitrage_before_end_minutes: u64, // 市场结束前N分钟停止执行套利，默认0（不停止）
+    /// 窗口开始后的预热时长（秒）：期间订单簿常闪烁、价差失真，只监控记录不执行套利，默认0（不预热）
+    pub trade_start_delay_secs: u64,
+    /// 单边 / 部分成交时按最新卖一价补齐另一腿的最多尝试次数，0 表示不补单（交由风控），默认0
+    pub complete_pair_retries: u32,
+    /// 双腿要么都持有要么都不持有：两腿成交在 atomic_pair_window_ms 内未一致时撤掉两腿剩余挂单，
+    /// 按买一价卖出多出的成交份额（需支付 taker 手续费并承担价差），默认 false
+    pub atomic_pair: bool,
+    /// ATOMIC_PAIR 等待两腿成交一致的时长（毫秒），默认2000
+    pub atomic_pair_window_ms: u64,
+    /// 连续套利执行失败达到此次数后暂停新的套利执行，0 表示不启用，默认5
+    pub trade_failure_limit: u32,
+    /// 连续失败触发后的暂停时长（秒），默认60秒
+    pub trade_failure_cooldown_secs: u64,
+    /// 每日亏损上限（USDC）：当日（UTC）已实现亏损达到此值后直到 UTC 零点不再开新仓，Merge 与收尾继续；0 表示不启用，默认0
+    pub daily_loss_limit_usdc: Decimal,
+    /// 同一市场两次套利交易的最小间隔（秒），0 表示不按市场限制，默认0
+    pub min_trade_interval_secs: f64,
+    /// 全局交易速率上限（笔/秒），跨所有市场，0 表示不限制；默认 1/3（每 3 秒 1 笔）
+    pub global_trade_rate_per_sec: f64,
+    /// 下单数量随机抖动比例（±），同时在交易间隔上叠加随机延迟；0 表示不启用，默认0
+    pub order_jitter_pct: f64,
+    /// 抖动随机数种子，设置后随机序列可复现；未设置时以启动时间为种子
+    pub order_jitter_seed: Option<u64>,
     /// 定时 Merge 间隔（分钟），0 表示不启用。CONDITION_ID 与订单簿一样由当前窗口市场获取。
     pub merge_interval_minutes: u64,
+    /// Merge 遇 RPC 限速时的最大重试次数（不含首次），默认3
+    pub merge_max_retries: u32,
+    /// Merge 限速重试的基础等待（秒），第 n 次重试等待 base * 2^(n-1) 加随机抖动，默认12
+    pub merge_retry_base_secs: u64,
+    /// 定时 merge 与收尾 merge 的最小双边份额：YES/NO 较小一侧低于此值的市场跳过，0=不限制，默认0
+    pub merge_min_size: Decimal,
+    /// 订单对两腿完全成交后立即对该市场 Merge（等待 merge_spacing_secs 后执行，遵守 merge_min_size），不等定时 Merge，默认 false
+    pub auto_merge_on_fill: bool,
+    /// 连续 merge 两个市场之间的间隔（秒），定时 merge、收尾与维护模式共用；收尾 merge 后卖出单腿前也等待该时长，默认30
+    pub merge_spacing_secs: u64,
+    /// 定时 merge 首次执行前的延迟（秒），让主循环先完成订单簿订阅，默认10
+    pub merge_initial_delay_secs: u64,
+    /// 收尾撤单后到 merge 前的等待（秒），避免撤单前刚成交的订单尚未上链更新持仓，默认10
+    pub wind_down_delay_after_cancel_secs: u64,
+    /// 分批撤单时每批的订单数（仓位平衡与收尾撤单），默认50
+    pub cancel_batch_size: usize,
+    /// 撤单批次请求失败后的最多重试次数（指数退避），默认2
+    pub cancel_max_retries: u32,
+    /// Merge 使用的 Polygon RPC 端点（按顺序故障切换），来自逗号分隔的 POLYGON_RPC_URLS；为空时使用默认 RPC
+    pub polygon_rpc_urls: Vec<String>,
     /// YES 价格阈值：只有当 YES 价格 >= 此阈值时才执行套利，默认 0.0（不限制）
-    pub min_yes_price_threshold: f64,
+    pub min_yes_price_threshold: Decimal,
     /// NO 价格阈值：只有当 NO 价格 >= 此阈值时才执行套利，默认 0.0（不限制）
-    pub min_no_price_threshold: f64,
+    pub min_no_price_threshold: Decimal,
+    /// 订单簿合理性下限：任一侧卖一价低于此值时视为数据异常并忽略机会，默认 0.02，0 表示不检查
+    pub min_valid_ask: Decimal,
+    /// 订单簿合理性上限：扣费后净利润率超过此比例（例如0.5表示50%）时视为数据异常并忽略机会，默认 0.5，0 表示不检查
+    pub max_plausible_profit_pct: Decimal,
+    /// 最小套利数量（份）：可成交数量低于此值的机会不执行，避免产生碎片持仓，默认 0.0（不限制）
+    pub min_arbitrage_size: Decimal,
+    /// 同一市场执行套利后的去重冷却（秒）：冷却期内价格变化不足 arbitrage_dedup_ticks 时不再执行，0 表示不启用，默认0
+    pub arbitrage_dedup_cooldown_secs: u64,
+    /// 去重时视为「价格已明显变化」所需的最小变动单位数（每单位 0.01，任一腿卖一价变化达到即可），默认2
+    pub arbitrage_dedup_ticks: u32,
+    /// 同一市场的机会须持续至少此毫秒数才执行（价差消失即重新计时），过滤一闪而过的价差，0 表示不限制，默认0
+    pub min_spread_persist_ms: u64,
+    /// 同一市场的机会须在至少此数量的连续订单簿更新中出现才执行，0 表示不限制，默认0
+    pub min_spread_persist_updates: u32,
     /// 持仓同步间隔（秒），默认10秒（从API获取最新持仓覆盖本地缓存）
     pub position_sync_interval_secs: u64,
+    /// 定时向 CLOB 查询未完成订单对两腿状态的间隔（秒），挂单后才成交、撤销或过期的订单据此更新，0 表示不启用，默认30
+    pub pair_refresh_interval_secs: u64,
+    /// 持仓同步差异容差（份），本地与 API 差异超过此值才记录并更新，默认0.01
+    pub position_sync_tolerance: Decimal,
+    /// 持仓同步告警阈值（份），一次同步使某持仓变化超过此值时告警，默认5.0
+    pub position_sync_warn_threshold: Decimal,
+    /// 同步时信任本地近期更新的时长（秒）：此时间内本地有成交的 token 保留本地值，0=完全以 API 为准，默认30秒
+    pub position_reconcile_grace_secs: u64,
     /// 仓位平衡检查间隔（秒），默认60秒
     pub position_balance_interval_secs: u64,
     /// 不平衡阈值，只有当持仓差异 >= 此阈值时才取消挂单，默认2.0
-    pub position_balance_threshold: f64,
+    pub position_balance_threshold: Decimal,
     /// 最小总持仓要求，只有当总持仓 >= 此值时才执行平衡，默认5.0
-    pub position_balance_min_total: f64,
-    /// 窗口结束前收尾：距离当前5分钟窗口结束还有多少分钟时触发收尾（取消挂单→Merge→市价卖剩余）。0=不启用。
+    pub position_balance_min_total: Decimal,
+    /// 实际持仓平衡但挂单失衡时的目标：average=两侧向均值靠拢（默认），min=两侧以较小一侧为准，取消较重一侧的全部超出挂单
+    pub balance_target_mode: BalanceTargetMode,
+    /// 窗口结束前收尾：距离当前窗口结束还有多少分钟时触发收尾（取消挂单→Merge→市价卖剩余）。0=不启用。
     pub wind_down_before_window_end_minutes: u64,
+    /// 切换到新窗口前取消所有挂单，避免旧窗口的挂单在市场结算期间成交；本窗口已收尾（收尾已撤单）时跳过，默认 false
+    pub cancel_orders_on_window_change: bool,
     /// 收尾时单腿卖出的限价单价格（尽量快速成交），默认0.01
-    pub wind_down_sell_price: f64,
+    pub wind_down_sell_price: Decimal,
+    /// 收尾时单腿按缓存的买一价减 wind_down_book_price_offset 卖出，无订单簿时回退到 wind_down_sell_price，默认 false
+    pub wind_down_use_book_price: bool,
+    /// 按买一价收尾卖出时相对买一价的让价，默认0.01
+    pub wind_down_book_price_offset: Decimal,
+    /// 收尾卖出单腿的最多尝试次数：>0 时以 FAK 卖出，未卖完按最新买一价逐次让价重试；0=单次 GTC 限价卖出（默认）
+    pub wind_down_sell_retries: u32,
+    /// 收尾卖出每次重试相对买一价多让的价格，默认0.01
+    pub wind_down_sell_price_step: Decimal,
+    /// 单腿持仓的最长持有时间（秒）：超过后不等窗口收尾，按收尾卖出逻辑强制卖出。0=不启用（默认）
+    pub max_position_age_secs: u64,
+    /// Prometheus 指标端口，未设置时不启动指标服务
+    pub metrics_port: Option<u16>,
+    /// 状态服务端口（/healthz、/status），未设置时不启动
+    pub status_port: Option<u16>,
+    /// 日志中时间的显示时区（IANA 名称，如 Asia/Shanghai），仅影响显示，内部计算一律使用 UTC，默认 UTC
+    pub display_tz: chrono_tz::Tz,
+    /// 模拟交易（paper trading）：只记录订单意图，不实际下单、撤单或 Merge，默认 false
+    pub dry_run: bool,
+    /// 启动认证（执行器、风险管理客户端、认证验证）临时性失败时的最多重试次数，0 表示不重试，默认3
+    pub auth_max_retries: u32,
+    /// 启动认证重试的初始间隔（秒），之后每次翻倍（最长 60 秒），默认5
+    pub auth_retry_delay_secs: u64,
+    /// 订单对持久化文件路径（JSON），重启后据此恢复未完成订单对；设为空字符串禁用，默认 pending_pairs.json
+    pub pending_pairs_path: Option<String>,
+    /// 对冲仓位持久化文件（JSON），重启时恢复监测中的单边仓位与止盈止损价；为空则不持久化，默认 hedge_positions.json
+    pub hedge_positions_path: Option<String>,
+    /// 订单簿录制文件路径（NDJSON，追加写入），未设置时不录制；录制文件可用 `replay <文件>` 回放
+    pub orderbook_record_path: Option<String>,
+    /// 成交记录文件路径（追加写入），未设置时不记录
+    pub trade_log_path: Option<String>,
+    /// 交易暂停文件：该文件存在时不再执行新的买入套利（进程继续运行，Merge 与收尾照常），删除后恢复；未设置时不检查
+    pub trading_halt_file: Option<String>,
+    /// 成交记录格式：json（每笔一个 JSON 块，`---` 分隔）或 csv（每笔一行，首次写入表头），默认 json
+    pub trade_log_format: TradeLogFormat,
+    /// 跳过记录文件：每个被执行前检查拦下的机会追加一行 JSON（时间、市场、原因、价格、数量、敞口），未设置时不记录
+    pub skipped_log_path: Option<String>,
+    /// 订单簿过期阈值（毫秒）：YES/NO 任一侧超过此时长未更新时不做套利检测，0=不检查，默认0
+    pub book_staleness_ms: u64,
+    /// 窗口开始订阅后超过该秒数仍有 token 未收到订单簿时输出告警；0 表示不告警（仍 debug 记录）
+    pub book_missing_warn_secs: u64,
+    /// 订单簿流静默看门狗：超过该秒数未收到任何订单簿更新时重建订单簿流；0 表示不检查，默认 30
+    pub ws_stall_timeout_secs: u64,
+    /// 收到订单簿更新时 debug 日志打印的买卖盘档数，0 表示不打印，默认 5
+    pub orderbook_log_depth: usize,
+    /// 卖出侧套利：YES 买一 + NO 买一 > 1 时卖出已持有的双边持仓（仅卖出持仓内数量），默认 false
+    pub enable_sell_arbitrage: bool,
+    /// Telegram 通知：bot token 与 chat id 同时设置时启用
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
         dotenvy::dotenv().ok();
+        Self::load(&HashMap::new())
+    }
+
+    /// 从 TOML 文件加载配置：键名与 Config 字段同名（如 `max_order_size_usdc = 50.0`、`slippage = [0.0, 0.01]`），
+    /// 同名环境变量存在时覆盖文件中的值；未写的键与 from_env 使用相同默认值。
+    pub fn from_file(path: &Path) -> Result<Self> {
+        dotenvy::dotenv().ok();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取配置文件失败: {}", path.display()))?;
+        let table: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("解析配置文件失败: {}", path.display()))?;
+
+        let mut file_vars = HashMap::new();
+        for (key, value) in table {
+            let env_key = if let Some(&(_, env_key)) = FILE_KEYS.iter().find(|(k, _)| *k == key) {
+                env_key.to_string()
+            } else if PER_SYMBOL_PREFIXES.iter().any(|p| key.to_uppercase().starts_with(p)) {
+                // 按币种覆盖项，如 max_order_size_usdc_btc → MAX_ORDER_SIZE_USDC_BTC
+                key.to_uppercase()
+            } else {
+                warn!(key = %key, path = %path.display(), "配置文件中存在未知键，已忽略");
+                continue;
+            };
+            file_vars.insert(env_key, toml_value_to_string(&value));
+        }
+        Self::load(&file_vars)
+    }
+
+    /// 测试用：以默认值加给定键值（环境变量名为键）构建配置，不读取进程环境变量；未提供私钥时使用固定的测试私钥
+    #[cfg(test)]
+    pub fn for_test(vars: &[(&str, &str)]) -> Self {
+        let mut vars: HashMap<String, String> =
+            vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        vars.entry("POLYMARKET_PRIVATE_KEY".to_string()).or_insert_with(|| {
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".to_string()
+        });
+        Self::from_vars(|key| vars.get(key).cloned(), None).expect("测试配置无效")
+    }
+
+    /// 按「环境变量 > 配置文件 > 默认值」的优先级构建配置；file_vars 以环境变量名为键
+    fn load(file_vars: &HashMap<String, String>) -> Result<Self> {
+        Self::from_vars(
+            |key| env::var(key).ok().or_else(|| file_vars.get(key).cloned()),
+            env::var("KEYSTORE_PASSWORD").ok(),
+        )
+    }
+
+    /// 由 var 查询各键（环境变量名为键，None 时取默认值）构建配置；keystore 密码只从环境变量读取，单独传入
+    fn from_vars(var: impl Fn(&str) -> Option<String>, keystore_password: Option<String>) -> Result<Self> {
+
+        // 必填项：缺失时一次性列出全部缺失的键
+        let mut missing = Vec::new();
+        // 设置 KEYSTORE_PATH 时私钥从加密 keystore 解密，此时不再要求 POLYMARKET_PRIVATE_KEY
+        let keystore_path = var("KEYSTORE_PATH").filter(|p| !p.trim().is_empty());
+        let private_key = var("POLYMARKET_PRIVATE_KEY").filter(|k| !k.trim().is_empty());
+        if private_key.is_none() && keystore_path.is_none() {
+            missing.push("POLYMARKET_PRIVATE_KEY (private_key) 或 KEYSTORE_PATH");
+        }
+        if keystore_path.is_some() && keystore_password.is_none() {
+            missing.push("KEYSTORE_PASSWORD（仅支持环境变量）");
+        }
+        if !missing.is_empty() {
+            bail!("缺少必填配置: {}", missing.join(", "));
+        }
+        let signer = match keystore_path {
+            Some(path) => signer::build_signer(&signer::Keystore {
+                path,
+                password: keystore_password.unwrap_or_default(),
+            })?,
+            None => signer::build_signer(&signer::RawKey(private_key.unwrap_or_default()))?,
+        };
+
+        let crypto_symbols: Vec<String> = var("CRYPTO_SYMBOLS")
+            .unwrap_or_else(|| "btc,eth,xrp,sol".to_string())
+            .split(',')
+            .map(|s| s.trim().to_lowercase())
+            .collect();
+        let trade_symbols: Vec<String> = var("TRADE_SYMBOLS")
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(|| crypto_symbols.clone()); // 默认全部币种都交易
+        let market_priority: Vec<String> = var("MARKET_PRIORITY")
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(|| crypto_symbols.clone()); // 默认按 CRYPTO_SYMBOLS 顺序
+
+        let mut invalid_symbol_overrides = Vec::new();
+        let mut invalid_decimals = Vec::new();
+        let max_order_size_overrides =
+            parse_per_symbol(&var, &crypto_symbols, "MAX_ORDER_SIZE_USDC_", &mut invalid_symbol_overrides);
+        let min_profit_threshold_overrides =
+            parse_per_symbol(&var, &crypto_symbols, "MIN_PROFIT_THRESHOLD_", &mut invalid_symbol_overrides);
+        let size_decimals_overrides =
+            parse_per_symbol(&var, &crypto_symbols, "SIZE_DECIMALS_", &mut invalid_symbol_overrides);
 
         // 解析proxy_address（可选）
-        let proxy_address: Option<Address> = env::var("POLYMARKET_PROXY_ADDRESS")
-            .ok()
-            .and_then(|addr| addr.parse().ok());
+        let proxy_address: Option<Address> = var("POLYMARKET_PROXY_ADDRESS")
+            .and_then(|addr| match addr.trim().parse() {
+                Ok(a) => Some(a),
+                Err(_) => {
+                    warn!(key = "POLYMARKET_PROXY_ADDRESS", value = %addr, "配置值无法解析，按未设置处理");
+                    None
+                }
+            });
+        // 未使用代理钱包（EOA 模式）时，份额直接在私钥对应的地址上
+        let wallet_address = proxy_address.unwrap_or_else(|| alloy::signers::Signer::address(&signer));
+
+        let arbitrage_execution_spread = parse_var(&var, "ARBITRAGE_EXECUTION_SPREAD", dec!(0.01)); // 默认0.01
+        let extra_accounts = match var("EXTRA_ACCOUNTS") {
+            Some(raw) => parse_extra_accounts(&raw)?,
+            None => Vec::new(),
+        };
 
         Ok(Config {
-            private_key: env::var("POLYMARKET_PRIVATE_KEY")
-                .expect("POLYMARKET_PRIVATE_KEY must be set"),
+            signer,
             proxy_address,
-            min_profit_threshold: env::var("MIN_PROFIT_THRESHOLD")
-                .unwrap_or_else(|_| "0.001".to_string())
-                .parse()
-                .unwrap_or(0.001),
-            max_order_size_usdc: env::var("MAX_ORDER_SIZE_USDC")
-                .unwrap_or_else(|_| "100.0".to_string())
-                .parse()
-                .unwrap_or(100.0),
-            crypto_symbols: env::var("CRYPTO_SYMBOLS")
-                .unwrap_or_else(|_| "btc,eth,xrp,sol".to_string())
-                .split(',')
-                .map(|s| s.trim().to_lowercase())
-                .collect(),
-            market_refresh_advance_secs: env::var("MARKET_REFRESH_ADVANCE_SECS")
-                .unwrap_or_else(|_| "5".to_string())
-                .parse()
-                .unwrap_or(5),
-            risk_max_exposure_usdc: env::var("RISK_MAX_EXPOSURE_USDC")
-                .unwrap_or_else(|_| "1000.0".to_string())
-                .parse()
-                .unwrap_or(1000.0),
-            risk_imbalance_threshold: env::var("RISK_IMBALANCE_THRESHOLD")
-                .unwrap_or_else(|_| "0.1".to_string())
-                .parse()
-                .unwrap_or(0.1),
-            hedge_take_profit_pct: env::var("HEDGE_TAKE_PROFIT_PCT")
-                .unwrap_or_else(|_| "0.05".to_string())
-                .parse()
-                .unwrap_or(0.05), // 默认5%止盈
-            hedge_stop_loss_pct: env::var("HEDGE_STOP_LOSS_PCT")
-                .unwrap_or_else(|_| "0.05".to_string())
-                .parse()
-                .unwrap_or(0.05), // 默认5%止损
-            arbitrage_execution_spread: env::var("ARBITRAGE_EXECUTION_SPREAD")
-                .unwrap_or_else(|_| "0.01".to_string())
-                .parse()
-                .unwrap_or(0.01), // 默认0.01
-            slippage: parse_slippage(&env::var("SLIPPAGE").unwrap_or_else(|_| "0,0.01".to_string())),
-            gtd_expiration_secs: env::var("GTD_EXPIRATION_SECS")
-                .unwrap_or_else(|_| "300".to_string())
-                .parse()
-                .unwrap_or(300), // 默认300秒（5分钟）
+            wallet_address,
+            account_id: PRIMARY_ACCOUNT_ID.to_string(),
+            extra_accounts,
+            min_profit_threshold: parse_var(&var, "MIN_PROFIT_THRESHOLD", dec!(0.001)),
+            max_order_size_usdc: parse_var(&var, "MAX_ORDER_SIZE_USDC", dec!(100.0)),
+            max_order_size_overrides,
+            min_profit_threshold_overrides,
+            size_decimals: parse_var(&var, "SIZE_DECIMALS", 2), // 默认2位小数
+            size_decimals_overrides,
+            invalid_symbol_overrides,
+            crypto_symbols,
+            max_markets_per_window: parse_var(&var, "MAX_MARKETS_PER_WINDOW", 0), // 默认0，不限制
+            market_priority,
+            carry_price_direction: parse_bool_var(&var, "CARRY_PRICE_DIRECTION", false), // 默认关闭
+            trade_symbols,
+            market_window: var("MARKET_WINDOW")
+                .map(|w| {
+                    WindowSize::parse(&w).unwrap_or_else(|| {
+                        warn!(key = "MARKET_WINDOW", value = %w, "配置值无法解析，使用默认值 5m");
+                        WindowSize::FiveMin
+                    })
+                })
+                .unwrap_or(WindowSize::FiveMin), // 默认5分钟
+            market_outcome_labels: parse_outcome_labels(
+                &var("MARKET_OUTCOME_LABELS").unwrap_or_else(|| "Up/Down".to_string()),
+            ), // 默认 Up/Down
+            market_refresh_advance_secs: parse_var(&var, "MARKET_REFRESH_ADVANCE_SECS", 5),
+            discovery_retry_base_secs: parse_var(&var, "DISCOVERY_RETRY_BASE_SECS", 1), // 默认1秒
+            discovery_retry_max_secs: parse_var(&var, "DISCOVERY_RETRY_MAX_SECS", 30), // 默认30秒
+            risk_max_exposure_usdc: parse_decimal_var(&var, "RISK_MAX_EXPOSURE_USDC", 1000.0, &mut invalid_decimals),
+            max_inflight_trades: parse_var(&var, "MAX_INFLIGHT_TRADES", 2), // 默认2
+            per_market_tasks: parse_bool_var(&var, "PER_MARKET_TASKS", false), // 默认串行处理
+            profit_prioritize: parse_bool_var(&var, "PROFIT_PRIORITIZE", false), // 默认逐个立即执行
+            profit_prioritize_window_ms: parse_var(&var, "PROFIT_PRIORITIZE_WINDOW_MS", 300), // 默认300毫秒
+            max_position_per_market_usdc: parse_decimal_var(&var, "MAX_POSITION_PER_MARKET_USDC", 0.0, &mut invalid_decimals), // 默认0，不限制
+            market_inventory_target: parse_decimal_var(&var, "MARKET_INVENTORY_TARGET", 0.0, &mut invalid_decimals), // 默认0，不限制
+            dynamic_sizing: parse_bool_var(&var, "DYNAMIC_SIZING", false), // 默认关闭
+            dynamic_sizing_fraction: parse_decimal_var(&var, "DYNAMIC_SIZING_FRACTION", 0.25, &mut invalid_decimals), // 默认剩余额度的25%
+            dynamic_sizing_floor_usdc: parse_decimal_var(&var, "DYNAMIC_SIZING_FLOOR_USDC", 5.0, &mut invalid_decimals), // 默认5 USDC
+            risk_imbalance_threshold: parse_decimal_var(&var, "RISK_IMBALANCE_THRESHOLD", 0.1, &mut invalid_decimals),
+            hedge_take_profit_pct: parse_decimal_var(&var, "HEDGE_TAKE_PROFIT_PCT", 0.05, &mut invalid_decimals), // 默认5%止盈
+            hedge_stop_loss_pct: parse_decimal_var(&var, "HEDGE_STOP_LOSS_PCT", 0.05, &mut invalid_decimals), // 默认5%止损
+            enable_hedge: parse_bool_var(&var, "ENABLE_HEDGE", false), // 默认关闭
+            enable_user_ws: parse_bool_var(&var, "ENABLE_USER_WS", false), // 默认关闭
+            hedge_trailing_pct: parse_decimal_var(&var, "HEDGE_TRAILING_PCT", 0.0, &mut invalid_decimals), // 默认0，不启用追踪止损
+            hedge_sell_offset: parse_var(&var, "HEDGE_SELL_OFFSET", 0), // 默认0，按买一价卖出
+            fee_c: parse_decimal_var(&var, "FEE_C", 100.0, &mut invalid_decimals), // 默认100.0
+            fee_rate: parse_decimal_var(&var, "FEE_RATE", 0.25, &mut invalid_decimals), // 默认0.25
+            fee_exponent: parse_decimal_var(&var, "FEE_EXPONENT", 2.0, &mut invalid_decimals), // 默认2.0
+            arbitrage_execution_spread,
+            spread_ramp_start: parse_var(&var, "SPREAD_RAMP_START", arbitrage_execution_spread), // 默认不随时间变化
+            spread_ramp_end: parse_var(&var, "SPREAD_RAMP_END", arbitrage_execution_spread), // 默认不随时间变化
+            arbitrage_multi_level: parse_bool_var(&var, "ARBITRAGE_MULTI_LEVEL", true),
+            slippage: parse_slippage(&var("SLIPPAGE").unwrap_or_else(|| "0,0.01".to_string())),
+            slippage_mode: parse_var(&var, "SLIPPAGE_MODE", SlippageMode::Directional), // 默认按涨跌方向
+            gtd_expiration_secs: parse_var(&var, "GTD_EXPIRATION_SECS", 300), // 默认300秒（5分钟）
+            gtd_anchor_to_window: parse_bool_var(&var, "GTD_ANCHOR_TO_WINDOW", false), // 默认按下单时间固定偏移
+            gtd_window_buffer_secs: parse_var(&var, "GTD_WINDOW_BUFFER_SECS", 10), // 默认10秒
             arbitrage_order_type: parse_arbitrage_order_type(
-                &env::var("ARBITRAGE_ORDER_TYPE").unwrap_or_else(|_| "GTD".to_string()),
+                &var("ARBITRAGE_ORDER_TYPE").unwrap_or_else(|| "GTD".to_string()),
             ),
-            stop_arbitrage_before_end_minutes: env::var("STOP_ARBITRAGE_BEFORE_END_MINUTES")
-                .unwrap_or_else(|_| "0".to_string())
-                .parse()
-                .unwrap_or(0), // 默认0（不停止）
-            merge_interval_minutes: env::var("MERGE_INTERVAL_MINUTES")
-                .unwrap_or_else(|_| "0".to_string())
-                .parse()
-                .unwrap_or(0), // 0=不启用
-            min_yes_price_threshold: env::var("MIN_YES_PRICE_THRESHOLD")
-                .unwrap_or_else(|_| "0.0".to_string())
-                .parse()
-                .unwrap_or(0.0), // 默认0.0（不限制）
-            min_no_price_threshold: env::var("MIN_NO_PRICE_THRESHOLD")
-                .unwrap_or_else(|_| "0.0".to_string())
-                .parse()
-                .unwrap_or(0.0), // 默认0.0（不限制）
-            position_sync_interval_secs: env::var("POSITION_SYNC_INTERVAL_SECS")
-                .unwrap_or_else(|_| "10".to_string())
-                .parse()
-                .unwrap_or(10), // 默认10秒
-            position_balance_interval_secs: env::var("POSITION_BALANCE_INTERVAL_SECS")
-                .unwrap_or_else(|_| "60".to_string())
-                .parse()
-                .unwrap_or(60), // 默认60秒
-            position_balance_threshold: env::var("POSITION_BALANCE_THRESHOLD")
-                .unwrap_or_else(|_| "2.0".to_string())
-                .parse()
-                .unwrap_or(2.0), // 默认2.0
-            position_balance_min_total: env::var("POSITION_BALANCE_MIN_TOTAL")
-                .unwrap_or_else(|_| "5.0".to_string())
-                .parse()
-                .unwrap_or(5.0), // 默认5.0
-            wind_down_before_window_end_minutes: env::var("WIND_DOWN_BEFORE_WINDOW_END_MINUTES")
-                .unwrap_or_else(|_| "0".to_string())
-                .parse()
-                .unwrap_or(0), // 0=不启用
-            wind_down_sell_price: env::var("WIND_DOWN_SELL_PRICE")
-                .unwrap_or_else(|_| "0.01".to_string())
-                .parse()
-                .unwrap_or(0.01), // 默认0.01
+            post_only: parse_bool_var(&var, "POST_ONLY", false), // 默认吃单（taker）
+            price_tick: parse_decimal_var(&var, "PRICE_TICK", 0.01, &mut invalid_decimals), // 默认0.01
+            stop_arbitrage_before_end_minutes: parse_var(&var, "STOP_ARBITRAGE_BEFORE_END_MINUTES", 0), // 默认0（不停止）
+            trade_start_delay_secs: parse_var(&var, "TRADE_START_DELAY_SECS", 0), // 默认0（不预热）
+            complete_pair_retries: parse_var(&var, "COMPLETE_PAIR_RETRIES", 0), // 默认0，不补单
+            atomic_pair: parse_bool_var(&var, "ATOMIC_PAIR", false), // 默认关闭
+            atomic_pair_window_ms: parse_var(&var, "ATOMIC_PAIR_WINDOW_MS", 2000), // 默认2秒
+            trade_failure_limit: parse_var(&var, "TRADE_FAILURE_LIMIT", 5), // 默认连续失败5次后暂停
+            trade_failure_cooldown_secs: parse_var(&var, "TRADE_FAILURE_COOLDOWN_SECS", 60), // 默认60秒
+            daily_loss_limit_usdc: parse_decimal_var(&var, "DAILY_LOSS_LIMIT_USDC", 0.0, &mut invalid_decimals), // 默认0（不启用）
+            min_trade_interval_secs: parse_var(&var, "MIN_TRADE_INTERVAL_SECS", 0.0), // 默认0，不按市场限制
+            global_trade_rate_per_sec: parse_var(&var, "GLOBAL_TRADE_RATE_PER_SEC", 1.0 / 3.0), // 默认每3秒1笔
+            order_jitter_pct: parse_var(&var, "ORDER_JITTER_PCT", 0.0), // 默认0，不启用抖动
+            order_jitter_seed: var("ORDER_JITTER_SEED").and_then(|v| match v.trim().parse() {
+                Ok(seed) => Some(seed),
+                Err(_) => {
+                    warn!(key = "ORDER_JITTER_SEED", value = %v, "配置值无法解析，按未设置处理");
+                    None
+                }
+            }),
+            merge_interval_minutes: parse_var(&var, "MERGE_INTERVAL_MINUTES", 0), // 0=不启用
+            merge_max_retries: parse_var(&var, "MERGE_MAX_RETRIES", 3), // 默认3次
+            merge_retry_base_secs: parse_var(&var, "MERGE_RETRY_BASE_SECS", 12), // 默认12秒（略大于 "retry in 10s"）
+            merge_min_size: parse_decimal_var(&var, "MERGE_MIN_SIZE", 0.0, &mut invalid_decimals), // 0=不限制
+            auto_merge_on_fill: parse_bool_var(&var, "AUTO_MERGE_ON_FILL", false), // 默认关闭
+            merge_spacing_secs: parse_var(&var, "MERGE_SPACING_SECS", 30), // 默认30秒
+            merge_initial_delay_secs: parse_var(&var, "MERGE_INITIAL_DELAY_SECS", 10), // 默认10秒
+            wind_down_delay_after_cancel_secs: parse_var(&var, "WIND_DOWN_DELAY_AFTER_CANCEL_SECS", 10), // 默认10秒
+            cancel_batch_size: parse_var(&var, "CANCEL_BATCH_SIZE", 50), // 默认每批50个
+            cancel_max_retries: parse_var(&var, "CANCEL_MAX_RETRIES", 2), // 默认重试2次
+            polygon_rpc_urls: var("POLYGON_RPC_URLS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(), // 默认空，使用 merge 内置 RPC
+            min_yes_price_threshold: parse_var(&var, "MIN_YES_PRICE_THRESHOLD", dec!(0.0)), // 默认0.0（不限制）
+            min_no_price_threshold: parse_var(&var, "MIN_NO_PRICE_THRESHOLD", dec!(0.0)), // 默认0.0（不限制）
+            min_valid_ask: parse_var(&var, "MIN_VALID_ASK", dec!(0.02)), // 默认0.02
+            max_plausible_profit_pct: parse_var(&var, "MAX_PLAUSIBLE_PROFIT_PCT", dec!(0.5)), // 默认50%
+            min_arbitrage_size: parse_decimal_var(&var, "MIN_ARBITRAGE_SIZE", 0.0, &mut invalid_decimals), // 默认0.0（不限制）
+            arbitrage_dedup_cooldown_secs: parse_var(&var, "ARBITRAGE_DEDUP_COOLDOWN_SECS", 0), // 默认0，不去重
+            arbitrage_dedup_ticks: parse_var(&var, "ARBITRAGE_DEDUP_TICKS", 2), // 默认2个tick（0.02）
+            min_spread_persist_ms: parse_var(&var, "MIN_SPREAD_PERSIST_MS", 0), // 默认0，不限制
+            min_spread_persist_updates: parse_var(&var, "MIN_SPREAD_PERSIST_UPDATES", 0), // 默认0，不限制
+            position_sync_interval_secs: parse_var(&var, "POSITION_SYNC_INTERVAL_SECS", 10), // 默认10秒
+            pair_refresh_interval_secs: parse_var(&var, "PAIR_REFRESH_INTERVAL_SECS", 30), // 默认30秒
+            position_sync_tolerance: parse_decimal_var(&var, "POSITION_SYNC_TOLERANCE", 0.01, &mut invalid_decimals), // 默认0.01
+            position_sync_warn_threshold: parse_decimal_var(&var, "POSITION_SYNC_WARN_THRESHOLD", 5.0, &mut invalid_decimals), // 默认5.0
+            position_reconcile_grace_secs: parse_var(&var, "POSITION_RECONCILE_GRACE_SECS", 30), // 默认30秒
+            position_balance_interval_secs: parse_var(&var, "POSITION_BALANCE_INTERVAL_SECS", 60), // 默认60秒
+            position_balance_threshold: parse_decimal_var(&var, "POSITION_BALANCE_THRESHOLD", 2.0, &mut invalid_decimals), // 默认2.0
+            position_balance_min_total: parse_decimal_var(&var, "POSITION_BALANCE_MIN_TOTAL", 5.0, &mut invalid_decimals), // 默认5.0
+            balance_target_mode: parse_var(&var, "BALANCE_TARGET_MODE", BalanceTargetMode::Average), // 默认average
+            wind_down_before_window_end_minutes: parse_var(&var, "WIND_DOWN_BEFORE_WINDOW_END_MINUTES", 0), // 0=不启用
+            cancel_orders_on_window_change: parse_bool_var(&var, "CANCEL_ORDERS_ON_WINDOW_CHANGE", false), // 默认关闭
+            wind_down_sell_price: parse_decimal_var(&var, "WIND_DOWN_SELL_PRICE", 0.01, &mut invalid_decimals), // 默认0.01
+            wind_down_use_book_price: parse_bool_var(&var, "WIND_DOWN_USE_BOOK_PRICE", false), // 默认按固定价卖出
+            wind_down_book_price_offset: parse_decimal_var(&var, "WIND_DOWN_BOOK_PRICE_OFFSET", 0.01, &mut invalid_decimals), // 默认让价0.01
+            wind_down_sell_retries: parse_var(&var, "WIND_DOWN_SELL_RETRIES", 0), // 0=单次 GTC 卖出
+            wind_down_sell_price_step: parse_decimal_var(&var, "WIND_DOWN_SELL_PRICE_STEP", 0.01, &mut invalid_decimals), // 默认每次多让0.01
+            max_position_age_secs: parse_var(&var, "MAX_POSITION_AGE_SECS", 0), // 0=不启用
+            metrics_port: var("METRICS_PORT").and_then(|p| match p.trim().parse() {
+                Ok(port) => Some(port),
+                Err(_) => {
+                    warn!(key = "METRICS_PORT", value = %p, "配置值无法解析，指标服务不启动");
+                    None
+                }
+            }),
+            status_port: var("STATUS_PORT").and_then(|p| match p.trim().parse() {
+                Ok(port) => Some(port),
+                Err(_) => {
+                    warn!(key = "STATUS_PORT", value = %p, "配置值无法解析，状态服务不启动");
+                    None
+                }
+            }),
+            display_tz: parse_var(&var, "DISPLAY_TZ", chrono_tz::Tz::UTC), // 默认 UTC
+            dry_run: parse_bool_var(&var, "DRY_RUN", false),
+            auth_max_retries: parse_var(&var, "AUTH_MAX_RETRIES", 3), // 默认重试3次
+            auth_retry_delay_secs: parse_var(&var, "AUTH_RETRY_DELAY_SECS", 5), // 默认5秒起退避
+            pending_pairs_path: Some(
+                var("PENDING_PAIRS_PATH").unwrap_or_else(|| "pending_pairs.json".to_string()),
+            )
+            .filter(|p| !p.trim().is_empty()),
+            hedge_positions_path: Some(
+                var("HEDGE_POSITIONS_PATH").unwrap_or_else(|| "hedge_positions.json".to_string()),
+            )
+            .filter(|p| !p.trim().is_empty()),
+            orderbook_record_path: var("ORDERBOOK_RECORD_PATH").filter(|p| !p.trim().is_empty()),
+            trade_log_path: var("TRADE_LOG_PATH").filter(|p| !p.trim().is_empty()),
+            trading_halt_file: var("TRADING_HALT_FILE").filter(|p| !p.trim().is_empty()),
+            trade_log_format: parse_var(&var, "TRADE_LOG_FORMAT", TradeLogFormat::Json), // 默认json
+            skipped_log_path: var("SKIPPED_LOG_PATH").filter(|p| !p.trim().is_empty()),
+            book_staleness_ms: parse_var(&var, "BOOK_STALENESS_MS", 0), // 0=不检查
+            book_missing_warn_secs: parse_var(&var, "BOOK_MISSING_WARN_SECS", 15), // 默认15秒，0=不告警
+            ws_stall_timeout_secs: parse_var(&var, "WS_STALL_TIMEOUT_SECS", 30), // 默认30秒，0=不检查
+            orderbook_log_depth: parse_var(&var, "ORDERBOOK_LOG_DEPTH", 5), // 默认5档，0=不打印
+            enable_sell_arbitrage: parse_bool_var(&var, "ENABLE_SELL_ARBITRAGE", false),
+            telegram_bot_token: var("TELEGRAM_BOT_TOKEN").filter(|s| !s.trim().is_empty()),
+            telegram_chat_id: var("TELEGRAM_CHAT_ID").filter(|s| !s.trim().is_empty()),
+            invalid_decimals,
         })
     }
+
+    /// 该币种是否实际下单；不在 TRADE_SYMBOLS 中的币种仅监控
+    pub fn is_trade_symbol(&self, symbol: &str) -> bool {
+        let symbol = symbol.trim().to_lowercase();
+        self.trade_symbols.contains(&symbol)
+    }
+
+    /// 指定币种的最大下单金额（USDC）：有按币种覆盖时用覆盖值，否则用全局 max_order_size_usdc
+    pub fn max_order_size_for(&self, symbol: &str) -> Decimal {
+        self.max_order_size_overrides
+            .get(&symbol.trim().to_lowercase())
+            .copied()
+            .unwrap_or(self.max_order_size_usdc)
+    }
+
+    /// 指定币种的最小利润阈值：有按币种覆盖时用覆盖值，否则用全局 min_profit_threshold
+    pub fn min_profit_threshold_for(&self, symbol: &str) -> Decimal {
+        self.min_profit_threshold_overrides
+            .get(&symbol.trim().to_lowercase())
+            .copied()
+            .unwrap_or(self.min_profit_threshold)
+    }
+
+    /// 指定币种的下单数量精度：有按币种覆盖时用覆盖值，否则用全局 size_decimals
+    pub fn size_decimals_for(&self, symbol: &str) -> u32 {
+        self.size_decimals_overrides
+            .get(&symbol.trim().to_lowercase())
+            .copied()
+            .unwrap_or(self.size_decimals)
+    }
+
+    /// 校验配置不变量，一次性返回所有不合法项
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: Vec<String> = Vec::new();
+        for (key, value) in &self.invalid_symbol_overrides {
+            errors.push(format!("{} 无法解析，当前 {}", key, value));
+        }
+        errors.extend(self.invalid_decimals.iter().cloned());
+        // Decimal 配置按 f64 复用下方的范围检查（仅用于比较，精度足够）
+        let f = |v: Decimal| v.to_f64().unwrap_or(f64::NAN);
+        let mut unit = |name: &str, v: f64| {
+            if !(0.0..=1.0).contains(&v) {
+                errors.push(format!("{} 须在 [0, 1] 内，当前 {}", name, v));
+            }
+        };
+        unit("MIN_PROFIT_THRESHOLD", f(self.min_profit_threshold));
+        for (sym, v) in &self.min_profit_threshold_overrides {
+            unit(&format!("MIN_PROFIT_THRESHOLD_{}", sym.to_uppercase()), f(*v));
+        }
+        unit("RISK_IMBALANCE_THRESHOLD", f(self.risk_imbalance_threshold));
+        unit("DYNAMIC_SIZING_FRACTION", f(self.dynamic_sizing_fraction));
+        unit("HEDGE_TAKE_PROFIT_PCT", f(self.hedge_take_profit_pct));
+        unit("HEDGE_STOP_LOSS_PCT", f(self.hedge_stop_loss_pct));
+        unit("HEDGE_TRAILING_PCT", f(self.hedge_trailing_pct));
+        unit("ORDER_JITTER_PCT", self.order_jitter_pct);
+        unit("ARBITRAGE_EXECUTION_SPREAD", f(self.arbitrage_execution_spread));
+        unit("SPREAD_RAMP_START", f(self.spread_ramp_start));
+        unit("SPREAD_RAMP_END", f(self.spread_ramp_end));
+        unit("MIN_YES_PRICE_THRESHOLD", f(self.min_yes_price_threshold));
+        unit("MIN_NO_PRICE_THRESHOLD", f(self.min_no_price_threshold));
+        unit("MIN_VALID_ASK", f(self.min_valid_ask));
+        unit("MAX_PLAUSIBLE_PROFIT_PCT", f(self.max_plausible_profit_pct));
+        unit("WIND_DOWN_SELL_PRICE", f(self.wind_down_sell_price));
+        unit("WIND_DOWN_BOOK_PRICE_OFFSET", f(self.wind_down_book_price_offset));
+        unit("WIND_DOWN_SELL_PRICE_STEP", f(self.wind_down_sell_price_step));
+
+        let mut positive = |name: &str, v: f64| {
+            if v.is_nan() || v <= 0.0 {
+                errors.push(format!("{} 须大于 0，当前 {}", name, v));
+            }
+        };
+        positive("MAX_ORDER_SIZE_USDC", f(self.max_order_size_usdc));
+        positive("PRICE_TICK", f(self.price_tick));
+        for (sym, v) in &self.max_order_size_overrides {
+            positive(&format!("MAX_ORDER_SIZE_USDC_{}", sym.to_uppercase()), f(*v));
+        }
+        positive("RISK_MAX_EXPOSURE_USDC", f(self.risk_max_exposure_usdc));
+        positive("DYNAMIC_SIZING_FRACTION", f(self.dynamic_sizing_fraction));
+
+        let mut non_negative = |name: &str, v: f64| {
+            if v.is_nan() || v < 0.0 {
+                errors.push(format!("{} 不能为负，当前 {}", name, v));
+            }
+        };
+        non_negative("FEE_C", f(self.fee_c));
+        non_negative("DAILY_LOSS_LIMIT_USDC", f(self.daily_loss_limit_usdc));
+        non_negative("FEE_RATE", f(self.fee_rate));
+        non_negative("MIN_ARBITRAGE_SIZE", f(self.min_arbitrage_size));
+        non_negative("MAX_POSITION_PER_MARKET_USDC", f(self.max_position_per_market_usdc));
+        non_negative("MARKET_INVENTORY_TARGET", f(self.market_inventory_target));
+        non_negative("DYNAMIC_SIZING_FLOOR_USDC", f(self.dynamic_sizing_floor_usdc));
+        non_negative("MIN_TRADE_INTERVAL_SECS", self.min_trade_interval_secs);
+        non_negative("GLOBAL_TRADE_RATE_PER_SEC", self.global_trade_rate_per_sec);
+        non_negative("FEE_EXPONENT", f(self.fee_exponent));
+        non_negative("MERGE_MIN_SIZE", f(self.merge_min_size));
+        non_negative("POSITION_SYNC_TOLERANCE", f(self.position_sync_tolerance));
+        non_negative("POSITION_SYNC_WARN_THRESHOLD", f(self.position_sync_warn_threshold));
+        non_negative("POSITION_BALANCE_THRESHOLD", f(self.position_balance_threshold));
+        non_negative("POSITION_BALANCE_MIN_TOTAL", f(self.position_balance_min_total));
+
+        for (i, v) in self.slippage.iter().enumerate() {
+            if !(-MAX_SLIPPAGE..=MAX_SLIPPAGE).contains(v) {
+                errors.push(format!(
+                    "SLIPPAGE 第 {} 项须在 [-{}, {}] 内，当前 {}",
+                    i + 1, MAX_SLIPPAGE, MAX_SLIPPAGE, v
+                ));
+            }
+        }
+        if self.crypto_symbols.iter().all(|s| s.is_empty()) {
+            errors.push("CRYPTO_SYMBOLS 不能为空".to_string());
+        }
+        for sym in &self.trade_symbols {
+            if !self.crypto_symbols.contains(sym) {
+                errors.push(format!("TRADE_SYMBOLS 中的 {} 不在 CRYPTO_SYMBOLS 中", sym));
+            }
+        }
+        if self.trade_start_delay_secs as i64 >= self.market_window.secs() {
+            errors.push(format!(
+                "TRADE_START_DELAY_SECS 须小于窗口时长 {} 秒，当前 {}",
+                self.market_window.secs(),
+                self.trade_start_delay_secs
+            ));
+        }
+        let mut account_ids = vec![PRIMARY_ACCOUNT_ID];
+        for account in &self.extra_accounts {
+            if account_ids.contains(&account.id.as_str()) {
+                errors.push(format!("EXTRA_ACCOUNTS 中的账户 ID {} 重复（{} 为主账户保留）", account.id, PRIMARY_ACCOUNT_ID));
+            }
+            account_ids.push(&account.id);
+        }
+        if self.market_outcome_labels.is_empty() {
+            errors.push("MARKET_OUTCOME_LABELS 至少需要一对有效的结果标签，如 Up/Down".to_string());
+        }
+        if self.cancel_batch_size == 0 {
+            errors.push("CANCEL_BATCH_SIZE 须大于 0".to_string());
+        }
+        if self.max_inflight_trades == 0 {
+            errors.push("MAX_INFLIGHT_TRADES 须大于 0".to_string());
+        }
+        if self.arbitrage_order_type == OrderType::GTD && self.gtd_expiration_secs == 0 {
+            errors.push("ARBITRAGE_ORDER_TYPE=GTD 时 GTD_EXPIRATION_SECS 须大于 0".to_string());
+        }
+        for (name, decimals) in std::iter::once(("SIZE_DECIMALS".to_string(), self.size_decimals)).chain(
+            self.size_decimals_overrides.iter().map(|(sym, d)| (format!("SIZE_DECIMALS_{}", sym.to_uppercase()), *d)),
+        ) {
+            if decimals > MAX_SIZE_DECIMALS {
+                errors.push(format!("{} 不能超过 {}，当前 {}", name, MAX_SIZE_DECIMALS, decimals));
+            }
+        }
+        if self.profit_prioritize && self.per_market_tasks {
+            errors.push("PROFIT_PRIORITIZE 需要在主循环中统一排序，不能与 PER_MARKET_TASKS 同时启用".to_string());
+        }
+        if self.profit_prioritize && self.profit_prioritize_window_ms == 0 {
+            errors.push("PROFIT_PRIORITIZE_WINDOW_MS 须大于 0".to_string());
+        }
+        if self.post_only && !matches!(self.arbitrage_order_type, OrderType::GTC | OrderType::GTD) {
+            errors.push("POST_ONLY=true 时 ARBITRAGE_ORDER_TYPE 须为 GTC 或 GTD".to_string());
+        }
+
+        if !errors.is_empty() {
+            bail!("配置校验失败（共 {} 项）:\n  - {}", errors.len(), errors.join("\n  - "));
+        }
+        Ok(())
+    }
+
+    /// 手续费公式参数（套利检测与对冲卖出共用）
+    pub fn fee_params(&self) -> FeeParams {
+        FeeParams::new(self.fee_c, self.fee_rate, self.fee_exponent)
+    }
+
+    /// 按账户展开配置：主账户在前，每个附加账户一份，替换私钥、代理地址与持仓地址，
+    /// 订单对与对冲仓位的持久化文件名加上账户 ID 后缀以免互相覆盖，其余配置各账户共用
+    pub fn account_configs(&self) -> Vec<Config> {
+        let mut configs = vec![self.clone()];
+        for account in &self.extra_accounts {
+            let mut config = self.clone();
+            config.account_id = account.id.clone();
+            config.signer = account.signer.clone();
+            config.proxy_address = account.proxy_address;
+            config.wallet_address = account.wallet_address;
+            config.pending_pairs_path = self.pending_pairs_path.as_deref().map(|p| with_account_suffix(p, &account.id));
+            config.hedge_positions_path = self.hedge_positions_path.as_deref().map(|p| with_account_suffix(p, &account.id));
+            configs.push(config);
+        }
+        configs
+    }
+
+    /// 生效配置的单行摘要（不含私钥等敏感信息），用于启动日志
+    pub fn summary(&self) -> String {
+        format!(
+            "窗口:{} | 币种:{} | 交易币种:{} | 最小利润:{} | 最大下单:{} USDC | 敞口上限:{} USDC | 执行价差:{} | 多档:{} | 滑点:{:?} | 订单类型:{} | 手续费:{}×{}^{} | Merge间隔:{}分钟 | 收尾:{}分钟 | 模拟:{} | 账户数:{}",
+            self.market_window,
+            self.crypto_symbols.join(","),
+            self.trade_symbols.join(","),
+            self.min_profit_threshold,
+            self.max_order_size_usdc,
+            self.risk_max_exposure_usdc,
+            self.arbitrage_execution_spread,
+            self.arbitrage_multi_level,
+            self.slippage,
+            self.arbitrage_order_type,
+            self.fee_c,
+            self.fee_rate,
+            self.fee_exponent,
+            self.merge_interval_minutes,
+            self.wind_down_before_window_end_minutes,
+            self.dry_run,
+            1 + self.extra_accounts.len(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_symbol_overrides_take_precedence() {
+        let config = Config::for_test(&[
+            ("CRYPTO_SYMBOLS", "btc,eth"),
+            ("MAX_ORDER_SIZE_USDC", "50"),
+            ("MAX_ORDER_SIZE_USDC_BTC", "200"),
+            ("MIN_PROFIT_THRESHOLD", "0.002"),
+            ("MIN_PROFIT_THRESHOLD_ETH", "0.005"),
+        ]);
+        assert_eq!(config.max_order_size_for("btc"), dec!(200));
+        assert_eq!(config.max_order_size_for(" BTC "), dec!(200));
+        assert_eq!(config.max_order_size_for("eth"), dec!(50));
+        assert_eq!(config.min_profit_threshold_for("eth"), dec!(0.005));
+        assert_eq!(config.min_profit_threshold_for("btc"), dec!(0.002));
+        // 未配置的币种回退到全局值
+        assert_eq!(config.max_order_size_for("doge"), dec!(50));
+        assert_eq!(config.min_profit_threshold_for("doge"), dec!(0.002));
+    }
+
+    #[test]
+    fn size_decimals_override_and_limit() {
+        let config = Config::for_test(&[("CRYPTO_SYMBOLS", "btc,eth"), ("SIZE_DECIMALS_ETH", "4")]);
+        assert_eq!(config.size_decimals_for("eth"), 4);
+        assert_eq!(config.size_decimals_for("btc"), 2);
+
+        let config = Config::for_test(&[("CRYPTO_SYMBOLS", "btc,eth"), ("SIZE_DECIMALS_BTC", "7")]);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("SIZE_DECIMALS_BTC 不能超过 6，当前 7"), "{}", err);
+    }
+
+    #[test]
+    fn unparsable_per_symbol_override_fails_validation() {
+        let config = Config::for_test(&[("CRYPTO_SYMBOLS", "btc,eth"), ("MAX_ORDER_SIZE_USDC_BTC", "abc")]);
+        // 无法解析的覆盖项不生效
+        assert_eq!(config.max_order_size_for("btc"), config.max_order_size_usdc);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("MAX_ORDER_SIZE_USDC_BTC 无法解析，当前 abc"), "{}", err);
+    }
+
+    #[test]
+    fn wallet_address_follows_proxy_or_signer() {
+        // EOA 模式：持仓地址为私钥推导的地址
+        let eoa = Config::for_test(&[]);
+        assert_eq!(eoa.proxy_address, None);
+        assert_eq!(
+            eoa.wallet_address,
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse::<Address>().unwrap()
+        );
+        // 代理钱包模式：持仓地址为代理地址
+        let proxy: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse().unwrap();
+        let config = Config::for_test(&[("POLYMARKET_PROXY_ADDRESS", "0x70997970C51812dc3A010C7d01b50e0d17dc79C8")]);
+        assert_eq!(config.proxy_address, Some(proxy));
+        assert_eq!(config.wallet_address, proxy);
+    }
 }
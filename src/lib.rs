@@ -1,5 +1,6 @@
 //! poly_15min_bot 库：供主程序和 binaries 复用的模块。
 
+pub mod clock;
 pub mod merge;
 pub mod positions;
 pub mod trial;
\ No newline at end of file
@@ -5,8 +5,9 @@ mod risk;
 mod trading;
 mod utils;
 
-use poly_5min_bot::merge;
-use poly_5min_bot::positions::{get_positions, Position};
+use poly_5min_bot::clock::{SharedClock, SystemClock};
+use poly_5min_bot::merge::MergeError;
+use poly_5min_bot::positions::{get_positions, yes_no_positions, Position};
 
 use anyhow::Result;
 use dashmap::DashMap;
@@ -15,167 +16,2206 @@ use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument};
+use polymarket_client_sdk::clob::types::Side;
 use polymarket_client_sdk::types::{Address, B256, U256};
 
 use crate::config::Config;
-use crate::market::{MarketDiscoverer, MarketInfo, MarketScheduler};
-use crate::monitor::{ArbitrageDetector, OrderBookMonitor};
+use crate::market::{cap_markets, MarketDiscoverer, MarketInfo, MarketScheduler, SymbolPriority};
+use crate::monitor::recorder::BookRecorder;
+use crate::monitor::{cached_best_bid, replay, user_stream, ArbitrageDetector, ArbitrageOpportunity, MarketInventory, BookCache, OrderBookMonitor, OrderBookPair};
 use crate::risk::positions::PositionTracker;
 use crate::risk::{HedgeMonitor, PositionBalancer, RiskManager};
+use crate::trading::executor::filled_amounts;
+use crate::trading::orders::SizePrecision;
+use crate::trading::signer::BotSigner;
 use crate::trading::TradingExecutor;
+use crate::utils::{arbitrage_logger, display_time, metrics, status};
+use crate::utils::errors::ArbitrageError;
+use crate::utils::jitter::OrderJitter;
+use crate::utils::kill_switch::HaltSwitch;
+use crate::utils::notifier::{self, Notifier, NotifyEvent};
+use crate::utils::rpc_pool::{EndpointHealth, RpcPool};
+use crate::utils::skip_logger::SkipLogger;
+use crate::utils::throttle::TradeThrottle;
+use crate::utils::window_report::{SkipReason, WindowReport};
+
+/// CLOB API 端点（下单健康闸门探测的目标）
+const CLOB_ENDPOINT: &str = "https://clob.polymarket.com";
+
+/// 为一个窗口的市场创建订单簿监控器并订阅所有市场（尚未建立订阅流）
+fn build_orderbook_monitor(
+    markets: &[MarketInfo],
+    config: &Config,
+    recorder: &Option<BookRecorder>,
+    book_cache: &BookCache,
+) -> OrderBookMonitor {
+    let max_book_age = (config.book_staleness_ms > 0).then(|| Duration::from_millis(config.book_staleness_ms));
+    let mut monitor = OrderBookMonitor::new()
+        .with_recorder(recorder.clone())
+        .with_book_cache(book_cache.clone())
+        .with_max_book_age(max_book_age)
+        .with_log_depth(config.orderbook_log_depth);
+    for market in markets {
+        if let Err(e) = monitor.subscribe_market(market) {
+            error!(error = %e, market_id = %market.market_id, "订阅市场失败");
+        }
+    }
+    monitor
+}
 
 /// 从持仓中筛出 **YES 和 NO 都持仓** 的 condition_id，仅这些市场才能 merge；单边持仓直接跳过。
-/// Data API 可能返回 outcome_index 0/1（0=Yes, 1=No）或 1/2（与 CTF index_set 一致），两种都支持。
+/// outcome_index 的两种约定由 `normalize_outcome` 统一处理。
 fn condition_ids_with_both_sides(positions: &[Position]) -> Vec<B256> {
-    let mut by_condition: HashMap<B256, HashSet<i32>> = HashMap::new();
-    for p in positions {
-        if p.size <= dec!(0) {
+    merge_info_with_both_sides(positions).into_keys().collect()
+}
+
+/// 从持仓中构建 condition_id -> (yes_token_id, no_token_id, merge_amount)，用于 merge 成功后扣减敞口。
+/// outcome_index 的两种约定由 `normalize_outcome` 统一处理。
+fn merge_info_with_both_sides(positions: &[Position]) -> HashMap<B256, (U256, U256, Decimal)> {
+    yes_no_positions(positions)
+        .into_iter()
+        .filter_map(|(c, sides)| match sides {
+            (Some(yes), Some(no)) if yes.size > dec!(0) && no.size > dec!(0) => {
+                Some((c, (yes.asset, no.asset, yes.size.min(no.size))))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// 定时 merge 与收尾 merge 的候选市场：双边可合并数量低于 min_size（MERGE_MIN_SIZE）的粉尘市场跳过，
+/// 不值得为其付 gas。返回 (condition_id 列表, merge 信息, 因低于阈值被跳过的市场数)
+fn merge_candidates(
+    positions: &[Position],
+    min_size: Decimal,
+) -> (Vec<B256>, HashMap<B256, (U256, U256, Decimal)>, usize) {
+    let mut merge_info = merge_info_with_both_sides(positions);
+    let before = merge_info.len();
+    merge_info.retain(|_, (_, _, amount)| *amount >= min_size);
+    let skipped = before - merge_info.len();
+    (merge_info.keys().copied().collect(), merge_info, skipped)
+}
+
+/// 进行中的 Merge（按 condition_id，每账户一个）：定时 Merge 与 AUTO_MERGE_ON_FILL 共用，同一市场同时只有一个 Merge
+#[derive(Default)]
+struct MergeGuard(Mutex<HashSet<B256>>);
+
+impl MergeGuard {
+    /// 占用 condition_id，该市场已有 Merge 进行中时返回 None；返回的占用在 drop 时释放
+    fn claim(self: &Arc<Self>, condition_id: B256) -> Option<MergeClaim> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(condition_id)
+            .then(|| MergeClaim { guard: self.clone(), condition_id })
+    }
+}
+
+struct MergeClaim {
+    guard: Arc<MergeGuard>,
+    condition_id: B256,
+}
+
+impl Drop for MergeClaim {
+    fn drop(&mut self) {
+        self.guard.0.lock().unwrap().remove(&self.condition_id);
+    }
+}
+
+/// Merge 成功后扣减双边持仓与风险敞口并记录盈亏（先扣敞口再扣持仓，保证 update_exposure_cost 读到的是合并前持仓）
+fn deduct_merged(position_tracker: &PositionTracker, condition_id: B256, (yes_token, no_token, merge_amt): (U256, U256, Decimal)) {
+    position_tracker.update_exposure_cost(yes_token, dec!(0), -merge_amt);
+    position_tracker.update_exposure_cost(no_token, dec!(0), -merge_amt);
+    position_tracker.update_position(yes_token, -merge_amt);
+    position_tracker.update_position(no_token, -merge_amt);
+    position_tracker.pnl().record_merge(condition_id, yes_token, no_token, merge_amt);
+    metrics::set_exposure(position_tracker.calculate_exposure());
+}
+
+/// AUTO_MERGE_ON_FILL：订单对两腿完全成交后立即对该市场执行一次 Merge，不等定时 Merge。
+/// 先等 MERGE_SPACING_SECS 让成交上链并与其他 Merge 保持间隔，再按持仓确认双边数量（低于 MERGE_MIN_SIZE 跳过）；
+/// 该市场已有 Merge 进行中（定时 Merge 或上一次自动 Merge）或收尾进行中时跳过
+async fn auto_merge_pair(
+    config: Config,
+    rpc_pool: Arc<RpcPool>,
+    position_tracker: Arc<PositionTracker>,
+    merge_guard: Arc<MergeGuard>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    notifier: Arc<dyn Notifier>,
+    condition_id: B256,
+) {
+    if wind_down_in_progress.load(Ordering::Relaxed) {
+        info!(condition_id = %condition_id, "⏭️ 自动 Merge：收尾进行中，跳过");
+        return;
+    }
+    sleep(Duration::from_secs(config.merge_spacing_secs)).await;
+    // 等待期间可能已开始收尾，收尾会自行 Merge 该市场
+    if wind_down_in_progress.load(Ordering::Relaxed) {
+        info!(condition_id = %condition_id, "⏭️ 自动 Merge：收尾进行中，跳过");
+        return;
+    }
+    // 等待结束后才占用该市场：占用后收尾会跳过该市场，此时即使收尾已开始也由本任务完成 Merge
+    let Some(_claim) = merge_guard.claim(condition_id) else {
+        debug!(condition_id = %condition_id, "⏭️ 自动 Merge：该市场已有 Merge 进行中，跳过");
+        return;
+    };
+    let merge_min_size = config.merge_min_size;
+    let merge_info = match get_positions(config.wallet_address).await {
+        Ok(positions) => merge_candidates(&positions, merge_min_size).1.remove(&condition_id),
+        Err(e) => {
+            warn!(condition_id = %condition_id, error = %e, "❌ 自动 Merge：获取持仓失败，留给定时 Merge");
+            return;
+        }
+    };
+    let Some(info) = merge_info else {
+        debug!(condition_id = %condition_id, "⏭️ 自动 Merge：双边持仓不足 MERGE_MIN_SIZE ({}) 或尚未上链，跳过", merge_min_size);
+        return;
+    };
+    let retry_base_delay = Duration::from_secs(config.merge_retry_base_secs);
+    let (result, attempts) =
+        merge_with_retry(&rpc_pool, condition_id, config.wallet_address, &config.signer, config.merge_max_retries, retry_base_delay).await;
+    match result {
+        Ok(tx) => {
+            metrics::record_merge(true);
+            status::record_merge();
+            notifier.notify(NotifyEvent::MergeSucceeded { condition_id, amount: Some(info.2) });
+            info!(
+                "✅ 自动 Merge 完成 | condition_id={:#x} | 数量:{} | 尝试:{}次 | 时间:{}",
+                condition_id, info.2, attempts, display_time::fmt(chrono::Utc::now())
+            );
+            info!("  📝 tx={}", tx);
+            deduct_merged(&position_tracker, condition_id, info);
+            info!("💰 自动 Merge 已扣减敞口 | condition_id={:#x} | 数量:{}", condition_id, info.2);
+        }
+        Err(MergeError::NoShares { .. }) => {
+            debug!(condition_id = %condition_id, "⏭️ 自动 Merge：无可用份额");
+        }
+        Err(e) => {
+            metrics::record_merge(false);
+            notifier.notify(NotifyEvent::MergeFailed { condition_id, error: e.to_string() });
+            warn!(condition_id = %condition_id, error = %e, attempts, "❌ 自动 Merge 失败，留给定时 Merge");
+        }
+    }
+}
+
+/// 经 RPC 端点池执行 merge_max（端点间自动故障切换），所有端点仍限速时按指数退避（带随机抖动）重试，最多重试 max_retries 次；其他错误立即返回。
+/// 返回结果与实际尝试次数（含首次）。
+async fn merge_with_retry(
+    rpc_pool: &RpcPool,
+    condition_id: B256,
+    proxy: Address,
+    signer: &BotSigner,
+    max_retries: u32,
+    base_delay: Duration,
+) -> (Result<String, MergeError>, u32) {
+    let mut attempts = 0u32;
+    loop {
+        attempts += 1;
+        let result = rpc_pool.merge_max(condition_id, proxy, signer).await;
+        let retryable = matches!(&result, Err(MergeError::RateLimited(_)));
+        if !retryable || attempts > max_retries {
+            return (result, attempts);
+        }
+        let backoff = base_delay.saturating_mul(1u32 << (attempts - 1).min(16));
+        let jitter_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64 % 1000)
+            .unwrap_or(0);
+        let delay = backoff + Duration::from_millis(jitter_ms);
+        warn!(
+            condition_id = %condition_id,
+            attempt = attempts,
+            max_retries,
+            "⏳ RPC 限速，等待 {:.1}s 后重试",
+            delay.as_secs_f64()
+        );
+        sleep(delay).await;
+    }
+}
+
+/// 定时 Merge 任务：每 MERGE_INTERVAL_MINUTES 分钟拉取**持仓**，仅对 YES+NO 双边都持仓的市场 **串行**执行 merge_max，
+/// 单边持仓跳过；每笔之间间隔、对 RPC 限速按指数退避重试（见 merge_with_retry）。Merge 成功后扣减 position_tracker 的持仓与敞口。
+/// 首次执行前短暂延迟，避免与订单簿监听的启动抢占同一 runtime，导致阻塞 stream。
+async fn run_merge_task(
+    config: Config,
+    rpc_pool: Arc<RpcPool>,
+    position_tracker: Arc<PositionTracker>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    merge_guard: Arc<MergeGuard>,
+    notifier: Arc<dyn Notifier>,
+    clock: SharedClock,
+) {
+    let interval = Duration::from_secs(config.merge_interval_minutes * 60);
+    let retry_base_delay = Duration::from_secs(config.merge_retry_base_secs);
+    let merge_min_size = config.merge_min_size;
+    // 每笔 merge 之间间隔，降低 RPC bursts
+    let merge_spacing = Duration::from_secs(config.merge_spacing_secs);
+
+    // 先让主循环完成 get_markets、创建 stream 并进入订单簿监听，再执行第一次 merge（MERGE_INITIAL_DELAY_SECS）
+    clock.sleep(Duration::from_secs(config.merge_initial_delay_secs)).await;
+
+    loop {
+        if wind_down_in_progress.load(Ordering::Relaxed) {
+            info!("收尾进行中，本轮回 merge 跳过");
+            clock.sleep(interval).await;
+            continue;
+        }
+        let (condition_ids, merge_info, skipped) = match get_positions(config.wallet_address).await {
+            Ok(positions) => merge_candidates(&positions, merge_min_size),
+            Err(e) => {
+                warn!(error = %e, "❌ 获取持仓失败，跳过本轮回 merge");
+                clock.sleep(interval).await;
+                continue;
+            }
+        };
+
+        if skipped > 0 {
+            info!(skipped, "⏭️ 本轮回 merge: {} 个市场双边数量低于 MERGE_MIN_SIZE ({})，跳过", skipped, merge_min_size);
+        }
+        if condition_ids.is_empty() {
+            debug!("🔄 本轮回 merge: 无满足 YES+NO 双边持仓的市场");
+        } else {
+            info!(
+                count = condition_ids.len(),
+                "🔄 本轮回 merge: 共 {} 个市场满足 YES+NO 双边持仓",
+                condition_ids.len()
+            );
+        }
+
+        for (i, &condition_id) in condition_ids.iter().enumerate() {
+            // 第 2 个及以后的市场：先等 MERGE_SPACING_SECS 再 merge，避免与上一笔链上处理重叠
+            if i > 0 {
+                info!("本轮回 merge: 等待 {} 秒后合并下一市场 (第 {}/{} 个)", merge_spacing.as_secs(), i + 1, condition_ids.len());
+                clock.sleep(merge_spacing).await;
+            }
+            // AUTO_MERGE_ON_FILL 正在合并该市场时跳过，其结果已扣减持仓
+            let Some(_claim) = merge_guard.claim(condition_id) else {
+                debug!(condition_id = %condition_id, "⏭️ 跳过 merge: 自动 Merge 进行中");
+                continue;
+            };
+            let (result, attempts) =
+                merge_with_retry(&rpc_pool, condition_id, config.wallet_address, &config.signer, config.merge_max_retries, retry_base_delay).await;
+            match result {
+                Ok(tx) => {
+                    metrics::record_merge(true);
+                    status::record_merge();
+                    notifier.notify(NotifyEvent::MergeSucceeded {
+                        condition_id,
+                        amount: merge_info.get(&condition_id).map(|(_, _, amt)| *amt),
+                    });
+                    info!(
+                        "✅ Merge 完成 | condition_id={:#x} | 尝试:{}次 | 时间:{}",
+                        condition_id, attempts, display_time::fmt(clock.now())
+                    );
+                    info!("  📝 tx={}", tx);
+                    // Merge 成功：扣减持仓与风险敞口
+                    if let Some(&info) = merge_info.get(&condition_id) {
+                        deduct_merged(&position_tracker, condition_id, info);
+                        info!(
+                            "💰 Merge 已扣减敞口 | condition_id={:#x} | 数量:{}",
+                            condition_id, info.2
+                        );
+                    }
+                }
+                Err(MergeError::NoShares { .. }) => {
+                    debug!(condition_id = %condition_id, "⏭️ 跳过 merge: 无可用份额");
+                }
+                Err(e) => {
+                    metrics::record_merge(false);
+                    notifier.notify(NotifyEvent::MergeFailed { condition_id, error: e.to_string() });
+                    warn!(condition_id = %condition_id, error = %e, attempts, "❌ Merge 失败");
+                }
+            }
+            tokio::task::yield_now().await;
+        }
+
+        clock.sleep(interval).await;
+    }
+}
+
+/// 卖出单腿持仓（收尾与持仓超时强平共用），size 按该市场的数量精度向下截断，过小则跳过。
+/// 卖价默认为 `wind_down_sell_price`；启用 `wind_down_use_book_price` 时取 book_cache 中的买一价减偏移，
+/// 无订单簿时回退到 `wind_down_sell_price`。`wind_down_sell_retries` > 0 时以 FAK 卖出并按最新买一价逐次让价重试，
+/// 否则单次 GTC 限价卖出。返回 (卖出份额, 所得 USDC)，失败或跳过时返回 None。context 为日志前缀，如「收尾」
+async fn sell_leg(
+    executor: &TradingExecutor,
+    config: &Config,
+    book_cache: &BookCache,
+    token_id: U256,
+    size: Decimal,
+    context: &str,
+) -> Option<(Decimal, Decimal)> {
+    let size_floor = executor.size_precision().round(token_id, size);
+    if size_floor < executor.size_precision().min_size(token_id) {
+        debug!(token_id = %token_id, size = %size, "{}：持仓过小，跳过卖出", context);
+        return None;
+    }
+    let wind_down_sell_price = config.wind_down_sell_price;
+    let sell_price = if config.wind_down_use_book_price {
+        let book_price_offset = config.wind_down_book_price_offset;
+        match cached_best_bid(book_cache, token_id) {
+            Some(bid) => (bid - book_price_offset).max(dec!(0.01)),
+            None => {
+                debug!(token_id = %token_id, "{}：无订单簿买一价，按 WIND_DOWN_SELL_PRICE 卖出", context);
+                wind_down_sell_price
+            }
+        }
+    } else {
+        wind_down_sell_price
+    };
+    // WIND_DOWN_SELL_RETRIES > 0：FAK 卖出，未卖完时按最新买一价逐次让价重试；否则单次 GTC 限价卖出
+    if config.wind_down_sell_retries > 0 {
+        let sell_price_step = config.wind_down_sell_price_step;
+        return match executor
+            .sell_with_reprice(
+                token_id,
+                sell_price,
+                size_floor,
+                config.wind_down_sell_retries,
+                sell_price_step,
+                || cached_best_bid(book_cache, token_id),
+            )
+            .await
+        {
+            Ok((sold, usdc)) => {
+                if sold < size_floor {
+                    warn!("⚠️ {}：单腿未卖完 | token_id={:#x} | 卖出:{}/{}", context, token_id, sold, size_floor);
+                } else {
+                    info!("✅ {}：已卖出 | token_id={:#x} | 数量:{} | 所得:{:.2} USDC", context, token_id, sold, usdc);
+                }
+                Some((sold, usdc))
+            }
+            Err(e) => {
+                warn!(token_id = %token_id, size = %size, error = %e, "{}：卖出单腿失败", context);
+                None
+            }
+        };
+    }
+    match executor.sell_at_price(token_id, sell_price, size_floor).await {
+        Ok(resp) => {
+            info!("✅ {}：已下卖单 | token_id={:#x} | 数量:{} | 价格:{:.4}", context, token_id, size_floor, sell_price);
+            Some(filled_amounts(&resp, Side::Sell))
+        }
+        Err(e) => {
+            warn!(token_id = %token_id, size = %size, error = %e, "{}：卖出单腿失败", context);
+            None
+        }
+    }
+}
+
+/// 各市场 YES/NO 持仓之差：返回 (持仓较多一侧的 token_id, 多出的数量)，即无法 merge 的单腿部分
+fn single_leg_excess(positions: &[Position]) -> Vec<(U256, Decimal)> {
+    yes_no_positions(positions)
+        .into_values()
+        .filter_map(|(yes, no)| {
+            let yes_size = yes.map(|p| p.size).unwrap_or(dec!(0));
+            let no_size = no.map(|p| p.size).unwrap_or(dec!(0));
+            match (yes, no) {
+                (Some(y), _) if yes_size > no_size => Some((y.asset, yes_size - no_size)),
+                (_, Some(n)) if no_size > yes_size => Some((n.asset, no_size - yes_size)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// 持仓时长上限任务：每 check_interval 从 API 获取持仓，单腿部分持有超过 max_age 的按收尾卖出逻辑强制卖出，
+/// 并扣减本地持仓与敞口。收尾进行中时跳过，避免与收尾卖出重复下单。
+async fn run_position_age_task(
+    executor: Arc<TradingExecutor>,
+    config: Config,
+    position_tracker: Arc<PositionTracker>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    book_cache: BookCache,
+    max_age: Duration,
+    check_interval: Duration,
+) {
+    loop {
+        sleep(check_interval).await;
+        if wind_down_in_progress.load(Ordering::Relaxed) {
             continue;
         }
-        by_condition
-            .entry(p.condition_id)
-            .or_default()
-            .insert(p.outcome_index);
+        let positions = match get_positions(config.wallet_address).await {
+            Ok(p) => p,
+            Err(e) => {
+                warn!(error = %e, "持仓超时检查：获取持仓失败，将在下次循环重试");
+                continue;
+            }
+        };
+        for (token_id, excess) in single_leg_excess(&positions) {
+            let Some(age) = position_tracker.position_age(token_id) else { continue };
+            if age < max_age {
+                continue;
+            }
+            warn!(
+                "⏰ 单腿持仓超过持仓时长上限，强制卖出 | token_id={:#x} | 数量:{} | 已持有:{}秒",
+                token_id, excess, age.as_secs()
+            );
+            if let Some((sold, usdc)) = sell_leg(&executor, &config, &book_cache, token_id, excess, "持仓超时").await {
+                if sold > dec!(0) {
+                    // 先扣敞口再扣持仓，与 merge 一致
+                    position_tracker.update_exposure_cost(token_id, dec!(0), -sold);
+                    position_tracker.update_position(token_id, -sold);
+                    position_tracker.pnl().record_sell(token_id, sold, usdc);
+                    metrics::set_exposure(position_tracker.calculate_exposure());
+                }
+            }
+        }
+    }
+}
+
+/// 是否已到收尾时间：距窗口结束 <= before_minutes 分钟（按秒比较）
+fn wind_down_due(now: chrono::DateTime<chrono::Utc>, window_end: chrono::DateTime<chrono::Utc>, before_minutes: u64) -> bool {
+    (window_end - now).num_seconds() <= before_minutes as i64 * 60
+}
+
+/// 收尾：取消所有挂单 → Merge 双边持仓（并扣减敞口）→ 卖出剩余单腿持仓（见 [`sell_leg`]）。
+/// 窗口结束前的定时收尾与收到退出信号时的收尾共用此流程。
+/// 调用方须先将 `wind_down_in_progress` 置为 true（定时 merge 据此跳过），本函数结束时将其复位。
+/// 收尾的 Merge 同样经 merge_guard 占用市场，已有 Merge 进行中的市场跳过。
+async fn wind_down(
+    executor: Arc<TradingExecutor>,
+    config: Config,
+    rpc_pool: Arc<RpcPool>,
+    risk_manager: Arc<RiskManager>,
+    merge_guard: Arc<MergeGuard>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    notifier: Arc<dyn Notifier>,
+    book_cache: BookCache,
+    clock: SharedClock,
+    reason: &str,
+) {
+    notifier.notify(NotifyEvent::WindDownStarted { reason: reason.to_string() });
+    // 与定时 merge 共用 MERGE_SPACING_SECS 与限速重试配置
+    let merge_spacing = Duration::from_secs(config.merge_spacing_secs);
+    let retry_base_delay = Duration::from_secs(config.merge_retry_base_secs);
+
+    // 1. 取消所有挂单
+    match executor.cancel_all_orders().await {
+        Ok(summary) => summary.log("收尾"),
+        Err(e) => warn!(error = %e, "收尾：取消所有挂单失败，继续执行 Merge 与卖出"),
+    }
+
+    // 取消后等 WIND_DOWN_DELAY_AFTER_CANCEL_SECS 再 Merge，避免取消前刚成交的订单尚未上链更新持仓
+    clock.sleep(Duration::from_secs(config.wind_down_delay_after_cancel_secs)).await;
+
+    // 2. Merge 双边持仓（每完成一个市场后等 MERGE_SPACING_SECS 再合并下一个，限速时退避重试）并更新敞口
+    let position_tracker = risk_manager.position_tracker();
+    let mut did_any_merge = false;
+    if executor.is_dry_run() {
+        info!("🧪 [DRY RUN] 收尾：将 Merge 双边持仓（未实际执行）");
+    } else {
+        match get_positions(config.wallet_address).await {
+            Ok(positions) => {
+                let merge_min_size = config.merge_min_size;
+                let (condition_ids, merge_info, skipped) = merge_candidates(&positions, merge_min_size);
+                if skipped > 0 {
+                    info!(skipped, "⏭️ 收尾：{} 个市场双边数量低于 MERGE_MIN_SIZE ({})，跳过 Merge", skipped, merge_min_size);
+                }
+                let n = condition_ids.len();
+                for (i, condition_id) in condition_ids.iter().enumerate() {
+                    let Some(_claim) = merge_guard.claim(*condition_id) else {
+                        info!(condition_id = %condition_id, "⏭️ 收尾：该市场已有 Merge 进行中，跳过");
+                        continue;
+                    };
+                    let (result, _) = merge_with_retry(
+                        &rpc_pool,
+                        *condition_id,
+                        config.wallet_address,
+                        &config.signer,
+                        config.merge_max_retries,
+                        retry_base_delay,
+                    )
+                    .await;
+                    match result {
+                        Ok(tx) => {
+                            did_any_merge = true;
+                            metrics::record_merge(true);
+                            status::record_merge();
+                            notifier.notify(NotifyEvent::MergeSucceeded {
+                                condition_id: *condition_id,
+                                amount: merge_info.get(condition_id).map(|(_, _, amt)| *amt),
+                            });
+                            info!(
+                                "✅ 收尾：Merge 完成 | condition_id={:#x} | tx={} | 时间:{}",
+                                condition_id, tx, display_time::fmt(clock.now())
+                            );
+                            if let Some(&info) = merge_info.get(condition_id) {
+                                deduct_merged(&position_tracker, *condition_id, info);
+                                info!("💰 收尾：Merge 已扣减敞口 | condition_id={:#x} | 数量:{}", condition_id, info.2);
+                            }
+                        }
+                        Err(e) => {
+                            metrics::record_merge(false);
+                            notifier.notify(NotifyEvent::MergeFailed { condition_id: *condition_id, error: e.to_string() });
+                            warn!(condition_id = %condition_id, error = %e, "收尾：Merge 失败");
+                        }
+                    }
+                    // 每完成一个市场的 merge 后等 MERGE_SPACING_SECS 再处理下一个，给链上时间
+                    if i + 1 < n {
+                        info!("收尾：等待 {} 秒后合并下一市场", merge_spacing.as_secs());
+                        clock.sleep(merge_spacing).await;
+                    }
+                }
+            }
+            Err(e) => { warn!(error = %e, "收尾：获取持仓失败，跳过 Merge"); }
+        }
+    }
+
+    // 若有执行过 Merge，等 MERGE_SPACING_SECS 再卖出单腿，给链上处理时间；无 Merge 则不等
+    if did_any_merge {
+        clock.sleep(merge_spacing).await;
+    }
+
+    // 3. 市价卖出剩余单腿持仓
+    match get_positions(config.wallet_address).await {
+        Ok(positions) => {
+            for pos in positions.iter().filter(|p| p.size > dec!(0)) {
+                if let Some((sold, usdc)) = sell_leg(&executor, &config, &book_cache, pos.asset, pos.size, "收尾").await {
+                    position_tracker.pnl().record_sell(pos.asset, sold, usdc);
+                }
+            }
+        }
+        Err(e) => { warn!(error = %e, "收尾：获取持仓失败，跳过卖出"); }
+    }
+
+    info!("🛑 收尾完成");
+    notifier.notify(NotifyEvent::WindDownFinished);
+    wind_down_in_progress.store(false, Ordering::Relaxed);
+}
+
+/// 启动认证的重试间隔上限（指数退避封顶）
+const MAX_AUTH_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// 认证失败是否明显为永久性（私钥或参数无效、凭证被拒、地区限制），此类失败重试无意义，立即退出。
+/// SDK 错误已转换为文本，只能按错误信息识别，关键字集中在此处维护
+fn is_permanent_auth_error(e: &anyhow::Error) -> bool {
+    let msg = e.to_string().to_ascii_lowercase();
+    ["validation", "invalid", "geoblock", "401", "403", "unauthorized", "forbidden"]
+        .iter()
+        .any(|p| msg.contains(p))
+}
+
+/// 启动认证步骤的重试：临时性失败（网络中断、超时、5xx、限速等）按 AUTH_RETRY_DELAY_SECS 起指数退避
+/// 重试至多 AUTH_MAX_RETRIES 次，避免启动时 Polymarket API 短暂不可用就需要人工重启；永久性失败立即返回
+async fn retry_auth<T, F, Fut>(config: &Config, step: &str, mut attempt_fn: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut attempt = 0u32;
+    loop {
+        match attempt_fn().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= config.auth_max_retries || is_permanent_auth_error(&e) => return Err(e),
+            Err(e) => {
+                attempt += 1;
+                let delay = Duration::from_secs(config.auth_retry_delay_secs)
+                    .saturating_mul(1u32 << (attempt - 1).min(16))
+                    .min(MAX_AUTH_RETRY_DELAY);
+                warn!(
+                    error = %e,
+                    attempt,
+                    max_retries = config.auth_max_retries,
+                    delay_secs = delay.as_secs(),
+                    "{}失败（可能为临时性错误），稍后重试",
+                    step
+                );
+                sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// 加载配置：设置 POLY_BOT_CONFIG 时从 TOML 文件加载（环境变量仍可覆盖文件中的值），否则仅从环境变量加载
+fn load_config() -> Result<Config> {
+    match std::env::var("POLY_BOT_CONFIG") {
+        Ok(path) if !path.trim().is_empty() => {
+            tracing::info!(path = %path, "从配置文件加载配置");
+            Config::from_file(std::path::Path::new(&path))
+        }
+        _ => Config::from_env(),
+    }
+}
+
+/// 启动自检（`--validate-only` 或 VALIDATE_ONLY=true）：依次检查许可证、配置、签名器、交易接口认证、
+/// 当前窗口的市场发现与 RPC / CLOB 端点，打印通过/失败报告。不下单、不进入交易循环；返回进程退出码（全部通过为 0，否则为 1）。
+async fn run_validation() -> i32 {
+    let mut report: Vec<(&str, Result<String>)> = Vec::new();
+
+    report.push(("许可证", poly_5min_bot::trial::check_license().map(|_| "有效".to_string())));
+
+    let config = load_config().and_then(|c| c.validate().map(|_| c));
+    match config {
+        Err(e) => report.push(("配置", Err(e))),
+        Ok(config) => {
+            report.push(("配置", Ok(config.summary())));
+
+            // 签名器在配置加载时已由私钥来源创建，私钥无效或 keystore 解密失败会体现在「配置」项
+            report.push(("签名器", Ok(format!("地址 {}", alloy::signers::Signer::address(&config.signer)))));
+            let auth = async {
+                let executor = TradingExecutor::new(
+                    config.signer.clone(),
+                    config.max_order_size_overrides.values().fold(config.max_order_size_usdc, |a, &b| a.max(b)),
+                    config.proxy_address,
+                    config.slippage,
+                    config.gtd_expiration_secs,
+                    config.arbitrage_order_type.clone(),
+                    true, // 自检不下单
+                )
+                .await?;
+                executor.verify_authentication().await
+            };
+            report.push(("交易接口认证", auth.await.map(|_| "API 调用正常".to_string())));
+
+            let discoverer = MarketDiscoverer::new(config.crypto_symbols.clone(), config.market_window)
+                .with_outcome_labels(config.market_outcome_labels.clone());
+            let window = MarketDiscoverer::calculate_current_window_timestamp(chrono::Utc::now(), config.market_window.secs());
+            let discovery = match discoverer.get_markets_for_timestamp(window).await {
+                Ok(markets) if markets.is_empty() => Err(anyhow::anyhow!("当前窗口 {} 未发现任何市场", window)),
+                Ok(markets) => Ok(format!("当前窗口发现 {} 个市场", markets.len())),
+                Err(e) => Err(e),
+            };
+            report.push(("市场发现", discovery));
+
+            let checker = Arc::new(rpc_check::RpcChecker::new(
+                rpc_check::CheckConfig::builder().timeout(Duration::from_secs(5)).build(),
+            ));
+            let rpc_urls = if config.polygon_rpc_urls.is_empty() {
+                vec![poly_5min_bot::merge::RPC_URL_DEFAULT.to_string()]
+            } else {
+                config.polygon_rpc_urls.clone()
+            };
+            let clob = rpc_check::EndpointValidator::new().normalize(CLOB_ENDPOINT);
+            for url in rpc_urls.iter().chain(std::iter::once(&clob)) {
+                let result = if !checker.validate_endpoint(url) {
+                    Err(anyhow::anyhow!("端点格式无效"))
+                } else if EndpointHealth::new(url.clone(), checker.clone()).probe().await {
+                    Ok("可用".to_string())
+                } else {
+                    Err(anyhow::anyhow!("健康检查未通过"))
+                };
+                report.push(("端点", result.map(|m| format!("{} {}", url, m)).map_err(|e| anyhow::anyhow!("{} {}", url, e))));
+            }
+        }
+    }
+
+    let failed = report.iter().filter(|(_, r)| r.is_err()).count();
+    info!("🩺 启动自检报告");
+    for (name, result) in &report {
+        match result {
+            Ok(detail) => info!("  ✅ {} | {}", name, detail),
+            Err(e) => error!("  ❌ {} | {}", name, e),
+        }
+    }
+    if failed == 0 {
+        info!("✅ 自检通过（{} 项）", report.len());
+        0
+    } else {
+        error!("❌ 自检失败 | {}/{} 项未通过", failed, report.len());
+        1
+    }
+}
+
+/// 维护模式：按需取消所有挂单，并对所有 YES+NO 双边持仓各执行一次 merge_max（每笔间隔 MERGE_SPACING_SECS，限速时退避重试），然后返回。
+/// 不启动订单簿监控与定时任务；DRY_RUN 下只打印将执行的操作。
+async fn run_maintenance(
+    executor: &TradingExecutor,
+    config: &Config,
+    rpc_pool: &RpcPool,
+    cancel_all: bool,
+    merge_once: bool,
+) -> Result<()> {
+    let merge_spacing = Duration::from_secs(config.merge_spacing_secs);
+    info!(cancel_all, merge_once, "🧰 维护模式：执行后退出，不进入交易循环");
+
+    if cancel_all {
+        // executor 在 DRY_RUN 下只记录撤单意图
+        executor.cancel_all_orders().await?.log("维护");
+    }
+
+    if merge_once {
+        let positions = get_positions(config.wallet_address).await?;
+        let condition_ids = condition_ids_with_both_sides(&positions);
+        let merge_info = merge_info_with_both_sides(&positions);
+        info!(count = condition_ids.len(), "维护：共 {} 个市场满足 YES+NO 双边持仓", condition_ids.len());
+
+        let retry_base_delay = Duration::from_secs(config.merge_retry_base_secs);
+        let mut failed = 0usize;
+        for (i, &condition_id) in condition_ids.iter().enumerate() {
+            let amount = merge_info.get(&condition_id).map(|(_, _, amt)| *amt);
+            if executor.is_dry_run() {
+                info!("🧪 [DRY RUN] 维护：将 Merge | condition_id={:#x} | 数量:{:?}", condition_id, amount);
+                continue;
+            }
+            if i > 0 {
+                info!("维护：等待 {} 秒后合并下一市场 (第 {}/{} 个)", merge_spacing.as_secs(), i + 1, condition_ids.len());
+                sleep(merge_spacing).await;
+            }
+            let (result, attempts) = merge_with_retry(
+                rpc_pool,
+                condition_id,
+                config.wallet_address,
+                &config.signer,
+                config.merge_max_retries,
+                retry_base_delay,
+            )
+            .await;
+            match result {
+                Ok(tx) => {
+                    info!(
+                        "✅ 维护：Merge 完成 | condition_id={:#x} | 数量:{:?} | 尝试:{}次 | 时间:{}",
+                        condition_id, amount, attempts, display_time::fmt(chrono::Utc::now())
+                    );
+                    info!("  📝 tx={}", tx);
+                }
+                Err(MergeError::NoShares { .. }) => {
+                    debug!(condition_id = %condition_id, "⏭️ 跳过 merge: 无可用份额");
+                }
+                Err(e) => {
+                    failed += 1;
+                    warn!(condition_id = %condition_id, error = %e, attempts, "❌ 维护：Merge 失败");
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow::anyhow!("维护：{} 个市场 Merge 失败", failed));
+        }
+    }
+
+    info!("🧰 维护完成，退出");
+    Ok(())
+}
+
+/// 输出已实现盈亏汇总：合计一行，各市场明细各一行
+fn log_pnl_summary(position_tracker: &PositionTracker, label: &str) {
+    let summary = position_tracker.pnl().pnl_summary();
+    info!("📒 {} | {}", label, summary);
+    for (condition_id, pnl) in &summary.markets {
+        info!(
+            "  📒 condition_id={:#x} | 已实现盈亏:{:+.4} USD | 收入:{:.4} | 成本:{:.4}",
+            condition_id,
+            pnl.realized(),
+            pnl.proceeds,
+            pnl.cost
+        );
+    }
+}
+
+/// 等待退出信号：SIGINT（Ctrl-C）或 SIGTERM（仅 Unix）
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!(error = %e, "注册 SIGTERM 处理失败，仅监听 Ctrl-C"),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// 启用 ORDER_JITTER_PCT 时，在固定间隔上额外叠加的随机延迟上限
+const MAX_TRADE_INTERVAL_JITTER: Duration = Duration::from_millis(1500);
+/// PER_MARKET_TASKS：每个市场任务待处理订单簿对的队列长度，积压时丢弃新更新（后续更新携带最新订单簿）
+const MARKET_TASK_QUEUE: usize = 64;
+
+/// 处理订单簿对所需的共享状态，每个窗口构建一次。敞口、并发、节流与暂停等全局检查都经此处的共享 Arc 状态进行，
+/// 启用 PER_MARKET_TASKS 时各市场任务共用同一份
+struct PairContext {
+    config: Config,
+    market_map: HashMap<B256, MarketInfo>,
+    window_end: chrono::DateTime<chrono::Utc>,
+    detector: Arc<ArbitrageDetector>,
+    risk_manager: Arc<RiskManager>,
+    executor: Arc<TradingExecutor>,
+    notifier: Arc<dyn Notifier>,
+    hedge_monitor: Arc<HedgeMonitor>,
+    position_balancer: Arc<PositionBalancer>,
+    clob_health: Arc<EndpointHealth>,
+    trade_permits: Arc<tokio::sync::Semaphore>,
+    trade_throttle: Arc<TradeThrottle>,
+    order_jitter: Arc<OrderJitter>,
+    halt_switch: Arc<Mutex<HaltSwitch>>,
+    shutdown_requested: Arc<AtomicBool>,
+    // 订单簿缓存：ATOMIC_PAIR 平仓时据此取最新买一价
+    book_cache: BookCache,
+    // AUTO_MERGE_ON_FILL：订单对完全成交后立即 Merge 所需的 RPC 端点池、进行中的 Merge 与收尾标志
+    rpc_pool: Arc<RpcPool>,
+    merge_guard: Arc<MergeGuard>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    // 按市场记录上一拍卖一价，用于计算涨跌方向；补单时也据此取最新卖一价
+    last_prices: Arc<DashMap<B256, (Decimal, Decimal)>>,
+    // CARRY_PRICE_DIRECTION：按币种记录的上一拍卖一价，跨窗口保留，新窗口首拍据此显示涨跌方向
+    symbol_last_prices: Option<Arc<DashMap<String, (Decimal, Decimal)>>>,
+    // SKIPPED_LOG_PATH：被检查拦下的机会逐条写入跳过记录
+    skip_logger: Option<SkipLogger>,
+    // PROFIT_PRIORITIZE：机会先进入缓冲，到期后按利润率顺序执行（仅串行模式）
+    profit_buffer: Option<ProfitBuffer>,
+    // 按市场记录上次执行套利时的卖一价与时间，用于去重（同一机会持续存在时不重复下单）
+    last_executed: DashMap<B256, (Decimal, Decimal, Instant)>,
+    spread_persist: Duration,
+    min_spread_updates: u32,
+    min_arbitrage_size: Decimal,
+    dynamic_sizing: bool,
+    dynamic_sizing_fraction: Decimal,
+    dynamic_sizing_floor: Decimal,
+    max_position_per_market: Decimal,
+    dedup_cooldown: Duration,
+    dedup_min_move: Decimal,
+}
+
+/// 对一个订单簿对的执行决策（见 PairContext::evaluate），不含任何副作用
+enum TradeDecision {
+    /// 价差未打开或检测器未给出买入侧机会
+    NoOpportunity,
+    /// 通过全部检查，应按 size 执行；opp 的双边数量已截断为 size（尚未抖动，见 execute_trade）
+    Execute {
+        opp: ArbitrageOpportunity,
+        size: Decimal,
+        yes_price: Decimal,
+        no_price: Decimal,
+    },
+    /// 有机会但被某项检查拦下
+    Skip { opp: ArbitrageOpportunity, reason: SkipReason },
+}
+
+impl PairContext {
+    /// 卖一总价是否达到执行阈值（1 - 当前执行价差，见 execution_spread）；挂单模式（POST_ONLY）按买一价挂单，
+    /// 卖一总价不作为前置条件，由 check_arbitrage 按挂价判断
+    fn spread_open(&self, pair: &OrderBookPair) -> bool {
+        let execution_threshold = dec!(1.0) - self.execution_spread();
+        let total_ask_price = pair.yes_book.asks.last()
+            .and_then(|y| pair.no_book.asks.last().map(|n| y.price + n.price));
+        self.config.post_only || total_ask_price.is_some_and(|t| t <= execution_threshold)
+    }
+
+    /// 当前的套利执行价差：按窗口内进度从 SPREAD_RAMP_START 线性过渡到 SPREAD_RAMP_END，
+    /// 进度从窗口开始计到停止套利的时刻（窗口结束前 STOP_ARBITRAGE_BEFORE_END_MINUTES）。未配置时两端均为 ARBITRAGE_EXECUTION_SPREAD
+    fn execution_spread(&self) -> Decimal {
+        let (start, end) = (self.config.spread_ramp_start, self.config.spread_ramp_end);
+        if start == end {
+            return start;
+        }
+        let ramp_secs = self.config.market_window.secs() - self.config.stop_arbitrage_before_end_minutes as i64 * 60;
+        if ramp_secs <= 0 {
+            return end;
+        }
+        let progress = (Decimal::from(self.window_elapsed_secs()) / Decimal::from(ramp_secs)).min(dec!(1));
+        start + (end - start) * progress
+    }
+
+    /// 当前窗口已开始的秒数（按 UTC 窗口边界计算）
+    fn window_elapsed_secs(&self) -> i64 {
+        let now = chrono::Utc::now();
+        now.timestamp() - MarketDiscoverer::calculate_current_window_timestamp(now, self.config.market_window.secs())
+    }
+
+    /// 给定订单簿对与当前配置、敞口、节流等状态，按主循环的全部检查给出执行决策，不下单、不占用名额、不修改任何状态。
+    /// spread_seen 为该机会首次出现的时间与连续出现的更新次数（未启用 MIN_SPREAD_PERSIST_* 时传 None）。
+    /// 并发名额与交易间隔只做检查，调用方执行前须真正占用；交易暂停与数量抖动不在此处理，由 execute_trade 执行时判断一次，
+    /// 因此 PROFIT_PRIORITIZE 下重复 evaluate 同一订单簿对得到相同结果
+    fn evaluate(&self, pair: &OrderBookPair, spread_seen: Option<(Instant, u32)>) -> TradeDecision {
+        if !self.spread_open(pair) {
+            return TradeDecision::NoOpportunity;
+        }
+        let market_symbol = self.market_map.get(&pair.market_id).map(|m| m.crypto_symbol.as_str()).unwrap_or("");
+        // 传入该市场当前持仓，按 MARKET_INVENTORY_TARGET 截断或抑制机会
+        let position_tracker = self.risk_manager.position_tracker();
+        let (yes_held, no_held) = position_tracker.get_pair_positions(pair.yes_book.asset_id, pair.no_book.asset_id);
+        let Some(opp) = self.detector.check_arbitrage(
+            &pair.yes_book,
+            &pair.no_book,
+            &pair.market_id,
+            market_symbol,
+            Some(MarketInventory { yes: yes_held, no: no_held }),
+            Some(dec!(1.0) - self.execution_spread()),
+        ) else {
+            return TradeDecision::NoOpportunity;
+        };
+        let skip = |opp: ArbitrageOpportunity, reason| TradeDecision::Skip { opp, reason };
+
+        // 不在 TRADE_SYMBOLS 中的市场仅监控
+        if !self.config.is_trade_symbol(market_symbol) {
+            return skip(opp, SkipReason::MonitorOnly);
+        }
+        // 机会持续时间或连续更新次数不足，过滤一闪而过的价差
+        if let Some((first_seen, updates)) = spread_seen {
+            if first_seen.elapsed() < self.spread_persist || updates < self.min_spread_updates {
+                return skip(opp, SkipReason::TooYoung);
+            }
+        }
+        // YES / NO 价格阈值
+        let min_yes_price = self.config.min_yes_price_threshold;
+        let min_no_price = self.config.min_no_price_threshold;
+        if (min_yes_price > dec!(0) && opp.yes_ask_price < min_yes_price)
+            || (min_no_price > dec!(0) && opp.no_ask_price < min_no_price)
+        {
+            return skip(opp, SkipReason::PriceThreshold);
+        }
+        // 收到退出信号后不再开新仓
+        if self.shutdown_requested.load(Ordering::Relaxed) {
+            return skip(opp, SkipReason::ShuttingDown);
+        }
+        // 当日已实现亏损达到 DAILY_LOSS_LIMIT_USDC：直到 UTC 零点不开新仓
+        if self.risk_manager.daily_loss_halted() {
+            return skip(opp, SkipReason::DailyLossLimit);
+        }
+        // 窗口开始后的预热期：新市场订单簿尚不稳定，仅监控不下单
+        if self.config.trade_start_delay_secs > 0
+            && self.window_elapsed_secs() < self.config.trade_start_delay_secs as i64
+        {
+            return skip(opp, SkipReason::WarmUp);
+        }
+        // 接近市场结束时间（秒级精度，5分钟市场下 num_minutes() 截断可能导致漏检）
+        if self.config.stop_arbitrage_before_end_minutes > 0 {
+            if let Some(market_info) = self.market_map.get(&pair.market_id) {
+                let seconds_until_end = market_info.end_date.signed_duration_since(chrono::Utc::now()).num_seconds();
+                if seconds_until_end <= self.config.stop_arbitrage_before_end_minutes as i64 * 60 {
+                    return skip(opp, SkipReason::NearEnd);
+                }
+            }
+        }
+
+        // 下单数量：使用套利机会中的实际可用数量，但一对的成本不超过该币种配置的最大下单金额（USDC，按每份一对的成本换算为份数）
+        let pair_cost = opp.yes_ask_price + opp.no_ask_price;
+        let mut available_size = opp.yes_size.min(opp.no_size);
+        if pair_cost > dec!(0) {
+            let max_order_size = self.config.max_order_size_for(market_symbol);
+            available_size = available_size.min(self.executor.size_precision().round(opp.yes_token_id, max_order_size / pair_cost));
+        }
+        // 动态下单：按剩余敞口额度的比例（不低于下限、不超过剩余额度）换算份数，随敞口增加逐步缩量
+        if self.dynamic_sizing {
+            let headroom = (position_tracker.max_exposure() - position_tracker.calculate_exposure()).max(dec!(0));
+            let budget = (headroom * self.dynamic_sizing_fraction).max(self.dynamic_sizing_floor).min(headroom);
+            if pair_cost > dec!(0) {
+                available_size = available_size.min(self.executor.size_precision().round(opp.yes_token_id, budget / pair_cost));
+            }
+        }
+        let order_size = available_size;
+        // 最小套利数量：按上限截断后的下单数量判断，避免产生碎片持仓（抖动后由 execute_trade 再判断一次）
+        if order_size < self.min_arbitrage_size {
+            return skip(opp, SkipReason::BelowMinSize);
+        }
+
+        // 风险敞口：总敞口与单市场敞口
+        let yes_cost = opp.yes_ask_price * order_size;
+        let no_cost = opp.no_ask_price * order_size;
+        if position_tracker.would_exceed_limit(yes_cost, no_cost) {
+            return skip(opp, SkipReason::ExposureLimit);
+        }
+        if self.max_position_per_market > dec!(0)
+            && position_tracker.market_exposure(opp.yes_token_id, opp.no_token_id) + yes_cost + no_cost
+                > self.max_position_per_market
+        {
+            return skip(opp, SkipReason::MarketExposureLimit);
+        }
+        // 持仓平衡（使用本地缓存，零延迟）
+        if self.position_balancer.should_skip_arbitrage(opp.yes_token_id, opp.no_token_id) {
+            return skip(opp, SkipReason::Imbalance);
+        }
+        // 去重：同一市场上次执行后，价格未变化足够 tick 且冷却未结束时不再执行
+        if !self.dedup_cooldown.is_zero() {
+            if let Some(last) = self.last_executed.get(&pair.market_id) {
+                let (last_yes, last_no, at) = *last;
+                let moved = (opp.yes_ask_price - last_yes).abs().max((opp.no_ask_price - last_no).abs());
+                if at.elapsed() < self.dedup_cooldown && moved < self.dedup_min_move {
+                    return skip(opp, SkipReason::Duplicate);
+                }
+            }
+        }
+        // 连续失败暂停中
+        if self.risk_manager.trading_paused().is_some() {
+            return skip(opp, SkipReason::FailurePause);
+        }
+        // CLOB 熔断中（到期时由 process_pair 触发后台半开探测）
+        if self.clob_health.is_open() {
+            return skip(opp, SkipReason::CircuitOpen);
+        }
+        // 执行中的套利任务已达上限，避免订单未结算时敞口叠加
+        if self.trade_permits.available_permits() == 0 {
+            return skip(opp, SkipReason::Saturated);
+        }
+        // 交易间隔：本市场与全局间隔都满足才执行
+        if !self.trade_throttle.remaining(pair.market_id).is_zero() {
+            return skip(opp, SkipReason::TradeInterval);
+        }
+
+        let opp = opp.with_size(order_size);
+        TradeDecision::Execute {
+            size: order_size,
+            yes_price: opp.yes_ask_price,
+            no_price: opp.no_ask_price,
+            opp,
+        }
+    }
+
+    /// 输出跳过原因日志（与 evaluate 的各项检查一一对应）并写入跳过记录；CLOB 熔断时顺带触发到期的半开探测
+    fn log_skip(&self, reason: SkipReason, opp: &ArbitrageOpportunity, market_display: &str, spread_seen: Option<(Instant, u32)>) {
+        let position_tracker = self.risk_manager.position_tracker();
+        if let Some(skip_logger) = &self.skip_logger {
+            let symbol = self.market_map.get(&opp.market_id).map(|m| m.crypto_symbol.as_str()).unwrap_or("");
+            skip_logger.record(opp, market_display, symbol, reason, position_tracker.calculate_exposure());
+        }
+        match reason {
+            SkipReason::MonitorOnly => info!(
+                "👀 仅监控市场，不下单 | 市场:{} | 利润:{:.2}% | 可成交:{}份",
+                market_display,
+                opp.profit_percentage,
+                opp.yes_size.min(opp.no_size)
+            ),
+            SkipReason::TooYoung => {
+                let (first_seen, updates) = spread_seen.unwrap_or((Instant::now(), 0));
+                debug!(
+                    "🐣 机会持续时间不足，跳过套利 | 市场:{} | 已持续:{}ms/{}ms | 连续更新:{}/{}",
+                    market_display,
+                    first_seen.elapsed().as_millis(),
+                    self.spread_persist.as_millis(),
+                    updates,
+                    self.min_spread_updates
+                );
+            }
+            SkipReason::PriceThreshold => debug!(
+                "⏸️ 价格未达到阈值，跳过套利执行 | 市场:{} | YES价格:{:.4} (阈值:{:.4}) | NO价格:{:.4} (阈值:{:.4})",
+                market_display,
+                opp.yes_ask_price,
+                self.config.min_yes_price_threshold,
+                opp.no_ask_price,
+                self.config.min_no_price_threshold
+            ),
+            SkipReason::ShuttingDown => debug!("🛑 正在退出，跳过套利执行 | 市场:{}", market_display),
+            SkipReason::Halted => debug!("⛔ 交易已暂停，跳过套利执行 | 市场:{}", market_display),
+            SkipReason::DailyLossLimit => debug!(
+                "🚨 已达每日亏损上限，跳过套利执行 | 市场:{} | 当日已实现盈亏:{:+.4} USDC",
+                market_display,
+                position_tracker.pnl().daily_realized()
+            ),
+            SkipReason::WarmUp => info!(
+                "⏳ 窗口预热中，暂不执行套利 | 市场:{} | 利润:{:.2}% | 窗口已开始:{}秒/{}秒",
+                market_display,
+                opp.profit_percentage,
+                self.window_elapsed_secs(),
+                self.config.trade_start_delay_secs
+            ),
+            SkipReason::NearEnd => debug!(
+                "⏰ 接近市场结束时间，跳过套利执行 | 市场:{} | 停止阈值:{}分钟",
+                market_display,
+                self.config.stop_arbitrage_before_end_minutes
+            ),
+            SkipReason::BelowMinSize => debug!(
+                "⏸️ 下单数量低于最小套利数量，跳过 | 市场:{} | 可成交:{}份 | 最小:{}份",
+                market_display,
+                opp.yes_size.min(opp.no_size),
+                self.min_arbitrage_size
+            ),
+            SkipReason::ExposureLimit => warn!(
+                "⚠️ 风险敞口超限，拒绝执行套利交易 | 市场:{} | 当前敞口:{:.2} USD | 限制:{:.2} USD",
+                market_display,
+                position_tracker.calculate_exposure(),
+                position_tracker.max_exposure()
+            ),
+            SkipReason::MarketExposureLimit => warn!(
+                "⚠️ 单市场敞口超限，拒绝执行套利交易 | 市场:{} | 该市场敞口:{:.2} USD | 限制:{:.2} USD",
+                market_display,
+                position_tracker.market_exposure(opp.yes_token_id, opp.no_token_id),
+                self.max_position_per_market
+            ),
+            SkipReason::Imbalance => warn!("⚠️ 持仓已严重不平衡，跳过套利执行 | 市场:{}", market_display),
+            SkipReason::Duplicate => debug!("🔁 重复套利信号，跳过 | 市场:{}", market_display),
+            SkipReason::FailurePause => debug!(
+                "⏸️ 连续失败暂停中，跳过套利 | 市场:{} | 剩余:{}秒",
+                market_display,
+                self.risk_manager.trading_paused().map(|d| d.as_secs()).unwrap_or(0)
+            ),
+            SkipReason::CircuitOpen => {
+                self.clob_health.try_acquire();
+                warn!("⚠️ CLOB 端点熔断中，跳过套利 | 市场:{}", market_display);
+            }
+            SkipReason::Saturated => warn!(
+                "🚦 执行中的套利任务已达上限 {}，跳过 | 市场:{}",
+                self.config.max_inflight_trades,
+                market_display
+            ),
+            SkipReason::TradeInterval => debug!(
+                "⏱️ 交易间隔不足，跳过 | 市场:{} | 还需等待:{:.1}秒",
+                market_display,
+                self.trade_throttle.remaining(opp.market_id).as_secs_f32()
+            ),
+        }
+    }
+}
+
+/// 订单簿对处理的可变状态：串行模式下主循环持有一份，PER_MARKET_TASKS 下每个市场任务各持有一份
+#[derive(Default)]
+struct PairState {
+    // 按市场记录当前机会首次出现的时间与连续出现的订单簿更新次数，价差消失时清除（MIN_SPREAD_PERSIST_*）
+    spread_seen: HashMap<B256, (Instant, u32)>,
+    // 本窗口机会统计，窗口结束时输出汇总
+    window_report: WindowReport,
+}
+
+/// PER_MARKET_TASKS：按 market_id 把订单簿对分发到各市场的独立任务，避免一个市场的处理阻塞其他市场的检测
+struct MarketTasks {
+    ctx: Arc<PairContext>,
+    senders: HashMap<B256, tokio::sync::mpsc::Sender<OrderBookPair>>,
+    handles: Vec<tokio::task::JoinHandle<PairState>>,
+}
+
+impl MarketTasks {
+    fn new(ctx: Arc<PairContext>) -> Self {
+        Self { ctx, senders: HashMap::new(), handles: Vec::new() }
+    }
+
+    /// 分发到该市场的任务（首次收到该市场的订单簿对时创建）
+    fn dispatch(&mut self, pair: OrderBookPair) {
+        let sender = self.senders.entry(pair.market_id).or_insert_with(|| {
+            let (tx, mut rx) = tokio::sync::mpsc::channel::<OrderBookPair>(MARKET_TASK_QUEUE);
+            let ctx = self.ctx.clone();
+            self.handles.push(tokio::spawn(
+                async move {
+                    let mut state = PairState::default();
+                    while let Some(pair) = rx.recv().await {
+                        process_pair(&ctx, &mut state, pair);
+                    }
+                    state
+                }
+                .in_current_span(),
+            ));
+            tx
+        });
+        if let Err(tokio::sync::mpsc::error::TrySendError::Full(pair)) = sender.try_send(pair) {
+            debug!(market_id = %pair.market_id, "市场任务积压，丢弃本次订单簿更新");
+        }
+    }
+
+    /// 关闭各市场任务，等待其处理完已排队的更新，并把各自的窗口报告汇总到 report
+    async fn finish(self, report: &mut WindowReport) {
+        drop(self.senders);
+        for handle in self.handles {
+            match handle.await {
+                Ok(state) => report.merge(state.window_report),
+                Err(e) => warn!(error = %e, "市场任务异常结束，其窗口统计丢失"),
+            }
+        }
+    }
+}
+
+/// PROFIT_PRIORITIZE 下缓冲的订单簿对：同一市场只保留最新一次通过检查的更新
+struct BufferedPair {
+    pair: OrderBookPair,
+    profit_percentage: Decimal,
+    market_display: String,
+    market_symbol: String,
+    yes_dir: &'static str,
+    no_dir: &'static str,
+}
+
+/// PROFIT_PRIORITIZE：把短时间窗口内各市场通过检查的机会缓冲起来，到期后按利润率从高到低执行，
+/// 使并发名额、交易间隔与敞口优先分给利润更高的机会。首个机会进入缓冲时开始计时
+struct ProfitBuffer {
+    window: Duration,
+    pending: Mutex<(Option<Instant>, HashMap<B256, BufferedPair>)>,
+}
+
+impl ProfitBuffer {
+    fn new(window: Duration) -> Self {
+        Self { window, pending: Mutex::new((None, HashMap::new())) }
+    }
+
+    /// 加入（或替换）该市场的机会，缓冲为空时开始计时
+    fn push(&self, buffered: BufferedPair) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.0.is_none() {
+            pending.0 = Some(Instant::now() + self.window);
+        }
+        pending.1.insert(buffered.pair.market_id, buffered);
+    }
+
+    /// 该市场的机会已消失或被拦下：移出缓冲
+    fn remove(&self, market_id: &B256) {
+        self.pending.lock().unwrap().1.remove(market_id);
+    }
+
+    /// 本批机会的执行时间，缓冲为空时为 None
+    fn deadline(&self) -> Option<Instant> {
+        self.pending.lock().unwrap().0
+    }
+
+    /// 取出本批全部机会，按利润率从高到低排序
+    fn take_sorted(&self) -> Vec<BufferedPair> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.0 = None;
+        let mut batch: Vec<BufferedPair> = pending.1.drain().map(|(_, b)| b).collect();
+        batch.sort_by(|a, b| b.profit_percentage.cmp(&a.profit_percentage));
+        batch
+    }
+}
+
+/// PROFIT_PRIORITIZE：缓冲到期后按利润率从高到低逐个重新 evaluate 并执行；
+/// 前面的交易占用名额、间隔或敞口后，后面的机会按正常检查被拦下并记录原因
+fn flush_prioritized(ctx: &PairContext, state: &mut PairState) {
+    let Some(buffer) = &ctx.profit_buffer else {
+        return;
+    };
+    let batch = buffer.take_sorted();
+    if batch.len() > 1 {
+        debug!(count = batch.len(), "按利润率顺序执行缓冲的套利机会");
+    }
+    for buffered in batch {
+        let seen = state.spread_seen.get(&buffered.pair.market_id).copied();
+        match ctx.evaluate(&buffered.pair, seen) {
+            TradeDecision::NoOpportunity => {}
+            TradeDecision::Skip { opp, reason } => {
+                ctx.log_skip(reason, &opp, &buffered.market_display, seen);
+                state.window_report.skipped(&buffered.market_symbol, reason);
+            }
+            TradeDecision::Execute { opp, size, yes_price, no_price } => {
+                execute_trade(ctx, state, TradeRequest {
+                    opp,
+                    size,
+                    yes_price,
+                    no_price,
+                    market_display: buffered.market_display,
+                    market_symbol: buffered.market_symbol,
+                    yes_dir: buffered.yes_dir,
+                    no_dir: buffered.no_dir,
+                    spread_seen: seen,
+                });
+            }
+        }
+    }
+}
+
+/// 待执行的买入侧套利：evaluate 给出的执行参数与日志所需的市场信息
+struct TradeRequest {
+    opp: ArbitrageOpportunity,
+    size: Decimal,
+    yes_price: Decimal,
+    no_price: Decimal,
+    market_display: String,
+    market_symbol: String,
+    yes_dir: &'static str,
+    no_dir: &'static str,
+    spread_seen: Option<(Instant, u32)>,
+}
+
+/// 执行一笔已通过 evaluate 全部检查的买入侧套利：占用并发名额与交易间隔、计入敞口，下单与风险处理在独立任务中进行。
+/// 逐笔执行时由 process_pair 调用，PROFIT_PRIORITIZE 下由 flush_prioritized 按利润率顺序调用
+fn execute_trade(ctx: &PairContext, state: &mut PairState, trade: TradeRequest) {
+    let TradeRequest {
+        opp,
+        size: order_size,
+        yes_price,
+        no_price,
+        market_display,
+        market_symbol,
+        yes_dir,
+        no_dir,
+        spread_seen: seen,
+    } = trade;
+    let market_symbol = market_symbol.as_str();
+    let market_id = opp.market_id;
+
+    // 交易暂停（kill switch）：不开新仓，卖出侧套利、Merge 与收尾不受影响
+    if ctx.halt_switch.lock().unwrap().is_halted() {
+        ctx.log_skip(SkipReason::Halted, &opp, &market_display, seen);
+        state.window_report.skipped(market_symbol, SkipReason::Halted);
+        return;
+    }
+    // 启用抖动时随机缩放数量（不超过 evaluate 给出的数量），抖动后仍须满足最小套利数量
+    let order_size = ctx.order_jitter.jitter_size(order_size, order_size);
+    if order_size < ctx.min_arbitrage_size {
+        ctx.log_skip(SkipReason::BelowMinSize, &opp, &market_display, seen);
+        state.window_report.skipped(market_symbol, SkipReason::BelowMinSize);
+        return;
+    }
+    let opp = opp.with_size(order_size);
+    // 扣费后的预计利润（USD）：下单数量 × 净利润比例
+    let est_profit = order_size * opp.profit_percentage / dec!(100);
+
+    // evaluate 只做检查，这里才真正占用并发名额与交易间隔；并发竞争下仍可能失败
+    let Ok(trade_permit) = ctx.trade_permits.clone().try_acquire_owned() else {
+        ctx.log_skip(SkipReason::Saturated, &opp, &market_display, seen);
+        state.window_report.skipped(market_symbol, SkipReason::Saturated);
+        return;
+    };
+    if ctx.trade_throttle.try_acquire(market_id, ctx.order_jitter.extra_delay(MAX_TRADE_INTERVAL_JITTER)).is_err() {
+        ctx.log_skip(SkipReason::TradeInterval, &opp, &market_display, seen);
+        state.window_report.skipped(market_symbol, SkipReason::TradeInterval);
+        return;
+    }
+    ctx.last_executed.insert(market_id, (opp.yes_ask_price, opp.no_ask_price, Instant::now()));
+
+    let current_exposure = ctx.risk_manager.position_tracker().calculate_exposure();
+    info!(
+        "⚡ 执行套利交易 | 市场:{} | 利润:{:.2}% | 下单数量:{}份 | YES:{:.4} NO:{:.4} | 订单成本:{:.2} USD | 预计利润:{:.4} USD | 当前敞口:{:.2} USD",
+        market_display,
+        opp.profit_percentage,
+        order_size,
+        yes_price,
+        no_price,
+        opp.total_cost,
+        est_profit,
+        current_exposure
+    );
+    // 简化敞口：只要执行套利就增加敞口，不管是否成交
+    let _pt = ctx.risk_manager.position_tracker();
+    _pt.update_exposure_cost(opp.yes_token_id, opp.yes_ask_price, order_size);
+    _pt.update_exposure_cost(opp.no_token_id, opp.no_ask_price, order_size);
+    metrics::set_exposure(_pt.calculate_exposure());
+    metrics::record_trade_executed();
+    state.window_report.executed(market_symbol);
+
+    // 套利执行：只要总价 <= 阈值即执行，不因涨跌组合跳过；涨跌仅用于滑点分配（仅下降=second，上涨与持平=first）
+    // 克隆需要的变量到独立任务中（涨跌方向用于按方向分配滑点）
+    let executor_clone = ctx.executor.clone();
+    let risk_manager_clone = ctx.risk_manager.clone();
+    // evaluate 已按币种上限与动态下单截断数量，上面已应用抖动
+    let opp_clone = opp.clone();
+    let yes_dir_s = yes_dir.to_string();
+    let no_dir_s = no_dir.to_string();
+    let notifier_trade = ctx.notifier.clone();
+    let market_display_s = market_display.clone();
+    let hedge_monitor_clone = ctx.hedge_monitor.clone();
+    let trade_log = ctx.config.trade_log_path.clone().map(|path| (path, ctx.config.trade_log_format));
+    let market_symbol_s = market_symbol.to_string();
+    let clob_health_trade = ctx.clob_health.clone();
+    let last_prices_trade = ctx.last_prices.clone();
+    let complete_pair_retries = ctx.config.complete_pair_retries;
+    let atomic_pair_window = ctx.config.atomic_pair.then(|| Duration::from_millis(ctx.config.atomic_pair_window_ms));
+    let book_cache_trade = ctx.book_cache.clone();
+    // AUTO_MERGE_ON_FILL（模拟交易不 Merge）：两腿完全成交后立即 Merge 该市场
+    let auto_merge = (ctx.config.auto_merge_on_fill && !ctx.executor.is_dry_run()).then(|| {
+        (
+            ctx.config.clone(),
+            ctx.rpc_pool.clone(),
+            ctx.merge_guard.clone(),
+            ctx.wind_down_in_progress.clone(),
+        )
+    });
+    // GTD_ANCHOR_TO_WINDOW：GTD 订单不晚于窗口结束前 GTD_WINDOW_BUFFER_SECS 过期
+    let expire_by = ctx.config.gtd_anchor_to_window.then(|| {
+        ctx.window_end - chrono::Duration::seconds(ctx.config.gtd_window_buffer_secs as i64)
+    });
+
+    // 订单对 ID 在此生成，下单、注册与风险处理的日志挂在同一 span 下
+    let pair_id = uuid::Uuid::new_v4().to_string();
+    let pair_span = utils::logger::pair_span(&pair_id);
+
+    // 使用 tokio::spawn 异步执行套利交易，不阻塞订单簿更新处理
+    tokio::spawn(async move {
+        // 任务结束时释放并发许可
+        let _trade_permit = trade_permit;
+        // 执行套利交易（滑点：仅下降=second，上涨与持平=first）
+        let trade_result = executor_clone.execute_arbitrage_pair(&opp_clone, &yes_dir_s, &no_dir_s, pair_id, expire_by).await;
+        // 只有连接类错误计为端点失败，业务错误（余额不足、价格变动等）说明端点可达
+        clob_health_trade.record(
+            !matches!(&trade_result, Err(e) if utils::errors::is_clob_endpoint_error(e)),
+        );
+        risk_manager_clone.record_trade_result(trade_result.is_ok());
+        match trade_result {
+            Ok(mut result) => {
+                // 单边 / 部分成交：启用 COMPLETE_PAIR_RETRIES 时按最新卖一价补齐成交较少的一腿
+                if complete_pair_retries > 0 && result.yes_filled != result.no_filled {
+                    let pt = risk_manager_clone.position_tracker();
+                    match executor_clone
+                        .complete_pair(
+                            &opp_clone,
+                            &result,
+                            complete_pair_retries,
+                            |is_yes| {
+                                last_prices_trade
+                                    .get(&opp_clone.market_id)
+                                    .map(|p| if is_yes { p.0 } else { p.1 })
+                            },
+                            |extra_cost| !pt.would_exceed_limit(extra_cost, dec!(0)),
+                        )
+                        .await
+                    {
+                        Ok(updated) => result = updated,
+                        Err(e) => warn!(error = %e, "补齐订单对失败，交由风控处理"),
+                    }
+                }
+                // ATOMIC_PAIR：仍不一致时撤掉两腿剩余挂单并卖出多出的一腿，不留单边持仓
+                let mut unwind = None;
+                if let Some(window) = atomic_pair_window {
+                    unwind = executor_clone
+                        .unwind_unpaired(&opp_clone, &result, window, |token_id| cached_best_bid(&book_cache_trade, token_id))
+                        .await;
+                    if let Some(u) = &unwind {
+                        result = u.result.clone();
+                    }
+                }
+                // 先保存 pair_id，因为 result 会被移动
+                let pair_id = result.pair_id.clone();
+                if result.yes_filled > dec!(0) || result.no_filled > dec!(0) {
+                    notifier_trade.notify(NotifyEvent::ArbitrageExecuted {
+                        market: market_display_s.clone(),
+                        profit_pct: opp_clone.profit_percentage,
+                        yes_filled: result.yes_filled,
+                        no_filled: result.no_filled,
+                    });
+                    if let Some((path, format)) = &trade_log {
+                        arbitrage_logger::log_trade(
+                            *format,
+                            &opp_clone,
+                            &result,
+                            &market_display_s,
+                            &market_symbol_s,
+                            path,
+                        );
+                    }
+                }
+
+                // 两腿完全成交（与风控的 BothFilled 判定一致），AUTO_MERGE_ON_FILL 据此立即 Merge
+                let both_filled = result.yes_filled > dec!(0)
+                    && result.yes_filled == result.yes_size
+                    && result.no_filled == result.no_size;
+                // 注册到风险管理器（传入价格信息以计算风险敞口）
+                risk_manager_clone.register_order_pair(
+                    result,
+                    opp_clone.market_id,
+                    opp_clone.yes_token_id,
+                    opp_clone.no_token_id,
+                    opp_clone.yes_ask_price,
+                    opp_clone.no_ask_price,
+                    market_display_s.clone(),
+                );
+                // ATOMIC_PAIR 卖出的份额：扣减持仓与敞口并记录卖出所得；两腿持仓一致后不再走风险恢复
+                let flattened = match &unwind {
+                    Some(u) if u.sold > dec!(0) => {
+                        let pt = risk_manager_clone.position_tracker();
+                        let entry_price = if u.token_id == opp_clone.yes_token_id {
+                            opp_clone.yes_ask_price
+                        } else {
+                            opp_clone.no_ask_price
+                        };
+                        pt.update_position(u.token_id, -u.sold);
+                        pt.pnl().record_sell(u.token_id, u.sold, u.proceeds);
+                        pt.update_exposure_cost(u.token_id, entry_price, -u.sold);
+                        metrics::set_exposure(pt.calculate_exposure());
+                        let (yes_filled, no_filled) = (u.result.yes_filled, u.result.no_filled);
+                        yes_filled.max(no_filled) - u.sold <= yes_filled.min(no_filled)
+                    }
+                    Some(u) => u.result.yes_filled == u.result.no_filled,
+                    None => false,
+                };
+                if let Some((config, rpc_pool, merge_guard, wind_down_flag)) = auto_merge.filter(|_| both_filled) {
+                    tokio::spawn(
+                        auto_merge_pair(
+                            config,
+                            rpc_pool,
+                            risk_manager_clone.position_tracker(),
+                            merge_guard,
+                            wind_down_flag,
+                            notifier_trade.clone(),
+                            opp_clone.market_id,
+                        )
+                        .in_current_span(),
+                    );
+                }
+                if flattened {
+                    info!("订单对已由 ATOMIC_PAIR 处理为双边一致，跳过风险恢复");
+                    return;
+                }
+
+                // 处理风险恢复（单边成交仅在 ENABLE_HEDGE 开启时返回 MonitorForExit）
+                match risk_manager_clone.handle_order_pair(&pair_id).await {
+                    Ok(action) => {
+                        match action {
+                            crate::risk::recovery::RecoveryAction::None => {
+                                // 正常情况，无需处理
+                            }
+                            crate::risk::recovery::RecoveryAction::MonitorForExit { .. } => {
+                                if let Err(e) = hedge_monitor_clone.add_position(&action) {
+                                    error!("添加对冲仓位失败: {}", e);
+                                }
+                            }
+                            crate::risk::recovery::RecoveryAction::SellExcess { .. } => {
+                                info!("部分成交不平衡，但对冲策略已关闭，不做处理");
+                            }
+                            crate::risk::recovery::RecoveryAction::ManualIntervention { reason } => {
+                                warn!("需要手动干预: {}", reason);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("风险处理失败: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                // 错误详情已在executor中记录，这里只记录简要信息
+                let error_msg = e.to_string();
+                notifier_trade.notify(NotifyEvent::ArbitrageFailed {
+                    market: market_display_s.clone(),
+                    error: error_msg.clone(),
+                });
+                match e.downcast_ref::<ArbitrageError>() {
+                    // 错误信息已经格式化好了，直接使用
+                    Some(ArbitrageError::BothUnfilled { .. }) => error!("{}", error_msg),
+                    Some(ArbitrageError::WindowExpired { .. }) => warn!("{}", error_msg),
+                    None => error!("执行套利交易失败: {}", error_msg),
+                }
+            }
+        }
+    }.instrument(pair_span));
+}
+
+/// 处理一个市场的订单簿对：打印行情、检测买入/卖出侧套利并按各项风控检查决定是否执行（下单在独立任务中进行）。
+/// 串行模式下由主循环直接调用；启用 PER_MARKET_TASKS 时由各市场任务调用，state 为该市场独有。
+fn process_pair(ctx: &PairContext, state: &mut PairState, pair: OrderBookPair) {
+    // 注意：asks 最后一个为卖一价
+    let yes_best_ask = pair.yes_book.asks.last().map(|a| (a.price, a.size));
+    let no_best_ask = pair.no_book.asks.last().map(|a| (a.price, a.size));
+    let total_ask_price = yes_best_ask.and_then(|(p, _)| no_best_ask.map(|(np, _)| p + np));
+
+    let market_id = pair.market_id;
+    let market_info = ctx.market_map.get(&pair.market_id);
+    let market_title = market_info.map(|m| m.title.as_str()).unwrap_or("未知市场");
+    let market_symbol = market_info.map(|m| m.crypto_symbol.as_str()).unwrap_or("");
+    // 与上一拍比较得到涨跌方向（↑涨 ↓跌 −平），首拍无箭头；启用 CARRY_PRICE_DIRECTION 时首拍与同币种上一窗口的最后价格比较
+    let (yes_dir, no_dir) = match (yes_best_ask, no_best_ask) {
+        (Some((yp, _)), Some((np, _))) => {
+            let prev = ctx.last_prices.get(&market_id).map(|r| (r.0, r.1)).or_else(|| {
+                ctx.symbol_last_prices
+                    .as_ref()
+                    .filter(|_| !market_symbol.is_empty())
+                    .and_then(|m| m.get(market_symbol).map(|r| (r.0, r.1)))
+            });
+            let (y_dir, n_dir) = prev
+                .map(|(ly, ln)| (
+                    if yp > ly { "↑" } else if yp < ly { "↓" } else { "−" },
+                    if np > ln { "↑" } else if np < ln { "↓" } else { "−" },
+                ))
+                .unwrap_or(("", ""));
+            ctx.last_prices.insert(market_id, (yp, np));
+            if let Some(m) = ctx.symbol_last_prices.as_ref().filter(|_| !market_symbol.is_empty()) {
+                m.insert(market_symbol.to_string(), (yp, np));
+            }
+            (y_dir, n_dir)
+        }
+        _ => ("", ""),
+    };
+
+    // 不在 TRADE_SYMBOLS 中的市场仅监控：照常记录订单簿与机会，但不下单
+    let monitor_only = !ctx.config.is_trade_symbol(market_symbol);
+    let market_display = if !market_symbol.is_empty() {
+        format!("{}预测市场", market_symbol)
+    } else {
+        market_title.to_string()
+    };
+    let market_display = if monitor_only {
+        format!("{}（仅监控）", market_display)
+    } else {
+        market_display
+    };
+
+    let (prefix, spread_info) = total_ask_price
+        .map(|t| {
+            if t < dec!(1.0) {
+                let profit_pct = (dec!(1.0) - t) * dec!(100.0);
+                ("🚨套利机会", format!("总价:{:.4} 利润:{:.2}%", t, profit_pct))
+            } else {
+                ("📊", format!("总价:{:.4} (无套利)", t))
+            }
+        })
+        .unwrap_or_else(|| ("📊", "无数据".to_string()));
+
+    // 涨跌箭头仅在套利机会时显示
+    let is_arbitrage = prefix == "🚨套利机会";
+    let yes_info = yes_best_ask
+        .map(|(p, s)| {
+            if is_arbitrage && !yes_dir.is_empty() {
+                format!("Yes:{:.4} 份额:{} {}", p, s, yes_dir)
+            } else {
+                format!("Yes:{:.4} 份额:{}", p, s)
+            }
+        })
+        .unwrap_or_else(|| "Yes:无".to_string());
+    let no_info = no_best_ask
+        .map(|(p, s)| {
+            if is_arbitrage && !no_dir.is_empty() {
+                format!("No:{:.4} 份额:{} {}", p, s, no_dir)
+            } else {
+                format!("No:{:.4} 份额:{}", p, s)
+            }
+        })
+        .unwrap_or_else(|| "No:无".to_string());
+
+    info!(
+        "{} {} | {} | {} | {}",
+        prefix,
+        market_display,
+        yes_info,
+        no_info,
+        spread_info
+    );
+
+    // 保留原有的结构化日志用于调试（可选）
+    debug!(
+        market_id = %pair.market_id,
+        yes_token = %pair.yes_book.asset_id,
+        no_token = %pair.no_book.asset_id,
+        "订单簿对详细信息"
+    );
+
+    // 检测套利机会（监控阶段：只有当总价 <= 1 - 套利执行价差 时才执行套利）
+    let spread_open = ctx.spread_open(&pair);
+    // 价差消失时重置该市场机会的持续计时
+    if !spread_open {
+        state.spread_seen.remove(&market_id);
+    }
+    // PROFIT_PRIORITIZE：先移出该市场此前缓冲的机会，本次更新仍通过检查时再以最新订单簿重新加入
+    if let Some(buffer) = &ctx.profit_buffer {
+        buffer.remove(&market_id);
+    }
+    if spread_open {
+        // 机会须持续 MIN_SPREAD_PERSIST_MS 且连续出现 MIN_SPREAD_PERSIST_UPDATES 次更新才执行：价差打开期间每次更新累加
+        let seen = (!ctx.spread_persist.is_zero() || ctx.min_spread_updates > 0).then(|| {
+            let seen = state.spread_seen.entry(market_id).or_insert((Instant::now(), 0));
+            seen.1 += 1;
+            *seen
+        });
+        match ctx.evaluate(&pair, seen) {
+            TradeDecision::NoOpportunity => {
+                state.spread_seen.remove(&market_id);
+            }
+            TradeDecision::Skip { opp, reason } => {
+                metrics::record_opportunity();
+                state.window_report.detected(market_symbol);
+                ctx.log_skip(reason, &opp, &market_display, seen);
+                state.window_report.skipped(market_symbol, reason);
+                return;
+            }
+            TradeDecision::Execute { opp, size: order_size, yes_price, no_price } => {
+                metrics::record_opportunity();
+                state.window_report.detected(market_symbol);
+                if let Some(buffer) = &ctx.profit_buffer {
+                    buffer.push(BufferedPair {
+                        pair: OrderBookPair {
+                            yes_book: pair.yes_book.clone(),
+                            no_book: pair.no_book.clone(),
+                            market_id,
+                        },
+                        profit_percentage: opp.profit_percentage,
+                        market_display: market_display.clone(),
+                        market_symbol: market_symbol.to_string(),
+                        yes_dir,
+                        no_dir,
+                    });
+                } else {
+                    execute_trade(ctx, state, TradeRequest {
+                        opp,
+                        size: order_size,
+                        yes_price,
+                        no_price,
+                        market_display: market_display.clone(),
+                        market_symbol: market_symbol.to_string(),
+                        yes_dir,
+                        no_dir,
+                        spread_seen: seen,
+                    });
+                }
+            }
+        }
+    }
+    // 卖出侧套利：YES 买一 + NO 买一 > 1，仅卖出已持有的双边持仓
+    if ctx.config.enable_sell_arbitrage && !monitor_only && !ctx.shutdown_requested.load(Ordering::Relaxed) {
+        if let Some(sell_opp) = ctx.detector.check_sell_arbitrage(
+            &pair.yes_book,
+            &pair.no_book,
+            &pair.market_id,
+            market_symbol,
+        ) {
+            let position_tracker = ctx.risk_manager.position_tracker();
+            let (yes_held, no_held) = position_tracker.get_pair_positions(sell_opp.yes_token_id, sell_opp.no_token_id);
+            let sell_size = ctx.executor.size_precision().round(sell_opp.yes_token_id, sell_opp.size.min(yes_held).min(no_held));
+            let min_value = sell_opp.yes_bid_price.min(sell_opp.no_bid_price) * sell_size;
+            if min_value < dec!(1) {
+                debug!(
+                    "⏸️ 卖出侧套利：持仓不足，跳过 | 市场:{} | YES持仓:{} | NO持仓:{} | 可卖:{}",
+                    market_display, yes_held, no_held, sell_opp.size
+                );
+            } else if let Ok(trade_permit) = ctx.trade_permits.clone().try_acquire_owned() {
+                let interval_ok = ctx.trade_throttle
+                    .try_acquire(market_id, ctx.order_jitter.extra_delay(MAX_TRADE_INTERVAL_JITTER))
+                    .is_ok();
+                if interval_ok {
+                    info!(
+                        "⚡ 执行卖出侧套利 | 市场:{} | YES买一:{:.4} NO买一:{:.4} | 毛利:{:.2}% 净利:{:.2}% | 卖出数量:{}份",
+                        market_display,
+                        sell_opp.yes_bid_price,
+                        sell_opp.no_bid_price,
+                        sell_opp.gross_edge * dec!(100),
+                        sell_opp.profit_percentage,
+                        sell_size
+                    );
+                    metrics::record_opportunity();
+                    let executor_clone = ctx.executor.clone();
+                    let position_tracker = position_tracker.clone();
+                    let notifier_trade = ctx.notifier.clone();
+                    let market_display_s = format!("{}（卖出侧）", market_display);
+                    let pair_id = uuid::Uuid::new_v4().to_string();
+                    let pair_span = utils::logger::pair_span(&pair_id);
+                    tokio::spawn(async move {
+                        let _trade_permit = trade_permit;
+                        match executor_clone.execute_sell_pair(&sell_opp, sell_size, pair_id).await {
+                            Ok(result) => {
+                                metrics::record_trade_executed();
+                                notifier_trade.notify(NotifyEvent::ArbitrageExecuted {
+                                    market: market_display_s.clone(),
+                                    profit_pct: sell_opp.profit_percentage,
+                                    yes_filled: result.yes_filled,
+                                    no_filled: result.no_filled,
+                                });
+                                // 卖出后扣减持仓与敞口（先扣敞口再扣持仓，与 merge 一致）
+                                for (token, filled, price) in [
+                                    (sell_opp.yes_token_id, result.yes_filled, sell_opp.yes_bid_price),
+                                    (sell_opp.no_token_id, result.no_filled, sell_opp.no_bid_price),
+                                ] {
+                                    if filled > dec!(0) {
+                                        position_tracker.update_exposure_cost(token, dec!(0), -filled);
+                                        position_tracker.update_position(token, -filled);
+                                        position_tracker.pnl().record_sell(token, filled, price * filled);
+                                    }
+                                }
+                                metrics::set_exposure(position_tracker.calculate_exposure());
+                            }
+                            Err(e) => {
+                                notifier_trade.notify(NotifyEvent::ArbitrageFailed {
+                                    market: market_display_s.clone(),
+                                    error: e.to_string(),
+                                });
+                                error!("执行卖出侧套利失败: {}", e);
+                            }
+                        }
+                    }.instrument(pair_span));
+                }
+            } else {
+                debug!(
+                    "🚦 执行中的套利任务已达上限 {}，跳过卖出侧套利 | 市场:{}",
+                    ctx.config.max_inflight_trades,
+                    market_display
+                );
+            }
+        }
+    }
+}
+
+/// 单个交易账户的组件：主账户与每个附加账户（EXTRA_ACCOUNTS）各一份。
+/// 各账户共用市场发现、订单簿监控、通知与 Merge 的 RPC 端点池，按账户各自下单、记账、Merge 与收尾
+struct Account {
+    config: Config,
+    executor: Arc<TradingExecutor>,
+    risk_manager: Arc<RiskManager>,
+    hedge_monitor: Arc<HedgeMonitor>,
+    position_balancer: Arc<PositionBalancer>,
+    // 收尾进行中标志：定时 merge 会检查并跳过，避免与收尾 merge 竞争
+    wind_down_in_progress: Arc<AtomicBool>,
+    // 进行中的 Merge：定时 Merge 与 AUTO_MERGE_ON_FILL 据此避免同时合并同一市场
+    merge_guard: Arc<MergeGuard>,
+    // 交易节流与并发上限按账户计算，各账户的 API 限额相互独立
+    trade_throttle: Arc<TradeThrottle>,
+    trade_permits: Arc<tokio::sync::Semaphore>,
+    // 账户 span：多账户时该账户的日志都挂在其下
+    span: tracing::Span,
+}
+
+/// 初始化一个账户：验证私钥，交易执行器与风险管理客户端认证，恢复订单对与对冲仓位，验证认证状态。
+/// 认证失败时发送通知并返回错误
+async fn init_account(
+    config: Config,
+    notifier: Arc<dyn Notifier>,
+    size_precision: Arc<SizePrecision>,
+    multi_account: bool,
+) -> Result<Account> {
+    let span = utils::logger::account_span(&config.account_id, multi_account);
+    async move {
+        // 下单、认证、对冲与 Merge 共用配置加载时由私钥来源创建的签名器
+        let signer = config.signer.clone();
+        info!(address = %alloy::signers::Signer::address(&signer), "签名器已就绪");
+
+        // 初始化交易执行器（需要认证）
+        info!("正在初始化交易执行器（需要API认证）...");
+        if let Some(ref proxy) = config.proxy_address {
+            info!(proxy_address = %proxy, "使用Proxy签名类型（Email/Magic或Browser Wallet）");
+        } else {
+            info!(wallet = %config.wallet_address, "使用EOA签名类型（直接交易，持仓查询与 Merge 使用私钥对应地址）");
+        }
+        info!("注意：如果看到'Could not create api key'警告，这是正常的。SDK会先尝试创建新API key，失败后会自动使用派生方式，认证仍然会成功。");
+        let price_tick = config.price_tick;
+        let executor = match retry_auth(&config, "交易执行器认证", || {
+            TradingExecutor::new(
+                signer.clone(),
+                // 执行器只做总上限兜底（取全局与各币种覆盖的最大值），按币种上限在主循环中下单前截断
+                config.max_order_size_overrides.values().fold(config.max_order_size_usdc, |a, &b| a.max(b)),
+                config.proxy_address,
+                config.slippage,
+                config.gtd_expiration_secs,
+                config.arbitrage_order_type.clone(),
+                config.dry_run,
+            )
+        })
+        .await
+        {
+            Ok(exec) => {
+                info!("交易执行器认证成功（可能使用了派生API key）");
+                Arc::new(
+                    exec.with_cancel_batching(config.cancel_batch_size, config.cancel_max_retries)
+                        .with_price_tick(price_tick)
+                        .with_post_only(config.post_only)
+                        .with_slippage_mode(config.slippage_mode)
+                        .with_size_precision(size_precision.clone()),
+                )
+            }
+            Err(e) => {
+                error!(error = %e, "交易执行器认证失败！无法继续运行。");
+                error!("请检查：");
+                error!("  1. POLYMARKET_PRIVATE_KEY 环境变量是否正确设置");
+                error!("  2. 私钥格式是否正确（应该是64字符的十六进制字符串，不带0x前缀）");
+                error!("  3. 网络连接是否正常");
+                error!("  4. Polymarket API服务是否可用");
+                notifier.notify(NotifyEvent::AuthFailed { error: e.to_string() });
+                notifier.flush().await;
+                return Err(anyhow::anyhow!("认证失败，程序退出: {}", e));
+            }
+        };
+
+        // 创建CLOB客户端用于风险管理（需要认证）
+        info!("正在初始化风险管理客户端（需要API认证）...");
+        use polymarket_client_sdk::clob::{Client, Config as ClobConfig};
+        use polymarket_client_sdk::clob::types::SignatureType;
+
+        let clob_client = match retry_auth(&config, "风险管理客户端认证", || async {
+            let clob_config = ClobConfig::builder().use_server_time(true).build();
+            let mut auth_builder_risk = Client::new(CLOB_ENDPOINT, clob_config)?
+                .authentication_builder(&signer);
+
+            // 如果提供了proxy_address，设置funder和signature_type
+            if let Some(funder) = config.proxy_address {
+                auth_builder_risk = auth_builder_risk
+                    .funder(funder)
+                    .signature_type(SignatureType::Proxy);
+            }
+
+            Ok(auth_builder_risk.authenticate().await?)
+        })
+        .await
+        {
+            Ok(client) => {
+                info!("风险管理客户端认证成功（可能使用了派生API key）");
+                client
+            }
+            Err(e) => {
+                error!(error = %e, "风险管理客户端认证失败！无法继续运行。");
+                error!("请检查：");
+                error!("  1. POLYMARKET_PRIVATE_KEY 环境变量是否正确设置");
+                error!("  2. 私钥格式是否正确");
+                error!("  3. 网络连接是否正常");
+                error!("  4. Polymarket API服务是否可用");
+                notifier.notify(NotifyEvent::AuthFailed { error: e.to_string() });
+                notifier.flush().await;
+                return Err(anyhow::anyhow!("认证失败，程序退出: {}", e));
+            }
+        };
+
+        let risk_manager = Arc::new(RiskManager::new(clob_client.clone(), &config));
+        // 恢复上次运行遗留的订单对（重建状态并回填敞口）
+        if let Err(e) = risk_manager.load_pending().await {
+            warn!(error = %e, "恢复订单对失败，从空状态启动");
+        }
+
+        // 用户频道：按推送的订单成交实时更新订单对与持仓（模拟交易不下真实订单，不订阅）
+        if config.enable_user_ws && !config.dry_run {
+            // 用户频道须以 API 凭证认证：按认证时同样的方式派生（同一签名器、默认 nonce 得到同一组凭证）
+            match Client::new(CLOB_ENDPOINT, ClobConfig::default())?.derive_api_key(&signer, None).await {
+                Ok(credentials) => {
+                    tokio::spawn(
+                        user_stream::run_user_stream(credentials, signer.address(), risk_manager.clone()).in_current_span(),
+                    );
+                }
+                Err(e) => warn!(error = %e, "派生 API 凭证失败，用户频道未启用"),
+            }
+        }
+
+        // 创建对冲监测器（传入PositionTracker的Arc引用以更新风险敞口）
+        // 仅在 ENABLE_HEDGE 开启时才会收到单边成交仓位
+        let hedge_monitor = Arc::new(HedgeMonitor::new(
+            clob_client.clone(),
+            signer.clone(),
+            config.proxy_address,
+            risk_manager.position_tracker(),
+            config.fee_params(),
+        )
+        .with_price_tick(price_tick)
+        .with_size_precision(size_precision.clone())
+        .with_sell_offset(config.hedge_sell_offset)
+        .with_persist_path(config.hedge_positions_path.as_ref().map(std::path::PathBuf::from)));
+        if config.enable_hedge {
+            info!("🛡️ 对冲已启用 | 单边成交将按买一价止盈止损卖出");
+            // 恢复上次运行遗留的对冲仓位（核对是否已平仓后继续监测）
+            if let Err(e) = hedge_monitor.load_positions(!config.dry_run).await {
+                warn!(error = %e, "恢复对冲仓位失败，从空状态启动");
+            }
+        }
+
+        // 验证认证是否真的成功 - 尝试一个简单的API调用
+        info!("正在验证认证状态（通过API调用测试）...");
+        match retry_auth(&config, "认证验证", || executor.verify_authentication()).await {
+            Ok(_) => {
+                info!("✅ 认证验证成功，API调用正常");
+            }
+            Err(e) => {
+                error!(error = %e, "❌ 认证验证失败！虽然authenticate()没有报错，但API调用失败。");
+                error!("这表明认证实际上没有成功，可能是：");
+                error!("  1. API密钥创建失败（看到'Could not create api key'警告）");
+                error!("  2. 私钥对应的账户可能没有在Polymarket上注册");
+                error!("  3. 账户可能被限制或暂停");
+                error!("  4. 网络连接问题");
+                error!("程序将退出，请解决认证问题后再运行。");
+                return Err(anyhow::anyhow!("认证验证失败: {}", e));
+            }
+        }
+
+        // 创建仓位平衡器
+        let position_balancer = Arc::new(PositionBalancer::new(
+            clob_client.clone(),
+            risk_manager.position_tracker(),
+            &config,
+        ));
+
+        // 交易节流：按市场的最小间隔 + 全局速率上限（固定间隔 + 随机延迟）
+        let trade_throttle = Arc::new(TradeThrottle::new(config.min_trade_interval_secs, config.global_trade_rate_per_sec));
+        // 执行中套利任务的并发上限：每个任务持有一个许可，任务结束时释放；无可用许可时跳过新机会
+        let trade_permits = Arc::new(tokio::sync::Semaphore::new(config.max_inflight_trades));
+        info!(
+            per_market_secs = trade_throttle.per_market_interval().as_secs_f64(),
+            global_secs = trade_throttle.global_interval().as_secs_f64(),
+            "⏱️ 交易节流 | 单市场最小间隔 / 全局最小间隔（0 表示不限制）"
+        );
+
+        Ok(Account {
+            config,
+            executor,
+            risk_manager,
+            hedge_monitor,
+            position_balancer,
+            wind_down_in_progress: Arc::new(AtomicBool::new(false)),
+            merge_guard: Arc::new(MergeGuard::default()),
+            trade_throttle,
+            trade_permits,
+            span: tracing::Span::current(),
+        })
+    }
+    .instrument(span)
+    .await
+}
+
+/// 启动账户的后台任务：定时持仓同步、订单对刷新、持仓时长上限与定时 Merge（任务日志挂在账户 span 下）
+fn spawn_account_tasks(
+    account: &Account,
+    rpc_pool: &Arc<RpcPool>,
+    book_cache: &BookCache,
+    notifier: &Arc<dyn Notifier>,
+    clock: &SharedClock,
+) {
+    let _account_guard = account.span.enter();
+    let config = &account.config;
+
+    // 定时持仓同步任务：每N秒从API获取最新持仓，覆盖本地缓存
+    // 模拟交易时不同步：API 持仓会覆盖模拟成交写入的本地持仓
+    let position_sync_interval = config.position_sync_interval_secs;
+    if config.dry_run {
+        info!("🧪 DRY_RUN：定时持仓同步已禁用，持仓由模拟成交累计");
+    } else if position_sync_interval > 0 {
+        let position_tracker_sync = account.risk_manager.position_tracker();
+        tokio::spawn(async move {
+            let interval = Duration::from_secs(position_sync_interval);
+            loop {
+                // 对账同步：近期有本地成交的 token 保留本地值，避免覆盖尚未结算到 Data API 的成交
+                match position_tracker_sync.reconcile().await {
+                    Ok(_) => {
+                        // 持仓信息已在 reconcile 中打印
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "持仓同步失败，将在下次循环重试");
+                    }
+                }
+                sleep(interval).await;
+            }
+        }.in_current_span());
+        info!(
+            interval_secs = position_sync_interval,
+            "已启动定时持仓同步任务，每 {} 秒从API获取最新持仓覆盖本地缓存",
+            position_sync_interval
+        );
+    } else {
+        warn!("POSITION_SYNC_INTERVAL_SECS=0，持仓同步已禁用");
+    }
+
+    // 定时刷新未完成订单对：查询两腿订单状态，计入挂单后才成交的部分，两腿结束（成交 / 撤销 / 过期）后移出
+    // 模拟交易的订单不存在于 CLOB，不启用
+    if config.pair_refresh_interval_secs > 0 && !config.dry_run {
+        /// 刚注册的订单对由下单流程处理，超过此时长才刷新
+        const PAIR_REFRESH_MIN_AGE: Duration = Duration::from_secs(10);
+        let risk_manager_refresh = account.risk_manager.clone();
+        let interval = Duration::from_secs(config.pair_refresh_interval_secs);
+        tokio::spawn(async move {
+            loop {
+                sleep(interval).await;
+                let refreshed = risk_manager_refresh.refresh_pending(PAIR_REFRESH_MIN_AGE).await;
+                if refreshed > 0 {
+                    debug!(refreshed, pending = risk_manager_refresh.pending_pair_count(), "订单对刷新完成");
+                }
+            }
+        }.in_current_span());
+    }
+
+    // 持仓时长上限任务：单腿持仓超过 MAX_POSITION_AGE_SECS 即强制卖出，不等窗口收尾
+    // 模拟交易时不启用：单腿判断依赖 API 持仓
+    if config.max_position_age_secs > 0 && !config.dry_run {
+        /// 检查持仓时长的间隔
+        const POSITION_AGE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+        tokio::spawn(
+            run_position_age_task(
+                account.executor.clone(),
+                config.clone(),
+                account.risk_manager.position_tracker(),
+                account.wind_down_in_progress.clone(),
+                book_cache.clone(),
+                Duration::from_secs(config.max_position_age_secs),
+                POSITION_AGE_CHECK_INTERVAL,
+            )
+            .in_current_span(),
+        );
+        info!(
+            max_age_secs = config.max_position_age_secs,
+            "已启动持仓时长上限任务，单腿持仓超过 {} 秒将强制卖出",
+            config.max_position_age_secs
+        );
     }
-    by_condition
-        .into_iter()
-        .filter(|(_, indices)| {
-            (indices.contains(&0) && indices.contains(&1)) || (indices.contains(&1) && indices.contains(&2))
-        })
-        .map(|(c, _)| c)
-        .collect()
-}
 
-/// 从持仓中构建 condition_id -> (yes_token_id, no_token_id, merge_amount)，用于 merge 成功后扣减敞口。
-/// 支持 outcome_index 0/1（0=Yes, 1=No）与 1/2（CTF 约定）。
-fn merge_info_with_both_sides(positions: &[Position]) -> HashMap<B256, (U256, U256, Decimal)> {
-    // outcome_index -> (asset, size) 按 condition 分组
-    let mut by_condition: HashMap<B256, HashMap<i32, (U256, Decimal)>> = HashMap::new();
-    for p in positions {
-        if p.size <= dec!(0) {
-            continue;
-        }
-        by_condition
-            .entry(p.condition_id)
-            .or_default()
-            .insert(p.outcome_index, (p.asset, p.size));
+    // 定时 Merge：每 N 分钟根据持仓执行 merge，仅对 YES+NO 双边都持仓的市场
+    let merge_interval = config.merge_interval_minutes;
+    if config.dry_run {
+        info!("🧪 DRY_RUN：定时 Merge 已禁用");
+    } else if merge_interval > 0 {
+        let config_merge = config.clone();
+        let position_tracker = account.risk_manager.position_tracker().clone();
+        let wind_down_flag = account.wind_down_in_progress.clone();
+        let merge_guard = account.merge_guard.clone();
+        let notifier_merge = notifier.clone();
+        let rpc_pool_merge = rpc_pool.clone();
+        let clock_merge = clock.clone();
+        tokio::spawn(async move {
+            run_merge_task(config_merge, rpc_pool_merge, position_tracker, wind_down_flag, merge_guard, notifier_merge, clock_merge).await;
+        }.in_current_span());
+        info!(
+            interval_minutes = merge_interval,
+            "已启动定时 Merge 任务，每 {} 分钟根据持仓执行（仅 YES+NO 双边）",
+            merge_interval
+        );
+    } else {
+        info!("定时 Merge 未启用（MERGE_INTERVAL_MINUTES=0），如需启用请在 .env 中设置 MERGE_INTERVAL_MINUTES 为正数，例如 5 或 15");
     }
-    by_condition
-        .into_iter()
-        .filter_map(|(c, map)| {
-            // 优先使用 CTF 约定 1=Yes, 2=No；否则使用 0=Yes, 1=No
-            if let (Some((yes_token, yes_size)), Some((no_token, no_size))) =
-                (map.get(&1).copied(), map.get(&2).copied())
-            {
-                return Some((c, (yes_token, no_token, yes_size.min(no_size))));
-            }
-            if let (Some((yes_token, yes_size)), Some((no_token, no_size))) =
-                (map.get(&0).copied(), map.get(&1).copied())
-            {
-                return Some((c, (yes_token, no_token, yes_size.min(no_size))));
-            }
-            None
-        })
-        .collect()
 }
 
-/// 定时 Merge 任务：每 interval_minutes 分钟拉取**持仓**，仅对 YES+NO 双边都持仓的市场 **串行**执行 merge_max，
-/// 单边持仓跳过；每笔之间间隔、对 RPC 限速做一次重试。Merge 成功后扣减 position_tracker 的持仓与敞口。
-/// 首次执行前短暂延迟，避免与订单簿监听的启动抢占同一 runtime，导致阻塞 stream。
-async fn run_merge_task(
-    interval_minutes: u64,
-    proxy: Address,
-    private_key: String,
-    position_tracker: Arc<PositionTracker>,
-    wind_down_in_progress: Arc<AtomicBool>,
+/// 对账户执行一次收尾；该账户的收尾已在进行中（窗口收尾与退出信号同时触发）时等待其完成，不重复收尾
+async fn wind_down_account(
+    account: Arc<Account>,
+    rpc_pool: Arc<RpcPool>,
+    notifier: Arc<dyn Notifier>,
+    book_cache: BookCache,
+    clock: SharedClock,
+    reason: String,
 ) {
-    let interval = Duration::from_secs(interval_minutes * 60);
-    /// 每笔 merge 之间间隔，降低 RPC  bursts
-    const DELAY_BETWEEN_MERGES: Duration = Duration::from_secs(30);
-    /// 遇限速时等待后重试的时长（略大于 "retry in 10s"）
-    const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(12);
-    /// 首次执行前延迟，让主循环先完成订单簿订阅并进入 select!，避免 merge 阻塞 stream
-    const INITIAL_DELAY: Duration = Duration::from_secs(10);
+    let span = account.span.clone();
+    async move {
+        if account.wind_down_in_progress.swap(true, Ordering::SeqCst) {
+            info!("收尾已在进行中，等待完成");
+            while account.wind_down_in_progress.load(Ordering::Relaxed) {
+                sleep(Duration::from_millis(500)).await;
+            }
+        } else {
+            wind_down(
+                account.executor.clone(),
+                account.config.clone(),
+                rpc_pool,
+                account.risk_manager.clone(),
+                account.merge_guard.clone(),
+                account.wind_down_in_progress.clone(),
+                notifier,
+                book_cache,
+                clock,
+                &reason,
+            )
+            .await;
+        }
+    }
+    .instrument(span)
+    .await
+}
 
-    // 先让主循环完成 get_markets、创建 stream 并进入订单簿监听，再执行第一次 merge
-    sleep(INITIAL_DELAY).await;
+/// 一个账户在当前窗口的处理通道：账户级的 PairContext，以及串行模式下的处理状态或 PER_MARKET_TASKS 下的市场任务
+struct AccountLane {
+    account: Arc<Account>,
+    ctx: Arc<PairContext>,
+    state: PairState,
+    tasks: Option<MarketTasks>,
+}
 
-    loop {
-        if wind_down_in_progress.load(Ordering::Relaxed) {
-            info!("收尾进行中，本轮回 merge 跳过");
-            sleep(interval).await;
-            continue;
+impl AccountLane {
+    fn dispatch(&mut self, pair: OrderBookPair) {
+        let _account_guard = self.account.span.enter();
+        match self.tasks.as_mut() {
+            Some(tasks) => tasks.dispatch(pair),
+            None => process_pair(&self.ctx, &mut self.state, pair),
         }
-        let (condition_ids, merge_info) = match get_positions().await {
-            Ok(positions) => (
-                condition_ids_with_both_sides(&positions),
-                merge_info_with_both_sides(&positions),
-            ),
-            Err(e) => {
-                warn!(error = %e, "❌ 获取持仓失败，跳过本轮回 merge");
-                sleep(interval).await;
-                continue;
-            }
-        };
+    }
 
-        if condition_ids.is_empty() {
-            debug!("🔄 本轮回 merge: 无满足 YES+NO 双边持仓的市场");
-        } else {
-            info!(
-                count = condition_ids.len(),
-                "🔄 本轮回 merge: 共 {} 个市场满足 YES+NO 双边持仓",
-                condition_ids.len()
-            );
+    /// PROFIT_PRIORITIZE：缓冲的最早到期时间
+    fn profit_deadline(&self) -> Option<Instant> {
+        self.ctx.profit_buffer.as_ref().and_then(|b| b.deadline())
+    }
+
+    /// 缓冲已到期时按利润率顺序执行
+    fn flush_due(&mut self) {
+        if self.profit_deadline().is_some_and(|d| d <= Instant::now()) {
+            let _account_guard = self.account.span.enter();
+            flush_prioritized(&self.ctx, &mut self.state);
         }
+    }
 
-        for (i, &condition_id) in condition_ids.iter().enumerate() {
-            // 第 2 个及以后的市场：先等 30 秒再 merge，避免与上一笔链上处理重叠
-            if i > 0 {
-                info!("本轮回 merge: 等待 30 秒后合并下一市场 (第 {}/{} 个)", i + 1, condition_ids.len());
-                sleep(DELAY_BETWEEN_MERGES).await;
-            }
-            let mut result = merge::merge_max(condition_id, proxy, &private_key, None).await;
-            if result.is_err() {
-                let msg = result.as_ref().unwrap_err().to_string();
-                if msg.contains("rate limit") || msg.contains("retry in") {
-                    warn!(condition_id = %condition_id, "⏳ RPC 限速，等待 {}s 后重试一次", RATE_LIMIT_BACKOFF.as_secs());
-                    sleep(RATE_LIMIT_BACKOFF).await;
-                    result = merge::merge_max(condition_id, proxy, &private_key, None).await;
-                }
-            }
-            match result {
-                Ok(tx) => {
-                    info!("✅ Merge 完成 | condition_id={:#x}", condition_id);
-                    info!("  📝 tx={}", tx);
-                    // Merge 成功：扣减持仓与风险敞口（先扣敞口再扣持仓，保证 update_exposure_cost 读到的是合并前持仓）
-                    if let Some((yes_token, no_token, merge_amt)) = merge_info.get(&condition_id) {
-                        position_tracker.update_exposure_cost(*yes_token, dec!(0), -*merge_amt);
-                        position_tracker.update_exposure_cost(*no_token, dec!(0), -*merge_amt);
-                        position_tracker.update_position(*yes_token, -*merge_amt);
-                        position_tracker.update_position(*no_token, -*merge_amt);
-                        info!(
-                            "💰 Merge 已扣减敞口 | condition_id={:#x} | 数量:{}",
-                            condition_id, merge_amt
-                        );
-                    }
-                }
-                Err(e) => {
-                    let msg = e.to_string();
-                    if msg.contains("无可用份额") {
-                        debug!(condition_id = %condition_id, "⏭️ 跳过 merge: 无可用份额");
-                    } else {
-                        warn!(condition_id = %condition_id, error = %e, "❌ Merge 失败");
-                    }
-                }
-            }
-            tokio::task::yield_now().await;
+    /// 窗口结束：汇总各市场任务的统计，输出本账户的窗口报告与累计盈亏
+    async fn finish(self, window_timestamp: i64) {
+        let mut window_report = self.state.window_report;
+        if let Some(tasks) = self.tasks {
+            tasks.finish(&mut window_report).await;
         }
+        let _account_guard = self.account.span.enter();
+        window_report.log_summary(window_timestamp);
+        log_pnl_summary(&self.account.risk_manager.position_tracker(), "累计盈亏");
+    }
+}
 
-        sleep(interval).await;
+/// 将订单簿对分发到每个账户通道（最后一个通道取得所有权，单账户时不复制）
+fn dispatch_to_lanes(lanes: &mut [AccountLane], pair: OrderBookPair) {
+    if let Some((last, rest)) = lanes.split_last_mut() {
+        for lane in rest {
+            lane.dispatch(pair.clone());
+        }
+        last.dispatch(pair);
     }
 }
 
@@ -183,125 +2223,74 @@ async fn run_merge_task(
 async fn main() -> Result<()> {
     // 初始化日志
     utils::logger::init_logger()?;
+    // JSON 日志下所有事件挂在携带 run_id 的根 span 下（main 由 block_on 在主线程执行，guard 不会跨线程）；
+    // 后台任务通过 in_current_span 继承该 span
+    let run_span = utils::logger::run_span();
+    let _run_guard = run_span.enter();
 
     tracing::info!("Polymarket 5分钟套利机器人启动");
 
+    // 自检模式：`poly_5min_bot --validate-only` 或 VALIDATE_ONLY=true，检查认证与配置后退出，不交易
+    let args: Vec<String> = std::env::args().collect();
+    dotenvy::dotenv().ok();
+    let validate_only = args.iter().any(|a| a == "--validate-only")
+        || std::env::var("VALIDATE_ONLY").ok().and_then(|v| config::parse_bool(&v)).unwrap_or(false);
+    if validate_only {
+        std::process::exit(run_validation().await);
+    }
+
     // 许可证校验：须存在有效 license.key，删除许可证将无法运行
-    poly_5min_bot::trial::check_license()?;
+    let license = poly_5min_bot::trial::check_license()?;
 
     // 加载配置
-    let config = Config::from_env()?;
-    tracing::info!("配置加载完成");
+    let config = load_config()?;
+    config.validate()?;
+    utils::display_time::init(config.display_tz);
+    tracing::info!("配置加载完成 | {}", config.summary());
+
+    // 回放模式：`poly_5min_bot replay <录制文件>`，离线回放订单簿并打印本会执行的套利，不连接交易接口
+    if args.get(1).map(String::as_str) == Some("replay") {
+        let path = args.get(2).ok_or_else(|| anyhow::anyhow!("用法: poly_5min_bot replay <录制文件>"))?;
+        return replay::run(&config, std::path::Path::new(path));
+    }
+    if config.dry_run {
+        warn!("🧪 DRY_RUN 已启用：只记录订单意图，不会实际下单、撤单或 Merge");
+    }
 
     // 初始化组件（暂时不使用，主循环已禁用）
-    let _discoverer = MarketDiscoverer::new(config.crypto_symbols.clone());
-    let _scheduler = MarketScheduler::new(_discoverer, config.market_refresh_advance_secs);
-    let _detector = ArbitrageDetector::new(config.min_profit_threshold);
-    
-    // 验证私钥格式
-    info!("正在验证私钥格式...");
-    use alloy::signers::local::LocalSigner;
-    use polymarket_client_sdk::POLYGON;
-    use std::str::FromStr;
+    let _discoverer = MarketDiscoverer::new(config.crypto_symbols.clone(), config.market_window)
+        .with_outcome_labels(config.market_outcome_labels.clone());
+    // 窗口边界、收尾触发与定时 Merge 共用的时钟
+    let clock: SharedClock = Arc::new(SystemClock);
+    let _scheduler = Arc::new(
+        MarketScheduler::new(_discoverer, config.market_refresh_advance_secs)
+            .with_clock(clock.clone())
+            .with_error_backoff(
+                Duration::from_secs(config.discovery_retry_base_secs.max(1)),
+                Duration::from_secs(config.discovery_retry_max_secs),
+            ),
+    );
+    let _detector = Arc::new(ArbitrageDetector::new(&config));
     
-    let _signer_test = LocalSigner::from_str(&config.private_key)
-        .map_err(|e| anyhow::anyhow!("私钥格式无效: {}", e))?;
-    info!("私钥格式验证通过");
-
-    // 初始化交易执行器（需要认证）
-    info!("正在初始化交易执行器（需要API认证）...");
-    if let Some(ref proxy) = config.proxy_address {
-        info!(proxy_address = %proxy, "使用Proxy签名类型（Email/Magic或Browser Wallet）");
-    } else {
-        info!("使用EOA签名类型（直接交易）");
-    }
-    info!("注意：如果看到'Could not create api key'警告，这是正常的。SDK会先尝试创建新API key，失败后会自动使用派生方式，认证仍然会成功。");
-    let executor = match TradingExecutor::new(
-        config.private_key.clone(),
-        config.max_order_size_usdc,
-        config.proxy_address,
-        config.slippage,
-        config.gtd_expiration_secs,
-        config.arbitrage_order_type.clone(),
-    ).await {
-        Ok(exec) => {
-            info!("交易执行器认证成功（可能使用了派生API key）");
-            Arc::new(exec)
-        }
-        Err(e) => {
-            error!(error = %e, "交易执行器认证失败！无法继续运行。");
-            error!("请检查：");
-            error!("  1. POLYMARKET_PRIVATE_KEY 环境变量是否正确设置");
-            error!("  2. 私钥格式是否正确（应该是64字符的十六进制字符串，不带0x前缀）");
-            error!("  3. 网络连接是否正常");
-            error!("  4. Polymarket API服务是否可用");
-            return Err(anyhow::anyhow!("认证失败，程序退出: {}", e));
-        }
-    };
+    // 事件通知（Telegram），未配置时为空实现
+    let notifier = notifier::from_config(&config);
 
-    // 创建CLOB客户端用于风险管理（需要认证）
-    info!("正在初始化风险管理客户端（需要API认证）...");
-    use alloy::signers::Signer;
-    use polymarket_client_sdk::clob::{Client, Config as ClobConfig};
-    use polymarket_client_sdk::clob::types::SignatureType;
-
-    let signer_for_risk = LocalSigner::from_str(&config.private_key)?
-        .with_chain_id(Some(POLYGON));
-    let clob_config = ClobConfig::builder().use_server_time(true).build();
-    let mut auth_builder_risk = Client::new("https://clob.polymarket.com", clob_config)?
-        .authentication_builder(&signer_for_risk);
-    
-    // 如果提供了proxy_address，设置funder和signature_type
-    if let Some(funder) = config.proxy_address {
-        auth_builder_risk = auth_builder_risk
-            .funder(funder)
-            .signature_type(SignatureType::Proxy);
+    // 按市场的数量精度：各账户的执行器、对冲监控与主循环共享，每个窗口开始时按币种登记
+    let size_precision = Arc::new(SizePrecision::new(config.size_decimals));
+    // 主账户与 EXTRA_ACCOUNTS 中的附加账户：共用市场发现与订单簿订阅，按账户各自下单、记账与 Merge
+    let account_configs = config.account_configs();
+    let multi_account = account_configs.len() > 1;
+    if multi_account {
+        info!(
+            accounts = %account_configs.iter().map(|c| c.account_id.as_str()).collect::<Vec<_>>().join(","),
+            "👥 多账户模式：共用市场发现与订单簿订阅，按账户各自执行"
+        );
     }
-    
-    let clob_client = match auth_builder_risk.authenticate().await {
-        Ok(client) => {
-            info!("风险管理客户端认证成功（可能使用了派生API key）");
-            client
-        }
-        Err(e) => {
-            error!(error = %e, "风险管理客户端认证失败！无法继续运行。");
-            error!("请检查：");
-            error!("  1. POLYMARKET_PRIVATE_KEY 环境变量是否正确设置");
-            error!("  2. 私钥格式是否正确");
-            error!("  3. 网络连接是否正常");
-            error!("  4. Polymarket API服务是否可用");
-            return Err(anyhow::anyhow!("认证失败，程序退出: {}", e));
-        }
-    };
-    
-    let _risk_manager = Arc::new(RiskManager::new(clob_client.clone(), &config));
-    
-    // 创建对冲监测器（传入PositionTracker的Arc引用以更新风险敞口）
-    // 对冲策略已暂时关闭，但保留hedge_monitor变量以备将来使用
-    let position_tracker = _risk_manager.position_tracker();
-    let _hedge_monitor = HedgeMonitor::new(
-        clob_client.clone(),
-        config.private_key.clone(),
-        config.proxy_address.clone(),
-        position_tracker,
-    );
-
-    // 验证认证是否真的成功 - 尝试一个简单的API调用
-    info!("正在验证认证状态（通过API调用测试）...");
-    match executor.verify_authentication().await {
-        Ok(_) => {
-            info!("✅ 认证验证成功，API调用正常");
-        }
-        Err(e) => {
-            error!(error = %e, "❌ 认证验证失败！虽然authenticate()没有报错，但API调用失败。");
-            error!("这表明认证实际上没有成功，可能是：");
-            error!("  1. API密钥创建失败（看到'Could not create api key'警告）");
-            error!("  2. 私钥对应的账户可能没有在Polymarket上注册");
-            error!("  3. 账户可能被限制或暂停");
-            error!("  4. 网络连接问题");
-            error!("程序将退出，请解决认证问题后再运行。");
-            return Err(anyhow::anyhow!("认证验证失败: {}", e));
-        }
+    let mut accounts: Vec<Arc<Account>> = Vec::with_capacity(account_configs.len());
+    for account_config in account_configs {
+        accounts.push(Arc::new(
+            init_account(account_config, notifier.clone(), size_precision.clone(), multi_account).await?,
+        ));
     }
 
     info!("✅ 所有组件初始化完成，认证验证通过");
@@ -310,44 +2299,45 @@ async fn main() -> Result<()> {
     let rpc_cfg = rpc_check::CheckConfig::builder()
         .timeout(Duration::from_secs(5))
         .build();
-    let _rpc_checker = rpc_check::RpcChecker::new(rpc_cfg);
-    let _rpc_circuit = rpc_check::CircuitBreaker::new();
-    let _rpc_metrics = rpc_check::Metrics::new();
-    let _ = _rpc_checker.validate_endpoint("https://clob.polymarket.com");
-    let _ = _rpc_checker.validate_endpoint("https://gamma-api.polymarket.com");
-
-    // 创建仓位平衡器
-    let position_balancer = Arc::new(PositionBalancer::new(
-        clob_client.clone(),
-        _risk_manager.position_tracker(),
-        &config,
+    let rpc_checker = Arc::new(rpc_check::RpcChecker::new(rpc_cfg));
+    for endpoint in [CLOB_ENDPOINT, "https://gamma-api.polymarket.com"] {
+        if !rpc_checker.validate_endpoint(endpoint) {
+            warn!(endpoint, "端点格式校验未通过");
+        }
+    }
+    // CLOB 健康闸门：下单失败（连接类错误）驱动熔断，熔断期间跳过套利，半开探测通过后自动恢复
+    let clob_health = Arc::new(EndpointHealth::new(
+        rpc_check::EndpointValidator::new().normalize(CLOB_ENDPOINT),
+        rpc_checker.clone(),
     ));
 
-    // 定时持仓同步任务：每N秒从API获取最新持仓，覆盖本地缓存
-    let position_sync_interval = config.position_sync_interval_secs;
-    if position_sync_interval > 0 {
-        let position_tracker_sync = _risk_manager.position_tracker();
-        tokio::spawn(async move {
-            let interval = Duration::from_secs(position_sync_interval);
-            loop {
-                match position_tracker_sync.sync_from_api().await {
-                    Ok(_) => {
-                        // 持仓信息已在 sync_from_api 中打印
-                    }
-                    Err(e) => {
-                        warn!(error = %e, "持仓同步失败，将在下次循环重试");
-                    }
-                }
-                sleep(interval).await;
-            }
-        });
-        info!(
-            interval_secs = position_sync_interval,
-            "已启动定时持仓同步任务，每 {} 秒从API获取最新持仓覆盖本地缓存",
-            position_sync_interval
-        );
-    } else {
-        warn!("POSITION_SYNC_INTERVAL_SECS=0，持仓同步已禁用");
+    // Merge 使用的 RPC 端点池：各账户的定时 merge 与收尾 merge 共用，熔断状态在其间共享
+    let rpc_pool = Arc::new(RpcPool::new(&config.polygon_rpc_urls, rpc_checker.clone()));
+
+    // 维护模式：`poly_5min_bot --cancel-all --merge-once`，对每个账户执行一次撤单 / Merge 后退出，不进入交易循环
+    let cancel_all = args.iter().any(|a| a == "--cancel-all");
+    let merge_once = args.iter().any(|a| a == "--merge-once");
+    if cancel_all || merge_once {
+        for account in &accounts {
+            run_maintenance(&account.executor, &account.config, &rpc_pool, cancel_all, merge_once)
+                .instrument(account.span.clone())
+                .await?;
+        }
+        return Ok(());
+    }
+
+    // Prometheus 指标服务：仅在设置 METRICS_PORT 时启动
+    if let Some(port) = config.metrics_port {
+        if let Err(e) = metrics::spawn_exporter(port) {
+            warn!(error = %e, "Prometheus 指标服务启动失败，继续运行");
+        }
+    }
+
+    // 状态服务（/healthz、/status）：仅在设置 STATUS_PORT 时启动；持仓与敞口为主账户的数据
+    if let Some(port) = config.status_port {
+        if let Err(e) = status::spawn_server(port, accounts[0].risk_manager.clone()).await {
+            warn!(error = %e, "状态服务启动失败，继续运行");
+        }
     }
 
     // 定时仓位平衡任务：每N秒检查持仓和挂单，取消多余挂单
@@ -363,78 +2353,169 @@ async fn main() -> Result<()> {
         info!("定时仓位平衡未启用（POSITION_BALANCE_INTERVAL_SECS=0）");
     }
 
-    // 收尾进行中标志：定时 merge 会检查并跳过，避免与收尾 merge 竞争
-    let wind_down_in_progress = Arc::new(AtomicBool::new(false));
+    // 退出中标志：收到退出信号后不再执行新的套利
+    let shutdown_requested = Arc::new(AtomicBool::new(false));
 
-    // 两次套利交易之间的最小间隔
-    const MIN_TRADE_INTERVAL: Duration = Duration::from_secs(3);
-    let last_trade_time: Arc<tokio::sync::Mutex<Option<Instant>>> = Arc::new(tokio::sync::Mutex::new(None));
+    // 订单簿缓存：各窗口的监控器共用，收尾时据此取单腿的买一价
+    let book_cache: BookCache = Arc::new(DashMap::new());
 
-    // 定时 Merge：每 N 分钟根据持仓执行 merge，仅对 YES+NO 双边都持仓的市场
-    let merge_interval = config.merge_interval_minutes;
-    if merge_interval > 0 {
-        if let Some(proxy) = config.proxy_address {
-            let private_key = config.private_key.clone();
-            let position_tracker = _risk_manager.position_tracker().clone();
-            let wind_down_flag = wind_down_in_progress.clone();
-            tokio::spawn(async move {
-                run_merge_task(merge_interval, proxy, private_key, position_tracker, wind_down_flag).await;
+    // 各账户的后台任务：持仓同步、订单对刷新、持仓时长上限与定时 Merge
+    for account in &accounts {
+        spawn_account_tasks(account, &rpc_pool, &book_cache, &notifier, &clock);
+    }
+
+    // 优雅退出：首次 SIGINT/SIGTERM 对每个账户执行一次收尾后退出；收尾期间再次收到信号则立即强制退出
+    {
+        let accounts_sd = accounts.clone();
+        let rpc_pool_sd = rpc_pool.clone();
+        let shutdown_flag = shutdown_requested.clone();
+        let notifier_sd = notifier.clone();
+        let book_cache_sd = book_cache.clone();
+        let clock_sd = clock.clone();
+        tokio::spawn(async move {
+            // 许可证宽限期结束与退出信号走同一收尾流程
+            tokio::select! {
+                _ = shutdown_signal() => {
+                    warn!("🛑 收到退出信号，停止套利并执行收尾（再次发送信号将立即强制退出）");
+                }
+                _ = poly_5min_bot::trial::watch_license(license) => {
+                    warn!("🛑 许可证已过期，停止套利并执行收尾（发送退出信号将立即强制退出）");
+                }
+            }
+            shutdown_flag.store(true, Ordering::Relaxed);
+            tokio::spawn(async {
+                shutdown_signal().await;
+                error!("再次收到退出信号，强制退出");
+                std::process::exit(130);
             });
-            info!(
-                interval_minutes = merge_interval,
-                "已启动定时 Merge 任务，每 {} 分钟根据持仓执行（仅 YES+NO 双边）",
-                merge_interval
-            );
-        } else {
-            warn!("MERGE_INTERVAL_MINUTES={} 但未设置 POLYMARKET_PROXY_ADDRESS，定时 Merge 已禁用", merge_interval);
-        }
-    } else {
-        info!("定时 Merge 未启用（MERGE_INTERVAL_MINUTES=0），如需启用请在 .env 中设置 MERGE_INTERVAL_MINUTES 为正数，例如 5 或 15");
+
+            // 各账户并行收尾；某账户的窗口收尾正在执行时等待其完成，不重复收尾
+            futures::future::join_all(accounts_sd.iter().map(|account| {
+                wind_down_account(
+                    account.clone(),
+                    rpc_pool_sd.clone(),
+                    notifier_sd.clone(),
+                    book_cache_sd.clone(),
+                    clock_sd.clone(),
+                    "收到退出信号".to_string(),
+                )
+            }))
+            .await;
+            for account in &accounts_sd {
+                let _account_guard = account.span.enter();
+                log_pnl_summary(&account.risk_manager.position_tracker(), "本次运行盈亏");
+            }
+            info!("👋 收尾完成，退出");
+            notifier_sd.flush().await;
+            std::process::exit(0);
+        }.in_current_span());
+    }
+
+    let order_jitter = Arc::new(OrderJitter::new(config.order_jitter_pct, config.order_jitter_seed));
+    let min_arbitrage_size = config.min_arbitrage_size;
+    let dynamic_sizing = config.dynamic_sizing;
+    let dynamic_sizing_fraction = config.dynamic_sizing_fraction;
+    let dynamic_sizing_floor = config.dynamic_sizing_floor_usdc;
+    let max_position_per_market = config.max_position_per_market_usdc;
+    // 重复信号去重：冷却时间为 0 时不启用；价格变化达到 N 个最小变动单位（0.01）即视为新机会
+    let dedup_cooldown = Duration::from_secs(config.arbitrage_dedup_cooldown_secs);
+    let dedup_min_move = Decimal::from(config.arbitrage_dedup_ticks) * dec!(0.01);
+    let spread_persist = Duration::from_millis(config.min_spread_persist_ms);
+    // 交易暂停开关：TRADING_HALT_FILE 存在或经状态服务 POST /halt 时不再执行新的买入套利（对所有账户生效）
+    let halt_switch = Arc::new(Mutex::new(HaltSwitch::new(config.trading_halt_file.clone())));
+    let min_spread_updates = config.min_spread_persist_updates;
+    if order_jitter.is_enabled() {
+        info!(
+            pct = config.order_jitter_pct,
+            seeded = config.order_jitter_seed.is_some(),
+            "🎲 下单抖动已启用：数量随机 ±{:.1}%，交易间隔额外随机延迟最多 {} 毫秒",
+            config.order_jitter_pct * 100.0,
+            MAX_TRADE_INTERVAL_JITTER.as_millis()
+        );
     }
 
+    // 订单簿录制：设置 ORDERBOOK_RECORD_PATH 时启用，各窗口共用同一文件
+    let recorder = match &config.orderbook_record_path {
+        Some(path) => match BookRecorder::spawn(std::path::Path::new(path)).await {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                warn!(error = %e, "订单簿录制启动失败，继续运行（不录制）");
+                None
+            }
+        },
+        None => None,
+    };
+
+    // 跳过记录：设置 SKIPPED_LOG_PATH 时启用，各窗口共用同一文件
+    let skip_logger = match &config.skipped_log_path {
+        Some(path) => match SkipLogger::spawn(std::path::Path::new(path)).await {
+            Ok(logger) => Some(logger),
+            Err(e) => {
+                warn!(error = %e, "跳过记录启动失败，继续运行（不记录）");
+                None
+            }
+        },
+        None => None,
+    };
+
+    // 首轮不重置敞口：启动时的敞口只来自恢复的订单对
+    let mut is_first_round = true;
+
+    // 上一窗口预取并预先建好的下一窗口（窗口时间戳, 市场, 订单簿监控器），窗口切换时直接使用
+    let mut prepared_window: Option<(i64, Vec<MarketInfo>, OrderBookMonitor)> = None;
+    let window_secs = config.market_window.secs();
+    // CARRY_PRICE_DIRECTION：按币种记录的上一拍价格在各窗口间共用
+    let symbol_last_prices = config.carry_price_direction.then(|| Arc::new(DashMap::new()));
+    // MAX_MARKETS_PER_WINDOW：市场过多时按币种优先级只订阅前 N 个
+    let market_selector = SymbolPriority::new(config.market_priority.clone());
+
     // 主循环已启用，开始监控和交易
     #[allow(unreachable_code)]
     loop {
-        // 立即获取当前窗口的市场，如果失败则等待下一个窗口
-        let markets = match _scheduler.get_markets_immediately_or_wait().await {
-            Ok(markets) => markets,
-            Err(e) => {
-                error!(error = %e, "获取市场失败");
-                sleep(Duration::from_secs(60)).await;
-                continue;
+        // 优先使用预取的下一窗口市场（零间隙切换），否则立即获取当前窗口的市场，失败则等待下一个窗口
+        let current_window = MarketDiscoverer::calculate_current_window_timestamp(clock.now(), window_secs);
+        let (markets, mut monitor) = match prepared_window.take() {
+            Some((window_timestamp, markets, monitor)) if window_timestamp == current_window => {
+                info!(market_count = markets.len(), "使用预取的市场切换到新窗口");
+                (markets, monitor)
             }
-        };
-
-        if markets.is_empty() {
-            warn!("未找到任何市场，跳过当前窗口");
-            continue;
-        }
+            _ => {
+                let markets = match _scheduler.get_markets_immediately_or_wait().await {
+                    Ok(markets) => markets,
+                    Err(e) => {
+                        error!(error = %e, "获取市场失败");
+                        sleep(Duration::from_secs(60)).await;
+                        continue;
+                    }
+                };
 
-        // RPC 端点健康检查（主循环前批量探测）
-        let _ep_validator = rpc_check::EndpointValidator::new();
-        let _ = _ep_validator.normalize("https://clob.polymarket.com");
-        let _status = _rpc_checker.perform_health_check_sync("https://clob.polymarket.com");
-        let _ = _rpc_circuit.status_allows_request(_status);
-        if !_rpc_circuit.is_open() {
-            _rpc_circuit.record_success();
-            _rpc_metrics.record_check(true);
-        }
+                if markets.is_empty() {
+                    warn!("未找到任何市场，跳过当前窗口");
+                    continue;
+                }
+                let markets = cap_markets(&market_selector, markets, config.max_markets_per_window);
 
-        // 新一轮开始：重置风险敞口，使本轮从 0 敞口重新累计
-        _risk_manager.position_tracker().reset_exposure();
+                let monitor = build_orderbook_monitor(&markets, &config, &recorder, &book_cache);
+                (markets, monitor)
+            }
+        };
 
-        // 初始化订单簿监控器
-        let mut monitor = OrderBookMonitor::new();
+        // CLOB 端点健康检查（每个窗口开始前探测一次，结果计入熔断器）
+        if !clob_health.probe().await {
+            warn!("⚠️ CLOB 端点健康检查未通过，熔断期间将跳过套利");
+        }
 
-        // 订阅所有市场
-        for market in &markets {
-            if let Err(e) = monitor.subscribe_market(market) {
-                error!(error = %e, market_id = %market.market_id, "订阅市场失败");
+        // 新一轮开始：重置各账户的风险敞口，使本轮从 0 敞口重新累计
+        for account in &accounts {
+            if !is_first_round {
+                account.risk_manager.position_tracker().reset_exposure();
             }
+            metrics::set_exposure(account.risk_manager.position_tracker().calculate_exposure());
         }
+        is_first_round = false;
 
-        // 创建订单簿流
-        let mut stream = match monitor.create_orderbook_stream() {
+        // 创建订单簿流（断线自动重连，不中断本窗口的监控循环）
+        let mut stream = match monitor.create_orderbook_stream_with_retry() {
             Ok(stream) => stream,
             Err(e) => {
                 error!(error = %e, "创建订单簿流失败");
@@ -443,19 +2524,64 @@ async fn main() -> Result<()> {
         };
 
         info!(market_count = markets.len(), "开始监控订单簿");
+        status::set_stream_alive(true);
+        // 订阅后尚未收到订单簿的 token：每秒 debug 记录，超过 BOOK_MISSING_WARN_SECS 后 warn 一次
+        let stream_started = Instant::now();
+        let book_missing_warn_after = (config.book_missing_warn_secs > 0)
+            .then(|| Duration::from_secs(config.book_missing_warn_secs));
+        let mut waiting_for_books = true;
+        // 订单簿流静默看门狗：自本流建立或最近一次订单簿更新起计时
+        let ws_stall_timeout = (config.ws_stall_timeout_secs > 0)
+            .then(|| Duration::from_secs(config.ws_stall_timeout_secs));
+        let mut last_book_at = Instant::now();
 
         // 记录当前窗口的时间戳，用于检测周期切换与收尾触发
-        use chrono::Utc;
-        use crate::market::discoverer::FIVE_MIN_SECS;
-        let current_window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(Utc::now());
-        let window_end = chrono::DateTime::from_timestamp(current_window_timestamp + FIVE_MIN_SECS, 0)
-            .unwrap_or_else(|| Utc::now());
+        let current_window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(clock.now(), window_secs);
+        let window_end = chrono::DateTime::from_timestamp(current_window_timestamp + window_secs, 0)
+            .unwrap_or_else(|| clock.now());
         let mut wind_down_done = false;
+        // 因进入新窗口而结束监控（订单簿流异常退出时仍在本窗口，为 false）
+        let mut window_changed = false;
+        status::set_window(current_window_timestamp, markets.len());
+        status::set_markets(monitor.snapshot_source());
+        info!(
+            "🪟 当前窗口 | 开始:{} | 结束:{}",
+            display_time::fmt_ts(current_window_timestamp),
+            display_time::fmt(window_end)
+        );
+        if config.spread_ramp_start != config.spread_ramp_end {
+            info!(
+                "📈 执行价差随窗口时间线性变化 | 开始:{} → 停止套利时:{}",
+                config.spread_ramp_start,
+                config.spread_ramp_end
+            );
+        }
+        if config.trade_start_delay_secs > 0 {
+            info!(
+                "⏳ 交易预热：窗口开始后 {} 秒内只监控不执行套利（至 {}）",
+                config.trade_start_delay_secs,
+                display_time::fmt_ts(current_window_timestamp + config.trade_start_delay_secs as i64)
+            );
+        }
 
-        // 创建市场ID到市场信息的映射
-        let market_map: HashMap<B256, &MarketInfo> = markets.iter()
-            .map(|m| (m.market_id, m))
-            .collect();
+        // 后台预取下一窗口的市场（窗口开始前 MARKET_REFRESH_ADVANCE_SECS 秒开始，未创建则持续重试）
+        let next_window_timestamp = current_window_timestamp + window_secs;
+        let scheduler_prefetch = _scheduler.clone();
+        let mut prefetch_task = Some(tokio::spawn(
+            async move { scheduler_prefetch.prefetch_window(next_window_timestamp).await }.in_current_span(),
+        ));
+        let mut next_window: Option<(i64, Vec<MarketInfo>, OrderBookMonitor)> = None;
+
+        // 精确在窗口边界切换，不依赖下方每秒一次的检查
+        let boundary = sleep(
+            (window_end - clock.now()).to_std().unwrap_or(Duration::ZERO),
+        );
+        tokio::pin!(boundary);
+
+        // 按币种登记本窗口各市场的数量精度（SIZE_DECIMALS_BTC 等）
+        for m in &markets {
+            size_precision.set_market(m.yes_token_id, m.no_token_id, config.size_decimals_for(&m.crypto_symbol));
+        }
 
         // 创建市场映射（condition_id -> (yes_token_id, no_token_id)）用于仓位平衡
         let market_token_map: HashMap<B256, (U256, U256)> = markets.iter()
@@ -463,8 +2589,9 @@ async fn main() -> Result<()> {
             .collect();
 
         // 创建定时仓位平衡定时器
+        // 模拟交易时不做挂单平衡：没有真实挂单，且平衡会撤销账户里的真实订单
         let balance_interval = config.position_balance_interval_secs;
-        let mut balance_timer = if balance_interval > 0 {
+        let mut balance_timer = if balance_interval > 0 && !config.dry_run {
             let mut timer = tokio::time::interval(Duration::from_secs(balance_interval));
             timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
             timer.tick().await; // 立即触发第一次
@@ -473,399 +2600,131 @@ async fn main() -> Result<()> {
             None
         };
 
-        // 按市场记录上一拍卖一价，用于计算涨跌方向（仅一次 HashMap 读写，不影响监控性能）
-        let last_prices: DashMap<B256, (Decimal, Decimal)> = DashMap::new();
+        // 每个账户一条处理通道：共用本窗口的市场与订单簿，按账户各自检查与执行
+        let market_map: HashMap<B256, MarketInfo> = markets.iter().map(|m| (m.market_id, m.clone())).collect();
+        let mut lanes: Vec<AccountLane> = accounts.iter().map(|account| {
+            account.trade_throttle.clear_markets();
+            let pair_ctx = Arc::new(PairContext {
+                config: account.config.clone(),
+                market_map: market_map.clone(),
+                window_end,
+                detector: _detector.clone(),
+                risk_manager: account.risk_manager.clone(),
+                executor: account.executor.clone(),
+                notifier: notifier.clone(),
+                hedge_monitor: account.hedge_monitor.clone(),
+                position_balancer: account.position_balancer.clone(),
+                clob_health: clob_health.clone(),
+                trade_permits: account.trade_permits.clone(),
+                trade_throttle: account.trade_throttle.clone(),
+                order_jitter: order_jitter.clone(),
+                halt_switch: halt_switch.clone(),
+                shutdown_requested: shutdown_requested.clone(),
+                last_prices: Arc::new(DashMap::new()),
+                symbol_last_prices: symbol_last_prices.clone(),
+                skip_logger: skip_logger.clone(),
+                book_cache: book_cache.clone(),
+                rpc_pool: rpc_pool.clone(),
+                merge_guard: account.merge_guard.clone(),
+                wind_down_in_progress: account.wind_down_in_progress.clone(),
+                profit_buffer: config
+                    .profit_prioritize
+                    .then(|| ProfitBuffer::new(Duration::from_millis(config.profit_prioritize_window_ms))),
+                last_executed: DashMap::new(),
+                spread_persist,
+                min_spread_updates,
+                min_arbitrage_size,
+                dynamic_sizing,
+                dynamic_sizing_fraction,
+                dynamic_sizing_floor,
+                max_position_per_market,
+                dedup_cooldown,
+                dedup_min_move,
+            });
+            AccountLane {
+                account: account.clone(),
+                // PER_MARKET_TASKS：各市场在独立任务中处理，主循环只负责接收与分发
+                tasks: config.per_market_tasks.then(|| MarketTasks::new(pair_ctx.clone())),
+                ctx: pair_ctx,
+                state: PairState::default(),
+            }
+        }).collect();
 
         // 监控订单簿更新
         loop {
             // 收尾检查：距窗口结束 <= N 分钟时执行一次收尾（不跳出，继续监控直到窗口结束由下方「新窗口检测」自然切换）
-            // 使用秒级精度，5分钟窗口下 num_minutes() 截断可能导致漏检
+            // 使用秒级精度，短窗口下 num_minutes() 截断可能导致漏检
             if config.wind_down_before_window_end_minutes > 0 && !wind_down_done {
-                let now = Utc::now();
+                let now = clock.now();
                 let seconds_until_end = (window_end - now).num_seconds();
-                let threshold_seconds = config.wind_down_before_window_end_minutes as i64 * 60;
-                if seconds_until_end <= threshold_seconds {
-                    info!("🛑 触发收尾 | 距窗口结束 {} 秒", seconds_until_end);
+                if wind_down_due(now, window_end, config.wind_down_before_window_end_minutes) {
                     wind_down_done = true;
-                    wind_down_in_progress.store(true, Ordering::Relaxed);
+                }
+                if wind_down_done {
+                    info!(
+                        "🛑 触发收尾 | 距窗口结束 {} 秒 | 触发时间:{} | 窗口结束:{}",
+                        seconds_until_end,
+                        display_time::fmt(now),
+                        display_time::fmt(window_end)
+                    );
+                }
+                for account in accounts.iter().filter(|_| wind_down_done) {
+                    // 退出信号触发的收尾正在执行时不重复收尾
+                    if account.wind_down_in_progress.swap(true, Ordering::SeqCst) {
+                        let _account_guard = account.span.enter();
+                        info!("收尾已在进行中，跳过本窗口收尾");
+                        continue;
+                    }
 
                     // 收尾在独立任务中执行，不阻塞订单簿；各市场 merge 之间间隔 30 秒
-                    let executor_wd = executor.clone();
-                    let config_wd = config.clone();
-                    let risk_manager_wd = _risk_manager.clone();
-                    let wind_down_flag = wind_down_in_progress.clone();
+                    let executor_wd = account.executor.clone();
+                    let config_wd = account.config.clone();
+                    let rpc_pool_wd = rpc_pool.clone();
+                    let risk_manager_wd = account.risk_manager.clone();
+                    let merge_guard_wd = account.merge_guard.clone();
+                    let wind_down_flag = account.wind_down_in_progress.clone();
+                    let notifier_wd = notifier.clone();
+                    let book_cache_wd = book_cache.clone();
+                    let clock_wd = clock.clone();
+                    let reason = format!("距窗口结束 {} 秒", seconds_until_end);
                     tokio::spawn(async move {
-                        const MERGE_INTERVAL: Duration = Duration::from_secs(30);
-
-                        // 1. 取消所有挂单
-                        if let Err(e) = executor_wd.cancel_all_orders().await {
-                            warn!(error = %e, "收尾：取消所有挂单失败，继续执行 Merge 与卖出");
-                        } else {
-                            info!("✅ 收尾：已取消所有挂单");
-                        }
-
-                        // 取消后等 10 秒再 Merge，避免取消前刚成交的订单尚未上链更新持仓
-                        const DELAY_AFTER_CANCEL: Duration = Duration::from_secs(10);
-                        sleep(DELAY_AFTER_CANCEL).await;
-
-                        // 2. Merge 双边持仓（每完成一个市场后等 30 秒再合并下一个）并更新敞口
-                        let position_tracker = risk_manager_wd.position_tracker();
-                        let mut did_any_merge = false;
-                        if let Some(proxy) = config_wd.proxy_address {
-                            match get_positions().await {
-                                Ok(positions) => {
-                                    let condition_ids = condition_ids_with_both_sides(&positions);
-                                    let merge_info = merge_info_with_both_sides(&positions);
-                                    let n = condition_ids.len();
-                                    for (i, condition_id) in condition_ids.iter().enumerate() {
-                                        match merge::merge_max(*condition_id, proxy, &config_wd.private_key, None).await {
-                                            Ok(tx) => {
-                                                did_any_merge = true;
-                                                info!("✅ 收尾：Merge 完成 | condition_id={:#x} | tx={}", condition_id, tx);
-                                                if let Some((yes_token, no_token, merge_amt)) = merge_info.get(condition_id) {
-                                                    position_tracker.update_exposure_cost(*yes_token, dec!(0), -*merge_amt);
-                                                    position_tracker.update_exposure_cost(*no_token, dec!(0), -*merge_amt);
-                                                    position_tracker.update_position(*yes_token, -*merge_amt);
-                                                    position_tracker.update_position(*no_token, -*merge_amt);
-                                                    info!("💰 收尾：Merge 已扣减敞口 | condition_id={:#x} | 数量:{}", condition_id, merge_amt);
-                                                }
-                                            }
-                                            Err(e) => {
-                                                warn!(condition_id = %condition_id, error = %e, "收尾：Merge 失败");
-                                            }
-                                        }
-                                        // 每完成一个市场的 merge 后等 30 秒再处理下一个，给链上时间
-                                        if i + 1 < n {
-                                            info!("收尾：等待 30 秒后合并下一市场");
-                                            sleep(MERGE_INTERVAL).await;
-                                        }
-                                    }
-                                }
-                                Err(e) => { warn!(error = %e, "收尾：获取持仓失败，跳过 Merge"); }
-                            }
-                        } else {
-                            warn!("收尾：未配置 POLYMARKET_PROXY_ADDRESS，跳过 Merge");
-                        }
-
-                        // 若有执行过 Merge，等半分钟再卖出单腿，给链上处理时间；无 Merge 则不等
-                        if did_any_merge {
-                            sleep(MERGE_INTERVAL).await;
-                        }
-
-                        // 3. 市价卖出剩余单腿持仓
-                        let wind_down_sell_price = Decimal::try_from(config_wd.wind_down_sell_price).unwrap_or(dec!(0.01));
-                        match get_positions().await {
-                            Ok(positions) => {
-                                for pos in positions.iter().filter(|p| p.size > dec!(0)) {
-                                    let size_floor = (pos.size * dec!(100)).floor() / dec!(100);
-                                    if size_floor < dec!(0.01) {
-                                        debug!(token_id = %pos.asset, size = %pos.size, "收尾：持仓过小，跳过卖出");
-                                        continue;
-                                    }
-                                    if let Err(e) = executor_wd.sell_at_price(pos.asset, wind_down_sell_price, size_floor).await {
-                                        warn!(token_id = %pos.asset, size = %pos.size, error = %e, "收尾：卖出单腿失败");
-                                    } else {
-                                        info!("✅ 收尾：已下卖单 | token_id={:#x} | 数量:{} | 价格:{:.4}", pos.asset, size_floor, wind_down_sell_price);
-                                    }
-                                }
-                            }
-                            Err(e) => { warn!(error = %e, "收尾：获取持仓失败，跳过卖出"); }
-                        }
-
-                        info!("🛑 收尾完成，继续监控至窗口结束");
-                        wind_down_flag.store(false, Ordering::Relaxed);
-                    });
+                        wind_down(executor_wd, config_wd, rpc_pool_wd, risk_manager_wd, merge_guard_wd, wind_down_flag, notifier_wd, book_cache_wd, clock_wd, &reason).await;
+                        info!("继续监控至窗口结束");
+                    }.instrument(account.span.clone()));
                 }
             }
 
+            // PROFIT_PRIORITIZE：缓冲中有机会时，到期后按利润率顺序执行
+            let profit_flush_at = lanes.iter().filter_map(AccountLane::profit_deadline).min();
+
             tokio::select! {
                 // 处理订单簿更新
                 book_result = stream.next() => {
                     match book_result {
                         Some(Ok(book)) => {
-                            // 然后处理订单簿更新（book会被move）
-                            if let Some(pair) = monitor.handle_book_update(book) {
-                                // 注意：asks 最后一个为卖一价
-                                let yes_best_ask = pair.yes_book.asks.last().map(|a| (a.price, a.size));
-                                let no_best_ask = pair.no_book.asks.last().map(|a| (a.price, a.size));
-                                let total_ask_price = yes_best_ask.and_then(|(p, _)| no_best_ask.map(|(np, _)| p + np));
-
-                                let market_id = pair.market_id;
-                                // 与上一拍比较得到涨跌方向（↑涨 ↓跌 −平），首拍无箭头
-                                let (yes_dir, no_dir) = match (yes_best_ask, no_best_ask) {
-                                    (Some((yp, _)), Some((np, _))) => {
-                                        let prev = last_prices.get(&market_id).map(|r| (r.0, r.1));
-                                        let (y_dir, n_dir) = prev
-                                            .map(|(ly, ln)| (
-                                                if yp > ly { "↑" } else if yp < ly { "↓" } else { "−" },
-                                                if np > ln { "↑" } else if np < ln { "↓" } else { "−" },
-                                            ))
-                                            .unwrap_or(("", ""));
-                                        last_prices.insert(market_id, (yp, np));
-                                        (y_dir, n_dir)
-                                    }
-                                    _ => ("", ""),
-                                };
-
-                                let market_info = market_map.get(&pair.market_id);
-                                let market_title = market_info.map(|m| m.title.as_str()).unwrap_or("未知市场");
-                                let market_symbol = market_info.map(|m| m.crypto_symbol.as_str()).unwrap_or("");
-                                let market_display = if !market_symbol.is_empty() {
-                                    format!("{}预测市场", market_symbol)
-                                } else {
-                                    market_title.to_string()
-                                };
-
-                                let (prefix, spread_info) = total_ask_price
-                                    .map(|t| {
-                                        if t < dec!(1.0) {
-                                            let profit_pct = (dec!(1.0) - t) * dec!(100.0);
-                                            ("🚨套利机会", format!("总价:{:.4} 利润:{:.2}%", t, profit_pct))
-                                        } else {
-                                            ("📊", format!("总价:{:.4} (无套利)", t))
-                                        }
-                                    })
-                                    .unwrap_or_else(|| ("📊", "无数据".to_string()));
-
-                                // 涨跌箭头仅在套利机会时显示
-                                let is_arbitrage = prefix == "🚨套利机会";
-                                let yes_info = yes_best_ask
-                                    .map(|(p, s)| {
-                                        if is_arbitrage && !yes_dir.is_empty() {
-                                            format!("Yes:{:.4} 份额:{} {}", p, s, yes_dir)
-                                        } else {
-                                            format!("Yes:{:.4} 份额:{}", p, s)
-                                        }
-                                    })
-                                    .unwrap_or_else(|| "Yes:无".to_string());
-                                let no_info = no_best_ask
-                                    .map(|(p, s)| {
-                                        if is_arbitrage && !no_dir.is_empty() {
-                                            format!("No:{:.4} 份额:{} {}", p, s, no_dir)
-                                        } else {
-                                            format!("No:{:.4} 份额:{}", p, s)
-                                        }
-                                    })
-                                    .unwrap_or_else(|| "No:无".to_string());
-
-                                info!(
-                                    "{} {} | {} | {} | {}",
-                                    prefix,
-                                    market_display,
-                                    yes_info,
-                                    no_info,
-                                    spread_info
-                                );
-                                
-                                // 保留原有的结构化日志用于调试（可选）
-                                debug!(
-                                    market_id = %pair.market_id,
-                                    yes_token = %pair.yes_book.asset_id,
-                                    no_token = %pair.no_book.asset_id,
-                                    "订单簿对详细信息"
-                                );
-
-                                // 检测套利机会（监控阶段：只有当总价 <= 1 - 套利执行价差 时才执行套利）
-                                use rust_decimal::Decimal;
-                                let execution_threshold = dec!(1.0) - Decimal::try_from(config.arbitrage_execution_spread)
-                                    .unwrap_or(dec!(0.01));
-                                if let Some(total_price) = total_ask_price {
-                                    if total_price <= execution_threshold {
-                                        if let Some(opp) = _detector.check_arbitrage(
-                                            &pair.yes_book,
-                                            &pair.no_book,
-                                            &pair.market_id,
-                                        ) {
-                                            // 检查 YES 价格是否达到阈值
-                                            if config.min_yes_price_threshold > 0.0 {
-                                                use rust_decimal::Decimal;
-                                                let min_yes_price_decimal = Decimal::try_from(config.min_yes_price_threshold)
-                                                    .unwrap_or(dec!(0.0));
-                                                if opp.yes_ask_price < min_yes_price_decimal {
-                                                    debug!(
-                                                        "⏸️ YES价格未达到阈值，跳过套利执行 | 市场:{} | YES价格:{:.4} | 阈值:{:.4}",
-                                                        market_display,
-                                                        opp.yes_ask_price,
-                                                        config.min_yes_price_threshold
-                                                    );
-                                                    continue; // 跳过这个套利机会
-                                                }
-                                            }
-                                            
-                                            // 检查 NO 价格是否达到阈值
-                                            if config.min_no_price_threshold > 0.0 {
-                                                use rust_decimal::Decimal;
-                                                let min_no_price_decimal = Decimal::try_from(config.min_no_price_threshold)
-                                                    .unwrap_or(dec!(0.0));
-                                                if opp.no_ask_price < min_no_price_decimal {
-                                                    debug!(
-                                                        "⏸️ NO价格未达到阈值，跳过套利执行 | 市场:{} | NO价格:{:.4} | 阈值:{:.4}",
-                                                        market_display,
-                                                        opp.no_ask_price,
-                                                        config.min_no_price_threshold
-                                                    );
-                                                    continue; // 跳过这个套利机会
-                                                }
-                                            }
-                                            
-                                            // 检查是否接近市场结束时间（如果配置了停止时间）
-                                            // 使用秒级精度，5分钟市场下 num_minutes() 截断可能导致漏检
-                                            if config.stop_arbitrage_before_end_minutes > 0 {
-                                                if let Some(market_info) = market_map.get(&pair.market_id) {
-                                                    use chrono::Utc;
-                                                    let now = Utc::now();
-                                                    let time_until_end = market_info.end_date.signed_duration_since(now);
-                                                    let seconds_until_end = time_until_end.num_seconds();
-                                                    let threshold_seconds = config.stop_arbitrage_before_end_minutes as i64 * 60;
-                                                    
-                                                    if seconds_until_end <= threshold_seconds {
-                                                        debug!(
-                                                            "⏰ 接近市场结束时间，跳过套利执行 | 市场:{} | 距离结束:{}秒 | 停止阈值:{}分钟",
-                                                            market_display,
-                                                            seconds_until_end,
-                                                            config.stop_arbitrage_before_end_minutes
-                                                        );
-                                                        continue; // 跳过这个套利机会
-                                                    }
-                                                }
-                                            }
-                                            
-                                            // 计算订单成本（USD）
-                                            // 使用套利机会中的实际可用数量，但不超过配置的最大订单大小
-                                            use rust_decimal::Decimal;
-                                            let max_order_size = Decimal::try_from(config.max_order_size_usdc).unwrap_or(dec!(100.0));
-                                            let order_size = opp.yes_size.min(opp.no_size).min(max_order_size);
-                                            let yes_cost = opp.yes_ask_price * order_size;
-                                            let no_cost = opp.no_ask_price * order_size;
-                                            let total_cost = yes_cost + no_cost;
-                                            
-                                            // 检查风险敞口限制
-                                            let position_tracker = _risk_manager.position_tracker();
-                                            let current_exposure = position_tracker.calculate_exposure();
-                                            
-                                            if position_tracker.would_exceed_limit(yes_cost, no_cost) {
-                                                warn!(
-                                                    "⚠️ 风险敞口超限，拒绝执行套利交易 | 市场:{} | 当前敞口:{:.2} USD | 订单成本:{:.2} USD | 限制:{:.2} USD",
-                                                    market_display,
-                                                    current_exposure,
-                                                    total_cost,
-                                                    position_tracker.max_exposure()
-                                                );
-                                                continue; // 跳过这个套利机会
-                                            }
-                                            
-                                            // 检查持仓平衡（使用本地缓存，零延迟）
-                                            if position_balancer.should_skip_arbitrage(opp.yes_token_id, opp.no_token_id) {
-                                                warn!(
-                                                    "⚠️ 持仓已严重不平衡，跳过套利执行 | 市场:{}",
-                                                    market_display
-                                                );
-                                                continue; // 跳过这个套利机会
-                                            }
-                                            
-                                            // 检查交易间隔：两次交易间隔不少于 3 秒
-                                            {
-                                                let mut guard = last_trade_time.lock().await;
-                                                let now = Instant::now();
-                                                if let Some(last) = *guard {
-                                                    if now.saturating_duration_since(last) < MIN_TRADE_INTERVAL {
-                                                        let elapsed = now.saturating_duration_since(last).as_secs_f32();
-                                                        debug!(
-                                                            "⏱️ 交易间隔不足 3 秒，跳过 | 市场:{} | 距上次:{}秒",
-                                                            market_display,
-                                                            elapsed
-                                                        );
-                                                        continue; // 跳过此套利机会
-                                                    }
-                                                }
-                                                *guard = Some(now);
-                                            }
-
-                                            info!(
-                                                "⚡ 执行套利交易 | 市场:{} | 利润:{:.2}% | 下单数量:{}份 | 订单成本:{:.2} USD | 当前敞口:{:.2} USD",
-                                                market_display,
-                                                opp.profit_percentage,
-                                                order_size,
-                                                total_cost,
-                                                current_exposure
-                                            );
-                                            // 简化敞口：只要执行套利就增加敞口，不管是否成交
-                                            let _pt = _risk_manager.position_tracker();
-                                            _pt.update_exposure_cost(opp.yes_token_id, opp.yes_ask_price, order_size);
-                                            _pt.update_exposure_cost(opp.no_token_id, opp.no_ask_price, order_size);
-                                            
-                                            // 套利执行：只要总价 <= 阈值即执行，不因涨跌组合跳过；涨跌仅用于滑点分配（仅下降=second，上涨与持平=first）
-                                            // 克隆需要的变量到独立任务中（涨跌方向用于按方向分配滑点）
-                                            let executor_clone = executor.clone();
-                                            let risk_manager_clone = _risk_manager.clone();
-                                            let opp_clone = opp.clone();
-                                            let yes_dir_s = yes_dir.to_string();
-                                            let no_dir_s = no_dir.to_string();
-                                            
-                                            // 使用 tokio::spawn 异步执行套利交易，不阻塞订单簿更新处理
-                                            tokio::spawn(async move {
-                                                // 执行套利交易（滑点：仅下降=second，上涨与持平=first）
-                                                match executor_clone.execute_arbitrage_pair(&opp_clone, &yes_dir_s, &no_dir_s).await {
-                                                    Ok(result) => {
-                                                        // 先保存 pair_id，因为 result 会被移动
-                                                        let pair_id = result.pair_id.clone();
-                                                        
-                                                        // 注册到风险管理器（传入价格信息以计算风险敞口）
-                                                        risk_manager_clone.register_order_pair(
-                                                            result,
-                                                            opp_clone.market_id,
-                                                            opp_clone.yes_token_id,
-                                                            opp_clone.no_token_id,
-                                                            opp_clone.yes_ask_price,
-                                                            opp_clone.no_ask_price,
-                                                        );
-
-                                                        // 处理风险恢复
-                                                        // 对冲策略已暂时关闭，买进单边不做任何处理
-                                                        match risk_manager_clone.handle_order_pair(&pair_id).await {
-                                                            Ok(action) => {
-                                                                // 对冲策略已关闭，不再处理MonitorForExit和SellExcess
-                                                                match action {
-                                                                    crate::risk::recovery::RecoveryAction::None => {
-                                                                        // 正常情况，无需处理
-                                                                    }
-                                                                    crate::risk::recovery::RecoveryAction::MonitorForExit { .. } => {
-                                                                        info!("单边成交，但对冲策略已关闭，不做处理");
-                                                                    }
-                                                                    crate::risk::recovery::RecoveryAction::SellExcess { .. } => {
-                                                                        info!("部分成交不平衡，但对冲策略已关闭，不做处理");
-                                                                    }
-                                                                    crate::risk::recovery::RecoveryAction::ManualIntervention { reason } => {
-                                                                        warn!("需要手动干预: {}", reason);
-                                                                    }
-                                                                }
-                                                            }
-                                                            Err(e) => {
-                                                                error!("风险处理失败: {}", e);
-                                                            }
-                                                        }
-                                                    }
-                                                    Err(e) => {
-                                                        // 错误详情已在executor中记录，这里只记录简要信息
-                                                        let error_msg = e.to_string();
-                                                        // 提取简化的错误信息
-                                                        if error_msg.contains("套利失败") {
-                                                            // 错误信息已经格式化好了，直接使用
-                                                            error!("{}", error_msg);
-                                                        } else {
-                                                            error!("执行套利交易失败: {}", error_msg);
-                                                        }
-                                                    }
-                                                }
-                                            });
-                                        }
+                            status::record_book_update();
+                            last_book_at = Instant::now();
+                            // 先检查各账户对冲仓位的止盈止损（卖出在独立任务中执行，不阻塞）
+                            if config.enable_hedge {
+                                for account in &accounts {
+                                    let checked = account.hedge_monitor
+                                        .check_and_execute(&book)
+                                        .instrument(account.span.clone())
+                                        .await;
+                                    if let Err(e) = checked {
+                                        warn!(error = %e, "对冲检查失败");
                                     }
                                 }
                             }
+
+                            // 然后处理订单簿更新（book会被move），分发到各账户
+                            if let Some(pair) = monitor.handle_book_update(book) {
+                                dispatch_to_lanes(&mut lanes, pair);
+                            }
                         }
                         Some(Err(e)) => {
-                            error!(error = %e, "订单簿更新错误");
-                            // 流错误，重新创建流
+                            error!(error = %e, "订单簿流多次重连失败");
+                            // 自动重连已放弃，重新创建流
                             break;
                         }
                         None => {
@@ -875,12 +2734,25 @@ async fn main() -> Result<()> {
                     }
                 }
 
+                // 缓冲的套利机会到期
+                _ = tokio::time::sleep_until(tokio::time::Instant::from_std(profit_flush_at.unwrap_or_else(Instant::now))), if profit_flush_at.is_some() => {
+                    for lane in lanes.iter_mut() {
+                        lane.flush_due();
+                    }
+                }
+
                 // 定时仓位平衡任务
                 _ = async {
                     if let Some(ref mut timer) = balance_timer {
                         timer.tick().await;
-                        if let Err(e) = position_balancer.check_and_balance_positions(&market_token_map).await {
-                            warn!(error = %e, "仓位平衡检查失败");
+                        for account in &accounts {
+                            let balanced = account.position_balancer
+                                .check_and_balance_positions(&market_token_map)
+                                .instrument(account.span.clone())
+                                .await;
+                            if let Err(e) = balanced {
+                                warn!(error = %e, "仓位平衡检查失败");
+                            }
                         }
                     } else {
                         futures::future::pending::<()>().await;
@@ -889,29 +2761,132 @@ async fn main() -> Result<()> {
                     // 仓位平衡任务已执行
                 }
 
-                // 定期检查：1) 是否进入新的5分钟窗口 2) 收尾触发（5分钟窗口需更频繁检查）
-                _ = sleep(Duration::from_secs(1)) => {
-                    let now = Utc::now();
-                    let new_window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(now);
+                // 下一窗口市场预取完成：提前建好订单簿监控器，窗口切换时直接订阅
+                prefetched = async { prefetch_task.as_mut().expect("预取任务存在").await }, if prefetch_task.is_some() => {
+                    prefetch_task = None;
+                    match prefetched {
+                        Ok(next_markets) => {
+                            let next_markets = cap_markets(&market_selector, next_markets, config.max_markets_per_window);
+                            let next_monitor = build_orderbook_monitor(&next_markets, &config, &recorder, &book_cache);
+                            info!(market_count = next_markets.len(), "📥 已预先建立下一窗口的订单簿监控器");
+                            next_window = Some((next_window_timestamp, next_markets, next_monitor));
+                        }
+                        Err(e) => warn!(error = %e, "预取下一窗口市场任务异常，窗口切换时重新获取"),
+                    }
+                }
+
+                // 到达窗口边界：立即切换
+                _ = &mut boundary => {
+                    info!(
+                        old_window = current_window_timestamp,
+                        new_window = next_window_timestamp,
+                        prefetched = next_window.is_some(),
+                        "到达窗口边界，切换到新窗口"
+                    );
+                    drop(stream);
+                    monitor.clear();
+                    window_changed = true;
+                    break;
+                }
+
+                // 定期检查：1) 是否进入新窗口 2) 收尾触发（短窗口需更频繁检查）
+                _ = clock.sleep(Duration::from_secs(1)) => {
+                    // 无机会时也定期检查每日亏损上限，使触发与 UTC 零点重置的日志及时输出
+                    for account in &accounts {
+                        let _account_guard = account.span.enter();
+                        account.risk_manager.daily_loss_halted();
+                    }
+                    let now = clock.now();
+                    let new_window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(now, window_secs);
 
                     // 如果当前窗口时间戳与记录的不同，说明已经进入新窗口
                     if new_window_timestamp != current_window_timestamp {
                         info!(
                             old_window = current_window_timestamp,
                             new_window = new_window_timestamp,
-                            "检测到新的5分钟窗口，准备取消旧订阅并切换到新窗口"
+                            window = %config.market_window,
+                            "检测到新窗口，准备取消旧订阅并切换到新窗口"
                         );
                         // 先drop stream以释放对monitor的借用，然后清理旧的订阅
                         drop(stream);
                         monitor.clear();
+                        window_changed = true;
                         break;
                     }
+
+                    // 订单簿流无报错也无结束却长时间不推送：按流异常处理，重建订阅
+                    if let Some(timeout) = ws_stall_timeout {
+                        let silent = last_book_at.elapsed();
+                        if silent >= timeout {
+                            warn!(
+                                silent_secs = silent.as_secs(),
+                                timeout_secs = timeout.as_secs(),
+                                last_book_update_ms = ?status::last_book_update_ms(),
+                                "⚠️ 订单簿流已静默超时，疑似 WebSocket 假死，重新创建订单簿流"
+                            );
+                            status::record_stream_stall();
+                            break;
+                        }
+                    }
+
+                    if waiting_for_books {
+                        let waited = stream_started.elapsed();
+                        let still_missing = monitor.log_missing_books(waited, book_missing_warn_after);
+                        // 全部到齐或已告警后不再检查，避免重复刷屏
+                        if !still_missing || book_missing_warn_after.is_some_and(|w| waited >= w) {
+                            waiting_for_books = false;
+                        }
+                    }
                 }
             }
         }
 
+        // 未完成的预取直接取消；已预取的下一窗口交给下一轮使用（订单簿流异常退出时仍在本窗口，下一轮按时间戳判断后丢弃）
+        if let Some(task) = prefetch_task.take() {
+            task.abort();
+        }
+        prepared_window = next_window;
+        status::set_stream_alive(false);
+
         // monitor 会在循环结束时自动 drop，无需手动清理
-        info!("当前窗口监控结束，刷新市场进入下一轮");
+        info!(reconnects = monitor.reconnect_count(), "当前窗口监控结束，刷新市场进入下一轮");
+        for lane in lanes {
+            lane.finish(current_window_timestamp).await;
+        }
+
+        // CANCEL_ORDERS_ON_WINDOW_CHANGE：取消旧窗口仍挂着的订单，避免其在市场结算期间成交；收尾已撤单时跳过。
+        // 在后台任务中撤单，不阻塞切换到新窗口
+        if window_changed && config.cancel_orders_on_window_change && !wind_down_done {
+            for account in &accounts {
+                let account = account.clone();
+                let span = account.span.clone();
+                tokio::spawn(
+                    async move {
+                        match account.executor.cancel_all_orders().await {
+                            Ok(summary) => summary.log("窗口切换"),
+                            Err(e) => warn!(error = %e, "窗口切换：取消旧窗口挂单失败"),
+                        }
+                    }
+                    .instrument(span),
+                );
+            }
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poly_5min_bot::clock::{Clock, MockClock};
+
+    #[test]
+    fn wind_down_fires_at_configured_offset() {
+        let window_end = chrono::DateTime::from_timestamp(1_770_972_300, 0).unwrap();
+        let clock = MockClock::new(window_end - chrono::Duration::seconds(61));
+        assert!(!wind_down_due(clock.now(), window_end, 1));
+        clock.advance(Duration::from_secs(1));
+        assert!(wind_down_due(clock.now(), window_end, 1));
+        clock.advance(Duration::from_secs(120));
+        assert!(wind_down_due(clock.now(), window_end, 1));
+    }
+}
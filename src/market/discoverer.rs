@@ -2,12 +2,61 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use polymarket_client_sdk::gamma::{Client, types::request::MarketsRequest};
 use polymarket_client_sdk::types::{B256, U256};
-use tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
-/// 5分钟窗口的秒数（供 main 等模块计算 window_end 使用）
-pub const FIVE_MIN_SECS: i64 = 300;
+/// 市场窗口大小：决定窗口对齐方式与 slug 后缀（如 btc-updown-5m-1770972300）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSize {
+    OneMin,
+    FiveMin,
+    FifteenMin,
+    OneHour,
+}
+
+impl WindowSize {
+    /// 解析窗口大小：1m、5m、15m、1h，大小写不敏感，无效或未知值返回 None
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "1m" => Some(WindowSize::OneMin),
+            "5m" => Some(WindowSize::FiveMin),
+            "15m" => Some(WindowSize::FifteenMin),
+            "1h" => Some(WindowSize::OneHour),
+            _ => None,
+        }
+    }
+
+    /// 窗口秒数（供 main 等模块计算 window_end 使用）
+    pub fn secs(&self) -> i64 {
+        match self {
+            WindowSize::OneMin => 60,
+            WindowSize::FiveMin => 300,
+            WindowSize::FifteenMin => 900,
+            WindowSize::OneHour => 3600,
+        }
+    }
+
+    /// slug 中的窗口后缀
+    pub fn slug_suffix(&self) -> &'static str {
+        match self {
+            WindowSize::OneMin => "1m",
+            WindowSize::FiveMin => "5m",
+            WindowSize::FifteenMin => "15m",
+            WindowSize::OneHour => "1h",
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
+impl std::fmt::Display for WindowSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.slug_suffix())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketInfo {
     pub market_id: B256,
     pub slug: String,
@@ -18,51 +67,104 @@ pub struct MarketInfo {
     pub crypto_symbol: String,
 }
 
+/// 空结果（市场尚未创建）的缓存时长：期间同一窗口的重复查询直接返回空，降低重试循环的请求量
+const EMPTY_RESULT_TTL: Duration = Duration::from_secs(5);
+
+/// 市场查询缓存项：非空结果在窗口内一直有效，空结果仅在 EMPTY_RESULT_TTL 内有效
+struct CachedMarkets {
+    markets: Vec<MarketInfo>,
+    fetched_at: Instant,
+}
+
 pub struct MarketDiscoverer {
     gamma_client: Client,
     crypto_symbols: Vec<String>,
+    window: WindowSize,
+    outcome_labels: Vec<(String, String)>, // 可接受的结果标签对（YES 标签, NO 标签），大小写不敏感
+    cache: Mutex<HashMap<(i64, Vec<String>), CachedMarkets>>, // (窗口时间戳, slug 列表) -> 查询结果
 }
 
 impl MarketDiscoverer {
-    pub fn new(crypto_symbols: Vec<String>) -> Self {
+    pub fn new(crypto_symbols: Vec<String>, window: WindowSize) -> Self {
         Self {
             gamma_client: Client::default(),
             crypto_symbols,
+            window,
+            outcome_labels: vec![("Up".to_string(), "Down".to_string())],
+            cache: Mutex::new(HashMap::new()),
         }
     }
 
-    /// 计算当前5分钟窗口的开始时间戳（UTC）
-    /// 窗口对齐到每5分钟整点：0, 5, 10, 15, 20, 25, 30, 35, 40, 45, 50, 55 分
-    pub fn calculate_current_window_timestamp(now: DateTime<Utc>) -> i64 {
+    /// 设置可接受的结果标签对，每对第一个标签映射为 YES、第二个映射为 NO
+    pub fn with_outcome_labels(mut self, outcome_labels: Vec<(String, String)>) -> Self {
+        self.outcome_labels = outcome_labels;
+        self
+    }
+
+    /// 当前配置的窗口大小
+    pub fn window(&self) -> WindowSize {
+        self.window
+    }
+
+    /// 计算当前窗口的开始时间戳（UTC）
+    /// 窗口对齐到 window_secs 的整数倍，如 5m 对齐到 0, 5, 10, ..., 55 分
+    pub fn calculate_current_window_timestamp(now: DateTime<Utc>, window_secs: i64) -> i64 {
         let ts = now.timestamp();
-        (ts / FIVE_MIN_SECS) * FIVE_MIN_SECS
+        (ts / window_secs) * window_secs
     }
 
-    /// 计算下一个5分钟窗口的开始时间戳（UTC）
-    pub fn calculate_next_window_timestamp(now: DateTime<Utc>) -> i64 {
+    /// 计算下一个窗口的开始时间戳（UTC）
+    pub fn calculate_next_window_timestamp(now: DateTime<Utc>, window_secs: i64) -> i64 {
         let ts = now.timestamp();
-        ((ts / FIVE_MIN_SECS) + 1) * FIVE_MIN_SECS
+        ((ts / window_secs) + 1) * window_secs
     }
 
     /// 生成市场slug列表
-    /// 5分钟市场格式：btc-updown-5m-1770972300
+    /// 市场格式：btc-updown-5m-1770972300（后缀随窗口大小变化：1m/5m/15m/1h）
     pub fn generate_market_slugs(&self, timestamp: i64) -> Vec<String> {
         self.crypto_symbols
             .iter()
-            .map(|symbol| format!("{}-updown-5m-{}", symbol, timestamp))
+            .map(|symbol| format!("{}-updown-{}-{}", symbol, self.window.slug_suffix(), timestamp))
             .collect()
     }
 
-    /// 获取指定时间戳的5分钟市场
+    /// 获取指定时间戳的窗口市场：已发现的窗口直接返回缓存，空结果在 EMPTY_RESULT_TTL 内不重复查询。
+    /// Ok(空) 表示市场尚未创建；Gamma 请求失败（网络错误、5xx 等）返回 Err 且不缓存，由调用方决定退避重试
     pub async fn get_markets_for_timestamp(&self, timestamp: i64) -> Result<Vec<MarketInfo>> {
         // 生成所有加密货币的slug
         let slugs = self.generate_market_slugs(timestamp);
+        let key = (timestamp, slugs.clone());
 
+        if let Some(cached) = self.cache.lock().unwrap().get(&key) {
+            if !cached.markets.is_empty() || cached.fetched_at.elapsed() < EMPTY_RESULT_TTL {
+                debug!(timestamp, count = cached.markets.len(), "使用缓存的市场查询结果");
+                return Ok(cached.markets.clone());
+            }
+        }
+
+        let markets = self.fetch_markets(timestamp, slugs).await?;
+
+        let mut cache = self.cache.lock().unwrap();
+        // 窗口切换后旧窗口的缓存不再需要（保留当前及之后的窗口，如预取的下一窗口）
+        let current = Self::calculate_current_window_timestamp(Utc::now(), self.window.secs());
+        cache.retain(|(ts, _), _| *ts >= current);
+        cache.insert(
+            key,
+            CachedMarkets {
+                markets: markets.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok(markets)
+    }
+
+    /// 调用 Gamma API 查询市场；请求失败时记录 HTTP 状态码（如有）并返回错误
+    async fn fetch_markets(&self, timestamp: i64, slugs: Vec<String>) -> Result<Vec<MarketInfo>> {
         info!(timestamp, slug_count = slugs.len(), "查询市场");
 
         // 使用Gamma API批量查询
         let request = MarketsRequest::builder()
-            .slug(slugs.clone())
+            .slug(slugs)
             .build();
 
         match self.gamma_client.markets(&request).await {
@@ -77,8 +179,15 @@ impl MarketDiscoverer {
                 Ok(valid_markets)
             }
             Err(e) => {
-                warn!(error = %e, timestamp = timestamp, "查询市场失败，可能市场尚未创建");
-                Ok(Vec::new())
+                let status = e
+                    .downcast_ref::<polymarket_client_sdk::error::Status>()
+                    .map(|s| s.status_code.as_u16());
+                warn!(error = %e, status = ?status, timestamp = timestamp, "Gamma 查询市场失败");
+                Err(anyhow::anyhow!(
+                    "Gamma 查询市场失败（HTTP 状态: {}）: {}",
+                    status.map(|s| s.to_string()).unwrap_or_else(|| "无".to_string()),
+                    e
+                ))
             }
         }
     }
@@ -92,25 +201,32 @@ impl MarketDiscoverer {
             return None;
         }
 
-        // 检查outcomes是否为["Up", "Down"]
+        // 检查outcomes是否为可接受的标签对之一（默认["Up", "Down"]）
         let outcomes = market.outcomes.as_ref()?;
 
-        if outcomes.len() != 2 
-           || !outcomes.contains(&"Up".to_string()) 
-           || !outcomes.contains(&"Down".to_string()) {
+        if outcomes.len() != 2 {
             return None;
         }
+        let (yes_label, no_label) = self.outcome_labels.iter().find(|(yes, no)| {
+            outcomes.iter().any(|o| o.eq_ignore_ascii_case(yes))
+                && outcomes.iter().any(|o| o.eq_ignore_ascii_case(no))
+        })?;
 
         // 获取clobTokenIds
         let token_ids = market.clob_token_ids.as_ref()?;
 
-        if token_ids.len() != 2 {
+        // clobTokenIds 与 outcomes 一一对应：按标签在 outcomes 中的位置分配 YES/NO，不依赖下标 0/1
+        let Some((yes_token_id, no_token_id)) =
+            resolve_token_order(outcomes, token_ids, yes_label, no_label)
+        else {
+            warn!(
+                slug = market.slug.as_deref().unwrap_or(""),
+                outcomes = ?outcomes,
+                token_count = token_ids.len(),
+                "市场 outcomes 与 clobTokenIds 不一致，跳过该市场"
+            );
             return None;
-        }
-
-        // 第一个是"Up"的token_id，第二个是"Down"的token_id
-        let yes_token_id = token_ids[0];
-        let no_token_id = token_ids[1];
+        };
 
         // 获取conditionId
         let market_id = market.condition_id?;
@@ -137,3 +253,74 @@ impl MarketDiscoverer {
         })
     }
 }
+
+/// 按 outcomes 中 YES/NO 标签的位置从 clobTokenIds 取出 (YES token, NO token)。
+/// 两个数组长度须均为 2，每个标签恰好出现一次，且两个 token_id 不同；否则视为元数据不一致返回 None。
+fn resolve_token_order(
+    outcomes: &[String],
+    token_ids: &[U256],
+    yes_label: &str,
+    no_label: &str,
+) -> Option<(U256, U256)> {
+    if outcomes.len() != 2 || token_ids.len() != 2 || token_ids[0] == token_ids[1] {
+        return None;
+    }
+    let position = |label: &str| {
+        let mut matches = outcomes.iter().enumerate().filter(|(_, o)| o.eq_ignore_ascii_case(label));
+        match (matches.next(), matches.next()) {
+            (Some((i, _)), None) => Some(i),
+            _ => None,
+        }
+    };
+    let yes_index = position(yes_label)?;
+    let no_index = position(no_label)?;
+    if yes_index == no_index {
+        return None;
+    }
+    Some((token_ids[yes_index], token_ids[no_index]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(a: &str, b: &str) -> Vec<String> {
+        vec![a.to_string(), b.to_string()]
+    }
+
+    #[test]
+    fn token_order_follows_outcome_labels() {
+        let tokens = [U256::from(11), U256::from(22)];
+        // outcomes 为 ["Up", "Down"]：YES 取第一个 token
+        assert_eq!(
+            resolve_token_order(&labels("Up", "Down"), &tokens, "Up", "Down"),
+            Some((tokens[0], tokens[1]))
+        );
+        // outcomes 反序为 ["Down", "Up"]：YES 取第二个 token
+        assert_eq!(
+            resolve_token_order(&labels("Down", "Up"), &tokens, "Up", "Down"),
+            Some((tokens[1], tokens[0]))
+        );
+        // 标签大小写不敏感
+        assert_eq!(
+            resolve_token_order(&labels("down", "UP"), &tokens, "Up", "Down"),
+            Some((tokens[1], tokens[0]))
+        );
+    }
+
+    #[test]
+    fn inconsistent_metadata_is_rejected() {
+        let tokens = [U256::from(11), U256::from(22)];
+        // token 数量与 outcomes 不匹配
+        assert_eq!(resolve_token_order(&labels("Up", "Down"), &tokens[..1], "Up", "Down"), None);
+        // 两个 token_id 相同
+        assert_eq!(
+            resolve_token_order(&labels("Up", "Down"), &[tokens[0], tokens[0]], "Up", "Down"),
+            None
+        );
+        // 同一标签出现两次
+        assert_eq!(resolve_token_order(&labels("Up", "up"), &tokens, "Up", "Down"), None);
+        // 缺少 NO 标签
+        assert_eq!(resolve_token_order(&labels("Up", "Yes"), &tokens, "Up", "Down"), None);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod discoverer;
 pub mod scheduler;
+pub mod selector;
 
 pub use discoverer::*;
 pub use scheduler::*;
+pub use selector::*;
@@ -1,14 +1,47 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use poly_5min_bot::clock::{SharedClock, SystemClock};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::sleep;
 use tracing::{error, info, warn};
 
 use super::discoverer::{MarketDiscoverer, MarketInfo};
 
+/// 市场尚未创建（查询结果为空）时的固定重试间隔
+const EMPTY_RETRY: Duration = Duration::from_secs(2);
+/// 当前窗口市场为空时的最长重试时间，超过后等待下一窗口
+const MAX_EMPTY_RETRY: Duration = Duration::from_secs(90);
+
+/// 查询出错时的指数退避：每次失败等待时间翻倍，不超过上限；查询成功（含空结果）后重置
+struct Backoff {
+    base: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max, next: base }
+    }
+
+    /// 本次应等待的时间，并把下次等待时间翻倍
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = self.next.saturating_mul(2).min(self.max);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.next = self.base;
+    }
+}
+
 pub struct MarketScheduler {
     discoverer: MarketDiscoverer,
     refresh_advance_secs: u64,
+    clock: SharedClock, // 当前时间与等待的来源，默认系统时钟
+    error_backoff_base: Duration, // 查询出错时的首次退避时间，默认 1 秒
+    error_backoff_max: Duration,  // 退避上限，默认 30 秒
 }
 
 impl MarketScheduler {
@@ -16,12 +49,33 @@ impl MarketScheduler {
         Self {
             discoverer,
             refresh_advance_secs,
+            clock: Arc::new(SystemClock),
+            error_backoff_base: Duration::from_secs(1),
+            error_backoff_max: Duration::from_secs(30),
         }
     }
 
-    /// 计算到下一个5分钟窗口的等待时间
+    /// 设置查询出错时的指数退避（首次等待 base，每次翻倍，不超过 max）
+    pub fn with_error_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.error_backoff_base = base;
+        self.error_backoff_max = max.max(base);
+        self
+    }
+
+    fn backoff(&self) -> Backoff {
+        Backoff::new(self.error_backoff_base, self.error_backoff_max)
+    }
+
+    /// 替换时钟（默认系统时钟）
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 计算到下一个窗口的等待时间
     pub fn calculate_wait_time(&self, now: DateTime<Utc>) -> Duration {
-        let next_window_ts = MarketDiscoverer::calculate_next_window_timestamp(now);
+        let window_secs = self.discoverer.window().secs();
+        let next_window_ts = MarketDiscoverer::calculate_next_window_timestamp(now, window_secs);
         let next_window = DateTime::from_timestamp(next_window_ts, 0)
             .expect("Invalid timestamp");
 
@@ -38,65 +92,99 @@ impl MarketScheduler {
     /// 立即获取当前窗口的市场，如果失败则等待下一个窗口
     pub async fn get_markets_immediately_or_wait(&self) -> Result<Vec<MarketInfo>> {
         // 首先尝试获取当前窗口的市场
-        let now = Utc::now();
-        let current_timestamp = MarketDiscoverer::calculate_current_window_timestamp(now);
-        let next_timestamp = MarketDiscoverer::calculate_next_window_timestamp(now);
+        let now = self.clock.now();
+        let window_secs = self.discoverer.window().secs();
+        let current_timestamp = MarketDiscoverer::calculate_current_window_timestamp(now, window_secs);
+        let next_timestamp = MarketDiscoverer::calculate_next_window_timestamp(now, window_secs);
 
-        // 如果当前窗口和下一个窗口相同（理论上 5m 不会发生），走等待逻辑
+        // 如果当前窗口和下一个窗口相同（理论上不会发生），走等待逻辑
         if current_timestamp == next_timestamp {
             return self.wait_for_next_window().await;
         }
 
         info!("尝试获取当前窗口的市场");
-        match self.discoverer.get_markets_for_timestamp(current_timestamp).await {
-            Ok(markets) => {
-                if !markets.is_empty() {
+        // 空结果（市场尚未创建）按固定短间隔重试，最多约 MAX_EMPTY_RETRY（5m 市场通常几秒内就绪）；
+        // 查询出错（网络错误、5xx）按指数退避重试，直到本窗口结束。若直接调用 wait_for_next_window 会等到下一窗口边界，导致跳过本窗口
+        let mut empty_waited = Duration::ZERO;
+        let mut backoff = self.backoff();
+        loop {
+            match self.discoverer.get_markets_for_timestamp(current_timestamp).await {
+                Ok(markets) if !markets.is_empty() => {
                     info!(count = markets.len(), "发现当前窗口的市场");
                     return Ok(markets);
                 }
-                // 当前窗口没有市场：可能是新市场尚未创建，先短间隔重试（5m 市场通常几秒内就绪）
-                // 若直接调用 wait_for_next_window 会等到下一窗口边界，导致跳过本窗口
-                const RETRY_SECS: u64 = 2;
-                const MAX_RETRY_SECS: u64 = 90; // 最多重试约 90 秒
-                let mut elapsed = 0u64;
-                while elapsed < MAX_RETRY_SECS {
-                    info!("当前窗口市场为空，{} 秒后重试（已等待 {} 秒）", RETRY_SECS, elapsed);
-                    sleep(Duration::from_secs(RETRY_SECS)).await;
-                    elapsed += RETRY_SECS;
-                    match self.discoverer.get_markets_for_timestamp(current_timestamp).await {
-                        Ok(markets) if !markets.is_empty() => {
-                            info!(count = markets.len(), "重试成功，发现当前窗口的市场");
-                            return Ok(markets);
-                        }
-                        _ => {}
+                Ok(_) => {
+                    backoff.reset();
+                    if empty_waited >= MAX_EMPTY_RETRY {
+                        warn!("重试 {} 秒后仍无市场，等待下一窗口", MAX_EMPTY_RETRY.as_secs());
+                        return self.wait_for_next_window().await;
                     }
+                    info!("当前窗口市场为空，{} 秒后重试（已等待 {} 秒）", EMPTY_RETRY.as_secs(), empty_waited.as_secs());
+                    self.clock.sleep(EMPTY_RETRY).await;
+                    empty_waited += EMPTY_RETRY;
+                }
+                Err(e) => {
+                    let delay = backoff.next_delay();
+                    let remaining = (next_timestamp - self.clock.now().timestamp()).max(0) as u64;
+                    if delay.as_secs() >= remaining {
+                        warn!(error = %e, "获取当前窗口市场失败且本窗口即将结束，等待下一个窗口");
+                        return self.wait_for_next_window().await;
+                    }
+                    warn!(error = %e, "获取当前窗口市场失败，{} 秒后重试", delay.as_secs_f64());
+                    self.clock.sleep(delay).await;
                 }
-                // 重试超时，等待下一窗口
-                warn!("重试 {} 秒后仍无市场，等待下一窗口", MAX_RETRY_SECS);
-                self.wait_for_next_window().await
-            }
-            Err(e) => {
-                warn!(error = %e, "获取当前窗口市场失败，等待下一个窗口");
-                self.wait_for_next_window().await
             }
         }
     }
 
-    /// 等待到下一个5分钟窗口开始，并获取市场
+    /// 预取指定窗口的市场：在窗口开始前 refresh_advance_secs 秒开始查询，
+    /// 市场尚未创建时每 2 秒重试、查询出错时指数退避重试，直到拿到市场为止（调用方在窗口切换后可直接取消）
+    pub async fn prefetch_window(&self, window_timestamp: i64) -> Vec<MarketInfo> {
+        let start_at = window_timestamp - self.refresh_advance_secs as i64;
+        let wait = (start_at - self.clock.now().timestamp()).max(0) as u64;
+        if wait > 0 {
+            self.clock.sleep(Duration::from_secs(wait)).await;
+        }
+
+        let mut backoff = self.backoff();
+        loop {
+            let delay = match self.discoverer.get_markets_for_timestamp(window_timestamp).await {
+                Ok(markets) if !markets.is_empty() => {
+                    info!(count = markets.len(), window_timestamp, "预取下一窗口市场成功");
+                    return markets;
+                }
+                Ok(_) => {
+                    backoff.reset();
+                    info!(window_timestamp, "下一窗口市场尚未创建，2 秒后重试预取");
+                    EMPTY_RETRY
+                }
+                Err(e) => {
+                    let delay = backoff.next_delay();
+                    warn!(error = %e, window_timestamp, "预取下一窗口市场失败，{} 秒后重试", delay.as_secs_f64());
+                    delay
+                }
+            };
+            self.clock.sleep(delay).await;
+        }
+    }
+
+    /// 等待到下一个窗口开始，并获取市场
     pub async fn wait_for_next_window(&self) -> Result<Vec<MarketInfo>> {
+        let mut backoff = self.backoff();
         loop {
-            let wait_time = self.calculate_wait_time(Utc::now());
+            let wait_time = self.calculate_wait_time(self.clock.now());
             if wait_time > Duration::ZERO {
                 info!(
                     wait_secs = wait_time.as_secs(),
-                    "等待下一个5分钟窗口"
+                    window = %self.discoverer.window(),
+                    "等待下一个窗口"
                 );
-                sleep(wait_time).await;
+                self.clock.sleep(wait_time).await;
             }
 
             // 查询当前窗口的市场
-            let now = Utc::now();
-            let timestamp = MarketDiscoverer::calculate_current_window_timestamp(now);
+            let now = self.clock.now();
+            let timestamp = MarketDiscoverer::calculate_current_window_timestamp(now, self.discoverer.window().secs());
             match self.discoverer.get_markets_for_timestamp(timestamp).await {
                 Ok(markets) => {
                     if !markets.is_empty() {
@@ -104,14 +192,41 @@ impl MarketScheduler {
                         return Ok(markets);
                     }
                     // 如果市场还未创建，等待一段时间后重试
+                    backoff.reset();
                     info!("市场尚未创建，等待重试...");
-                    sleep(Duration::from_secs(2)).await;
+                    self.clock.sleep(EMPTY_RETRY).await;
                 }
                 Err(e) => {
-                    error!(error = %e, "获取市场失败，重试...");
-                    sleep(Duration::from_secs(2)).await;
+                    let delay = backoff.next_delay();
+                    error!(error = %e, "获取市场失败，{} 秒后重试...", delay.as_secs_f64());
+                    self.clock.sleep(delay).await;
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::discoverer::WindowSize;
+    use poly_5min_bot::clock::{Clock, MockClock};
+
+    #[test]
+    fn wait_time_aligns_to_next_window() {
+        // 2026-02-13 08:45:00 UTC，5 分钟窗口边界
+        let boundary = DateTime::from_timestamp(1_770_972_300, 0).unwrap();
+        let clock = Arc::new(MockClock::new(boundary + chrono::Duration::seconds(210)));
+        let scheduler = MarketScheduler::new(MarketDiscoverer::new(vec!["btc".to_string()], WindowSize::FiveMin), 5)
+            .with_clock(clock.clone());
+
+        // 窗口内 3 分 30 秒：距下一窗口 90 秒，提前 5 秒查询
+        assert_eq!(scheduler.calculate_wait_time(clock.now()), Duration::from_secs(85));
+        // 恰好在窗口边界：等待下一个完整窗口
+        clock.set(boundary);
+        assert_eq!(scheduler.calculate_wait_time(clock.now()), Duration::from_secs(295));
+        // 提前量之内：不等待
+        clock.set(boundary + chrono::Duration::seconds(297));
+        assert_eq!(scheduler.calculate_wait_time(clock.now()), Duration::ZERO);
+    }
+}
@@ -0,0 +1,57 @@
+//! 每窗口市场数量上限（MAX_MARKETS_PER_WINDOW）：发现市场后按选择策略保留至多 N 个，其余不订阅。
+//! 选择策略经 MarketSelector 抽象，目前提供按币种优先级选择（SymbolPriority）。
+
+use tracing::info;
+
+use super::MarketInfo;
+
+/// 市场选择策略：从本窗口发现的市场中挑出至多 max 个，返回 (保留, 丢弃)
+pub trait MarketSelector: Send + Sync {
+    fn select(&self, markets: Vec<MarketInfo>, max: usize) -> (Vec<MarketInfo>, Vec<MarketInfo>);
+}
+
+/// 按币种优先级选择：币种在列表中越靠前越优先，不在列表中的排在最后；同一优先级保持发现顺序
+pub struct SymbolPriority {
+    order: Vec<String>,
+}
+
+impl SymbolPriority {
+    pub fn new(order: Vec<String>) -> Self {
+        Self { order }
+    }
+
+    fn rank(&self, market: &MarketInfo) -> usize {
+        self.order
+            .iter()
+            .position(|s| s.eq_ignore_ascii_case(&market.crypto_symbol))
+            .unwrap_or(usize::MAX)
+    }
+}
+
+impl MarketSelector for SymbolPriority {
+    fn select(&self, mut markets: Vec<MarketInfo>, max: usize) -> (Vec<MarketInfo>, Vec<MarketInfo>) {
+        markets.sort_by_key(|m| self.rank(m));
+        let dropped = markets.split_off(max.min(markets.len()));
+        (markets, dropped)
+    }
+}
+
+/// 按上限截取本窗口要订阅的市场，max 为 0 时不限制；被丢弃的市场逐个记录日志
+pub fn cap_markets(selector: &dyn MarketSelector, markets: Vec<MarketInfo>, max: usize) -> Vec<MarketInfo> {
+    if max == 0 || markets.len() <= max {
+        return markets;
+    }
+    let total = markets.len();
+    let (kept, dropped) = selector.select(markets, max);
+    info!(
+        total,
+        kept = kept.len(),
+        "✂️ 市场数量超过 MAX_MARKETS_PER_WINDOW={}，本窗口只订阅 {} 个",
+        max,
+        kept.len()
+    );
+    for market in &dropped {
+        info!(symbol = %market.crypto_symbol, slug = %market.slug, "  ✂️ 本窗口不订阅: {}", market.title);
+    }
+    kept
+}
@@ -1,6 +1,7 @@
 //! CTF Merge 模块：将等量 YES/NO 代币合并回 USDC。
 //!
-//! 支持 **Gnosis Safe**（execTransaction）与 **Magic/Email EIP-1167**（Polymarket Relayer）。
+//! 支持 **EOA**（直接调用 CTF mergePositions）、**Gnosis Safe**（execTransaction）与 **Magic/Email EIP-1167**（Polymarket Relayer）。
+//! EOA 模式下 `proxy` 传入签名器自身的地址。
 //! 合并数量自动取 `min(YES余额, NO余额)`，无需传入。
 //!
 //! ## 调用示例
@@ -12,7 +13,7 @@
 //! let tx = poly_15min_bot::merge::merge_max(
 //!     condition_id,
 //!     proxy,
-//!     &private_key,
+//!     &signer,
 //!     Some("https://polygon-rpc.com"),
 //! ).await?;
 //! ```
@@ -21,7 +22,7 @@ use std::env;
 
 use alloy::primitives::{keccak256, Address, B256, Bytes, U256};
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::signers::local::LocalSigner;
+use alloy::signers::local::PrivateKeySigner;
 use alloy::signers::Signer as _;
 use alloy::sol_types::SolCall;
 use anyhow::Result;
@@ -29,7 +30,6 @@ use polymarket_client_sdk::ctf::types::{CollectionIdRequest, MergePositionsReque
 use polymarket_client_sdk::ctf::Client;
 use polymarket_client_sdk::types::address;
 use polymarket_client_sdk::{contract_config, POLYGON};
-use std::str::FromStr as _;
 use tracing::{info, warn};
 
 use alloy::sol;
@@ -79,7 +79,7 @@ sol! {
     function proxy(ProxyCallTuple[] calls) external payable returns (bytes[] returnValues);
 }
 
-const RPC_URL_DEFAULT: &str = "https://polygon-rpc.com";
+pub const RPC_URL_DEFAULT: &str = "https://polygon-rpc.com";
 const RELAYER_URL_DEFAULT: &str = "https://relayer-v2.polymarket.com";
 const USDC_POLYGON: Address = address!("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174");
 
@@ -317,27 +317,90 @@ async fn relayer_execute_merge(
     Ok(hash.unwrap_or_else(|| text))
 }
 
+/// merge_max 失败的分类
+#[derive(Debug)]
+pub enum MergeError {
+    /// RPC 限速（rate limit / retry in），可等待后重试
+    RateLimited(anyhow::Error),
+    /// 无可用份额：YES 或 NO 余额为 0，跳过即可
+    NoShares { yes: U256, no: U256 },
+    /// RPC 端点不可用（连接失败、超时、网关错误），换端点重试才有意义
+    Rpc(anyhow::Error),
+    /// 其他错误（合约回滚、签名或 Relayer 失败等）
+    Other(anyhow::Error),
+}
+
+impl MergeError {
+    /// 将底层错误归类；RPC 传输层错误只能从错误信息识别，关键字集中在此处维护
+    pub fn classify(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<MergeError>() {
+            Ok(merge_err) => return merge_err,
+            Err(e) => e,
+        };
+        let msg = e.to_string().to_ascii_lowercase();
+        if ["rate limit", "retry in", "429"].iter().any(|p| msg.contains(p)) {
+            MergeError::RateLimited(e)
+        } else if ["connection", "timed out", "timeout", "error sending request", "502", "503", "504"]
+            .iter()
+            .any(|p| msg.contains(p))
+        {
+            MergeError::Rpc(e)
+        } else {
+            MergeError::Other(e)
+        }
+    }
+
+    /// 是否为端点本身的问题（限速或不可用），可切换端点
+    pub fn is_endpoint_error(&self) -> bool {
+        matches!(self, MergeError::RateLimited(_) | MergeError::Rpc(_))
+    }
+}
+
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::RateLimited(e) => write!(f, "RPC 限速: {}", e),
+            MergeError::NoShares { yes, no } => write!(f, "无可用份额可 merge：YES={} NO={}，至少一方为 0。", yes, no),
+            MergeError::Rpc(e) => write!(f, "RPC 端点不可用: {}", e),
+            MergeError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
 /// 对指定 `condition_id` 在 `proxy` 上合并最大可用 YES+NO 为 USDC。
 ///
 /// 合并数量为 `min(YES余额, NO余额)`。支持 Gnosis Safe（execTransaction）与 Magic/Email（Relayer）。
 ///
 /// - `condition_id`: 市场的 condition ID（32 字节十六进制）
 /// - `proxy`: Proxy 地址（Gnosis Safe 或 EIP-1167）
-/// - `private_key`: EOA 私钥
+/// - `signer`: EOA 签名器（链 ID 按 Polygon 重新绑定）
 /// - `rpc_url`: Polygon RPC，`None` 时用 `https://polygon-rpc.com`
 ///
 /// Magic/Email 路径会从环境变量读取：`POLY_BUILDER_API_KEY`、`POLY_BUILDER_SECRET`、`POLY_BUILDER_PASSPHRASE`、`RELAYER_URL`（可选）。
 ///
-/// 返回交易哈希（十六进制字符串）。
+/// 返回交易哈希（十六进制字符串）；失败时按 [`MergeError`] 分类，调用方据此决定重试或跳过。
 pub async fn merge_max(
     condition_id: B256,
     proxy: Address,
-    private_key: &str,
+    signer: &PrivateKeySigner,
+    rpc_url: Option<&str>,
+) -> std::result::Result<String, MergeError> {
+    merge_max_inner(condition_id, proxy, signer, rpc_url)
+        .await
+        .map_err(MergeError::classify)
+}
+
+async fn merge_max_inner(
+    condition_id: B256,
+    proxy: Address,
+    signer: &PrivateKeySigner,
     rpc_url: Option<&str>,
 ) -> Result<String> {
     let rpc = rpc_url.unwrap_or(RPC_URL_DEFAULT);
     let chain = POLYGON;
-    let signer = LocalSigner::from_str(private_key)?.with_chain_id(Some(chain));
+    let signer = signer.clone().with_chain_id(Some(chain));
     let wallet = signer.address();
 
     let provider = ProviderBuilder::new().wallet(signer.clone()).connect(rpc).await?;
@@ -362,11 +425,22 @@ pub async fn merge_max(
 
     let merge_amount = b_yes.min(b_no);
     if merge_amount == U256::ZERO {
-        anyhow::bail!("无可用份额可 merge：YES={} NO={}，至少一方为 0。", b_yes, b_no);
+        return Err(MergeError::NoShares { yes: b_yes, no: b_no }.into());
     }
     info!("🔄 合并数量: {} ({} USDC)", merge_amount, merge_amount / U256::from(1_000_000));
 
     let merge_req = MergePositionsRequest::for_binary_market(USDC_POLYGON, condition_id, merge_amount);
+
+    // EOA 模式：份额在签名地址本身，直接由 EOA 调用 CTF mergePositions
+    if proxy == wallet {
+        let resp = client
+            .merge_positions(&merge_req)
+            .await
+            .map_err(|e| anyhow::anyhow!("CTF.mergePositions 失败: {}", e))?;
+        info!("✅ Merge 成功（EOA）tx: {:#x}", resp.transaction_hash);
+        return Ok(format!("{:#x}", resp.transaction_hash));
+    }
+
     let merge_calldata = encode_merge_calldata(&merge_req);
     let code = provider.get_code_at(proxy).await.unwrap_or_default();
 
@@ -1,75 +1,440 @@
-use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use anyhow::{bail, Result};
+use polymarket_client_sdk::clob::ws::types::response::{BookUpdate, OrderBookLevel};
 use polymarket_client_sdk::types::{B256, Decimal, U256};
 use rust_decimal_macros::dec;
-use tracing::debug;
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+use crate::config::Config as BotConfig;
+use crate::trading::fees::FeeParams;
 
 #[derive(Debug, Clone)]
 pub struct ArbitrageOpportunity {
     pub market_id: B256,
     pub yes_token_id: U256,
     pub no_token_id: U256,
+    /// YES 成交均价（多档聚合时为 VWAP，单档时即卖一价）
     pub yes_ask_price: Decimal,
+    /// NO 成交均价（多档聚合时为 VWAP，单档时即卖一价）
     pub no_ask_price: Decimal,
+    /// YES 吃到的最深一档价格，executor 以此为限价才能吃满 yes_size
+    pub yes_worst_price: Decimal,
+    /// NO 吃到的最深一档价格
+    pub no_worst_price: Decimal,
     pub total_cost: Decimal,
     pub profit_percentage: Decimal,
     pub yes_size: Decimal,
     pub no_size: Decimal,
 }
 
+impl ArbitrageOpportunity {
+    /// 由选档结果构造套利机会并校验不变量（见 validate），total_cost 按双边均价 × 数量计算。
+    /// 不满足不变量时返回错误，避免异常机会进入执行
+    pub fn from_selection(
+        market_id: B256,
+        yes_token_id: U256,
+        no_token_id: U256,
+        sel: &Selection,
+    ) -> Result<Self> {
+        let opp = Self {
+            market_id,
+            yes_token_id,
+            no_token_id,
+            yes_ask_price: sel.yes_price,
+            no_ask_price: sel.no_price,
+            yes_worst_price: sel.yes_worst_price,
+            no_worst_price: sel.no_worst_price,
+            total_cost: sel.yes_price * sel.size + sel.no_price * sel.size,
+            profit_percentage: sel.profit_pct,
+            yes_size: sel.size,
+            no_size: sel.size,
+        };
+        opp.validate()?;
+        Ok(opp)
+    }
+
+    /// 按新的下单数量（如按币种上限截断后）更新双边数量，并同步重算 total_cost
+    pub fn with_size(mut self, size: Decimal) -> Self {
+        self.yes_size = size;
+        self.no_size = size;
+        self.total_cost = self.yes_ask_price * size + self.no_ask_price * size;
+        self
+    }
+
+    /// 校验不变量：价格在 (0, 1) 内且均价不高于最深档价格、YES/NO token 不同、双边数量为正、
+    /// total_cost 等于双边成本之和、净利润为正且不超过按价格计算的毛利润
+    pub fn validate(&self) -> Result<()> {
+        let in_range = |p: Decimal| p > dec!(0) && p < dec!(1);
+        for (name, price) in [
+            ("YES 均价", self.yes_ask_price),
+            ("NO 均价", self.no_ask_price),
+            ("YES 最深档价格", self.yes_worst_price),
+            ("NO 最深档价格", self.no_worst_price),
+        ] {
+            if !in_range(price) {
+                bail!("{} {} 不在 (0, 1) 内", name, price);
+            }
+        }
+        if self.yes_ask_price > self.yes_worst_price || self.no_ask_price > self.no_worst_price {
+            bail!(
+                "均价高于最深档价格: YES {}>{} 或 NO {}>{}",
+                self.yes_ask_price, self.yes_worst_price, self.no_ask_price, self.no_worst_price
+            );
+        }
+        if self.yes_token_id == self.no_token_id {
+            bail!("YES 与 NO token 相同: {}", self.yes_token_id);
+        }
+        if self.yes_size <= dec!(0) || self.no_size <= dec!(0) {
+            bail!("数量须为正: YES {} NO {}", self.yes_size, self.no_size);
+        }
+        let expected_cost = self.yes_ask_price * self.yes_size + self.no_ask_price * self.no_size;
+        if self.total_cost != expected_cost {
+            bail!("total_cost {} 与双边成本之和 {} 不一致", self.total_cost, expected_cost);
+        }
+        let gross_pct = (dec!(1) - self.yes_ask_price - self.no_ask_price) * dec!(100);
+        if self.profit_percentage <= dec!(0) || self.profit_percentage > gross_pct {
+            bail!("净利润 {}% 须为正且不超过毛利润 {}%", self.profit_percentage, gross_pct);
+        }
+        Ok(())
+    }
+}
+
+/// 卖出侧套利机会：YES 买一 + NO 买一 > 1 时，卖出已持有的 YES+NO 比 merge 赎回（每对 1 USDC）收益更高
+#[derive(Debug, Clone)]
+pub struct SellArbitrageOpportunity {
+    pub market_id: B256,
+    pub yes_token_id: U256,
+    pub no_token_id: U256,
+    /// YES 买一价
+    pub yes_bid_price: Decimal,
+    /// NO 买一价
+    pub no_bid_price: Decimal,
+    /// 毛收益比例：yes_bid + no_bid - 1
+    pub gross_edge: Decimal,
+    /// 扣除双边手续费后的利润百分比
+    pub profit_percentage: Decimal,
+    /// 买一档可卖份额（两侧较小值，未考虑持仓，执行前须按持仓截断）
+    pub size: Decimal,
+}
+
+/// evaluate 的显式输入：阈值、手续费与数量上限均由调用方给定，不读取配置
+#[derive(Debug, Clone)]
+pub struct EvaluateParams {
+    /// 扣费后最小净利润比例
+    pub min_profit_threshold: Decimal,
+    /// 手续费公式参数
+    pub fee: FeeParams,
+    /// 多档聚合时累计成本上限（USD）
+    pub max_order_cost_usd: Decimal,
+    /// 单侧最小订单金额（USD）
+    pub min_order_value_usd: Decimal,
+    /// 多档聚合时每对 YES+NO 成本上限（1 - 套利执行价差）
+    pub execution_threshold: Decimal,
+    /// 多档聚合最大探测深度
+    pub max_depth: usize,
+    /// true=多档聚合，false=仅卖一档
+    pub multi_level: bool,
+}
+
+/// 选档结果（价格均保留原始精度，size 已向下取整到 2 位小数）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
+    pub yes_worst_price: Decimal,
+    pub no_worst_price: Decimal,
+    pub size: Decimal,
+    pub profit_pct: Decimal,
+    pub total_price: Decimal,
+}
+
+/// 套利核心计算（纯函数）：给定 YES/NO 卖单阶梯（按价格降序，最后一个为卖一价，与 BookUpdate.asks 一致），
+/// 单档模式只看卖一价，多档模式按 VWAP 聚合；不满足最小金额或扣费后净利润时返回 None。
+pub fn evaluate(
+    yes_asks: &[OrderBookLevel],
+    no_asks: &[OrderBookLevel],
+    params: &EvaluateParams,
+) -> Option<Selection> {
+    if params.multi_level {
+        find_best_opportunity_multi_level(yes_asks, no_asks, params)
+    } else {
+        find_best_opportunity(yes_asks, no_asks, params)
+    }
+}
+
+/// 挂单（POST_ONLY）模式选档（纯函数）：双边各以买一价上方一个最小变动单位挂买单，挂价须低于卖一价，
+/// 否则会与卖单成交（post-only 被交易所拒绝），任一侧会穿价或无买盘时返回 None。
+/// 挂单为 maker，不计 taker 手续费；数量按累计成本上限折算，不受盘口数量限制（挂单可能部分或完全不成交）。
+pub fn evaluate_maker(
+    yes_book: &BookUpdate,
+    no_book: &BookUpdate,
+    params: &EvaluateParams,
+    tick: Decimal,
+) -> Option<Selection> {
+    let maker_price = |book: &BookUpdate| -> Option<Decimal> {
+        let price = book.bids.last()?.price + tick;
+        match book.asks.last() {
+            Some(ask) if price >= ask.price => None,
+            _ => Some(price),
+        }
+    };
+    let yes_price = maker_price(yes_book)?;
+    let no_price = maker_price(no_book)?;
+    let pair_price = yes_price + no_price;
+    if pair_price > params.execution_threshold {
+        return None;
+    }
+    let size = (params.max_order_cost_usd / pair_price * dec!(100.0)).floor() / dec!(100.0);
+    if size <= dec!(0) {
+        return None;
+    }
+    let maker_params = EvaluateParams {
+        fee: FeeParams::new(dec!(0), dec!(0), dec!(1)),
+        ..params.clone()
+    };
+    finish_selection(yes_price, no_price, yes_price, no_price, size, &maker_params)
+}
+
+/// 每对 YES+NO 扣除双边手续费后的净收益比例：
+/// 到手份额按 (100 - fee)/100 缩减，merge 只能按较少一侧计算，故取两侧净份额较小值再减去总价。
+fn net_profit_ratio(fee: &FeeParams, yes_price: Decimal, no_price: Decimal) -> Decimal {
+    let payout = fee.net_share_ratio(yes_price).min(fee.net_share_ratio(no_price));
+    payout - (yes_price + no_price)
+}
+
+/// 选中价格：仅用卖一价。
+/// 后续在 executor 中：比较哪个价格高 → 加滑点 → 放入订单创建。
+fn find_best_opportunity(
+    yes_asks: &[OrderBookLevel],
+    no_asks: &[OrderBookLevel],
+    params: &EvaluateParams,
+) -> Option<Selection> {
+    // asks 最后一个为卖一价（最低卖价）
+    let yes_best = yes_asks.last()?;
+    let no_best = no_asks.last()?;
+
+    let yes_price = yes_best.price.round_dp(2);
+    let no_price = no_best.price.round_dp(2);
+    let total_price = yes_price + no_price;
+
+    if total_price > dec!(1.0) {
+        return None; // 卖一总价 > 1，无套利
+    }
+
+    // 卖一档的可用份额取两者较小值，向下取整到 2 位小数
+    let raw_size = yes_best.size.min(no_best.size);
+    let final_size = if raw_size.is_zero() {
+        dec!(0.01)
+    } else {
+        (raw_size * dec!(100.0)).floor() / dec!(100.0)
+    };
+
+    finish_selection(yes_price, no_price, yes_price, no_price, final_size, params)
+}
+
+/// 多档聚合：沿 YES/NO 卖单阶梯同时向深处走，只要当前两档价格之和 <= 执行阈值就继续吃单，
+/// 直到任一侧耗尽、超过 max_depth 档或累计成本达到 max_order_cost_usd。返回双边 VWAP 与最深档价格。
+fn find_best_opportunity_multi_level(
+    yes_asks: &[OrderBookLevel],
+    no_asks: &[OrderBookLevel],
+    params: &EvaluateParams,
+) -> Option<Selection> {
+    // asks 按价格降序，倒序遍历即从卖一价开始
+    let yes_levels: Vec<(Decimal, Decimal)> = yes_asks
+        .iter()
+        .rev()
+        .take(params.max_depth)
+        .map(|l| (l.price.round_dp(2), l.size))
+        .collect();
+    let no_levels: Vec<(Decimal, Decimal)> = no_asks
+        .iter()
+        .rev()
+        .take(params.max_depth)
+        .map(|l| (l.price.round_dp(2), l.size))
+        .collect();
+
+    let (mut yi, mut ni) = (0usize, 0usize);
+    let mut yes_remaining = yes_levels.first()?.1;
+    let mut no_remaining = no_levels.first()?.1;
+    let mut size = dec!(0);
+    let mut yes_cost = dec!(0);
+    let mut no_cost = dec!(0);
+    let mut yes_worst = yes_levels[0].0;
+    let mut no_worst = no_levels[0].0;
+
+    while yi < yes_levels.len() && ni < no_levels.len() {
+        let (yp, _) = yes_levels[yi];
+        let (np, _) = no_levels[ni];
+        let pair_price = yp + np;
+        if pair_price > params.execution_threshold {
+            break;
+        }
+
+        let mut take = yes_remaining.min(no_remaining);
+        // 按累计成本上限折算为份额
+        let room = (params.max_order_cost_usd - yes_cost - no_cost) / pair_price;
+        let capped = room < take;
+        if capped {
+            take = room;
+        }
+        if take <= dec!(0) {
+            break;
+        }
+
+        size += take;
+        yes_cost += yp * take;
+        no_cost += np * take;
+        yes_worst = yp;
+        no_worst = np;
+        yes_remaining -= take;
+        no_remaining -= take;
+        if capped {
+            break;
+        }
+
+        if yes_remaining <= dec!(0) {
+            yi += 1;
+            if let Some(&(_, s)) = yes_levels.get(yi) {
+                yes_remaining = s;
+            }
+        }
+        if no_remaining <= dec!(0) {
+            ni += 1;
+            if let Some(&(_, s)) = no_levels.get(ni) {
+                no_remaining = s;
+            }
+        }
+    }
+
+    if size <= dec!(0) {
+        return None;
+    }
+
+    let final_size = (size * dec!(100.0)).floor() / dec!(100.0);
+    if final_size.is_zero() {
+        return None;
+    }
+    let yes_vwap = (yes_cost / size).round_dp(4);
+    let no_vwap = (no_cost / size).round_dp(4);
+
+    finish_selection(yes_vwap, no_vwap, yes_worst, no_worst, final_size, params)
+}
+
+/// 公共收尾：最小下单金额与扣费后净利润检查。
+/// profit_pct 已扣除双边预估手续费，低于 min_profit_threshold 时不返回。
+fn finish_selection(
+    yes_price: Decimal,
+    no_price: Decimal,
+    yes_worst_price: Decimal,
+    no_worst_price: Decimal,
+    size: Decimal,
+    params: &EvaluateParams,
+) -> Option<Selection> {
+    let total_price = yes_price + no_price;
+
+    let yes_order_value = yes_price * size;
+    let no_order_value = no_price * size;
+    if yes_order_value < params.min_order_value_usd || no_order_value < params.min_order_value_usd {
+        return None;
+    }
+
+    let net_ratio = net_profit_ratio(&params.fee, yes_price, no_price);
+    if net_ratio < params.min_profit_threshold {
+        debug!(
+            total_price = %total_price,
+            gross_profit_pct = %((dec!(1.0) - total_price) * dec!(100.0)),
+            net_profit_pct = %(net_ratio * dec!(100.0)),
+            "扣除手续费后利润不足，跳过"
+        );
+        return None;
+    }
+
+    Some(Selection {
+        yes_price,
+        no_price,
+        yes_worst_price,
+        no_worst_price,
+        size,
+        profit_pct: net_ratio * dec!(100.0),
+        total_price,
+    })
+}
+
+/// 某市场当前持仓（份），供 check_arbitrage 按 MARKET_INVENTORY_TARGET 控制单市场集中度
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarketInventory {
+    pub yes: Decimal,
+    pub no: Decimal,
+}
+
 pub struct ArbitrageDetector {
     min_profit_threshold: Decimal,
     max_depth: usize, // 最大探测深度
     min_order_value_usd: Decimal, // 最小订单金额（USD）
+    min_arbitrage_size: Decimal, // 最小套利数量（份），低于此值的机会直接忽略
+    fee: FeeParams, // 手续费公式参数，用于计算扣费后的净利润
+    execution_threshold: Decimal, // 每对 YES+NO 成本上限（1 - 套利执行价差），多档聚合时逐档比较
+    max_order_cost_usd: Decimal, // 多档聚合时累计成本上限（USD）
+    multi_level: bool, // true=多档聚合，false=仅卖一档（旧行为）
+    min_profit_overrides: HashMap<String, Decimal>, // 按币种的最小利润阈值（已合并全局默认）
+    max_order_cost_overrides: HashMap<String, Decimal>, // 按币种的累计成本上限（已合并全局默认）
+    market_inventory_target: Decimal, // 单市场持仓目标（份），0 表示不按持仓调整
+    post_only: bool, // true=按挂单价（买一 + 一个 tick）评估，见 evaluate_maker
+    price_tick: Decimal, // 最小价格单位，挂单模式下用于计算挂价
+    min_valid_ask: Decimal, // 任一侧卖一价低于此值视为订单簿数据异常，0 表示不检查
+    max_plausible_profit_pct: Decimal, // 净利润率（百分比）超过此值视为数据异常，0 表示不检查
 }
 
 impl ArbitrageDetector {
-    pub fn new(min_profit_threshold: f64) -> Self {
+    pub fn new(config: &BotConfig) -> Self {
         Self {
-            min_profit_threshold: Decimal::try_from(min_profit_threshold)
-                .unwrap_or(dec!(0.001)),
+            min_profit_threshold: config.min_profit_threshold,
             max_depth: 10, // 默认最多探测10档
             min_order_value_usd: dec!(1.0), // 最小订单金额$1
+            min_arbitrage_size: config.min_arbitrage_size,
+            market_inventory_target: config.market_inventory_target,
+            post_only: config.post_only,
+            price_tick: config.price_tick,
+            min_valid_ask: config.min_valid_ask,
+            max_plausible_profit_pct: config.max_plausible_profit_pct * dec!(100.0),
+            fee: config.fee_params(),
+            execution_threshold: dec!(1.0) - config.arbitrage_execution_spread,
+            max_order_cost_usd: config.max_order_size_usdc,
+            multi_level: config.arbitrage_multi_level,
+            min_profit_overrides: config
+                .crypto_symbols
+                .iter()
+                .map(|sym| (sym.clone(), config.min_profit_threshold_for(sym)))
+                .collect(),
+            max_order_cost_overrides: config
+                .crypto_symbols
+                .iter()
+                .map(|sym| (sym.clone(), config.max_order_size_for(sym)))
+                .collect(),
         }
     }
 
-    /// 选中价格：仅用卖一价。返回 (yes_ask, no_ask, size, profit_pct, total_price)。
-    /// 后续在 executor 中：比较哪个价格高 → 加滑点 → 放入订单创建。
-    fn find_best_opportunity(
-        &self,
-        yes_book: &BookUpdate,
-        no_book: &BookUpdate,
-    ) -> Option<(Decimal, Decimal, Decimal, Decimal, Decimal)> {
-        // asks 最后一个为卖一价（最低卖价）
-        let yes_best = yes_book.asks.last()?;
-        let no_best = no_book.asks.last()?;
-
-        let yes_price = yes_best.price.round_dp(2);
-        let no_price = no_best.price.round_dp(2);
-        let total_price = yes_price + no_price;
-
-        if total_price > dec!(1.0) {
-            return None; // 卖一总价 > 1，无套利
+    /// 按币种组装 evaluate 参数，无覆盖时用全局值
+    pub fn params_for(&self, symbol: &str) -> EvaluateParams {
+        EvaluateParams {
+            min_profit_threshold: self
+                .min_profit_overrides
+                .get(symbol)
+                .copied()
+                .unwrap_or(self.min_profit_threshold),
+            fee: self.fee,
+            max_order_cost_usd: self
+                .max_order_cost_overrides
+                .get(symbol)
+                .copied()
+                .unwrap_or(self.max_order_cost_usd),
+            min_order_value_usd: self.min_order_value_usd,
+            execution_threshold: self.execution_threshold,
+            max_depth: self.max_depth,
+            multi_level: self.multi_level,
         }
-
-        // 卖一档的可用份额取两者较小值，向下取整到 2 位小数
-        let raw_size = yes_best.size.min(no_best.size);
-        let final_size = if raw_size.is_zero() {
-            dec!(0.01)
-        } else {
-            (raw_size * dec!(100.0)).floor() / dec!(100.0)
-        };
-
-        let yes_order_value = yes_price * final_size;
-        let no_order_value = no_price * final_size;
-        if yes_order_value < self.min_order_value_usd || no_order_value < self.min_order_value_usd {
-            return None;
-        }
-
-        let profit_pct = (dec!(1.0) - total_price) * dec!(100.0);
-        Some((yes_price, no_price, final_size, profit_pct, total_price))
     }
 
-
     /// 打印订单深度（debug 级别，减少 info 刷屏）
     fn print_orderbook_depth(
         &self,
@@ -108,39 +473,389 @@ impl ArbitrageDetector {
         // 选档日志已移至 executor 中，在执行套利时打印加滑点后的价格
     }
 
-    /// 检查订单簿是否存在套利机会
+    /// 订单簿数据合理性检查：任一侧卖一价（或选中价格）低于 MIN_VALID_ASK，或净利润率超过 MAX_PLAUSIBLE_PROFIT_PCT 时
+    /// 视为订单簿异常（如某侧瞬时报出接近 0 的卖价），返回原因；正常时返回 None
+    fn implausible_reason(&self, yes_book: &BookUpdate, no_book: &BookUpdate, sel: &Selection) -> Option<String> {
+        if self.min_valid_ask > dec!(0) {
+            let lowest = |book: &BookUpdate, price: Decimal| book.asks.last().map_or(price, |a| a.price.min(price));
+            let (yes_low, no_low) = (lowest(yes_book, sel.yes_price), lowest(no_book, sel.no_price));
+            if yes_low < self.min_valid_ask || no_low < self.min_valid_ask {
+                return Some(format!(
+                    "卖一价过低（YES:{} NO:{}，下限 {}）",
+                    yes_low, no_low, self.min_valid_ask
+                ));
+            }
+        }
+        if self.max_plausible_profit_pct > dec!(0) && sel.profit_pct > self.max_plausible_profit_pct {
+            return Some(format!(
+                "利润率 {:.2}% 超过上限 {:.2}%",
+                sel.profit_pct, self.max_plausible_profit_pct
+            ));
+        }
+        None
+    }
+
+    /// 按单市场持仓目标计算剩余可买数量：已持有 max(YES, NO) 份，剩余 = 目标 - 已持有。
+    /// 未设置目标或未提供持仓时不限制（None）
+    fn inventory_headroom(&self, inventory: Option<MarketInventory>) -> Option<Decimal> {
+        if self.market_inventory_target <= dec!(0) {
+            return None;
+        }
+        let held = inventory.map(|inv| inv.yes.max(inv.no))?;
+        Some((self.market_inventory_target - held).max(dec!(0)))
+    }
+
+    /// 检查订单簿是否存在套利机会。
+    /// inventory 为该市场当前持仓：设置了 MARKET_INVENTORY_TARGET 时，返回的数量按剩余额度截断，
+    /// 剩余额度低于最小套利数量时不返回机会，使资金分散到各市场；传 None 时不按持仓调整。
+    /// execution_threshold 为每对 YES+NO 成本上限（SPREAD_RAMP_* 按窗口内时间插值后的值），None 时用 1 - ARBITRAGE_EXECUTION_SPREAD
     pub fn check_arbitrage(
         &self,
         yes_book: &BookUpdate,
         no_book: &BookUpdate,
         market_id: &B256,
+        symbol: &str,
+        inventory: Option<MarketInventory>,
+        execution_threshold: Option<Decimal>,
     ) -> Option<ArbitrageOpportunity> {
-        // 先选卖一价；executor 中再：比较谁高 → 加滑点 → 放入订单创建
-        let (yes_ask, no_ask, final_size, net_profit_pct, total_price) =
-            self.find_best_opportunity(yes_book, no_book)?;
+        let mut params = self.params_for(symbol);
+        if let Some(threshold) = execution_threshold {
+            params.execution_threshold = threshold;
+        }
+        // 先选档（单档=卖一价，多档=VWAP；挂单模式=买一价 + 一个 tick）；executor 中再：比较谁高 → 加滑点 → 放入订单创建
+        let sel = if self.post_only {
+            evaluate_maker(yes_book, no_book, &params, self.price_tick)?
+        } else {
+            evaluate(&yes_book.asks, &no_book.asks, &params)?
+        };
+        if let Some(reason) = self.implausible_reason(yes_book, no_book, &sel) {
+            warn!(
+                market_id = %market_id,
+                yes_price = %sel.yes_price,
+                no_price = %sel.no_price,
+                total_price = %sel.total_price,
+                "⚠️ 疑似订单簿数据异常，忽略机会 | {}",
+                reason
+            );
+            return None;
+        }
+        if sel.size < self.min_arbitrage_size {
+            debug!(
+                market_id = %market_id,
+                size = %sel.size,
+                min_size = %self.min_arbitrage_size,
+                "可成交数量低于最小套利数量，忽略"
+            );
+            return None;
+        }
+        let headroom = self.inventory_headroom(inventory);
+        if let Some(headroom) = headroom {
+            if headroom <= dec!(0) || headroom < self.min_arbitrage_size {
+                debug!(
+                    market_id = %market_id,
+                    headroom = %headroom,
+                    target = %self.market_inventory_target,
+                    "单市场持仓已接近目标，忽略"
+                );
+                return None;
+            }
+        }
 
-        self.print_orderbook_depth(yes_book, no_book, yes_ask, no_ask, final_size, final_size);
+        self.print_orderbook_depth(
+            yes_book,
+            no_book,
+            sel.yes_worst_price,
+            sel.no_worst_price,
+            sel.size,
+            sel.size,
+        );
 
         debug!(
             market_id = %market_id,
-            yes_price = %yes_ask,
-            no_price = %no_ask,
-            total_price = %total_price,
-            net_profit_pct = %net_profit_pct,
-            order_size = %final_size,
-            "发现套利机会（卖一价）"
+            yes_price = %sel.yes_price,
+            no_price = %sel.no_price,
+            total_price = %sel.total_price,
+            net_profit_pct = %sel.profit_pct,
+            order_size = %sel.size,
+            multi_level = self.multi_level,
+            "发现套利机会"
         );
 
-        Some(ArbitrageOpportunity {
+        match ArbitrageOpportunity::from_selection(*market_id, yes_book.asset_id, no_book.asset_id, &sel) {
+            Ok(opp) => match headroom {
+                Some(headroom) if headroom < sel.size => Some(opp.with_size(headroom)),
+                _ => Some(opp),
+            },
+            Err(e) => {
+                warn!(market_id = %market_id, error = %e, "套利机会校验失败，忽略");
+                None
+            }
+        }
+    }
+
+    /// 检查卖出侧套利：YES 与 NO 买一价之和 > 1 时，卖出一对 YES+NO 的到手金额高于 merge 赎回的 1 USDC。
+    /// 净利润 = 两侧扣费后的卖出所得 - 1；数量取两侧买一档较小值，调用方须再按实际持仓截断。
+    pub fn check_sell_arbitrage(
+        &self,
+        yes_book: &BookUpdate,
+        no_book: &BookUpdate,
+        market_id: &B256,
+        symbol: &str,
+    ) -> Option<SellArbitrageOpportunity> {
+        let min_profit_threshold = self.params_for(symbol).min_profit_threshold;
+        // bids 最后一个为买一价（最高买价）
+        let yes_best = yes_book.bids.last()?;
+        let no_best = no_book.bids.last()?;
+        let yes_bid = yes_best.price.round_dp(2);
+        let no_bid = no_best.price.round_dp(2);
+
+        let gross_edge = yes_bid + no_bid - dec!(1.0);
+        if gross_edge <= dec!(0) {
+            return None;
+        }
+
+        let size = (yes_best.size.min(no_best.size) * dec!(100.0)).floor() / dec!(100.0);
+        if yes_bid * size < self.min_order_value_usd || no_bid * size < self.min_order_value_usd {
+            return None;
+        }
+
+        let proceeds = yes_bid * self.fee.net_share_ratio(yes_bid) + no_bid * self.fee.net_share_ratio(no_bid);
+        let net_ratio = proceeds - dec!(1.0);
+        if net_ratio < min_profit_threshold {
+            debug!(
+                market_id = %market_id,
+                total_bid = %(yes_bid + no_bid),
+                gross_edge_pct = %(gross_edge * dec!(100.0)),
+                net_profit_pct = %(net_ratio * dec!(100.0)),
+                "卖出侧扣除手续费后利润不足，跳过"
+            );
+            return None;
+        }
+
+        Some(SellArbitrageOpportunity {
             market_id: *market_id,
             yes_token_id: yes_book.asset_id,
             no_token_id: no_book.asset_id,
-            yes_ask_price: yes_ask,
-            no_ask_price: no_ask,
-            total_cost: total_price * final_size,
-            profit_percentage: net_profit_pct,
-            yes_size: final_size,
-            no_size: final_size,
+            yes_bid_price: yes_bid,
+            no_bid_price: no_bid,
+            gross_edge,
+            profit_percentage: net_ratio * dec!(100.0),
+            size,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// asks 按价格降序传入（最后一个为卖一价），与 BookUpdate.asks 一致
+    fn book(asset_id: u64, asks: &[(Decimal, Decimal)]) -> BookUpdate {
+        BookUpdate::builder()
+            .asset_id(U256::from(asset_id))
+            .market(B256::ZERO)
+            .timestamp(0)
+            .bids(Vec::new())
+            .asks(
+                asks.iter()
+                    .map(|&(price, size)| OrderBookLevel::builder().price(price).size(size).build())
+                    .collect(),
+            )
+            .build()
+    }
+
+    fn detector(vars: &[(&str, &str)]) -> ArbitrageDetector {
+        ArbitrageDetector::new(&BotConfig::for_test(vars))
+    }
+
+    fn check(detector: &ArbitrageDetector, yes: &BookUpdate, no: &BookUpdate) -> Option<ArbitrageOpportunity> {
+        detector.check_arbitrage(yes, no, &B256::ZERO, "btc", None, None)
+    }
+
+    #[test]
+    fn gross_profit_eaten_by_fees_is_rejected() {
+        // 0.49 + 0.50 = 0.99，毛利润 1%；默认费率下两腿各扣约 1.56% 份额，净利润为负
+        let yes = book(1, &[(dec!(0.49), dec!(100))]);
+        let no = book(2, &[(dec!(0.50), dec!(100))]);
+        assert!(check(&detector(&[]), &yes, &no).is_none());
+
+        // 同一订单簿不计手续费时是有效机会
+        let no_fee = detector(&[("FEE_RATE", "0")]);
+        let opp = check(&no_fee, &yes, &no).expect("无手续费时应有套利机会");
+        assert_eq!(opp.profit_percentage, dec!(1.00));
+    }
+
+    #[test]
+    fn check_arbitrage_table() {
+        struct Case {
+            name: &'static str,
+            multi_level: bool,
+            yes_asks: Vec<(Decimal, Decimal)>,
+            no_asks: Vec<(Decimal, Decimal)>,
+            /// (YES 均价, NO 均价, 数量)
+            expected: Option<(Decimal, Decimal, Decimal)>,
+        }
+        let cases = [
+            Case {
+                name: "YES 侧无卖单",
+                multi_level: true,
+                yes_asks: vec![],
+                no_asks: vec![(dec!(0.50), dec!(100))],
+                expected: None,
+            },
+            Case {
+                name: "双边均无卖单",
+                multi_level: false,
+                yes_asks: vec![],
+                no_asks: vec![],
+                expected: None,
+            },
+            Case {
+                name: "单档取卖一价与较小数量",
+                multi_level: false,
+                yes_asks: vec![(dec!(0.45), dec!(80)), (dec!(0.40), dec!(50))],
+                no_asks: vec![(dec!(0.50), dec!(30))],
+                expected: Some((dec!(0.40), dec!(0.50), dec!(30))),
+            },
+            Case {
+                name: "多档按 VWAP 聚合直到一侧耗尽",
+                multi_level: true,
+                yes_asks: vec![(dec!(0.45), dec!(100)), (dec!(0.40), dec!(10))],
+                no_asks: vec![(dec!(0.50), dec!(100))],
+                // 10 份 @0.40 + 90 份 @0.45
+                expected: Some((dec!(0.445), dec!(0.50), dec!(100))),
+            },
+            Case {
+                name: "多档在超过执行阈值的档位停止",
+                multi_level: true,
+                yes_asks: vec![(dec!(0.55), dec!(100)), (dec!(0.40), dec!(20))],
+                no_asks: vec![(dec!(0.50), dec!(100))],
+                expected: Some((dec!(0.40), dec!(0.50), dec!(20))),
+            },
+            Case {
+                name: "交叉盘（总价 > 1）单档",
+                multi_level: false,
+                yes_asks: vec![(dec!(0.60), dec!(100))],
+                no_asks: vec![(dec!(0.55), dec!(100))],
+                expected: None,
+            },
+            Case {
+                name: "交叉盘（总价 > 1）多档",
+                multi_level: true,
+                yes_asks: vec![(dec!(0.60), dec!(100))],
+                no_asks: vec![(dec!(0.55), dec!(100))],
+                expected: None,
+            },
+            Case {
+                name: "总价恰好等于执行阈值 0.99",
+                multi_level: true,
+                yes_asks: vec![(dec!(0.49), dec!(100))],
+                no_asks: vec![(dec!(0.50), dec!(100))],
+                expected: Some((dec!(0.49), dec!(0.50), dec!(100))),
+            },
+            Case {
+                name: "总价高于执行阈值",
+                multi_level: true,
+                yes_asks: vec![(dec!(0.50), dec!(100))],
+                no_asks: vec![(dec!(0.50), dec!(100))],
+                expected: None,
+            },
+        ];
+
+        for case in cases {
+            let detector = detector(&[
+                ("FEE_RATE", "0"),
+                ("MIN_PROFIT_THRESHOLD", "0"),
+                ("ARBITRAGE_EXECUTION_SPREAD", "0.01"),
+                ("MAX_ORDER_SIZE_USDC", "1000"),
+                ("ARBITRAGE_MULTI_LEVEL", if case.multi_level { "true" } else { "false" }),
+            ]);
+            let yes = book(1, &case.yes_asks);
+            let no = book(2, &case.no_asks);
+            let got = check(&detector, &yes, &no).map(|o| (o.yes_ask_price, o.no_ask_price, o.yes_size));
+            assert_eq!(got, case.expected, "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn min_arbitrage_size_boundary() {
+        let detector = detector(&[("FEE_RATE", "0"), ("MIN_ARBITRAGE_SIZE", "10")]);
+        let no = book(2, &[(dec!(0.50), dec!(100))]);
+        for (size, accepted) in [(dec!(9.99), false), (dec!(10), true), (dec!(10.01), true)] {
+            let yes = book(1, &[(dec!(0.40), size)]);
+            let opp = check(&detector, &yes, &no);
+            assert_eq!(opp.is_some(), accepted, "size {}", size);
+            if let Some(opp) = opp {
+                assert_eq!(opp.yes_size, size);
+            }
+        }
+    }
+
+    fn selection() -> Selection {
+        Selection {
+            yes_price: dec!(0.40),
+            no_price: dec!(0.50),
+            yes_worst_price: dec!(0.41),
+            no_worst_price: dec!(0.50),
+            size: dec!(10),
+            profit_pct: dec!(8),
+            total_price: dec!(0.90),
+        }
+    }
+
+    fn from_selection(sel: &Selection) -> Result<ArbitrageOpportunity> {
+        ArbitrageOpportunity::from_selection(B256::ZERO, U256::from(1), U256::from(2), sel)
+    }
+
+    #[test]
+    fn from_selection_accepts_valid_selection() {
+        let opp = from_selection(&selection()).expect("合法选档应通过校验");
+        assert_eq!(opp.total_cost, dec!(9.0));
+        assert!(opp.clone().with_size(dec!(5)).validate().is_ok());
+    }
+
+    type Mutation = fn(&mut Selection);
+
+    #[test]
+    fn from_selection_rejects_invariant_violations() {
+        let cases: Vec<(&str, Mutation)> = vec![
+            ("价格为 0", |s| s.yes_price = dec!(0)),
+            ("价格为 1", |s| {
+                s.no_price = dec!(1);
+                s.no_worst_price = dec!(1);
+            }),
+            ("均价高于最深档", |s| s.yes_worst_price = dec!(0.39)),
+            ("数量为 0", |s| s.size = dec!(0)),
+            ("数量为负", |s| s.size = dec!(-1)),
+            ("净利润为 0", |s| s.profit_pct = dec!(0)),
+            ("净利润超过毛利润", |s| s.profit_pct = dec!(10.01)),
+        ];
+        for (name, mutate) in cases {
+            let mut sel = selection();
+            mutate(&mut sel);
+            assert!(from_selection(&sel).is_err(), "{}", name);
+        }
+
+        let same_token = ArbitrageOpportunity::from_selection(B256::ZERO, U256::from(1), U256::from(1), &selection());
+        assert!(same_token.is_err());
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_fields() {
+        let opp = from_selection(&selection()).unwrap();
+
+        let mut wrong_cost = opp.clone();
+        wrong_cost.total_cost += dec!(0.01);
+        assert!(wrong_cost.validate().is_err());
+
+        // 单边改数量而未重算 total_cost
+        let mut one_sided = opp.clone();
+        one_sided.no_size = dec!(5);
+        assert!(one_sided.validate().is_err());
+
+        let mut no_leg = opp;
+        no_leg.no_size = dec!(0);
+        no_leg.total_cost = no_leg.yes_ask_price * no_leg.yes_size;
+        assert!(no_leg.validate().is_err());
+    }
+}
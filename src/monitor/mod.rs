@@ -1,5 +1,8 @@
 pub mod arbitrage;
 pub mod orderbook;
+pub mod recorder;
+pub mod replay;
+pub mod user_stream;
 
 pub use arbitrage::*;
 pub use orderbook::*;
@@ -3,12 +3,187 @@ use dashmap::DashMap;
 use futures::Stream;
 use futures::StreamExt;
 use polymarket_client_sdk::clob::ws::{Client as WsClient, types::response::BookUpdate};
-use polymarket_client_sdk::types::{B256, U256};
+use polymarket_client_sdk::types::{B256, Decimal, U256};
+use rust_decimal_macros::dec;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::pin::Pin;
-use tracing::{debug, info};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
 
 use crate::market::MarketInfo;
+use crate::monitor::recorder::BookRecorder;
+use crate::utils::metrics;
+
+/// 订单簿更新流（SDK 错误已转换为 anyhow::Error）
+pub type BookStream<'a> = Pin<Box<dyn Stream<Item = Result<BookUpdate>> + Send + 'a>>;
+
+/// 订单簿缓存：token_id -> 最新订单簿。监控器写入，收尾等监控器之外的任务可共享只读
+pub type BookCache = Arc<DashMap<U256, BookUpdate>>;
+
+/// 缓存中 token 的买一价，未收到订单簿或买盘为空时返回 None（最优价为最后一档）
+pub fn cached_best_bid(cache: &BookCache, token_id: U256) -> Option<Decimal> {
+    cache.get(&token_id)?.bids.last().map(|b| b.price)
+}
+
+/// 订阅市场的行情快照（/status 的 markets 字段）：由订阅信息与订单簿缓存拼出，不做额外计算
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketSnapshot {
+    pub market_id: String,
+    pub symbol: String,
+    pub title: String,
+    pub yes_best_ask: Option<Decimal>,
+    pub yes_best_bid: Option<Decimal>,
+    pub no_best_ask: Option<Decimal>,
+    pub no_best_bid: Option<Decimal>,
+    /// YES 卖一 + NO 卖一，任一侧无卖盘时为 None
+    pub total_ask: Option<Decimal>,
+    /// 卖一总价低于 1（与行情日志中的「套利机会」一致，未扣手续费与执行价差）
+    pub has_arbitrage: bool,
+}
+
+/// 订阅市场的标识与订单簿缓存，供监控器之外（状态服务）按需生成行情快照
+#[derive(Clone)]
+pub struct MarketSnapshotSource {
+    markets: Vec<SubscribedMarket>,
+    books: BookCache,
+}
+
+impl MarketSnapshotSource {
+    pub fn snapshots(&self) -> Vec<MarketSnapshot> {
+        self.markets.iter().map(|m| m.snapshot(&self.books)).collect()
+    }
+}
+
+/// 订阅的市场：token 与显示用的币种、标题
+#[derive(Debug, Clone)]
+struct SubscribedMarket {
+    market_id: B256,
+    yes_token_id: U256,
+    no_token_id: U256,
+    symbol: String,
+    title: String,
+}
+
+impl SubscribedMarket {
+    fn snapshot(&self, books: &BookCache) -> MarketSnapshot {
+        // 最优价为最后一档
+        let top = |token_id: U256| {
+            books.get(&token_id).map_or((None, None), |b| {
+                (b.asks.last().map(|l| l.price), b.bids.last().map(|l| l.price))
+            })
+        };
+        let (yes_best_ask, yes_best_bid) = top(self.yes_token_id);
+        let (no_best_ask, no_best_bid) = top(self.no_token_id);
+        let total_ask = yes_best_ask.zip(no_best_ask).map(|(y, n)| y + n);
+        MarketSnapshot {
+            market_id: self.market_id.to_string(),
+            symbol: self.symbol.clone(),
+            title: self.title.clone(),
+            yes_best_ask,
+            yes_best_bid,
+            no_best_ask,
+            no_best_bid,
+            total_ask,
+            has_arbitrage: total_ask.is_some_and(|t| t < dec!(1)),
+        }
+    }
+}
+
+/// 重连退避：初始间隔、上限与随机抖动范围
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+const RECONNECT_JITTER_MS: u64 = 250;
+/// 连续重新订阅失败超过此次数后放弃，把错误交给调用方
+const RECONNECT_MAX_CONSECUTIVE_FAILURES: u32 = 10;
+
+/// 第 attempt 次重连前的等待时间：指数退避（封顶）+ 随机抖动
+fn reconnect_delay(attempt: u32) -> Duration {
+    let exp = RECONNECT_BASE_DELAY.saturating_mul(1u32 << attempt.saturating_sub(1).min(16));
+    let jitter_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64 % RECONNECT_JITTER_MS)
+        .unwrap_or(0);
+    exp.min(RECONNECT_MAX_DELAY) + Duration::from_millis(jitter_ms)
+}
+
+/// 包装订单簿流：内部流出错或结束时按退避重新调用 factory 订阅，对调用方隐藏短暂断线。
+/// 重新订阅失败、以及订阅成功但未收到任何数据就出错或结束，都计为一次连续失败；
+/// 连续失败超过 RECONNECT_MAX_CONSECUTIVE_FAILURES 次时输出一个 Err 并结束。
+pub fn reconnecting_stream<'a, F>(factory: F, reconnects: Arc<AtomicU64>) -> BookStream<'a>
+where
+    F: Fn() -> Result<BookStream<'a>> + Send + 'a,
+{
+    struct State<'a, F> {
+        factory: F,
+        inner: Option<BookStream<'a>>,
+        failures: u32,
+        done: bool,
+        reconnects: Arc<AtomicU64>,
+    }
+
+    let state = State {
+        factory,
+        inner: None,
+        failures: 0,
+        done: false,
+        reconnects,
+    };
+
+    Box::pin(futures::stream::unfold(state, |mut st| async move {
+        if st.done {
+            return None;
+        }
+        loop {
+            if st.inner.is_none() {
+                if st.failures > 0 {
+                    let delay = reconnect_delay(st.failures);
+                    warn!(attempt = st.failures, delay_ms = delay.as_millis() as u64, "订单簿流断开，退避后重新订阅");
+                    tokio::time::sleep(delay).await;
+                }
+                match (st.factory)() {
+                    Ok(stream) => st.inner = Some(stream),
+                    Err(e) => {
+                        st.failures += 1;
+                        if st.failures > RECONNECT_MAX_CONSECUTIVE_FAILURES {
+                            st.done = true;
+                            return Some((Err(e), st));
+                        }
+                        warn!(error = %e, attempt = st.failures, "重新订阅订单簿失败");
+                        continue;
+                    }
+                }
+            }
+
+            let next = st.inner.as_mut().expect("inner stream 已创建").next().await;
+            let err = match next {
+                Some(Ok(book)) => {
+                    st.failures = 0;
+                    return Some((Ok(book), st));
+                }
+                Some(Err(e)) => {
+                    warn!(error = %e, "订单簿流错误，准备重连");
+                    e
+                }
+                None => {
+                    warn!("订单簿流结束，准备重连");
+                    anyhow::anyhow!("订单簿流结束")
+                }
+            };
+            st.inner = None;
+            // 收到过数据的流断开后 failures 已清零，此处为 1；未收到任何数据就断开则继续累加
+            st.failures += 1;
+            if st.failures > RECONNECT_MAX_CONSECUTIVE_FAILURES {
+                st.done = true;
+                return Some((Err(err.context(format!("订单簿流连续 {} 次未收到数据即断开", st.failures))), st));
+            }
+            st.reconnects.fetch_add(1, Ordering::Relaxed);
+            metrics::record_ws_reconnect();
+        }
+    }))
+}
 
 /// 缩短 B256 用于日志：保留 0x + 前 8 位 hex，如 0xb91126b7..
 #[inline]
@@ -30,10 +205,17 @@ fn short_u256(u: &U256) -> String {
 
 pub struct OrderBookMonitor {
     ws_client: WsClient,
-    books: DashMap<U256, BookUpdate>,
+    books: BookCache,
+    updated_at: DashMap<U256, Instant>, // 每个 token 订单簿最后一次更新的时间
+    max_book_age: Option<Duration>, // 订单簿最大允许时长，超过视为过期；None 表示不检查
     market_map: HashMap<B256, (U256, U256)>, // market_id -> (yes_token_id, no_token_id)
+    subscribed: Vec<SubscribedMarket>, // 按订阅顺序记录的市场，用于行情快照
+    reconnects: Arc<AtomicU64>, // 订单簿流自动重连次数
+    recorder: Option<BookRecorder>, // 订单簿录制（ORDERBOOK_RECORD_PATH），None 表示不录制
+    log_depth: usize, // debug 日志打印的买卖盘档数（ORDERBOOK_LOG_DEPTH），0 表示不打印
 }
 
+#[derive(Clone)]
 pub struct OrderBookPair {
     pub yes_book: BookUpdate,
     pub no_book: BookUpdate,
@@ -46,11 +228,55 @@ impl OrderBookMonitor {
             // 使用未认证的客户端：订单簿订阅不需要认证，这是公开数据
             // 只有订阅用户数据（如用户订单、交易等）才需要认证
             ws_client: WsClient::default(),
-            books: DashMap::new(),
+            books: Arc::new(DashMap::new()),
+            updated_at: DashMap::new(),
+            max_book_age: None,
             market_map: HashMap::new(),
+            subscribed: Vec::new(),
+            reconnects: Arc::new(AtomicU64::new(0)),
+            recorder: None,
+            log_depth: 5,
+        }
+    }
+
+    /// 使用外部共享的订单簿缓存（各窗口的监控器共用，供收尾任务读取最新买一价）
+    pub fn with_book_cache(mut self, books: BookCache) -> Self {
+        self.books = books;
+        self
+    }
+
+    /// 设置 debug 日志打印的买卖盘档数，0 表示不打印，默认 5
+    pub fn with_log_depth(mut self, log_depth: usize) -> Self {
+        self.log_depth = log_depth;
+        self
+    }
+
+    /// 设置订单簿过期阈值：YES/NO 任一侧超过此时长未更新时不返回 OrderBookPair；None 表示不检查
+    pub fn with_max_book_age(mut self, max_book_age: Option<Duration>) -> Self {
+        self.max_book_age = max_book_age;
+        self
+    }
+
+    /// 距 token 订单簿最后一次更新的时长，未收到过该 token 的订单簿时返回 None
+    pub fn book_age(&self, token_id: U256) -> Option<Duration> {
+        self.updated_at.get(&token_id).map(|t| t.elapsed())
+    }
+
+    /// 订单簿是否过期（未收到过也视为过期）
+    fn is_stale(&self, token_id: U256) -> bool {
+        match (self.max_book_age, self.book_age(token_id)) {
+            (None, _) => false,
+            (Some(max), Some(age)) => age > max,
+            (Some(_), None) => true,
         }
     }
 
+    /// 设置订单簿录制器：订阅的市场与收到的每条订单簿更新都会写入录制文件
+    pub fn with_recorder(mut self, recorder: Option<BookRecorder>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
     /// 订阅新市场
     pub fn subscribe_market(&mut self, market: &MarketInfo) -> Result<()> {
         // 记录市场映射
@@ -58,6 +284,17 @@ impl OrderBookMonitor {
             market.market_id,
             (market.yes_token_id, market.no_token_id),
         );
+        self.subscribed.retain(|m| m.market_id != market.market_id);
+        self.subscribed.push(SubscribedMarket {
+            market_id: market.market_id,
+            yes_token_id: market.yes_token_id,
+            no_token_id: market.no_token_id,
+            symbol: market.crypto_symbol.clone(),
+            title: market.title.clone(),
+        });
+        if let Some(recorder) = &self.recorder {
+            recorder.record_market(market);
+        }
 
         info!(
             market_id = short_b256(&market.market_id),
@@ -73,9 +310,7 @@ impl OrderBookMonitor {
     /// 
     /// 注意：订单簿订阅使用未认证的 WebSocket 客户端，因为订单簿数据是公开的。
     /// 只有订阅用户相关数据（如用户订单状态、交易历史等）才需要认证。
-    pub fn create_orderbook_stream(
-        &self,
-    ) -> Result<Pin<Box<dyn Stream<Item = Result<BookUpdate>> + Send + '_>>> {
+    pub fn create_orderbook_stream(&self) -> Result<BookStream<'_>> {
         // 收集所有需要订阅的token_id
         let token_ids: Vec<U256> = self
             .market_map
@@ -96,38 +331,73 @@ impl OrderBookMonitor {
         Ok(Box::pin(stream))
     }
 
+    /// 创建带自动重连的订单簿订阅流：断线或流结束时以指数退避（封顶、带抖动）重新订阅同一批 token，
+    /// 调用方的 select! 循环不会感知短暂断线。首次订阅失败（如无市场）直接返回错误。
+    pub fn create_orderbook_stream_with_retry(&self) -> Result<BookStream<'_>> {
+        let first = self.create_orderbook_stream()?;
+        let first = std::cell::Cell::new(Some(first));
+        let factory = move || match first.take() {
+            Some(stream) => Ok(stream),
+            None => self.create_orderbook_stream(),
+        };
+        Ok(reconnecting_stream(factory, self.reconnects.clone()))
+    }
+
+    /// 自创建以来订单簿流自动重连的次数
+    pub fn reconnect_count(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
     /// 处理订单簿更新
     pub fn handle_book_update(&self, book: BookUpdate) -> Option<OrderBookPair> {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_book(&book);
+        }
 
-        // 打印前5档买卖价格（用于调试）
-        if !book.bids.is_empty() {
+        // 打印前 N 档买卖价格（用于调试）
+        let depth = self.log_depth;
+        if depth > 0 && !book.bids.is_empty() {
             let top_bids: Vec<String> = book.bids.iter()
-                .take(5)
+                .take(depth)
                 .map(|b| format!("{}@{}", b.size, b.price))
                 .collect();
             debug!(
                 asset_id = %book.asset_id,
-                "买盘前5档: {}",
+                "买盘前{}档: {}",
+                depth,
                 top_bids.join(", ")
             );
         }
-        if !book.asks.is_empty() {
+        if depth > 0 && !book.asks.is_empty() {
             let top_asks: Vec<String> = book.asks.iter()
-                .take(5)
+                .take(depth)
                 .map(|a| format!("{}@{}", a.size, a.price))
                 .collect();
             debug!(
                 asset_id = short_u256(&book.asset_id),
-                "卖盘前5档: {}",
+                "卖盘前{}档: {}",
+                depth,
                 top_asks.join(", ")
             );
         }
 
         // 更新订单簿缓存
         self.books.insert(book.asset_id, book.clone());
+        self.updated_at.insert(book.asset_id, Instant::now());
 
         // 查找这个 token 属于哪个市场；任一侧（YES 或 NO）更新都返回 OrderBookPair，以便及时反应套利
         for (market_id, (yes_token, no_token)) in &self.market_map {
+            if (book.asset_id == *yes_token || book.asset_id == *no_token)
+                && (self.is_stale(*yes_token) || self.is_stale(*no_token))
+            {
+                debug!(
+                    market_id = short_b256(market_id),
+                    yes_age_ms = self.book_age(*yes_token).map(|a| a.as_millis() as u64),
+                    no_age_ms = self.book_age(*no_token).map(|a| a.as_millis() as u64),
+                    "订单簿已过期，跳过"
+                );
+                return None;
+            }
             if book.asset_id == *yes_token {
                 if let Some(no_book) = self.books.get(no_token) {
                     return Some(OrderBookPair {
@@ -150,14 +420,126 @@ impl OrderBookMonitor {
         None
     }
 
+    /// 尚未收到订单簿的已订阅 token：按市场返回 (market_id, 缺失的 token 列表)，便于区分「无机会」与「未收到数据」
+    pub fn missing_books(&self) -> Vec<(B256, Vec<U256>)> {
+        self.market_map
+            .iter()
+            .filter_map(|(market_id, (yes_token, no_token))| {
+                let missing: Vec<U256> = [*yes_token, *no_token]
+                    .into_iter()
+                    .filter(|t| !self.books.contains_key(t))
+                    .collect();
+                (!missing.is_empty()).then_some((*market_id, missing))
+            })
+            .collect()
+    }
+
+    /// 记录尚未收到订单簿的市场：waited 未超过 warn_after 时输出 debug，超过后输出 warn。
+    /// 返回是否仍有缺失（调用方据此决定是否继续检查）
+    pub fn log_missing_books(&self, waited: Duration, warn_after: Option<Duration>) -> bool {
+        let missing = self.missing_books();
+        if missing.is_empty() {
+            return false;
+        }
+        let overdue = warn_after.is_some_and(|w| waited >= w);
+        for (market_id, tokens) in &missing {
+            let (yes_token, no_token) = self.market_map[market_id];
+            let sides: Vec<&str> = tokens
+                .iter()
+                .map(|t| if *t == yes_token { "YES" } else if *t == no_token { "NO" } else { "?" })
+                .collect();
+            let tokens: Vec<String> = tokens.iter().map(short_u256).collect();
+            if overdue {
+                warn!(
+                    market_id = short_b256(market_id),
+                    sides = %sides.join("/"),
+                    tokens = %tokens.join(","),
+                    waited_secs = waited.as_secs(),
+                    "⚠️ 订阅后长时间未收到订单簿，该市场无法检测套利"
+                );
+            } else {
+                debug!(
+                    market_id = short_b256(market_id),
+                    sides = %sides.join("/"),
+                    tokens = %tokens.join(","),
+                    waited_secs = waited.as_secs(),
+                    "等待订单簿"
+                );
+            }
+        }
+        true
+    }
+
     /// 获取订单簿（如果存在）
     pub fn get_book(&self, token_id: U256) -> Option<BookUpdate> {
         self.books.get(&token_id).map(|b| b.clone())
     }
 
+    /// 各订阅市场的行情快照来源（YES/NO 买一卖一、卖一总价、是否存在套利），交给状态服务按需生成快照（订单簿缓存共享，随更新保持最新）
+    pub fn snapshot_source(&self) -> MarketSnapshotSource {
+        MarketSnapshotSource { markets: self.subscribed.clone(), books: self.books.clone() }
+    }
+
     /// 清除所有订阅
     pub fn clear(&mut self) {
         self.books.clear();
+        self.updated_at.clear();
         self.market_map.clear();
+        self.subscribed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    fn book(asset_id: u64) -> BookUpdate {
+        BookUpdate::builder()
+            .asset_id(U256::from(asset_id))
+            .market(B256::ZERO)
+            .timestamp(0)
+            .bids(Vec::new())
+            .asks(Vec::new())
+            .build()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconnects_after_factory_failures() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let reconnects = Arc::new(AtomicU64::new(0));
+        let factory_calls = calls.clone();
+        let mut stream = reconnecting_stream(
+            move || -> Result<BookStream<'static>> {
+                if factory_calls.fetch_add(1, Ordering::Relaxed) < 2 {
+                    anyhow::bail!("订阅失败");
+                }
+                Ok(Box::pin(futures::stream::iter(vec![Ok(book(1))])))
+            },
+            reconnects,
+        );
+        let first = stream.next().await.expect("应输出订单簿").expect("不应输出错误");
+        assert_eq!(first.asset_id, U256::from(1));
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn empty_streams_count_as_failures() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let reconnects = Arc::new(AtomicU64::new(0));
+        let factory_calls = calls.clone();
+        let mut stream = reconnecting_stream(
+            move || -> Result<BookStream<'static>> {
+                factory_calls.fetch_add(1, Ordering::Relaxed);
+                Ok(Box::pin(futures::stream::empty()))
+            },
+            reconnects.clone(),
+        );
+        // 每次订阅都立即结束且没有数据：超过上限后输出一个 Err 并结束，而不是无限重连
+        assert!(stream.next().await.expect("应输出错误").is_err());
+        assert!(stream.next().await.is_none());
+        assert_eq!(calls.load(Ordering::Relaxed), RECONNECT_MAX_CONSECUTIVE_FAILURES + 1);
+        // 最后一次失败直接结束，不再计为重连
+        assert_eq!(reconnects.load(Ordering::Relaxed), u64::from(RECONNECT_MAX_CONSECUTIVE_FAILURES));
     }
 }
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::clob::ws::types::response::{BookUpdate, OrderBookLevel};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::market::MarketInfo;
+
+/// 写入队列容量：写盘跟不上时丢弃新记录，而不是阻塞订单簿热路径
+const RECORD_CHANNEL_CAPACITY: usize = 10_000;
+
+/// 录制文件中的一行（NDJSON）。先写市场订阅信息，回放时据此重建 YES/NO 映射。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordEntry {
+    Market(MarketInfo),
+    Book {
+        /// 本地接收时间（毫秒时间戳）
+        recv_ts_ms: i64,
+        /// 与 WebSocket 推送相同格式的 BookUpdate，回放时直接反序列化
+        book: serde_json::Value,
+    },
+}
+
+/// 将 BookUpdate 还原为 WebSocket 推送格式（SDK 类型只实现了 Deserialize）
+fn book_to_json(book: &BookUpdate) -> serde_json::Value {
+    let levels = |levels: &[OrderBookLevel]| -> Vec<serde_json::Value> {
+        levels
+            .iter()
+            .map(|l| json!({ "price": l.price.to_string(), "size": l.size.to_string() }))
+            .collect()
+    };
+    json!({
+        "asset_id": book.asset_id.to_string(),
+        "market": book.market.to_string(),
+        "timestamp": book.timestamp.to_string(),
+        "bids": levels(&book.bids),
+        "asks": levels(&book.asks),
+        "hash": book.hash,
+    })
+}
+
+/// 订单簿录制器：热路径只做序列化并 try_send，写盘由独立任务完成。可 clone，多个窗口共用同一文件。
+#[derive(Clone)]
+pub struct BookRecorder {
+    tx: mpsc::Sender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl BookRecorder {
+    /// 以追加方式打开录制文件并启动写盘任务
+    pub async fn spawn(path: &Path) -> Result<Self> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("打开订单簿录制文件失败: {}", path.display()))?;
+        let (tx, mut rx) = mpsc::channel::<String>(RECORD_CHANNEL_CAPACITY);
+        let path_display = path.display().to_string();
+
+        tokio::spawn(async move {
+            let mut writer = tokio::io::BufWriter::new(file);
+            while let Some(line) = rx.recv().await {
+                let result = async {
+                    writer.write_all(line.as_bytes()).await?;
+                    writer.write_all(b"\n").await?;
+                    // 队列已空时刷盘，突发时批量写入
+                    if rx.is_empty() {
+                        writer.flush().await?;
+                    }
+                    std::io::Result::Ok(())
+                }
+                .await;
+                if let Err(e) = result {
+                    warn!(error = %e, path = %path_display, "订单簿录制写入失败，停止录制");
+                    return;
+                }
+            }
+            let _ = writer.flush().await;
+        });
+
+        info!(path = %path.display(), "📼 订单簿录制已启用");
+        Ok(Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// 记录市场订阅信息
+    pub fn record_market(&self, market: &MarketInfo) {
+        self.send(&RecordEntry::Market(market.clone()));
+    }
+
+    /// 记录一条订单簿更新（附本地接收时间）
+    pub fn record_book(&self, book: &BookUpdate) {
+        self.send(&RecordEntry::Book {
+            recv_ts_ms: chrono::Utc::now().timestamp_millis(),
+            book: book_to_json(book),
+        });
+    }
+
+    fn send(&self, entry: &RecordEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(error = %e, "订单簿录制序列化失败");
+                return;
+            }
+        };
+        if self.tx.try_send(line).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            // 避免刷屏：每丢弃 1000 条提示一次
+            if dropped % 1000 == 1 {
+                warn!(dropped, "订单簿录制队列已满或写盘任务已停止，丢弃记录");
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::B256;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::market::MarketInfo;
+use crate::monitor::recorder::RecordEntry;
+use crate::monitor::{ArbitrageDetector, OrderBookMonitor};
+
+/// 两次套利之间的最小间隔（毫秒），与主循环一致
+const MIN_TRADE_INTERVAL_MS: i64 = 3_000;
+
+/// 回放录制文件：按录制顺序把订单簿喂给 ArbitrageDetector，并套用主循环中的执行前检查
+/// （执行阈值、YES/NO 价格阈值、结束前停止、按币种下单上限、交易间隔），打印本会执行的套利。
+/// 时间相关检查使用录制时的接收时间而非当前时间。
+pub fn run(config: &Config, path: &Path) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("打开回放文件失败: {}", path.display()))?;
+
+    let detector = ArbitrageDetector::new(config);
+    let mut monitor = OrderBookMonitor::new();
+    let mut markets: HashMap<B256, MarketInfo> = HashMap::new();
+
+    let execution_threshold = dec!(1.0) - config.arbitrage_execution_spread;
+
+    let mut books = 0u64;
+    let mut bad_lines = 0u64;
+    let mut trades = 0u64;
+    let mut total_cost = dec!(0);
+    let mut total_profit = dec!(0);
+    let mut last_trade_ms: Option<i64> = None;
+
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: RecordEntry = match serde_json::from_str(&line) {
+            Ok(entry) => entry,
+            Err(e) => {
+                bad_lines += 1;
+                warn!(line = line_no + 1, error = %e, "回放：跳过无法解析的行");
+                continue;
+            }
+        };
+
+        let (recv_ts_ms, book) = match entry {
+            RecordEntry::Market(market) => {
+                monitor.subscribe_market(&market)?;
+                markets.insert(market.market_id, market);
+                continue;
+            }
+            RecordEntry::Book { recv_ts_ms, book } => match serde_json::from_value::<BookUpdate>(book) {
+                Ok(book) => (recv_ts_ms, book),
+                Err(e) => {
+                    bad_lines += 1;
+                    warn!(line = line_no + 1, error = %e, "回放：跳过无法解析的订单簿");
+                    continue;
+                }
+            },
+        };
+        books += 1;
+
+        let Some(pair) = monitor.handle_book_update(book) else {
+            continue;
+        };
+        let yes_best = pair.yes_book.asks.last().map(|a| a.price);
+        let no_best = pair.no_book.asks.last().map(|a| a.price);
+        let Some(total_price) = yes_best.zip(no_best).map(|(y, n)| y + n) else {
+            continue;
+        };
+        if total_price > execution_threshold {
+            continue;
+        }
+
+        let market = markets.get(&pair.market_id);
+        let symbol = market.map(|m| m.crypto_symbol.as_str()).unwrap_or("");
+        let Some(opp) = detector.check_arbitrage(&pair.yes_book, &pair.no_book, &pair.market_id, symbol, None, None) else {
+            continue;
+        };
+
+        if config.min_yes_price_threshold > dec!(0) && opp.yes_ask_price < config.min_yes_price_threshold {
+            continue;
+        }
+        if config.min_no_price_threshold > dec!(0) && opp.no_ask_price < config.min_no_price_threshold {
+            continue;
+        }
+        if config.stop_arbitrage_before_end_minutes > 0 {
+            if let Some(m) = market {
+                let seconds_until_end = (m.end_date.timestamp_millis() - recv_ts_ms) / 1000;
+                if seconds_until_end <= config.stop_arbitrage_before_end_minutes as i64 * 60 {
+                    continue;
+                }
+            }
+        }
+        if let Some(last) = last_trade_ms {
+            if recv_ts_ms - last < MIN_TRADE_INTERVAL_MS {
+                continue;
+            }
+        }
+        last_trade_ms = Some(recv_ts_ms);
+
+        let order_size = opp.yes_size.min(opp.no_size).min(config.max_order_size_for(symbol));
+        let cost = (opp.yes_ask_price + opp.no_ask_price) * order_size;
+        let profit = opp.profit_percentage / dec!(100.0) * order_size;
+        trades += 1;
+        total_cost += cost;
+        total_profit += profit;
+
+        let recv_time = chrono::DateTime::from_timestamp_millis(recv_ts_ms)
+            .map(|t| t.format("%H:%M:%S%.3f").to_string())
+            .unwrap_or_default();
+        info!(
+            "⚡ [回放] {} | {}预测市场 | YES:{:.4} NO:{:.4} | 利润:{:.2}% | 下单数量:{}份 | 成本:{:.2} USD | 预估收益:{:.4} USD",
+            recv_time,
+            symbol,
+            opp.yes_ask_price,
+            opp.no_ask_price,
+            opp.profit_percentage,
+            order_size,
+            cost,
+            profit
+        );
+    }
+
+    info!(
+        books,
+        bad_lines,
+        markets = markets.len(),
+        "回放完成 | 套利次数:{} | 总成本:{:.2} USD | 预估总收益（扣费后）:{:.4} USD",
+        trades,
+        total_cost,
+        total_profit
+    );
+    Ok(())
+}
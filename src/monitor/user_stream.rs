@@ -0,0 +1,62 @@
+//! 用户频道（认证 WebSocket）：订阅自己订单的状态推送，挂单（GTC/GTD）在提交后才成交的部分
+//! 据此实时计入订单对与持仓，而不只依赖下单响应中的即时成交量。
+
+use futures::StreamExt;
+use polymarket_client_sdk::auth::Credentials;
+use polymarket_client_sdk::clob::ws::Client as WsClient;
+use polymarket_client_sdk::types::Address;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::risk::RiskManager;
+
+/// 用户频道断开后重新订阅前的等待
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// 订阅用户频道的订单推送（不指定市场，即接收该 API key 下所有市场的订单），
+/// 将订单累计成交量交给 [`RiskManager::apply_order_fill`]。流结束或订阅失败时等待后重新订阅，不会返回。
+pub async fn run_user_stream(credentials: Credentials, address: Address, risk_manager: Arc<RiskManager>) {
+    loop {
+        let client = match WsClient::default().authenticate(credentials.clone(), address) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(error = %e, "用户频道认证失败，{} 秒后重试", RESUBSCRIBE_DELAY.as_secs());
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+                continue;
+            }
+        };
+        let mut stream = match client.subscribe_orders(Vec::new()) {
+            Ok(stream) => Box::pin(stream),
+            Err(e) => {
+                warn!(error = %e, "用户频道订阅失败，{} 秒后重试", RESUBSCRIBE_DELAY.as_secs());
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+                continue;
+            }
+        };
+        info!("📡 已订阅用户频道，实时接收订单成交");
+
+        while let Some(msg) = stream.next().await {
+            let order = match msg {
+                Ok(order) => order,
+                Err(e) => {
+                    warn!(error = %e, "用户频道消息错误");
+                    continue;
+                }
+            };
+            let Some(size_matched) = order.size_matched else {
+                continue;
+            };
+            match risk_manager.apply_order_fill(&order.id, size_matched) {
+                Some((token_id, delta)) => info!(
+                    "📥 用户频道成交 | order_id={} | token_id={:#x} | 新增成交:{} | 累计:{}",
+                    &order.id[..order.id.len().min(16)], token_id, delta, size_matched
+                ),
+                None => debug!(order_id = %order.id, size_matched = %size_matched, "用户频道：订单无新增成交或不属于订单对"),
+            }
+        }
+
+        warn!("用户频道已断开，{} 秒后重新订阅", RESUBSCRIBE_DELAY.as_secs());
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
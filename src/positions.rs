@@ -3,21 +3,60 @@
 use anyhow::{Context, Result};
 use polymarket_client_sdk::data::types::request::PositionsRequest;
 use polymarket_client_sdk::data::Client;
-use polymarket_client_sdk::types::Address;
+use polymarket_client_sdk::types::{Address, B256};
+use std::collections::HashMap;
 
 /// Data API 返回的持仓结构，重新导出便于调用方使用
 pub use polymarket_client_sdk::data::types::response::Position;
 
-/// 从环境变量 `POLYMARKET_PROXY_ADDRESS` 读取用户地址，调用 Data API 获取当前未平仓持仓。
+/// 持仓对应的结果方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutcomeSide {
+    Yes,
+    No,
+}
+
+/// 将 Data API 的 outcome_index 归一为 YES/NO。
 ///
-/// # 环境变量
+/// Data API 可能使用 0/1（0=Yes, 1=No）或 1/2（与 CTF index_set 一致，1=Yes, 2=No）两种约定，
+/// index 为 1 时有歧义，须结合同一 condition 下出现的其他 index 判断：出现 0 为 0/1 约定，出现 2 为 1/2 约定。
+/// 同一 condition 下只有 index 1 或出现未知 index 时返回 None。
+pub fn normalize_outcome(index: i32, indices_in_condition: &[i32]) -> Option<OutcomeSide> {
+    match index {
+        0 => Some(OutcomeSide::Yes),
+        2 => Some(OutcomeSide::No),
+        1 if indices_in_condition.contains(&0) => Some(OutcomeSide::No),
+        1 if indices_in_condition.contains(&2) => Some(OutcomeSide::Yes),
+        _ => None,
+    }
+}
+
+/// 按 condition 分组并归一为 (YES 持仓, NO 持仓)，方向由 [`normalize_outcome`] 判断；
+/// 无法判断方向的持仓不计入（该 condition 两侧均可能为 None）。不过滤 size，调用方按需筛选。
+pub fn yes_no_positions(positions: &[Position]) -> HashMap<B256, (Option<&Position>, Option<&Position>)> {
+    let mut indices: HashMap<B256, Vec<i32>> = HashMap::new();
+    for p in positions {
+        indices.entry(p.condition_id).or_default().push(p.outcome_index);
+    }
+    let mut by_condition: HashMap<B256, (Option<&Position>, Option<&Position>)> = HashMap::new();
+    for p in positions {
+        let entry = by_condition.entry(p.condition_id).or_default();
+        match normalize_outcome(p.outcome_index, &indices[&p.condition_id]) {
+            Some(OutcomeSide::Yes) => entry.0 = Some(p),
+            Some(OutcomeSide::No) => entry.1 = Some(p),
+            None => {}
+        }
+    }
+    by_condition
+}
+
+/// 调用 Data API 获取 `user` 当前未平仓持仓。
 ///
-/// - `POLYMARKET_PROXY_ADDRESS`: 必填，Polymarket 代理钱包地址（或 EOA 地址）
+/// `user` 为持仓所在地址：使用代理钱包（Email/Magic、Browser Wallet）时为 `POLYMARKET_PROXY_ADDRESS`，
+/// EOA 模式下为签名器自身的地址。
 ///
 /// # 错误
 ///
-/// - 未设置 `POLYMARKET_PROXY_ADDRESS`
-/// - 地址格式无效
 /// - 调用 Data API 失败
 ///
 /// # 示例
@@ -25,19 +64,40 @@ pub use polymarket_client_sdk::data::types::response::Position;
 /// ```ignore
 /// use poly_15min_bot::positions::{get_positions, Position};
 ///
-/// let positions = get_positions().await?;
+/// let positions = get_positions(user).await?;
 /// for p in positions {
 ///     println!("{}: {} @ {}", p.title, p.size, p.cur_price);
 /// }
 /// ```
-pub async fn get_positions() -> Result<Vec<Position>> {
-    dotenvy::dotenv().ok();
-    let addr = std::env::var("POLYMARKET_PROXY_ADDRESS")
-        .context("POLYMARKET_PROXY_ADDRESS 未设置")?;
-    let user: Address = addr
-        .parse()
-        .context("POLYMARKET_PROXY_ADDRESS 格式无效")?;
+pub async fn get_positions(user: Address) -> Result<Vec<Position>> {
     let client = Client::default();
     let req = PositionsRequest::builder().user(user).build();
     client.positions(&req).await.context("获取持仓失败")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_based_indices() {
+        let indices = [0, 1];
+        assert_eq!(normalize_outcome(0, &indices), Some(OutcomeSide::Yes));
+        assert_eq!(normalize_outcome(1, &indices), Some(OutcomeSide::No));
+    }
+
+    #[test]
+    fn index_set_indices() {
+        let indices = [1, 2];
+        assert_eq!(normalize_outcome(1, &indices), Some(OutcomeSide::Yes));
+        assert_eq!(normalize_outcome(2, &indices), Some(OutcomeSide::No));
+    }
+
+    #[test]
+    fn ambiguous_or_unknown_index_is_none() {
+        // 同一 condition 下只有 index 1：无法判断约定
+        assert_eq!(normalize_outcome(1, &[1]), None);
+        assert_eq!(normalize_outcome(3, &[0, 3]), None);
+        assert_eq!(normalize_outcome(-1, &[0, 1]), None);
+    }
+}
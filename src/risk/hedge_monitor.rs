@@ -1,22 +1,61 @@
 use anyhow::Result;
 use alloy::signers::Signer;
-use alloy::signers::local::LocalSigner;
 use dashmap::DashMap;
 use polymarket_client_sdk::clob::Client;
-use polymarket_client_sdk::clob::types::{OrderType, Side};
+use polymarket_client_sdk::clob::types::request::OrderBookSummaryRequest;
+use polymarket_client_sdk::clob::types::{OrderStatusType, OrderType, Side};
 use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
 use polymarket_client_sdk::types::{Address, Decimal, U256};
-use polymarket_client_sdk::POLYGON;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
-use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{debug, error, info, trace, warn};
 
 use super::positions::PositionTracker;
 use super::recovery::RecoveryAction;
+use crate::trading::executor::filled_amounts;
+use crate::trading::fees::FeeParams;
+use crate::trading::orders::{min_size, round_size, round_to_tick, SizePrecision};
+use crate::trading::signer::BotSigner;
+
+/// 计算扣除买入手续费后可卖出的份额：base_amount * (100 - fee%) / 100，
+/// 按该市场的数量精度向下截断（避免超过实际持有份额）；截断为 0 时取该精度的最小单位（2 位小数即 0.01 份）
+fn effective_sell_size(fee: &FeeParams, entry_price: Decimal, base_amount: Decimal, size_decimals: u32) -> Decimal {
+    let available = base_amount * fee.net_share_ratio(entry_price);
+    let floored = round_size(available, size_decimals);
+    if floored.is_zero() {
+        min_size(size_decimals)
+    } else {
+        floored
+    }
+}
+
+/// 卖出任务进行中的占位订单ID，避免重复下单；持久化文件中的该标记在恢复时清除
+const PROCESSING_ORDER_ID: &str = "processing";
+
+/// 将监测中的对冲仓位写入持久化文件（先写临时文件再 rename，避免写一半被中断导致文件损坏）
+fn persist_positions(positions: &DashMap<String, HedgePosition>, path: Option<&Path>) {
+    let Some(path) = path else {
+        return;
+    };
+    let snapshot: Vec<HedgePosition> = positions.iter().map(|e| e.value().clone()).collect();
+    let result = serde_json::to_string_pretty(&snapshot)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, json)?;
+            fs::rename(&tmp, path)?;
+            Ok(())
+        });
+    if let Err(e) = result {
+        warn!(error = %e, path = %path.display(), "对冲仓位持久化失败");
+    }
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HedgePosition {
     pub token_id: U256,
     pub opposite_token_id: U256, // 对立边的token_id（用于计算差值）
@@ -24,6 +63,8 @@ pub struct HedgePosition {
     pub entry_price: Decimal, // 买入价格（卖一价）
     pub take_profit_price: Decimal, // 止盈价格
     pub stop_loss_price: Decimal,   // 止损价格
+    pub trailing_pct: Option<Decimal>, // 追踪止损回落比例；None 表示使用固定止盈止损
+    pub high_water_price: Decimal,     // 监测期间的最高买一价（追踪止损基准）
     pub pair_id: String,
     pub market_display: String, // 市场显示名称（例如"btc预测市场"）
     pub order_id: Option<String>, // 如果已下GTC订单，保存订单ID
@@ -32,26 +73,160 @@ pub struct HedgePosition {
 
 pub struct HedgeMonitor {
     client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
-    private_key: String,
+    signer: BotSigner, // 已绑定 Polygon 链 ID，见 signer::build_signer
     proxy_address: Option<Address>,
-    positions: DashMap<String, HedgePosition>, // pair_id -> position
+    positions: Arc<DashMap<String, HedgePosition>>, // pair_id -> position，与卖出任务共享
+    persist_path: Option<PathBuf>, // 对冲仓位持久化文件（JSON），None 表示不持久化
     position_tracker: Arc<PositionTracker>, // 用于更新风险敞口
+    fee: FeeParams, // 手续费公式参数，用于计算扣费后可卖出的份额
+    price_tick: Decimal, // 最小价格单位，卖出价向下取整到该单位
+    sell_offset_ticks: i32, // 止盈止损卖出价相对买一价下调的 tick 数，负数表示高于买一价挂单
+    size_precision: Arc<SizePrecision>, // 按市场的数量精度，卖出数量向下截断到该精度
 }
 
 impl HedgeMonitor {
     pub fn new(
         client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
-        private_key: String,
+        signer: BotSigner,
         proxy_address: Option<Address>,
         position_tracker: Arc<PositionTracker>,
+        fee: FeeParams,
     ) -> Self {
         Self {
             client,
-            private_key,
+            signer,
             proxy_address,
-            positions: DashMap::new(),
+            positions: Arc::new(DashMap::new()),
+            persist_path: None,
             position_tracker,
+            fee,
+            price_tick: dec!(0.01),
+            sell_offset_ticks: 0,
+            size_precision: Arc::new(SizePrecision::default()),
+        }
+    }
+
+    /// 设置最小价格单位（PRICE_TICK），止盈止损卖出价向下取整到该单位
+    pub fn with_price_tick(mut self, tick: Decimal) -> Self {
+        self.price_tick = tick;
+        self
+    }
+
+    /// 设置止盈止损卖出价的偏移（HEDGE_SELL_OFFSET）：正数为买一价下方若干 tick（更快成交），负数为上方（挂单等待成交）
+    pub fn with_sell_offset(mut self, ticks: i32) -> Self {
+        self.sell_offset_ticks = ticks;
+        self
+    }
+
+    /// 止盈止损卖单价格：买一价减去偏移 tick 后向下取整到 tick，限制在 [tick, 1 - tick] 内
+    fn sell_price(&self, best_bid_price: Decimal) -> Decimal {
+        let price = best_bid_price - self.price_tick * Decimal::from(self.sell_offset_ticks);
+        round_to_tick(price, self.price_tick, Side::Sell).clamp(self.price_tick, dec!(1) - self.price_tick)
+    }
+
+    /// 设置按市场的数量精度（SIZE_DECIMALS 及按币种覆盖），与执行器共享
+    pub fn with_size_precision(mut self, size_precision: Arc<SizePrecision>) -> Self {
+        self.size_precision = size_precision;
+        self
+    }
+
+    /// 设置对冲仓位持久化文件（HEDGE_POSITIONS_PATH）：仓位的增删改都会写入该文件，重启后由 load_positions 恢复
+    pub fn with_persist_path(mut self, path: Option<PathBuf>) -> Self {
+        self.persist_path = path;
+        self
+    }
+
+    fn persist(&self) {
+        persist_positions(&self.positions, self.persist_path.as_deref());
+    }
+
+    /// 启动时从持久化文件恢复对冲仓位：已挂出的卖单向 CLOB 查询最新状态（已结束的清除订单ID），
+    /// 再按当前持仓核对，当前边持仓已不超过对立边（已卖出或已 Merge）的视为已平仓并丢弃。
+    /// sync_positions 为 true 时先从 Data API 同步持仓再核对（模拟交易时传 false，仅按本地持仓核对）。
+    /// 恢复后按 REST 订单簿的最新买一价立即检查一次止盈止损，之后随订单簿更新继续监测。
+    /// 文件缺失或损坏时从空状态启动。返回恢复的仓位数量
+    pub async fn load_positions(&self, sync_positions: bool) -> Result<usize> {
+        let Some(path) = &self.persist_path else {
+            return Ok(0);
+        };
+        if !path.exists() {
+            info!(path = %path.display(), "未找到对冲仓位持久化文件，从空状态启动");
+            return Ok(0);
+        }
+        let saved: Vec<HedgePosition> = match fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| serde_json::from_str(&s).map_err(anyhow::Error::from))
+        {
+            Ok(saved) => saved,
+            Err(e) => {
+                warn!(error = %e, path = %path.display(), "对冲仓位持久化文件读取失败或已损坏，从空状态启动");
+                return Ok(0);
+            }
+        };
+
+        if sync_positions {
+            if let Err(e) = self.position_tracker.sync_from_api().await {
+                warn!(error = %e, "同步持仓失败，按本地持仓核对对冲仓位");
+            }
+        }
+
+        for mut position in saved {
+            if let Some(order_id) = position.order_id.take() {
+                if order_id != PROCESSING_ORDER_ID {
+                    match self.client.order(&order_id).await {
+                        Ok(order) if matches!(order.status, OrderStatusType::Live | OrderStatusType::Delayed) => {
+                            position.pending_sell_amount = order.original_size - order.size_matched;
+                            position.order_id = Some(order_id);
+                        }
+                        Ok(_) => position.pending_sell_amount = dec!(0),
+                        Err(e) => {
+                            debug!(order_id = %order_id, error = %e, "卖单查询失败，视为已结束");
+                            position.pending_sell_amount = dec!(0);
+                        }
+                    }
+                }
+            }
+
+            let current = self.position_tracker.get_position(position.token_id);
+            let opposite = self.position_tracker.get_position(position.opposite_token_id);
+            if current - opposite <= dec!(0) && position.order_id.is_none() {
+                info!(
+                    "🧹 对冲仓位已平仓，不再恢复 | 市场:{} | 当前持仓:{}份 | 对立边持仓:{}份",
+                    position.market_display,
+                    current,
+                    opposite
+                );
+                continue;
+            }
+            info!(
+                "♻️ 恢复对冲监测 | 市场:{} | 持仓:{}份 | 买入价:{:.4} | 止损:{:.4} | 挂单:{}",
+                position.market_display,
+                current - opposite,
+                position.entry_price,
+                position.stop_loss_price,
+                position.order_id.as_deref().map(|id| &id[..16.min(id.len())]).unwrap_or("无")
+            );
+            self.positions.insert(position.pair_id.clone(), position);
+        }
+        self.persist();
+
+        let restored = self.positions.len();
+        let mut tokens: Vec<U256> = self.positions.iter().map(|e| e.value().token_id).collect();
+        tokens.sort();
+        tokens.dedup();
+        for token_id in tokens {
+            let request = OrderBookSummaryRequest::builder().token_id(token_id).build();
+            match self.client.order_book(&request).await {
+                Ok(book) => {
+                    if let Some(best_bid_price) = book.bids.iter().map(|b| b.price).max() {
+                        self.check_bid(token_id, best_bid_price).await?;
+                    }
+                }
+                Err(e) => warn!(token_id = %token_id, error = %e, "获取订单簿失败，等待订单簿更新后再检查止盈止损"),
+            }
         }
+        info!(restored, path = %path.display(), "对冲仓位恢复完成");
+        Ok(restored)
     }
 
     /// 添加需要监测的对冲仓位
@@ -63,6 +238,7 @@ impl HedgeMonitor {
             entry_price,
             take_profit_pct,
             stop_loss_pct,
+            trailing_pct,
             pair_id,
             market_display,
         } = action
@@ -71,14 +247,24 @@ impl HedgeMonitor {
             let take_profit_price = *entry_price * (dec!(1.0) + *take_profit_pct);
             let stop_loss_price = *entry_price * (dec!(1.0) - *stop_loss_pct);
 
-            info!(
-                "🛡️ 开始对冲监测 | 市场:{} | 持仓:{}份 | 买入价:{:.4} | 止盈:{:.4} | 止损:{:.4}",
-                market_display,
-                amount,
-                entry_price,
-                take_profit_price,
-                stop_loss_price
-            );
+            match trailing_pct {
+                Some(pct) => info!(
+                    "🛡️ 开始对冲监测（追踪止损） | 市场:{} | 持仓:{}份 | 买入价:{:.4} | 回落:{:.2}% | 止损:{:.4}",
+                    market_display,
+                    amount,
+                    entry_price,
+                    *pct * dec!(100.0),
+                    stop_loss_price
+                ),
+                None => info!(
+                    "🛡️ 开始对冲监测 | 市场:{} | 持仓:{}份 | 买入价:{:.4} | 止盈:{:.4} | 止损:{:.4}",
+                    market_display,
+                    amount,
+                    entry_price,
+                    take_profit_price,
+                    stop_loss_price
+                ),
+            }
 
             let position = HedgePosition {
                 token_id: *token_id,
@@ -87,6 +273,8 @@ impl HedgeMonitor {
                 entry_price: *entry_price,
                 take_profit_price,
                 stop_loss_price,
+                trailing_pct: *trailing_pct,
+                high_water_price: *entry_price,
                 pair_id: pair_id.clone(),
                 market_display: market_display.clone(),
                 order_id: None,
@@ -94,6 +282,7 @@ impl HedgeMonitor {
             };
 
             self.positions.insert(pair_id.clone(), position);
+            self.persist();
         }
         Ok(())
     }
@@ -118,6 +307,7 @@ impl HedgeMonitor {
                 "更新买入价格"
             );
         }
+        self.persist();
     }
 
     /// 检查订单簿更新，如果达到止盈止损则卖出
@@ -128,16 +318,29 @@ impl HedgeMonitor {
             Some(bid) => bid.price,
             None => return Ok(()), // 没有买盘，无法卖出
         };
+        self.check_bid(book.asset_id, best_bid_price).await
+    }
 
+    /// 按 token 的最新买一价检查该 token 上的对冲仓位，达到止盈止损则卖出
+    async fn check_bid(&self, asset_id: U256, best_bid_price: Decimal) -> Result<()> {
         // 查找所有需要监测的仓位
         let positions_to_check: Vec<(String, HedgePosition)> = self
             .positions
             .iter()
-            .filter(|entry| entry.value().token_id == book.asset_id)
+            .filter(|entry| entry.value().token_id == asset_id)
             .map(|entry| (entry.key().clone(), entry.value().clone()))
             .collect();
 
-        for (pair_id, position) in positions_to_check {
+        for (pair_id, mut position) in positions_to_check {
+            // 追踪止损：每个 tick 上调最高买一价
+            if position.trailing_pct.is_some() && best_bid_price > position.high_water_price {
+                position.high_water_price = best_bid_price;
+                if let Some(mut pos) = self.positions.get_mut(&pair_id) {
+                    pos.high_water_price = best_bid_price;
+                }
+                self.persist();
+            }
+
             // 检查是否已经下过GTC订单，如果有则使用订单簿最新价格重新挂单
             if let Some(ref order_id) = position.order_id {
                 let pending_amount = position.pending_sell_amount;
@@ -161,8 +364,21 @@ impl HedgeMonitor {
                 }
             }
 
-            // 检查是否达到止盈或止损
-            let (should_sell, reason) = if best_bid_price >= position.take_profit_price {
+            // 检查是否达到止盈或止损（追踪模式下不设固定止盈，由最高点回落触发卖出，固定止损仍然生效）
+            let trailing_stop_price = position
+                .trailing_pct
+                .map(|pct| position.high_water_price * (dec!(1.0) - pct));
+            let (should_sell, reason) = if let Some(trailing_stop) = trailing_stop_price {
+                if best_bid_price <= position.stop_loss_price {
+                    let loss_pct = ((position.entry_price - best_bid_price) / position.entry_price * dec!(100.0)).to_f64().unwrap_or(0.0);
+                    (true, format!("止损({:.2}%)", loss_pct))
+                } else if best_bid_price <= trailing_stop {
+                    let pnl_pct = ((best_bid_price - position.entry_price) / position.entry_price * dec!(100.0)).to_f64().unwrap_or(0.0);
+                    (true, format!("追踪止损(最高:{:.4} 盈亏:{:.2}%)", position.high_water_price, pnl_pct))
+                } else {
+                    (false, String::new())
+                }
+            } else if best_bid_price >= position.take_profit_price {
                 let profit_pct = ((best_bid_price - position.entry_price) / position.entry_price * dec!(100.0)).to_f64().unwrap_or(0.0);
                 (true, format!("止盈({:.2}%)", profit_pct))
             } else if best_bid_price <= position.stop_loss_price {
@@ -220,36 +436,37 @@ impl HedgeMonitor {
                 let pair_id_clone = pair_id.clone();
                 let position_tracker = self.position_tracker.clone();
                 let positions = self.positions.clone();
+                let persist_path = self.persist_path.clone();
                 let client = self.client.clone();
-                let private_key = self.private_key.clone();
+                let signer = self.signer.clone();
+                let fee = self.fee;
+                let sell_price = self.sell_price(best_bid_price);
+                info!(
+                    "🎯 卖出定价 | 市场:{} | 买一价:{:.4} | 偏移:{} tick | 卖出价:{:.4}",
+                    position.market_display,
+                    best_bid_price,
+                    self.sell_offset_ticks,
+                    sell_price
+                );
+                let size_decimals = self.size_precision.decimals_for(position.token_id);
                 
                 // 先标记为正在处理，避免重复下单（使用remove+insert避免阻塞）
                 if let Some((_, mut pos)) = self.positions.remove(&pair_id) {
-                    pos.order_id = Some("processing".to_string());
+                    pos.order_id = Some(PROCESSING_ORDER_ID.to_string());
                     self.positions.insert(pair_id.clone(), pos);
                 }
+                self.persist();
                 
                 tokio::spawn(async move {
-                    // 重新创建 signer（因为不能在 spawn 中直接使用 self）
-                    let signer = match LocalSigner::from_str(&private_key) {
-                        Ok(s) => s.with_chain_id(Some(POLYGON)),
-                        Err(e) => {
-                            error!(
-                                "❌ 创建signer失败 | 市场:{} | 错误:{}",
-                                position_clone.market_display,
-                                e
-                            );
-                            return;
-                        }
-                    };
-                    
                     // 执行卖出操作
                     match Self::execute_sell_order(
                         &client,
                         &signer,
+                        &fee,
                         &position_clone,
-                        best_bid_price,
+                        sell_price,
                         sell_amount,
+                        size_decimals,
                     ).await {
                         Ok((order_id, filled, remaining)) => {
                             // 更新仓位，标记已下订单（使用remove+insert避免get_mut阻塞）
@@ -272,12 +489,14 @@ impl HedgeMonitor {
                             } else {
                                 warn!("⚠️ 未找到仓位 | pair_id:{}", pair_id_clone);
                             }
+                            persist_positions(&positions, persist_path.as_deref());
                             
                             // 只有实际成交的部分才更新持仓和风险敞口
                             if filled > dec!(0) {
                                 info!("📊 开始更新持仓 | 市场:{} | 减少:{}份", 
                                     position_clone.market_display, filled);
                                 position_tracker.update_position(position_clone.token_id, -filled);
+                                position_tracker.pnl().record_sell(position_clone.token_id, filled, sell_price * filled);
                                 info!("📊 持仓更新完成 | 市场:{}", position_clone.market_display);
                                 
                                 // 更新风险敞口成本
@@ -306,13 +525,14 @@ impl HedgeMonitor {
                             error!(
                                 "❌ 卖出订单失败 | 市场:{} | 价格:{:.4} | 错误:{}",
                                 position_clone.market_display,
-                                best_bid_price,
+                                sell_price,
                                 e
                             );
                             // 如果失败，清除 processing 标记
                             if let Some(mut pos) = positions.get_mut(&pair_id_clone) {
                                 pos.order_id = None;
                             }
+                            persist_positions(&positions, persist_path.as_deref());
                         }
                     }
                 });
@@ -322,112 +542,25 @@ impl HedgeMonitor {
         Ok(())
     }
 
-    /// 计算实际卖出数量（考虑手续费）
-    fn calculate_sell_amount(&self, position: &HedgePosition) -> Decimal {
-        self.calculate_sell_amount_with_size(position, position.amount)
-    }
-
-    /// 计算指定数量的实际卖出数量（考虑手续费）
-    fn calculate_sell_amount_with_size(&self, position: &HedgePosition, base_amount: Decimal) -> Decimal {
-        // 计算手续费
-        let p = position.entry_price.to_f64().unwrap_or(0.0);
-        let c = 100.0;
-        let fee_rate = 0.25;
-        let exponent = 2.0;
-        
-        let base = p * (1.0 - p);
-        let fee_value = c * fee_rate * base.powf(exponent);
-        let fee_decimal = Decimal::try_from(fee_value).unwrap_or(dec!(0));
-        
-        // 计算实际可用份额
-        let available_amount = if fee_decimal >= dec!(100.0) {
-            dec!(0.01)
-        } else {
-            let multiplier = (dec!(100.0) - fee_decimal) / dec!(100.0);
-            base_amount * multiplier
-        };
-        
-        // 向下取整到2位小数
-        let floored_size = (available_amount * dec!(100.0)).floor() / dec!(100.0);
-        
-        if floored_size.is_zero() {
-            dec!(0.01)
-        } else {
-            floored_size
-        }
-    }
-
-    /// 静态方法：计算指定数量的实际卖出数量（考虑手续费）
-    fn calculate_sell_amount_static(position: &HedgePosition, base_amount: Decimal) -> Decimal {
-        // 计算手续费
-        let p = position.entry_price.to_f64().unwrap_or(0.0);
-        let c = 100.0;
-        let fee_rate = 0.25;
-        let exponent = 2.0;
-        
-        let base = p * (1.0 - p);
-        let fee_value = c * fee_rate * base.powf(exponent);
-        let fee_decimal = Decimal::try_from(fee_value).unwrap_or(dec!(0));
-        
-        // 计算实际可用份额
-        let available_amount = if fee_decimal >= dec!(100.0) {
-            dec!(0.01)
-        } else {
-            let multiplier = (dec!(100.0) - fee_decimal) / dec!(100.0);
-            base_amount * multiplier
-        };
-        
-        // 向下取整到2位小数
-        let floored_size = (available_amount * dec!(100.0)).floor() / dec!(100.0);
-        
-        if floored_size.is_zero() {
-            dec!(0.01)
-        } else {
-            floored_size
-        }
-    }
-
     /// 静态方法：执行卖出订单
     async fn execute_sell_order(
         client: &Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
         signer: &impl Signer<alloy::primitives::Signature>,
+        fee: &FeeParams,
         position: &HedgePosition,
         price: Decimal,
         size: Decimal,
+        size_decimals: u32,
     ) -> Result<(String, Decimal, Decimal)> {
-        // 计算手续费
-        let p = position.entry_price.to_f64().unwrap_or(0.0);
-        let c = 100.0;
-        let fee_rate = 0.25;
-        let exponent = 2.0;
-        
-        let base = p * (1.0 - p);
-        let fee_value = c * fee_rate * base.powf(exponent);
-        let fee_decimal = Decimal::try_from(fee_value).unwrap_or(dec!(0));
-        
-        // 计算实际可用份额
-        let available_amount = if fee_decimal >= dec!(100.0) {
-            dec!(0.01)
-        } else {
-            let multiplier = (dec!(100.0) - fee_decimal) / dec!(100.0);
-            size * multiplier
-        };
-        
-        // 向下取整到2位小数
-        let floored_size = (available_amount * dec!(100.0)).floor() / dec!(100.0);
-        let order_size = if floored_size.is_zero() {
-            dec!(0.01)
-        } else {
-            floored_size
-        };
+        let fee_pct = fee.fee_pct(position.entry_price);
+        let order_size = effective_sell_size(fee, position.entry_price, size, size_decimals);
 
         info!(
-            "💰 计算卖出份额 | 市场:{} | 基础数量:{:.2}份 | 买入价:{:.4} | 手续费:{:.2}% | 可用份额:{:.2}份 | 下单数量:{:.2}份",
+            "💰 计算卖出份额 | 市场:{} | 基础数量:{:.2}份 | 买入价:{:.4} | 手续费:{:.2}% | 下单数量:{:.2}份",
             position.market_display,
             size,
             position.entry_price,
-            fee_decimal,
-            available_amount,
+            fee_pct,
             order_size
         );
 
@@ -453,118 +586,8 @@ impl HedgeMonitor {
             return Err(anyhow::anyhow!("GTC卖出订单失败: {}", error_msg));
         }
 
-        // 检查订单是否立即成交
-        let filled = result.taking_amount;
-        let remaining = order_size - filled;
-        
-        if filled > dec!(0) {
-            info!(
-                "💰 卖出订单已部分成交 | 市场:{} | 订单ID:{} | 已成交:{}份 | 剩余:{}份",
-                position.market_display,
-                &result.order_id[..16],
-                filled,
-                remaining
-            );
-        } else {
-            info!(
-                "📋 卖出订单已提交（未立即成交） | 市场:{} | 订单ID:{} | 数量:{}份 | 价格:{:.4}",
-                position.market_display,
-                &result.order_id[..16],
-                order_size,
-                price
-            );
-        }
-        
-        Ok((result.order_id, filled, remaining))
-    }
-
-    /// 使用GTC订单卖出
-    /// size: 可选，如果提供则使用该数量，否则使用position.amount
-    async fn sell_with_gtc(
-        &self,
-        position: &HedgePosition,
-        price: Decimal,
-        size: Option<Decimal>,
-    ) -> Result<(String, Decimal, Decimal)> {
-        let signer = LocalSigner::from_str(&self.private_key)?
-            .with_chain_id(Some(POLYGON));
-
-        // 计算手续费
-        // 公式: fee = c * fee_rate * (p * (1-p))^exponent
-        // 其中: p = entry_price（买入单价），c = 100（固定值），fee_rate = 0.25，exponent = 2
-        // 手续费计算出来是一个介于 0-1.56 之间的浮点数（比例值，不是绝对值）
-        let p = position.entry_price.to_f64().unwrap_or(0.0);
-        let c = 100.0; // 固定为100
-        let fee_rate = 0.25;
-        let exponent = 2.0;
-        
-        // 计算手续费比例值（0-1.56之间）
-        let base = p * (1.0 - p);
-        let fee_value = c * fee_rate * base.powf(exponent);
-        
-        // 将手续费转换为 Decimal
-        let fee_decimal = Decimal::try_from(fee_value).unwrap_or(dec!(0));
-        
-        // 使用提供的size，如果没有提供则使用position.amount
-        let base_amount = size.unwrap_or(position.amount);
-        
-        // 计算实际可用份额 = 成交份额 * (100 - Fee) / 100
-        // 如果 Fee >= 100，说明异常情况，使用最小可交易单位
-        let available_amount = if fee_decimal >= dec!(100.0) {
-            dec!(0.01) // 异常情况，使用最小单位
-        } else {
-            // 正常情况：可用份额 = 成交份额 * (100 - Fee) / 100
-            let multiplier = (dec!(100.0) - fee_decimal) / dec!(100.0);
-            base_amount * multiplier
-        };
-        
-        // 将订单大小向下取整到2位小数（Polymarket要求）
-        // 使用向下取整而不是四舍五入，避免订单大小超过实际持有份额
-        // 方法：乘以100，向下取整，再除以100
-        let floored_size = (available_amount * dec!(100.0)).floor() / dec!(100.0);
-        
-        // 如果向下取整后为0，则使用最小可交易单位
-        let order_size = if floored_size.is_zero() {
-            dec!(0.01) // 最小单位
-        } else {
-            floored_size
-        };
-
-        info!(
-            "💰 计算卖出份额 | 市场:{} | 基础数量:{:.2}份 | 买入价:{:.4} | 手续费:{:.2}% | 可用份额:{:.2}份 | 下单数量:{:.2}份",
-            position.market_display,
-            base_amount,
-            position.entry_price,
-            fee_decimal,
-            available_amount,
-            order_size
-        );
-
-        // 构建GTC卖出订单
-        let sell_order = self
-            .client
-            .limit_order()
-            .token_id(position.token_id)
-            .side(Side::Sell)
-            .price(price)
-            .size(order_size)
-            .order_type(OrderType::GTC)
-            .build()
-            .await?;
-
-        // 签名订单
-        let signed_order = self.client.sign(&signer, sell_order).await?;
-
-        // 提交订单
-        let result = self.client.post_order(signed_order).await?;
-
-        if !result.success {
-            let error_msg = result.error_msg.as_deref().unwrap_or("未知错误");
-            return Err(anyhow::anyhow!("GTC卖出订单失败: {}", error_msg));
-        }
-
-        // 检查订单是否立即成交
-        let filled = result.taking_amount;
+        // 检查订单是否立即成交（卖出份额）
+        let (filled, _) = filled_amounts(&result, Side::Sell);
         let remaining = order_size - filled;
         
         if filled > dec!(0) {
@@ -591,6 +614,7 @@ impl HedgeMonitor {
     /// 移除已完成的仓位
     pub fn remove_position(&self, pair_id: &str) {
         self.positions.remove(pair_id);
+        self.persist();
         info!(pair_id = %pair_id, "移除对冲仓位");
     }
 
@@ -599,3 +623,26 @@ impl HedgeMonitor {
         self.positions.iter().map(|e| e.value().clone()).collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sell_size_deducts_entry_fee_once() {
+        let fee = FeeParams::default();
+        // 0.5: 手续费 1.5625% → 98.4375，2 位精度向下截断
+        assert_eq!(effective_sell_size(&fee, dec!(0.5), dec!(100), 2), dec!(98.43));
+        // 0.2: 手续费 0.64% → 99.36
+        assert_eq!(effective_sell_size(&fee, dec!(0.2), dec!(100), 2), dec!(99.36));
+        // 0.9: 手续费 0.2025% → 99.7975 → 99.79
+        assert_eq!(effective_sell_size(&fee, dec!(0.9), dec!(100), 2), dec!(99.79));
+    }
+
+    #[test]
+    fn sell_size_never_rounds_to_zero() {
+        let fee = FeeParams::default();
+        assert_eq!(effective_sell_size(&fee, dec!(0.5), dec!(0.005), 2), dec!(0.01));
+        assert_eq!(effective_sell_size(&fee, dec!(0.5), dec!(0.5), 0), dec!(1));
+    }
+}
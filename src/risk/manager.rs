@@ -1,17 +1,25 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use dashmap::DashMap;
-use polymarket_client_sdk::clob::Client;
+use polymarket_client_sdk::clob::types::OrderStatusType;
 use polymarket_client_sdk::types::{B256, Decimal, U256};
 use rust_decimal_macros::dec;
-use tracing::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{debug, error, info, warn};
 
 use super::positions::PositionTracker;
 use super::recovery::{RecoveryAction, RecoveryStrategy};
 use crate::config::Config as BotConfig;
+use crate::trading::clob::{AuthClient, ClobClient};
 use crate::trading::executor::OrderPairResult;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PairStatus {
     Submitted,
     BothFilled,
@@ -21,46 +29,371 @@ pub enum PairStatus {
     Recovering,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderPair {
     pub pair_id: String,
     pub market_id: B256,
     pub yes_order_id: String,
     pub no_order_id: String,
+    /// 客户端订单 ID（由 pair_id + 腿派生，亦为订单 salt），便于与交易所订单对账
+    #[serde(default)]
+    pub yes_client_order_id: String,
+    #[serde(default)]
+    pub no_client_order_id: String,
     pub yes_token_id: U256,
     pub no_token_id: U256,
     pub yes_size: Decimal,
     pub no_size: Decimal,
     pub yes_filled: Decimal,
     pub no_filled: Decimal,
+    /// 买入价格（用于重启后按成交量恢复风险敞口）
+    #[serde(default)]
+    pub yes_price: Decimal,
+    #[serde(default)]
+    pub no_price: Decimal,
+    /// 市场显示名称（例如"btc预测市场"），用于对冲日志
+    #[serde(default)]
+    pub market_display: String,
     pub status: PairStatus,
     pub created_at: DateTime<Utc>,
 }
 
-pub struct RiskManager {
-    clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+/// 根据成交情况判断订单对状态
+/// 订单对持久化的合并间隔：期间的多次变更只写一次文件
+const PERSIST_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// 将订单对写入持久化文件（先写临时文件再 rename，避免写一半被中断导致文件损坏）
+fn write_pairs(path: &std::path::Path, pairs: &[OrderPair]) -> Result<()> {
+    let json = serde_json::to_string_pretty(pairs)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// 启动订单对写盘任务：热路径只替换最新快照，任务合并 PERSIST_DEBOUNCE 内的变更后在阻塞线程池写盘
+fn spawn_persist_writer(path: PathBuf) -> watch::Sender<Vec<OrderPair>> {
+    let (tx, mut rx) = watch::channel(Vec::new());
+    tokio::spawn(async move {
+        while rx.changed().await.is_ok() {
+            tokio::time::sleep(PERSIST_DEBOUNCE).await;
+            let pairs = rx.borrow_and_update().clone();
+            let write_path = path.clone();
+            let result = tokio::task::spawn_blocking(move || write_pairs(&write_path, &pairs))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|r| r);
+            if let Err(e) = result {
+                warn!(error = %e, path = %path.display(), "订单对持久化失败");
+            }
+        }
+    });
+    tx
+}
+
+fn classify_pair(yes_filled: Decimal, yes_size: Decimal, no_filled: Decimal, no_size: Decimal) -> PairStatus {
+    if yes_filled == yes_size && no_filled == no_size {
+        PairStatus::BothFilled
+    } else if yes_filled > dec!(0) && no_filled > dec!(0) {
+        PairStatus::PartiallyFilled
+    } else if yes_filled > dec!(0) && no_filled == dec!(0) {
+        PairStatus::OneFailed
+    } else if yes_filled == dec!(0) && no_filled > dec!(0) {
+        PairStatus::OneFailed
+    } else {
+        PairStatus::BothFailed
+    }
+}
+
+pub struct RiskManager<C: ClobClient = AuthClient> {
+    clob_client: C,
     pending_pairs: DashMap<String, OrderPair>,
     position_tracker: std::sync::Arc<PositionTracker>,
     recovery_strategy: RecoveryStrategy,
+    persist_path: Option<PathBuf>, // 订单对持久化文件（JSON），None 表示不持久化
+    persist_tx: Option<watch::Sender<Vec<OrderPair>>>, // 写盘任务的快照通道，持久化关闭时为 None
+    failure_streak: AtomicU32, // 连续套利执行失败次数，首次成功时清零
+    failure_limit: u32, // 连续失败达到此次数后暂停新的套利执行，0 表示不启用
+    failure_cooldown: Duration, // 暂停时长
+    paused_until: Mutex<Option<Instant>>, // 暂停截止时间
+    daily_loss_limit: Decimal, // 当日（UTC）已实现亏损达到此值后当日不再开新仓，0 表示不启用
+    daily_loss_tripped: Mutex<Option<NaiveDate>>, // 触发每日亏损上限的 UTC 日期
 }
 
-impl RiskManager {
+impl<C: ClobClient> RiskManager<C> {
     pub fn new(
-        clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+        clob_client: C,
         config: &BotConfig,
     ) -> Self {
         Self {
             clob_client,
             pending_pairs: DashMap::new(),
-            position_tracker: std::sync::Arc::new(PositionTracker::new(
-                Decimal::try_from(config.risk_max_exposure_usdc).unwrap_or(dec!(1000.0)),
-            )),
+            position_tracker: std::sync::Arc::new(
+                PositionTracker::new(
+                    config.risk_max_exposure_usdc,
+                    config.wallet_address,
+                )
+                    .with_sync_params(
+                        config.position_sync_tolerance,
+                        config.position_sync_warn_threshold,
+                        std::time::Duration::from_secs(config.position_reconcile_grace_secs),
+                    ),
+            ),
             recovery_strategy: RecoveryStrategy::new(
                 config.risk_imbalance_threshold,
                 config.hedge_take_profit_pct,
                 config.hedge_stop_loss_pct,
+                config.hedge_trailing_pct,
+                config.enable_hedge,
             ),
+            persist_path: config.pending_pairs_path.as_ref().map(PathBuf::from),
+            persist_tx: config.pending_pairs_path.as_ref().map(|p| spawn_persist_writer(PathBuf::from(p))),
+            failure_streak: AtomicU32::new(0),
+            failure_limit: config.trade_failure_limit,
+            failure_cooldown: Duration::from_secs(config.trade_failure_cooldown_secs),
+            paused_until: Mutex::new(None),
+            daily_loss_limit: config.daily_loss_limit_usdc,
+            daily_loss_tripped: Mutex::new(None),
+        }
+    }
+
+    /// 记录一次套利执行结果：成功时清零连续失败计数；连续失败达到 failure_limit 时暂停 failure_cooldown
+    pub fn record_trade_result(&self, success: bool) {
+        if success {
+            if self.failure_streak.swap(0, Ordering::Relaxed) >= self.failure_limit && self.failure_limit > 0 {
+                info!("✅ 套利执行恢复成功，连续失败计数已清零");
+            }
+            return;
+        }
+        let streak = self.failure_streak.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.failure_limit > 0 && streak >= self.failure_limit {
+            *self.paused_until.lock().unwrap() = Some(Instant::now() + self.failure_cooldown);
+            error!(
+                "🚨 套利连续失败 {} 次，暂停新的套利执行 {} 秒 | 请检查账户余额、API 状态与下单参数",
+                streak,
+                self.failure_cooldown.as_secs()
+            );
+        }
+    }
+
+    /// 当前连续失败次数
+    pub fn failure_streak(&self) -> u32 {
+        self.failure_streak.load(Ordering::Relaxed)
+    }
+
+    /// 因连续失败暂停时返回剩余暂停时长，未暂停时返回 None
+    pub fn trading_paused(&self) -> Option<Duration> {
+        let until = (*self.paused_until.lock().unwrap())?;
+        let remaining = until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// 是否因当日已实现亏损达到 DAILY_LOSS_LIMIT_USDC 而停止开新仓：触发后持续到 UTC 零点，
+    /// 触发与跨日重置时各输出一次日志。Merge 与收尾不受影响
+    pub fn daily_loss_halted(&self) -> bool {
+        if self.daily_loss_limit <= dec!(0) {
+            return false;
+        }
+        let today = Utc::now().date_naive();
+        let mut tripped = self.daily_loss_tripped.lock().unwrap();
+        match *tripped {
+            Some(day) if day == today => return true,
+            Some(_) => {
+                *tripped = None;
+                info!("🔄 UTC 日期已切换至 {}，每日亏损上限已重置，恢复套利执行", today);
+            }
+            None => {}
+        }
+        let daily = self.position_tracker.pnl().daily_realized();
+        if daily <= -self.daily_loss_limit {
+            *tripped = Some(today);
+            error!(
+                "🚨 当日（UTC {}）已实现亏损 {:.4} USDC 达到每日亏损上限 {} USDC，今日不再开新仓；Merge 与收尾继续",
+                today,
+                -daily,
+                self.daily_loss_limit
+            );
+            return true;
+        }
+        false
+    }
+
+    /// 将当前所有订单对的快照交给写盘任务（不在调用方线程序列化或写文件）
+    fn persist(&self) {
+        let Some(tx) = &self.persist_tx else {
+            return;
+        };
+        let pairs: Vec<OrderPair> = self.pending_pairs.iter().map(|e| e.value().clone()).collect();
+        tx.send_replace(pairs);
+    }
+
+    /// 启动时从持久化文件恢复订单对：逐个向 CLOB 查询订单最新成交量以重建 PairStatus，
+    /// 仍有一腿挂单的订单对按恢复的成交量回填风险敞口与买入成本。服务器上已不存在的订单视为已结束；
+    /// 两腿都已结束的订单对直接丢弃（其成交已体现在持仓中，由持仓同步负责，不再重复计入敞口）。
+    /// 文件缺失或损坏时从空状态启动。返回恢复的订单对数量。
+    pub async fn load_pending(&self) -> Result<usize> {
+        let Some(path) = &self.persist_path else {
+            return Ok(0);
+        };
+        if !path.exists() {
+            info!(path = %path.display(), "未找到订单对持久化文件，从空状态启动");
+            return Ok(0);
+        }
+        let pairs: Vec<OrderPair> = match fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| serde_json::from_str(&s).map_err(anyhow::Error::from))
+        {
+            Ok(pairs) => pairs,
+            Err(e) => {
+                warn!(error = %e, path = %path.display(), "订单对持久化文件读取失败或已损坏，从空状态启动");
+                return Ok(0);
+            }
+        };
+
+        let mut restored = 0usize;
+        for mut pair in pairs {
+            let (yes_filled, yes_live) = self.query_order_fill(&pair.yes_order_id, pair.yes_filled).await;
+            let (no_filled, no_live) = self.query_order_fill(&pair.no_order_id, pair.no_filled).await;
+            pair.yes_filled = yes_filled;
+            pair.no_filled = no_filled;
+            pair.status = classify_pair(yes_filled, pair.yes_size, no_filled, pair.no_size);
+
+            if !yes_live && !no_live {
+                debug!(pair_id = %pair.pair_id, status = ?pair.status, "订单对两腿均已结束，不再恢复");
+                continue;
+            }
+
+            self.position_tracker.update_exposure_cost(pair.yes_token_id, pair.yes_price, yes_filled);
+            self.position_tracker.update_exposure_cost(pair.no_token_id, pair.no_price, no_filled);
+            let pnl = self.position_tracker.pnl();
+            pnl.record_buy(pair.market_id, pair.yes_token_id, pair.yes_price, yes_filled);
+            pnl.record_buy(pair.market_id, pair.no_token_id, pair.no_price, no_filled);
+            restored += 1;
+
+            info!(
+                pair_id = %pair.pair_id,
+                status = ?pair.status,
+                yes_filled = %yes_filled,
+                no_filled = %no_filled,
+                "♻️ 恢复订单对"
+            );
+            self.pending_pairs.insert(pair.pair_id.clone(), pair);
+        }
+
+        self.persist();
+        info!(
+            restored,
+            pending = self.pending_pairs.len(),
+            exposure = %self.position_tracker.calculate_exposure(),
+            "订单对恢复完成"
+        );
+        Ok(restored)
+    }
+
+    /// 查询订单成交量，返回 (成交量, 是否仍挂单)。订单不存在或查询失败时视为已结束，沿用已知成交量。
+    async fn query_order_fill(&self, order_id: &str, known_filled: Decimal) -> (Decimal, bool) {
+        match self.fetch_order_fill(order_id, known_filled).await {
+            Ok(fill) => fill,
+            Err(e) => {
+                debug!(order_id = %order_id, error = %e, "订单查询失败，视为已结束");
+                (known_filled, false)
+            }
+        }
+    }
+
+    /// 同 query_order_fill，但查询失败时返回错误，由调用方决定是否稍后重试。
+    /// 已撤销、已过期（GTD）的订单不再挂单，成交量为撤销 / 过期前的成交量
+    async fn fetch_order_fill(&self, order_id: &str, known_filled: Decimal) -> Result<(Decimal, bool)> {
+        if order_id.is_empty() {
+            return Ok((known_filled, false));
         }
+        let order = self
+            .clob_client
+            .order(order_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("查询订单 {} 失败: {}", order_id, e))?;
+        Ok((
+            order.size_matched.max(known_filled),
+            matches!(order.status, OrderStatusType::Live | OrderStatusType::Delayed),
+        ))
+    }
+
+    /// 向 CLOB 查询订单对两腿的最新状态，更新 yes_filled / no_filled 与 status：
+    /// 任一腿仍挂单时为 Submitted；两腿都已结束（成交、撤销或过期）时按最终成交量判定，并从未完成订单对中移除。
+    /// 新增成交计入持仓与买入成本（敞口已在执行套利时按订单成本增加）。查询失败时保持原状态并返回错误。
+    pub async fn refresh_pair(&self, pair_id: &str) -> Result<PairStatus> {
+        let pair = self
+            .pending_pairs
+            .get(pair_id)
+            .ok_or_else(|| anyhow::anyhow!("订单对 {} 不存在", pair_id))?
+            .clone();
+        let (yes_filled, yes_live) = self.fetch_order_fill(&pair.yes_order_id, pair.yes_filled).await?;
+        let (no_filled, no_live) = self.fetch_order_fill(&pair.no_order_id, pair.no_filled).await?;
+        let live = yes_live || no_live;
+
+        let mut fills = Vec::new();
+        let status = {
+            let mut entry = self
+                .pending_pairs
+                .get_mut(pair_id)
+                .ok_or_else(|| anyhow::anyhow!("订单对 {} 不存在", pair_id))?;
+            let p = entry.value_mut();
+            // 查询期间用户频道可能已更新成交量，只计入超出当前已知成交量的部分
+            if yes_filled > p.yes_filled {
+                fills.push((p.yes_token_id, p.yes_price, yes_filled - p.yes_filled));
+                p.yes_filled = yes_filled;
+            }
+            if no_filled > p.no_filled {
+                fills.push((p.no_token_id, p.no_price, no_filled - p.no_filled));
+                p.no_filled = no_filled;
+            }
+            p.status = if live {
+                PairStatus::Submitted
+            } else {
+                classify_pair(p.yes_filled, p.yes_size, p.no_filled, p.no_size)
+            };
+            p.status.clone()
+        };
+
+        for (token_id, price, delta) in fills {
+            self.position_tracker.update_position(token_id, delta);
+            self.position_tracker.pnl().record_buy(pair.market_id, token_id, price, delta);
+        }
+        if status != pair.status {
+            info!(
+                pair_id = %pair_id,
+                from = ?pair.status,
+                to = ?status,
+                yes_filled = %yes_filled,
+                no_filled = %no_filled,
+                "🔄 订单对状态更新"
+            );
+        }
+        if !live {
+            self.pending_pairs.remove(pair_id);
+            debug!(pair_id = %pair_id, status = ?status, "订单对两腿均已结束，移出未完成列表");
+        }
+        self.persist();
+        Ok(status)
+    }
+
+    /// 刷新创建超过 min_age 的所有未完成订单对（刚注册的订单对留给下单流程处理），返回刷新成功的数量
+    pub async fn refresh_pending(&self, min_age: Duration) -> usize {
+        let cutoff = Utc::now() - chrono::Duration::from_std(min_age).unwrap_or_default();
+        let pair_ids: Vec<String> = self
+            .pending_pairs
+            .iter()
+            .filter(|e| e.created_at <= cutoff)
+            .map(|e| e.key().clone())
+            .collect();
+        let mut refreshed = 0usize;
+        for pair_id in pair_ids {
+            match self.refresh_pair(&pair_id).await {
+                Ok(_) => refreshed += 1,
+                Err(e) => debug!(pair_id = %pair_id, error = %e, "刷新订单对失败，下次重试"),
+            }
+        }
+        refreshed
     }
 
     /// 注册新的订单对
@@ -74,30 +407,26 @@ impl RiskManager {
         no_token: U256,
         yes_price: Decimal,
         no_price: Decimal,
+        market_display: String,
     ) {
-        let status = if result.yes_filled == result.yes_size && result.no_filled == result.no_size {
-            PairStatus::BothFilled
-        } else if result.yes_filled > dec!(0) && result.no_filled > dec!(0) {
-            PairStatus::PartiallyFilled
-        } else if result.yes_filled > dec!(0) && result.no_filled == dec!(0) {
-            PairStatus::OneFailed
-        } else if result.yes_filled == dec!(0) && result.no_filled > dec!(0) {
-            PairStatus::OneFailed
-        } else {
-            PairStatus::BothFailed
-        };
+        let status = classify_pair(result.yes_filled, result.yes_size, result.no_filled, result.no_size);
 
         let pair = OrderPair {
             pair_id: result.pair_id.clone(),
             market_id,
             yes_order_id: result.yes_order_id,
             no_order_id: result.no_order_id,
+            yes_client_order_id: result.yes_client_order_id,
+            no_client_order_id: result.no_client_order_id,
             yes_token_id: yes_token,
             no_token_id: no_token,
             yes_size: result.yes_size,
             no_size: result.no_size,
             yes_filled: result.yes_filled,
             no_filled: result.no_filled,
+            yes_price,
+            no_price,
+            market_display,
             status: status.clone(),
             created_at: Utc::now(),
         };
@@ -105,6 +434,10 @@ impl RiskManager {
         // 更新持仓（敞口已在「执行套利」时按订单成本增加，此处不再按成交更新敞口）
         self.position_tracker.update_position(yes_token, pair.yes_filled);
         self.position_tracker.update_position(no_token, pair.no_filled);
+        // 按成交记录买入成本（盈亏核算）
+        let pnl = self.position_tracker.pnl();
+        pnl.record_buy(market_id, yes_token, yes_price, pair.yes_filled);
+        pnl.record_buy(market_id, no_token, no_price, pair.no_filled);
 
         // 这个日志已经在executor中打印了，这里不再重复打印
         debug!(
@@ -117,6 +450,7 @@ impl RiskManager {
 
         // 使用 pair.pair_id 的克隆来插入，因为 DashMap 需要拥有所有权
         self.pending_pairs.insert(pair.pair_id.clone(), pair);
+        self.persist();
     }
 
     /// 处理订单对并决定恢复策略
@@ -154,8 +488,146 @@ impl RiskManager {
         }
     }
 
+    /// 按用户频道推送的订单累计成交量更新订单对：只处理已登记订单对中的订单，
+    /// 累计成交量超过已知成交量时，将增量计入订单对与持仓（含盈亏核算的买入成本），并重新判定订单对状态。
+    /// 返回 (token_id, 成交增量)；订单不属于任何订单对或无新增成交时返回 None。
+    pub fn apply_order_fill(&self, order_id: &str, size_matched: Decimal) -> Option<(U256, Decimal)> {
+        let (token_id, delta, market_id, price) = {
+            let mut entry = self
+                .pending_pairs
+                .iter_mut()
+                .find(|e| e.yes_order_id == order_id || e.no_order_id == order_id)?;
+            let pair = entry.value_mut();
+            let is_yes = pair.yes_order_id == order_id;
+            let (filled, token_id, price) = if is_yes {
+                (&mut pair.yes_filled, pair.yes_token_id, pair.yes_price)
+            } else {
+                (&mut pair.no_filled, pair.no_token_id, pair.no_price)
+            };
+            let delta = size_matched - *filled;
+            if delta <= dec!(0) {
+                return None;
+            }
+            *filled = size_matched;
+            pair.status = classify_pair(pair.yes_filled, pair.yes_size, pair.no_filled, pair.no_size);
+            debug!(
+                pair_id = %pair.pair_id,
+                order_id = %order_id,
+                status = ?pair.status,
+                yes_filled = %pair.yes_filled,
+                no_filled = %pair.no_filled,
+                "用户频道：订单对成交更新"
+            );
+            (token_id, delta, pair.market_id, price)
+        };
+
+        // 敞口已在「执行套利」时按订单成本增加，与 register_order_pair 一致只更新持仓与买入成本
+        self.position_tracker.update_position(token_id, delta);
+        self.position_tracker.pnl().record_buy(market_id, token_id, price, delta);
+        self.persist();
+        Some((token_id, delta))
+    }
+
+    /// 未完成订单对数量
+    pub fn pending_pair_count(&self) -> usize {
+        self.pending_pairs.len()
+    }
+
     /// 获取持仓跟踪器（Arc引用）
     pub fn position_tracker(&self) -> std::sync::Arc<PositionTracker> {
         self.position_tracker.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::clob::mock::{open_order, MockClobClient};
+
+    fn manager(mock: &MockClobClient) -> RiskManager<MockClobClient> {
+        RiskManager::new(mock.clone(), &BotConfig::for_test(&[("PENDING_PAIRS_PATH", "")]))
+    }
+
+    fn result(pair_id: &str, yes_filled: Decimal, no_filled: Decimal) -> OrderPairResult {
+        OrderPairResult {
+            pair_id: pair_id.to_string(),
+            yes_order_id: format!("{}-yes", pair_id),
+            no_order_id: format!("{}-no", pair_id),
+            yes_client_order_id: String::new(),
+            no_client_order_id: String::new(),
+            yes_filled,
+            no_filled,
+            yes_size: dec!(10),
+            no_size: dec!(10),
+            success: true,
+        }
+    }
+
+    fn register(manager: &RiskManager<MockClobClient>, pair_id: &str, yes_filled: Decimal, no_filled: Decimal) -> PairStatus {
+        manager.register_order_pair(
+            result(pair_id, yes_filled, no_filled),
+            B256::ZERO,
+            U256::from(1),
+            U256::from(2),
+            dec!(0.45),
+            dec!(0.50),
+            "test".to_string(),
+        );
+        manager.pending_pairs.get(pair_id).unwrap().status.clone()
+    }
+
+    #[test]
+    fn register_order_pair_classifies_fills() {
+        let mock = MockClobClient::default();
+        let manager = manager(&mock);
+        assert_eq!(register(&manager, "both", dec!(10), dec!(10)), PairStatus::BothFilled);
+        assert_eq!(register(&manager, "partial", dec!(4), dec!(10)), PairStatus::PartiallyFilled);
+        assert_eq!(register(&manager, "one", dec!(10), dec!(0)), PairStatus::OneFailed);
+        assert_eq!(register(&manager, "none", dec!(0), dec!(0)), PairStatus::BothFailed);
+    }
+
+    #[tokio::test]
+    async fn refresh_pair_follows_order_status() {
+        let mock = MockClobClient::default();
+        let manager = manager(&mock);
+        register(&manager, "p", dec!(0), dec!(0));
+
+        // 两腿仍挂单且有部分成交：保持 Submitted，新增成交计入持仓
+        mock.set_order(open_order("p-yes", U256::from(1), OrderStatusType::Live, dec!(0.45), dec!(10), dec!(4)));
+        mock.set_order(open_order("p-no", U256::from(2), OrderStatusType::Live, dec!(0.50), dec!(10), dec!(0)));
+        assert_eq!(manager.refresh_pair("p").await.unwrap(), PairStatus::Submitted);
+        assert_eq!(manager.position_tracker.get_pair_positions(U256::from(1), U256::from(2)), (dec!(4), dec!(0)));
+
+        // 两腿都已全部成交：转为 BothFilled 并移出未完成列表
+        mock.set_order(open_order("p-yes", U256::from(1), OrderStatusType::Matched, dec!(0.45), dec!(10), dec!(10)));
+        mock.set_order(open_order("p-no", U256::from(2), OrderStatusType::Matched, dec!(0.50), dec!(10), dec!(10)));
+        assert_eq!(manager.refresh_pair("p").await.unwrap(), PairStatus::BothFilled);
+        assert!(manager.pending_pairs.get("p").is_none());
+        assert_eq!(manager.position_tracker.get_pair_positions(U256::from(1), U256::from(2)), (dec!(10), dec!(10)));
+    }
+    #[tokio::test]
+    async fn load_pending_restores_only_live_pairs() {
+        let path = std::env::temp_dir().join(format!("pending_pairs_{}.json", uuid::Uuid::new_v4()));
+        let config = BotConfig::for_test(&[("PENDING_PAIRS_PATH", path.to_str().unwrap())]);
+
+        let writer = RiskManager::new(MockClobClient::default(), &config);
+        writer.register_order_pair(result("live", dec!(4), dec!(0)), B256::ZERO, U256::from(1), U256::from(2), dec!(0.45), dec!(0.50), "test".to_string());
+        writer.register_order_pair(result("done", dec!(10), dec!(10)), B256::ZERO, U256::from(3), U256::from(4), dec!(0.45), dec!(0.50), "test".to_string());
+        // 写盘在后台任务中合并执行
+        tokio::time::sleep(PERSIST_DEBOUNCE * 5).await;
+        assert!(path.exists());
+
+        // "live" 的 YES 腿仍挂单；"done" 的两腿在服务器上已不存在（视为已结束）
+        let mock = MockClobClient::default();
+        mock.set_order(open_order("live-yes", U256::from(1), OrderStatusType::Live, dec!(0.45), dec!(10), dec!(4)));
+        mock.set_order(open_order("live-no", U256::from(2), OrderStatusType::Canceled, dec!(0.50), dec!(10), dec!(0)));
+        let reader = RiskManager::new(mock, &config);
+        assert_eq!(reader.load_pending().await.unwrap(), 1);
+        assert!(reader.pending_pairs.contains_key("live"));
+        assert!(!reader.pending_pairs.contains_key("done"));
+        // 只有仍挂单的订单对回填敞口：4 × 0.45
+        assert_eq!(reader.position_tracker.calculate_exposure(), dec!(1.8));
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod hedge_monitor;
 pub mod manager;
+pub mod pnl;
 pub mod position_balancer;
 pub mod positions;
 pub mod recovery;
@@ -0,0 +1,167 @@
+//! 已实现盈亏（PnL）核算：买入时按成交价记录成本，卖出 / Merge 时按平均成本结转，
+//! 按 condition_id 汇总已实现盈亏。全程使用 Decimal，避免浮点累计误差。
+//! 只对本次运行（含重启恢复的订单对）中记录过买入的份额核算，来源不明的持仓不计入。
+//! 另按 UTC 日累计当日已实现盈亏，供每日亏损上限（DAILY_LOSS_LIMIT_USDC）使用。
+
+use chrono::{NaiveDate, Utc};
+use dashmap::DashMap;
+use polymarket_client_sdk::types::{B256, Decimal, U256};
+use rust_decimal_macros::dec;
+use std::fmt;
+use std::sync::Mutex;
+
+/// 单个 token 的持仓批次（平均成本法）
+#[derive(Debug, Clone)]
+struct Lot {
+    condition_id: B256,
+    shares: Decimal,
+    cost: Decimal,
+}
+
+/// 单个市场的已实现盈亏
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarketPnl {
+    /// 卖出 / Merge 所得（USD）
+    pub proceeds: Decimal,
+    /// 结转的买入成本（USD）
+    pub cost: Decimal,
+}
+
+impl MarketPnl {
+    pub fn realized(&self) -> Decimal {
+        self.proceeds - self.cost
+    }
+}
+
+/// pnl_summary 的结果：按市场与合计的已实现盈亏
+#[derive(Debug, Clone)]
+pub struct PnlSummary {
+    /// 按已实现盈亏从高到低排序
+    pub markets: Vec<(B256, MarketPnl)>,
+    pub total: MarketPnl,
+}
+
+impl fmt::Display for PnlSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "已实现盈亏:{:+.4} USD | 收入:{:.4} USD | 成本:{:.4} USD | 市场数:{}",
+            self.total.realized(),
+            self.total.proceeds,
+            self.total.cost,
+            self.markets.len()
+        )
+    }
+}
+
+pub struct PnlTracker {
+    lots: DashMap<U256, Lot>,             // token_id -> 未结转的持仓批次
+    realized: DashMap<B256, MarketPnl>,   // condition_id -> 已实现盈亏
+    daily: Mutex<(NaiveDate, Decimal)>,   // (UTC 日期, 当日已实现盈亏)，跨日时清零
+}
+
+impl Default for PnlTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PnlTracker {
+    pub fn new() -> Self {
+        Self {
+            lots: DashMap::new(),
+            realized: DashMap::new(),
+            daily: Mutex::new((Utc::now().date_naive(), dec!(0))),
+        }
+    }
+
+    /// 记录买入：size 份 × price 计入该 token 的成本
+    pub fn record_buy(&self, condition_id: B256, token_id: U256, price: Decimal, size: Decimal) {
+        if size <= dec!(0) {
+            return;
+        }
+        let mut lot = self.lots.entry(token_id).or_insert(Lot {
+            condition_id,
+            shares: dec!(0),
+            cost: dec!(0),
+        });
+        lot.shares += size;
+        lot.cost += price * size;
+    }
+
+    /// 从批次中移出最多 size 份，返回 (实际移出的份数, 结转的成本, condition_id)；无记录时返回 None
+    fn dispose(&self, token_id: U256, size: Decimal) -> Option<(Decimal, Decimal, B256)> {
+        let mut lot = self.lots.get_mut(&token_id)?;
+        let shares = size.min(lot.shares);
+        if shares <= dec!(0) {
+            return None;
+        }
+        let cost = lot.cost * shares / lot.shares;
+        lot.shares -= shares;
+        lot.cost -= cost;
+        let condition_id = lot.condition_id;
+        let empty = lot.shares <= dec!(0);
+        drop(lot);
+        if empty {
+            self.lots.remove(&token_id);
+        }
+        Some((shares, cost, condition_id))
+    }
+
+    fn credit(&self, condition_id: B256, proceeds: Decimal, cost: Decimal) {
+        let mut entry = self.realized.entry(condition_id).or_default();
+        entry.proceeds += proceeds;
+        entry.cost += cost;
+        drop(entry);
+        let today = Utc::now().date_naive();
+        let mut daily = self.daily.lock().unwrap();
+        if daily.0 != today {
+            *daily = (today, dec!(0));
+        }
+        daily.1 += proceeds - cost;
+    }
+
+    /// 当前 UTC 日的已实现盈亏（本次运行内累计，跨 UTC 零点清零）
+    pub fn daily_realized(&self) -> Decimal {
+        let daily = self.daily.lock().unwrap();
+        if daily.0 == Utc::now().date_naive() {
+            daily.1
+        } else {
+            dec!(0)
+        }
+    }
+
+    /// 记录卖出：size 份共得 proceeds（USD）。超出已记录份数的部分不计入
+    pub fn record_sell(&self, token_id: U256, size: Decimal, proceeds: Decimal) {
+        if size <= dec!(0) {
+            return;
+        }
+        if let Some((shares, cost, condition_id)) = self.dispose(token_id, size) {
+            self.credit(condition_id, proceeds * shares / size, cost);
+        }
+    }
+
+    /// 记录 Merge：每对 YES+NO 赎回 1 USD，结转双边成本。只核算双边都有记录的份数
+    pub fn record_merge(&self, condition_id: B256, yes_token: U256, no_token: U256, amount: Decimal) {
+        let tracked = |token: U256| self.lots.get(&token).map(|l| l.shares).unwrap_or(dec!(0));
+        let pairs = amount.min(tracked(yes_token)).min(tracked(no_token));
+        if pairs <= dec!(0) {
+            return;
+        }
+        let yes_cost = self.dispose(yes_token, pairs).map(|(_, c, _)| c).unwrap_or(dec!(0));
+        let no_cost = self.dispose(no_token, pairs).map(|(_, c, _)| c).unwrap_or(dec!(0));
+        self.credit(condition_id, pairs, yes_cost + no_cost);
+    }
+
+    /// 按市场与合计汇总已实现盈亏
+    pub fn pnl_summary(&self) -> PnlSummary {
+        let mut markets: Vec<(B256, MarketPnl)> =
+            self.realized.iter().map(|e| (*e.key(), *e.value())).collect();
+        markets.sort_by_key(|(_, m)| std::cmp::Reverse(m.realized()));
+        let total = markets.iter().fold(MarketPnl::default(), |acc, (_, m)| MarketPnl {
+            proceeds: acc.proceeds + m.proceeds,
+            cost: acc.cost + m.cost,
+        });
+        PnlSummary { markets, total }
+    }
+}
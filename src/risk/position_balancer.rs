@@ -1,42 +1,104 @@
 //! 仓位平衡器：定时检查持仓和挂单，取消多余挂单以保持平衡
 
 use anyhow::Result;
-use polymarket_client_sdk::clob::Client;
 use polymarket_client_sdk::clob::types::request::OrdersRequest;
 use polymarket_client_sdk::clob::types::Side;
 use polymarket_client_sdk::types::{B256, Decimal, U256};
 use rust_decimal_macros::dec;
 use std::collections::HashMap;
-use tracing::{debug, error, info, warn};
+use std::fmt;
+use std::str::FromStr;
+use tracing::{debug, info, warn};
 
 use super::positions::PositionTracker;
 use crate::config::Config as BotConfig;
-use poly_5min_bot::positions::get_positions;
+use crate::trading::clob::{AuthClient, ClobClient};
+use crate::trading::orders::cancel_in_batches;
+use poly_5min_bot::positions::{get_positions, yes_no_positions};
+
+/// 实际持仓平衡、挂单失衡时的目标总量（BALANCE_TARGET_MODE）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceTargetMode {
+    /// 两侧都向 (YES总量 + NO总量) / 2 靠拢，较重一侧取消超出均值的挂单
+    Average,
+    /// 两侧都以 min(YES总量, NO总量) 为目标，较重一侧取消全部超出部分，保证成对成交
+    Min,
+}
+
+impl BalanceTargetMode {
+    /// 由两侧总量（持仓 + 挂单）计算目标总量
+    pub fn target(self, yes_total: Decimal, no_total: Decimal) -> Decimal {
+        match self {
+            Self::Average => (yes_total + no_total) / dec!(2),
+            Self::Min => yes_total.min(no_total),
+        }
+    }
+}
+
+impl FromStr for BalanceTargetMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "average" => Ok(Self::Average),
+            "min" => Ok(Self::Min),
+            other => Err(format!("未知的平衡目标模式: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for BalanceTargetMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Average => write!(f, "average"),
+            Self::Min => write!(f, "min"),
+        }
+    }
+}
 
-/// 仓位平衡器
-pub struct PositionBalancer {
-    clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+/// 仓位平衡器：经 ClobClient 查询挂单与撤单，测试时可注入模拟客户端
+pub struct PositionBalancer<C: ClobClient = AuthClient> {
+    clob_client: C,
     position_tracker: std::sync::Arc<PositionTracker>,
     threshold: Decimal,
     min_total: Decimal,
     max_order_size: Decimal,
+    target_mode: BalanceTargetMode,
+    cancel_batch_size: usize,
+    cancel_max_retries: u32,
 }
 
-impl PositionBalancer {
+impl<C: ClobClient> PositionBalancer<C> {
     pub fn new(
-        clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+        clob_client: C,
         position_tracker: std::sync::Arc<PositionTracker>,
         config: &BotConfig,
     ) -> Self {
         Self {
             clob_client,
             position_tracker,
-            threshold: Decimal::try_from(config.position_balance_threshold).unwrap_or(dec!(2.0)),
-            min_total: Decimal::try_from(config.position_balance_min_total).unwrap_or(dec!(5.0)),
-            max_order_size: Decimal::try_from(config.max_order_size_usdc).unwrap_or(dec!(5.0)),
+            threshold: config.position_balance_threshold,
+            min_total: config.position_balance_min_total,
+            max_order_size: config.max_order_size_usdc,
+            target_mode: config.balance_target_mode,
+            cancel_batch_size: config.cancel_batch_size,
+            cancel_max_retries: config.cancel_max_retries,
         }
     }
 
+    /// 分批撤单（CANCEL_BATCH_SIZE / CANCEL_MAX_RETRIES）并输出撤单结果
+    async fn cancel_batched(&self, order_ids: &[String], context: &str) {
+        let summary = cancel_in_batches(order_ids, self.cancel_batch_size, self.cancel_max_retries, |ids| async move {
+            let refs: Vec<&str> = ids.iter().map(|s| s.as_str()).collect();
+            self.clob_client
+                .cancel_orders(&refs)
+                .await
+                .map_err(|e| anyhow::anyhow!("{}", e))
+        })
+        .await;
+        summary.log(context);
+    }
+
     /// 检查并平衡仓位：获取持仓和挂单，分析每个市场的YES/NO平衡情况，取消多余挂单
     pub async fn check_and_balance_positions(
         &self,
@@ -65,7 +127,7 @@ impl PositionBalancer {
         }
 
         // 获取持仓（从PositionTracker，已通过定时同步更新）
-        let positions = get_positions().await?;
+        let positions = get_positions(self.position_tracker.wallet()).await?;
 
         // 按市场分组订单和持仓
         let mut market_data: HashMap<B256, MarketBalanceData> = HashMap::new();
@@ -83,13 +145,23 @@ impl PositionBalancer {
             });
         }
 
-        // 填充持仓数据
-        for pos in positions {
+        // 填充持仓数据：outcome_index 统一归一为 YES/NO（兼容 0/1 与 1/2 两种约定）
+        for (condition_id, (yes, no)) in yes_no_positions(&positions) {
+            if let Some(data) = market_data.get_mut(&condition_id) {
+                if let Some(yes) = yes {
+                    data.yes_position = yes.size;
+                }
+                if let Some(no) = no {
+                    data.no_position = no.size;
+                }
+            }
+        }
+        // 仅持有 index 1 一侧时方向无法由 index 判断，按 token_id 归属
+        for pos in &positions {
             if let Some(data) = market_data.get_mut(&pos.condition_id) {
-                // outcome_index: 0=YES, 1=NO
-                if pos.outcome_index == 0 {
+                if data.yes_position.is_zero() && pos.asset == data.yes_token_id {
                     data.yes_position = pos.size;
-                } else if pos.outcome_index == 1 {
+                } else if data.no_position.is_zero() && pos.asset == data.no_token_id {
                     data.no_position = pos.size;
                 }
             }
@@ -177,12 +249,7 @@ impl PositionBalancer {
 
                     // 取消YES订单
                     if cancel_yes_count > 0 {
-                        let yes_order_ids: Vec<&str> = cancel_yes_order_ids.iter().map(|s| s.as_str()).collect();
-                        if let Err(e) = self.clob_client.cancel_orders(&yes_order_ids).await {
-                            error!(error = %e, "❌ 取消YES订单失败");
-                        } else {
-                            info!("✅ 已取消 {} 个YES订单", cancel_yes_count);
-                        }
+                        self.cancel_batched(&cancel_yes_order_ids, "仓位平衡 取消YES订单").await;
                     }
 
                     // 取消NO订单（按价格排序，取消价格最低的，直到累计数量达到cancel_no_size）
@@ -202,12 +269,7 @@ impl PositionBalancer {
                         }
                         
                         if !cancel_no_order_ids.is_empty() {
-                            let cancel_no_order_ids_ref: Vec<&str> = cancel_no_order_ids.iter().map(|s| s.as_str()).collect();
-                            if let Err(e) = self.clob_client.cancel_orders(&cancel_no_order_ids_ref).await {
-                                error!(error = %e, "取消NO订单失败");
-                            } else {
-                                info!("已取消 {} 个NO订单（累计 {} 份）", cancel_no_order_ids.len(), accumulated_size);
-                            }
+                            self.cancel_batched(&cancel_no_order_ids, "仓位平衡 取消NO订单").await;
                         }
                     }
                 }
@@ -230,12 +292,7 @@ impl PositionBalancer {
 
                     // 取消NO订单
                     if cancel_no_count > 0 {
-                        let no_order_ids: Vec<&str> = cancel_no_order_ids.iter().map(|s| s.as_str()).collect();
-                        if let Err(e) = self.clob_client.cancel_orders(&no_order_ids).await {
-                            error!(error = %e, "取消NO订单失败");
-                        } else {
-                            info!("已取消 {} 个NO订单", cancel_no_count);
-                        }
+                        self.cancel_batched(&cancel_no_order_ids, "仓位平衡 取消NO订单").await;
                     }
 
                     // 取消YES订单（按价格排序，取消价格最低的，直到累计数量达到cancel_yes_size）
@@ -255,12 +312,7 @@ impl PositionBalancer {
                         }
                         
                         if !cancel_yes_order_ids.is_empty() {
-                            let cancel_yes_order_ids_ref: Vec<&str> = cancel_yes_order_ids.iter().map(|s| s.as_str()).collect();
-                            if let Err(e) = self.clob_client.cancel_orders(&cancel_yes_order_ids_ref).await {
-                                error!(error = %e, "❌ 取消YES订单失败");
-                            } else {
-                                info!("✅ 已取消 {} 个YES订单（累计 {} 份）", cancel_yes_order_ids.len(), accumulated_size);
-                            }
+                            self.cancel_batched(&cancel_yes_order_ids, "仓位平衡 取消YES订单").await;
                         }
                     }
                 }
@@ -268,8 +320,8 @@ impl PositionBalancer {
             return Ok(());
         }
 
-        // 情况2：实际持仓平衡，但挂单导致总持仓失衡
-        let target = (yes_total + no_total) / dec!(2);
+        // 情况2：实际持仓平衡，但挂单导致总持仓失衡，按 BALANCE_TARGET_MODE 计算两侧目标
+        let target = self.target_mode.target(yes_total, no_total);
         let yes_imbalance = yes_total - target;
         let no_imbalance = no_total - target;
 
@@ -292,12 +344,7 @@ impl PositionBalancer {
             if !cancel_order_ids.is_empty() {
                 info!("⚠️ YES挂单过多，取消 {} 个YES订单", cancel_order_ids.len());
 
-                let cancel_order_ids_ref: Vec<&str> = cancel_order_ids.iter().map(|s| s.as_str()).collect();
-                if let Err(e) = self.clob_client.cancel_orders(&cancel_order_ids_ref).await {
-                    error!(error = %e, "❌ 取消YES订单失败");
-                } else {
-                    info!("✅ 已取消 {} 个YES订单", cancel_order_ids.len());
-                }
+                self.cancel_batched(&cancel_order_ids, "仓位平衡 取消YES订单").await;
             }
         }
 
@@ -320,12 +367,7 @@ impl PositionBalancer {
             if !cancel_order_ids.is_empty() {
                 info!("NO挂单过多，取消 {} 个NO订单", cancel_order_ids.len());
 
-                let cancel_order_ids_ref: Vec<&str> = cancel_order_ids.iter().map(|s| s.as_str()).collect();
-                if let Err(e) = self.clob_client.cancel_orders(&cancel_order_ids_ref).await {
-                    error!(error = %e, "取消NO订单失败");
-                } else {
-                    info!("已取消 {} 个NO订单", cancel_order_ids.len());
-                }
+                self.cancel_batched(&cancel_order_ids, "仓位平衡 取消NO订单").await;
             }
         }
 
@@ -371,3 +413,149 @@ struct OrderInfo {
     price: Decimal,
     pending_size: Decimal,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::clob::mock::MockClobClient;
+    use std::sync::Arc;
+
+    fn balancer(mock: &MockClobClient) -> PositionBalancer<MockClobClient> {
+        balancer_with_mode(mock, "average")
+    }
+
+    fn balancer_with_mode(mock: &MockClobClient, mode: &str) -> PositionBalancer<MockClobClient> {
+        let config = BotConfig::for_test(&[
+            ("POSITION_BALANCE_THRESHOLD", "2"),
+            ("POSITION_BALANCE_MIN_TOTAL", "5"),
+            ("BALANCE_TARGET_MODE", mode),
+            ("PENDING_PAIRS_PATH", ""),
+        ]);
+        let tracker = Arc::new(PositionTracker::new(dec!(1000), config.wallet_address));
+        PositionBalancer::new(mock.clone(), tracker, &config)
+    }
+
+    fn order(id: &str, price: Decimal, pending_size: Decimal) -> OrderInfo {
+        OrderInfo {
+            order_id: id.to_string(),
+            price,
+            pending_size,
+        }
+    }
+
+    fn market(
+        yes_position: Decimal,
+        no_position: Decimal,
+        yes_orders: Vec<OrderInfo>,
+        no_orders: Vec<OrderInfo>,
+    ) -> MarketBalanceData {
+        MarketBalanceData {
+            condition_id: B256::ZERO,
+            yes_token_id: U256::from(1),
+            no_token_id: U256::from(2),
+            yes_position,
+            no_position,
+            yes_orders,
+            no_orders,
+        }
+    }
+
+    #[tokio::test]
+    async fn yes_heavy_cancels_all_yes_and_matching_no_orders() {
+        let mock = MockClobClient::default();
+        let data = market(
+            dec!(10),
+            dec!(0),
+            vec![order("y1", dec!(0.45), dec!(5))],
+            vec![order("n2", dec!(0.50), dec!(5)), order("n1", dec!(0.40), dec!(5))],
+        );
+        balancer(&mock).balance_market(&data).await.unwrap();
+        // YES 挂单全部撤销；NO 挂单按价格从低到高撤销约 min(5, 10) = 5 份
+        assert_eq!(mock.canceled(), vec!["y1", "n1"]);
+    }
+
+    #[tokio::test]
+    async fn no_heavy_cancels_all_no_and_matching_yes_orders() {
+        let mock = MockClobClient::default();
+        let data = market(
+            dec!(0),
+            dec!(10),
+            vec![order("y1", dec!(0.40), dec!(3)), order("y2", dec!(0.30), dec!(3))],
+            vec![order("n1", dec!(0.45), dec!(4))],
+        );
+        balancer(&mock).balance_market(&data).await.unwrap();
+        // NO 挂单全部撤销；YES 挂单需撤约 min(4, 6) = 4 份，3 份不够，继续撤下一笔
+        assert_eq!(mock.canceled(), vec!["n1", "y2", "y1"]);
+    }
+
+    #[tokio::test]
+    async fn pending_imbalance_cancels_cheapest_excess_orders() {
+        let mock = MockClobClient::default();
+        let data = market(
+            dec!(5),
+            dec!(5),
+            vec![order("y1", dec!(0.45), dec!(4)), order("y2", dec!(0.40), dec!(4))],
+            vec![],
+        );
+        balancer(&mock).balance_market(&data).await.unwrap();
+        // 持仓平衡；YES 总量 13、NO 总量 5，average 目标 9，YES 超出 4 份，撤最便宜的 y2 即可
+        assert_eq!(mock.canceled(), vec!["y2"]);
+    }
+
+    #[tokio::test]
+    async fn balanced_market_cancels_nothing() {
+        let mock = MockClobClient::default();
+        let data = market(
+            dec!(5),
+            dec!(5),
+            vec![order("y1", dec!(0.45), dec!(1))],
+            vec![order("n1", dec!(0.45), dec!(1))],
+        );
+        balancer(&mock).balance_market(&data).await.unwrap();
+        assert!(mock.canceled().is_empty());
+    }
+
+    /// 持仓 5/5；YES 挂单 4+4+2，NO 挂单 2：YES 总量 15、NO 总量 7
+    fn asymmetric_pending() -> MarketBalanceData {
+        market(
+            dec!(5),
+            dec!(5),
+            vec![
+                order("y1", dec!(0.45), dec!(4)),
+                order("y2", dec!(0.40), dec!(4)),
+                order("y3", dec!(0.50), dec!(2)),
+            ],
+            vec![order("n1", dec!(0.45), dec!(2))],
+        )
+    }
+
+    #[tokio::test]
+    async fn average_mode_cancels_down_to_the_mean() {
+        let mock = MockClobClient::default();
+        balancer_with_mode(&mock, "average").balance_market(&asymmetric_pending()).await.unwrap();
+        // 目标 (15 + 7) / 2 = 11，YES 超出 4 份，撤最便宜的 y2 即可
+        assert_eq!(mock.canceled(), vec!["y2"]);
+    }
+
+    #[tokio::test]
+    async fn min_mode_cancels_down_to_the_lighter_side() {
+        let mock = MockClobClient::default();
+        balancer_with_mode(&mock, "min").balance_market(&asymmetric_pending()).await.unwrap();
+        // 目标 min(15, 7) = 7，YES 超出 8 份，按价格从低到高撤 y2、y1
+        assert_eq!(mock.canceled(), vec!["y2", "y1"]);
+    }
+
+    #[tokio::test]
+    async fn min_mode_acts_below_the_average_threshold() {
+        // YES 总量 8、NO 总量 5：average 目标 6.5 只超出 1.5 份，低于阈值 2；min 目标 5 超出 3 份
+        let data = market(dec!(5), dec!(5), vec![order("y1", dec!(0.45), dec!(3))], vec![]);
+
+        let mock = MockClobClient::default();
+        balancer_with_mode(&mock, "average").balance_market(&data).await.unwrap();
+        assert!(mock.canceled().is_empty());
+
+        let mock = MockClobClient::default();
+        balancer_with_mode(&mock, "min").balance_market(&data).await.unwrap();
+        assert_eq!(mock.canceled(), vec!["y1"]);
+    }
+}
@@ -1,34 +1,87 @@
 use anyhow::Result;
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
-use polymarket_client_sdk::types::{Decimal, U256};
+use polymarket_client_sdk::types::{Address, B256, Decimal, U256};
 use rust_decimal_macros::dec;
-use tracing::{debug, info, trace};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, trace, warn};
 
-use poly_5min_bot::positions::{get_positions, Position};
+use poly_5min_bot::positions::{get_positions, normalize_outcome, OutcomeSide, Position};
+
+use super::pnl::PnlTracker;
+
+/// PositionTracker 某一时刻的只读快照，可自由克隆，供状态服务与调试使用
+#[derive(Debug, Clone, Default)]
+pub struct PositionSnapshot {
+    /// token_id -> 持仓数量（不含已清零的 token）
+    pub positions: HashMap<U256, Decimal>,
+    /// token_id -> 成本（USD）
+    pub exposure_costs: HashMap<U256, Decimal>,
+    /// 当前总风险敞口，等于 exposure_costs 之和
+    pub exposure: Decimal,
+    /// 风险敞口上限
+    pub max_exposure: Decimal,
+}
 
 pub struct PositionTracker {
+    wallet: Address, // 持仓所在地址（代理钱包或 EOA），同步持仓时查询此地址
     positions: DashMap<U256, Decimal>, // token_id -> 数量（正数=持有多头，负数=持有空头）
     exposure_costs: DashMap<U256, Decimal>, // token_id -> 成本（USD），用于跟踪风险敞口
     max_exposure: Decimal,
+    local_updated_at: DashMap<U256, Instant>, // token_id -> 本地最近一次更新持仓的时间（reconcile 据此信任本地）
+    generation: AtomicU64, // 本地持仓更新计数，每次 update_position 加一
+    touched_generation: DashMap<U256, u64>, // token_id -> 最近一次本地更新时的 generation，同步据此识别请求快照后的本地更新
+    acquired_at: DashMap<U256, Instant>, // token_id -> 持仓从 0 变为正数的时间（持仓清零时移除），用于持仓时长上限
+    sync_tolerance: Decimal, // 同步时本地与 API 差异超过此值才记录
+    sync_warn_threshold: Decimal, // 同步使持仓变化超过此值时告警（常见于成交尚未结算到 Data API）
+    reconcile_grace: Duration, // reconcile 时信任此时长内的本地更新
+    pnl: PnlTracker, // 已实现盈亏核算
 }
 
 impl PositionTracker {
-    pub fn new(max_exposure: Decimal) -> Self {
+    pub fn new(max_exposure: Decimal, wallet: Address) -> Self {
         Self {
+            wallet,
             positions: DashMap::new(),
             exposure_costs: DashMap::new(),
             max_exposure,
+            local_updated_at: DashMap::new(),
+            generation: AtomicU64::new(0),
+            touched_generation: DashMap::new(),
+            acquired_at: DashMap::new(),
+            sync_tolerance: dec!(0.01),
+            sync_warn_threshold: dec!(5),
+            reconcile_grace: Duration::from_secs(30),
+            pnl: PnlTracker::new(),
         }
     }
 
+    /// 设置持仓同步参数：差异记录阈值、告警阈值、reconcile 信任本地更新的时长
+    pub fn with_sync_params(mut self, tolerance: Decimal, warn_threshold: Decimal, reconcile_grace: Duration) -> Self {
+        self.sync_tolerance = tolerance;
+        self.sync_warn_threshold = warn_threshold;
+        self.reconcile_grace = reconcile_grace;
+        self
+    }
+
     pub fn update_position(&self, token_id: U256, delta: Decimal) {
         trace!("update_position: 开始 | token_id:{} | delta:{}", token_id, delta);
         
         trace!("update_position: 准备获取positions写锁");
         let mut entry = self.positions.entry(token_id).or_insert(dec!(0));
         trace!("update_position: positions写锁已获取");
+        let was_flat = *entry <= dec!(0.0001);
         *entry += delta;
+        if was_flat && *entry > dec!(0.0001) {
+            self.acquired_at.insert(token_id, Instant::now());
+        }
         trace!("update_position: 持仓已更新，新值:{}", *entry);
+        self.local_updated_at.insert(token_id, Instant::now());
+        // 持有 positions 写锁期间记录 generation，保证同步看到新持仓时也能看到对应的 generation
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.touched_generation.insert(token_id, generation);
 
         // 如果持仓变为0或接近0，可以清理
         // 关键修复：先释放 positions 的写锁，再访问 exposure_costs
@@ -45,6 +98,7 @@ impl PositionTracker {
         
         // 现在可以安全地访问 exposure_costs
         if should_remove {
+            self.acquired_at.remove(&token_id);
             trace!("update_position: 准备remove exposure_costs");
             self.exposure_costs.remove(&token_id);
             trace!("update_position: exposure_costs已remove");
@@ -126,6 +180,16 @@ impl PositionTracker {
         trace!("update_exposure_cost: 完成");
     }
 
+    /// 已实现盈亏核算（买入、卖出、Merge 时记录）
+    pub fn pnl(&self) -> &PnlTracker {
+        &self.pnl
+    }
+
+    /// 持仓所在地址（代理钱包或 EOA）
+    pub fn wallet(&self) -> Address {
+        self.wallet
+    }
+
     /// 获取最大风险敞口限制
     pub fn max_exposure(&self) -> Decimal {
         self.max_exposure
@@ -137,6 +201,35 @@ impl PositionTracker {
         info!("🔄 风险敞口已重置（新一轮）");
     }
 
+    /// 持仓与成本快照。按 update_exposure_cost 的加锁顺序先读 positions 再读 exposure_costs，
+    /// 每次只持有单个分片的锁；exposure 由快照内的成本求和，保证与 exposure_costs 一致
+    pub fn snapshot(&self) -> PositionSnapshot {
+        let positions: HashMap<U256, Decimal> = self
+            .positions
+            .iter()
+            .filter(|e| !e.value().is_zero())
+            .map(|e| (*e.key(), *e.value()))
+            .collect();
+        let exposure_costs: HashMap<U256, Decimal> = self
+            .exposure_costs
+            .iter()
+            .map(|e| (*e.key(), *e.value()))
+            .collect();
+        let exposure = exposure_costs.values().sum();
+        PositionSnapshot {
+            positions,
+            exposure_costs,
+            exposure,
+            max_exposure: self.max_exposure,
+        }
+    }
+
+    /// 持仓时长：自该 token 持仓从 0 变为正数起经过的时间；无持仓时返回 None。
+    /// 由同步首次发现的持仓（如重启前已持有）从发现时开始计时
+    pub fn position_age(&self, token_id: U256) -> Option<Duration> {
+        self.acquired_at.get(&token_id).map(|t| t.elapsed())
+    }
+
     pub fn get_position(&self, token_id: U256) -> Decimal {
         self.positions
             .get(&token_id)
@@ -171,6 +264,12 @@ impl PositionTracker {
         costs.iter().sum()
     }
 
+    /// 单个市场（condition）的风险敞口：该市场 YES 与 NO token 的成本之和
+    pub fn market_exposure(&self, yes_token: U256, no_token: U256) -> Decimal {
+        let cost = |token: U256| self.exposure_costs.get(&token).map(|v| *v.value()).unwrap_or(dec!(0));
+        cost(yes_token) + cost(no_token)
+    }
+
     pub fn is_within_limits(&self) -> bool {
         self.calculate_exposure() <= self.max_exposure
     }
@@ -189,31 +288,116 @@ impl PositionTracker {
         (self.get_position(yes_token), self.get_position(no_token))
     }
 
-    /// 从 Data API 同步持仓，完全覆盖本地缓存
-    /// 这个方法会从API获取最新持仓，清空并重建本地positions map
-    /// 用于定时同步任务，确保本地缓存与链上实际持仓一致
+    /// 从 Data API 同步持仓，以 API 为准更新本地缓存。
+    /// 先与本地持仓逐个 token 比对，差异超过容差的记录日志（超过告警阈值时 warn），再应用差异。
+    /// 请求 API 期间发生的本地更新（成交、Merge 扣减）不会被覆盖，见 apply_snapshot。
     pub async fn sync_from_api(&self) -> Result<Vec<Position>> {
-        use std::collections::HashMap;
-        use polymarket_client_sdk::types::B256;
-        
-        let positions = get_positions().await?;
-        
-        // 清空现有持仓（敞口仅由「执行套利」时增加、Merge 时扣减，不从 API 回填）
-        self.positions.clear();
-        
-        // 从API获取的持仓更新到本地缓存
-        let mut updated_count = 0;
-        let mut valid_positions = Vec::new();
-        
-        for pos in positions {
-            if pos.size > dec!(0) {
-                // Position.asset 就是 token_id
-                self.positions.insert(pos.asset, pos.size);
-                valid_positions.push(pos);
-                updated_count += 1;
+        let generation = self.generation.load(Ordering::SeqCst);
+        let positions = get_positions(self.wallet).await?;
+        Ok(self.apply_snapshot(positions, None, generation))
+    }
+
+    /// 与 sync_from_api 相同，但信任最近 reconcile_grace 内有本地更新的 token（如刚成交、尚未结算到 Data API），
+    /// 这些 token 保留本地值，只记录差异。用于定时同步任务，避免覆盖在途成交导致敞口跳动。
+    pub async fn reconcile(&self) -> Result<Vec<Position>> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        let positions = get_positions(self.wallet).await?;
+        Ok(self.apply_snapshot(positions, Some(self.reconcile_grace), generation))
+    }
+
+    /// 该 token 在 generation 之后是否有本地更新
+    fn touched_since(&self, token_id: U256, generation: u64) -> bool {
+        self.touched_generation.get(&token_id).is_some_and(|g| *g > generation)
+    }
+
+    /// 对比 API 快照与本地持仓并应用差异；trust_recent 为 Some 时跳过该时长内有本地更新的 token。
+    /// snapshot_generation 为请求快照前的本地更新计数：之后有本地更新的 token 说明快照已过时，保留本地值
+    fn apply_snapshot(&self, positions: Vec<Position>, trust_recent: Option<Duration>, snapshot_generation: u64) -> Vec<Position> {
+        // 敞口仅由「执行套利」时增加、Merge 时扣减，不从 API 回填
+        let valid_positions: Vec<Position> = positions.into_iter().filter(|p| p.size > dec!(0)).collect();
+        let api_tokens: HashSet<U256> = valid_positions.iter().map(|p| p.asset).collect();
+        let local: Vec<(U256, Decimal)> = self.positions.iter().map(|e| (*e.key(), *e.value())).collect();
+
+        // 本地有、API 无的 token 视为 API 持仓为 0
+        let mut changes: Vec<(U256, Decimal, Decimal)> = valid_positions
+            .iter()
+            .map(|p| (p.asset, self.get_position(p.asset), p.size))
+            .collect();
+        changes.extend(
+            local
+                .iter()
+                .filter(|(token, size)| !api_tokens.contains(token) && *size != dec!(0))
+                .map(|(token, size)| (*token, *size, dec!(0))),
+        );
+
+        let mut applied = 0usize;
+        let mut kept_local = 0usize;
+        let mut concurrent = 0usize;
+        for (token_id, local_size, api_size) in changes {
+            let diff = api_size - local_size;
+            if diff.abs() <= self.sync_tolerance {
+                continue;
+            }
+            let recent = trust_recent.is_some_and(|grace| {
+                self.local_updated_at
+                    .get(&token_id)
+                    .is_some_and(|t| t.elapsed() < grace)
+            });
+            if recent {
+                kept_local += 1;
+                debug!(token_id = %token_id, local = %local_size, api = %api_size, "持仓差异：本地近期有更新，保留本地值");
+                continue;
+            }
+            if diff.abs() > self.sync_warn_threshold {
+                warn!(
+                    token_id = %token_id,
+                    local = %local_size,
+                    api = %api_size,
+                    diff = %diff,
+                    "⚠️ 持仓同步变化较大，可能存在结算延迟"
+                );
+            } else {
+                debug!(token_id = %token_id, local = %local_size, api = %api_size, "持仓差异，按 API 更新");
             }
+            // 持有 positions 分片写锁期间检查 generation 并写入，避免与 update_position 交错导致本地更新丢失
+            let entry = self.positions.entry(token_id);
+            let written = if self.touched_since(token_id, snapshot_generation) {
+                false
+            } else {
+                match entry {
+                    Entry::Occupied(mut e) => {
+                        if api_size > dec!(0) {
+                            e.insert(api_size);
+                        } else {
+                            e.remove();
+                        }
+                        true
+                    }
+                    Entry::Vacant(e) => {
+                        if api_size > dec!(0) {
+                            e.insert(api_size);
+                        }
+                        true
+                    }
+                }
+            };
+            if !written {
+                concurrent += 1;
+                debug!(token_id = %token_id, api = %api_size, "持仓差异：请求快照后本地有更新，保留本地值");
+                continue;
+            }
+            if api_size > dec!(0) {
+                self.acquired_at.entry(token_id).or_insert_with(Instant::now);
+            } else {
+                self.acquired_at.remove(&token_id);
+            }
+            applied += 1;
         }
-        
+        if applied > 0 || kept_local > 0 || concurrent > 0 {
+            info!(applied, kept_local, concurrent, "🔄 持仓差异已处理");
+        }
+        let updated_count = valid_positions.len();
+
         // 按市场分组打印持仓
         if !valid_positions.is_empty() {
             let mut by_market: HashMap<B256, Vec<&Position>> = HashMap::new();
@@ -228,12 +412,13 @@ impl PositionTracker {
                 let mut yes_pos = dec!(0);
                 let mut no_pos = dec!(0);
                 let mut market_title = "";
+                let indices: Vec<i32> = market_positions.iter().map(|p| p.outcome_index).collect();
                 
                 for pos in market_positions {
-                    if pos.outcome_index == 0 {
-                        yes_pos = pos.size;
-                    } else if pos.outcome_index == 1 {
-                        no_pos = pos.size;
+                    match normalize_outcome(pos.outcome_index, &indices) {
+                        Some(OutcomeSide::Yes) => yes_pos = pos.size,
+                        Some(OutcomeSide::No) => no_pos = pos.size,
+                        None => {}
                     }
                     if market_title.is_empty() {
                         market_title = &pos.title;
@@ -258,6 +443,65 @@ impl PositionTracker {
             info!("📊 持仓同步完成 | 当前无持仓");
         }
         
-        Ok(valid_positions)
+        valid_positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buy(tracker: &PositionTracker, token: U256, price: Decimal, size: Decimal) {
+        tracker.update_exposure_cost(token, price, size);
+        tracker.update_position(token, size);
+    }
+
+    fn sell(tracker: &PositionTracker, token: U256, size: Decimal) {
+        // 卖出须先按卖出前的持仓比例扣减成本，再更新持仓
+        tracker.update_exposure_cost(token, dec!(0), -size);
+        tracker.update_position(token, -size);
+    }
+
+    #[test]
+    fn snapshot_tracks_exposure_through_buys_and_sells() {
+        let (yes, no) = (U256::from(1), U256::from(2));
+        let tracker = PositionTracker::new(dec!(10), Address::ZERO);
+        buy(&tracker, yes, dec!(0.4), dec!(10));
+        buy(&tracker, no, dec!(0.5), dec!(10));
+
+        let snap = tracker.snapshot();
+        assert_eq!(snap.positions[&yes], dec!(10));
+        assert_eq!(snap.exposure_costs[&no], dec!(5));
+        assert_eq!(snap.exposure, dec!(9));
+        assert_eq!(snap.max_exposure, dec!(10));
+        // 恰好达到上限不算超出
+        assert!(!tracker.would_exceed_limit(dec!(0.5), dec!(0.5)));
+        assert!(tracker.would_exceed_limit(dec!(0.5), dec!(0.51)));
+
+        // 卖出一半 YES：成本按比例减半
+        sell(&tracker, yes, dec!(5));
+        // 全部卖出 NO：持仓与成本均移除
+        sell(&tracker, no, dec!(10));
+        let snap = tracker.snapshot();
+        assert_eq!(snap.positions.get(&yes), Some(&dec!(5)));
+        assert_eq!(snap.exposure_costs.get(&yes), Some(&dec!(2)));
+        assert!(!snap.positions.contains_key(&no));
+        assert!(!snap.exposure_costs.contains_key(&no));
+        assert_eq!(snap.exposure, dec!(2));
+    }
+
+    #[test]
+    fn reset_exposure_keeps_positions() {
+        let yes = U256::from(1);
+        let tracker = PositionTracker::new(dec!(10), Address::ZERO);
+        buy(&tracker, yes, dec!(0.6), dec!(10));
+        assert!(tracker.would_exceed_limit(dec!(4.01), dec!(0)));
+
+        tracker.reset_exposure();
+        let snap = tracker.snapshot();
+        assert_eq!(snap.exposure, dec!(0));
+        assert!(snap.exposure_costs.is_empty());
+        assert_eq!(snap.positions[&yes], dec!(10));
+        assert!(!tracker.would_exceed_limit(dec!(10), dec!(0)));
     }
 }
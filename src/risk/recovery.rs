@@ -17,6 +17,7 @@ pub enum RecoveryAction {
         entry_price: Decimal, // 买入价格（卖一价）
         take_profit_pct: Decimal, // 止盈百分比（例如0.05表示5%）
         stop_loss_pct: Decimal, // 止损百分比（例如0.05表示5%）
+        trailing_pct: Option<Decimal>, // 追踪止损回落比例；None 表示使用固定止盈止损
         pair_id: String,
         market_display: String, // 市场显示名称（例如"btc预测市场"）
     },
@@ -27,17 +28,24 @@ pub struct RecoveryStrategy {
     imbalance_threshold: Decimal,
     take_profit_pct: Decimal, // 止盈百分比
     stop_loss_pct: Decimal,   // 止损百分比
+    trailing_pct: Option<Decimal>, // 追踪止损回落比例
+    enable_hedge: bool,       // 单边成交时是否交给 HedgeMonitor 止盈止损
 }
 
 impl RecoveryStrategy {
-    pub fn new(imbalance_threshold: f64, take_profit_pct: f64, stop_loss_pct: f64) -> Self {
+    pub fn new(
+        imbalance_threshold: Decimal,
+        take_profit_pct: Decimal,
+        stop_loss_pct: Decimal,
+        trailing_pct: Decimal,
+        enable_hedge: bool,
+    ) -> Self {
         Self {
-            imbalance_threshold: Decimal::try_from(imbalance_threshold)
-                .unwrap_or(dec!(0.1)),
-            take_profit_pct: Decimal::try_from(take_profit_pct)
-                .unwrap_or(dec!(0.05)), // 默认5%止盈
-            stop_loss_pct: Decimal::try_from(stop_loss_pct)
-                .unwrap_or(dec!(0.05)), // 默认5%止损
+            imbalance_threshold,
+            take_profit_pct,
+            stop_loss_pct,
+            trailing_pct: Some(trailing_pct).filter(|p| *p > dec!(0)), // 0 表示不启用追踪止损
+            enable_hedge,
         }
     }
 
@@ -110,55 +118,53 @@ impl RecoveryStrategy {
     }
 
     /// 处理只购买一边成功（GTC订单的情况）
-    /// 对冲策略已暂时关闭，单边成交不做任何处理
+    /// 启用对冲（ENABLE_HEDGE）时返回 MonitorForExit，由 HedgeMonitor 监测买一价止盈止损；否则不处理
     pub async fn handle_one_sided_fill(
         &self,
         pair: &OrderPair,
         _position_tracker: &PositionTracker,
     ) -> Result<RecoveryAction> {
         // 确定哪个订单成功，哪个失败
-        let (side, filled_amount) =
+        let (side, filled_amount, token_id, opposite_token_id, entry_price) =
             if pair.yes_filled > dec!(0) && pair.no_filled == dec!(0) {
                 // YES成功，NO失败（可能还在挂单）
-                ("YES", pair.yes_filled)
+                ("YES", pair.yes_filled, pair.yes_token_id, pair.no_token_id, pair.yes_price)
             } else if pair.no_filled > dec!(0) && pair.yes_filled == dec!(0) {
                 // NO成功，YES失败（可能还在挂单）
-                ("NO", pair.no_filled)
+                ("NO", pair.no_filled, pair.no_token_id, pair.yes_token_id, pair.no_price)
             } else {
                 return Ok(RecoveryAction::None);
             };
 
-        // 对冲策略已关闭，单边成交不做任何处理（详情由 executor 的 ⚠️ 单边成交 已记录）
-        debug!(
-            "单边成交 | {} 成交 {} 份 | 对冲已关，不处理",
-            side, filled_amount
-        );
+        if !self.enable_hedge {
+            // 对冲未启用，单边成交不做任何处理（详情由 executor 的 ⚠️ 单边成交 已记录）
+            debug!(
+                "单边成交 | {} 成交 {} 份 | 对冲未启用，不处理",
+                side, filled_amount
+            );
+            return Ok(RecoveryAction::None);
+        }
 
-        // 返回None，不做任何对冲处理
-        Ok(RecoveryAction::None)
-        
-        // 旧代码：对冲策略：监测买一价，达到止盈止损时卖出
-        // // 确定对立边的token_id
-        // let success_token = if pair.yes_filled > dec!(0) {
-        //     pair.yes_token_id
-        // } else {
-        //     pair.no_token_id
-        // };
-        // let opposite_token = if success_token == pair.yes_token_id {
-        //     pair.no_token_id
-        // } else {
-        //     pair.yes_token_id
-        // };
-        // 
-        // Ok(RecoveryAction::MonitorForExit {
-        //     token_id: success_token,
-        //     opposite_token_id: opposite_token,
-        //     amount: filled_amount,
-        //     entry_price: dec!(0), // 占位符，需要在主程序中从订单簿获取
-        //     take_profit_pct: self.take_profit_pct,
-        //     stop_loss_pct: self.stop_loss_pct,
-        //     pair_id: pair.pair_id.clone(),
-        //     market_display: "未知市场".to_string(), // 占位符，需要在主程序中从市场信息获取
-        // })
+        // 没有买入价无法计算止盈止损价格（例如旧版本持久化的订单对）
+        if entry_price <= dec!(0) {
+            debug!(
+                pair_id = %pair.pair_id,
+                "单边成交 | {} 成交 {} 份 | 缺少买入价，无法对冲",
+                side, filled_amount
+            );
+            return Ok(RecoveryAction::None);
+        }
+
+        Ok(RecoveryAction::MonitorForExit {
+            token_id,
+            opposite_token_id,
+            amount: filled_amount,
+            entry_price,
+            take_profit_pct: self.take_profit_pct,
+            stop_loss_pct: self.stop_loss_pct,
+            trailing_pct: self.trailing_pct,
+            pair_id: pair.pair_id.clone(),
+            market_display: pair.market_display.clone(),
+        })
     }
 }
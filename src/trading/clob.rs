@@ -0,0 +1,267 @@
+//! CLOB 客户端抽象：交易执行（TradingExecutor）、风控（RiskManager）与仓位平衡（PositionBalancer）
+//! 只通过 ClobClient 访问 CLOB 的下单 / 查单 / 撤单接口。生产环境的实现为 SDK 的已认证客户端（AuthClient），
+//! 测试时可注入模拟实现，无需访问线上 API。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::auth::state::Authenticated;
+use polymarket_client_sdk::auth::Normal;
+use polymarket_client_sdk::clob::types::request::OrdersRequest;
+use polymarket_client_sdk::clob::types::response::{
+    ApiKeysResponse, CancelOrdersResponse, OpenOrderResponse, Page, PostOrderResponse,
+};
+use polymarket_client_sdk::clob::types::{OrderType, Side, SignableOrder, SignedOrder};
+use polymarket_client_sdk::clob::Client;
+use polymarket_client_sdk::types::{Decimal, U256};
+use std::future::Future;
+
+use super::signer::BotSigner;
+
+/// SDK 的已认证 CLOB 客户端（生产环境使用的 ClobClient 实现）
+pub type AuthClient = Client<Authenticated<Normal>>;
+
+/// 限价单参数（对应 SDK limit_order 构建器上用到的字段）
+#[derive(Debug, Clone)]
+pub struct LimitOrderArgs {
+    pub token_id: U256,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub order_type: OrderType,
+    pub post_only: bool,
+    /// 过期时间，仅 GTD 订单设置（SDK 规定非 GTD 不可设过期）
+    pub expiration: Option<DateTime<Utc>>,
+}
+
+impl LimitOrderArgs {
+    /// 非 post-only、不设过期时间的限价单
+    pub fn new(token_id: U256, side: Side, price: Decimal, size: Decimal, order_type: OrderType) -> Self {
+        Self {
+            token_id,
+            side,
+            price,
+            size,
+            order_type,
+            post_only: false,
+            expiration: None,
+        }
+    }
+}
+
+/// CLOB 下单 / 查单 / 撤单接口
+pub trait ClobClient: Clone + Send + Sync + 'static {
+    /// 构建待签名的限价单（SDK 会按市场查询 tick size、neg risk 与费率）
+    fn limit_order(&self, args: LimitOrderArgs) -> impl Future<Output = Result<SignableOrder>> + Send;
+    /// 签名订单
+    fn sign(&self, signer: &BotSigner, order: SignableOrder) -> impl Future<Output = Result<SignedOrder>> + Send;
+    /// 提交单个订单
+    fn post_order(&self, order: SignedOrder) -> impl Future<Output = Result<PostOrderResponse>> + Send;
+    /// 批量提交订单，结果与提交顺序一致
+    fn post_orders(&self, orders: Vec<SignedOrder>) -> impl Future<Output = Result<Vec<PostOrderResponse>>> + Send;
+    /// 查询单个订单
+    fn order(&self, order_id: &str) -> impl Future<Output = Result<OpenOrderResponse>> + Send;
+    /// 分页查询挂单，next_cursor 为 None 时从第一页开始
+    fn orders(
+        &self,
+        request: &OrdersRequest,
+        next_cursor: Option<String>,
+    ) -> impl Future<Output = Result<Page<OpenOrderResponse>>> + Send;
+    /// 撤销单个订单
+    fn cancel_order(&self, order_id: &str) -> impl Future<Output = Result<CancelOrdersResponse>> + Send;
+    /// 批量撤单
+    fn cancel_orders(&self, order_ids: &[&str]) -> impl Future<Output = Result<CancelOrdersResponse>> + Send;
+    /// 查询 API key（用于验证认证是否成功）
+    fn api_keys(&self) -> impl Future<Output = Result<ApiKeysResponse>> + Send;
+}
+
+impl ClobClient for AuthClient {
+    async fn limit_order(&self, args: LimitOrderArgs) -> Result<SignableOrder> {
+        let builder = Client::limit_order(self)
+            .token_id(args.token_id)
+            .side(args.side)
+            .price(args.price)
+            .size(args.size)
+            .order_type(args.order_type)
+            .post_only(args.post_only);
+        let builder = match args.expiration {
+            Some(expiration) => builder.expiration(expiration),
+            None => builder,
+        };
+        Ok(builder.build().await?)
+    }
+
+    async fn sign(&self, signer: &BotSigner, order: SignableOrder) -> Result<SignedOrder> {
+        Ok(Client::sign(self, signer, order).await?)
+    }
+
+    async fn post_order(&self, order: SignedOrder) -> Result<PostOrderResponse> {
+        Ok(Client::post_order(self, order).await?)
+    }
+
+    async fn post_orders(&self, orders: Vec<SignedOrder>) -> Result<Vec<PostOrderResponse>> {
+        Ok(Client::post_orders(self, orders).await?)
+    }
+
+    async fn order(&self, order_id: &str) -> Result<OpenOrderResponse> {
+        Ok(Client::order(self, order_id).await?)
+    }
+
+    async fn orders(&self, request: &OrdersRequest, next_cursor: Option<String>) -> Result<Page<OpenOrderResponse>> {
+        Ok(Client::orders(self, request, next_cursor).await?)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<CancelOrdersResponse> {
+        Ok(Client::cancel_order(self, order_id).await?)
+    }
+
+    async fn cancel_orders(&self, order_ids: &[&str]) -> Result<CancelOrdersResponse> {
+        Ok(Client::cancel_orders(self, order_ids).await?)
+    }
+
+    async fn api_keys(&self) -> Result<ApiKeysResponse> {
+        Ok(Client::api_keys(self).await?)
+    }
+}
+
+/// 测试用的模拟 CLOB 客户端：按脚本返回挂单、下单与查单结果，并记录撤单请求
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use alloy::primitives::{Address, Signature, B256};
+    use anyhow::anyhow;
+    use polymarket_client_sdk::clob::types::{Order, OrderStatusType};
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct State {
+        open_orders: Vec<OpenOrderResponse>,
+        order_responses: HashMap<String, OpenOrderResponse>,
+        post_responses: VecDeque<PostOrderResponse>,
+        canceled: Vec<String>,
+    }
+
+    #[derive(Clone, Default)]
+    pub struct MockClobClient {
+        state: Arc<Mutex<State>>,
+    }
+
+    impl MockClobClient {
+        /// orders() 返回的挂单（单页）
+        pub fn set_open_orders(&self, orders: Vec<OpenOrderResponse>) {
+            self.state.lock().unwrap().open_orders = orders;
+        }
+
+        /// order(order_id) 返回的订单状态
+        pub fn set_order(&self, order: OpenOrderResponse) {
+            self.state.lock().unwrap().order_responses.insert(order.id.clone(), order);
+        }
+
+        /// 依次作为 post_order / post_orders 的结果返回
+        pub fn push_post_response(&self, resp: PostOrderResponse) {
+            self.state.lock().unwrap().post_responses.push_back(resp);
+        }
+
+        /// 已请求撤销的订单 ID（按调用顺序）
+        pub fn canceled(&self) -> Vec<String> {
+            self.state.lock().unwrap().canceled.clone()
+        }
+
+        fn next_post_response(&self) -> Result<PostOrderResponse> {
+            self.state
+                .lock()
+                .unwrap()
+                .post_responses
+                .pop_front()
+                .ok_or_else(|| anyhow!("模拟客户端没有预设的下单结果"))
+        }
+    }
+
+    /// 构造一个挂单（original_size 为下单量，size_matched 为已成交量）
+    pub fn open_order(
+        id: &str,
+        asset_id: U256,
+        status: OrderStatusType,
+        price: Decimal,
+        original_size: Decimal,
+        size_matched: Decimal,
+    ) -> OpenOrderResponse {
+        OpenOrderResponse::builder()
+            .id(id)
+            .status(status)
+            .owner(uuid::Uuid::nil())
+            .maker_address(Address::ZERO)
+            .market(B256::ZERO)
+            .asset_id(asset_id)
+            .side(Side::Buy)
+            .original_size(original_size)
+            .size_matched(size_matched)
+            .price(price)
+            .associate_trades(Vec::new())
+            .outcome("Up")
+            .created_at(Utc::now())
+            .expiration(Utc::now())
+            .order_type(OrderType::GTC)
+            .build()
+    }
+
+    impl ClobClient for MockClobClient {
+        async fn limit_order(&self, args: LimitOrderArgs) -> Result<SignableOrder> {
+            let mut order = Order::default();
+            order.tokenId = args.token_id;
+            Ok(SignableOrder::builder()
+                .order(order)
+                .order_type(args.order_type)
+                .post_only(args.post_only)
+                .build())
+        }
+
+        async fn sign(&self, _signer: &BotSigner, order: SignableOrder) -> Result<SignedOrder> {
+            Ok(SignedOrder::builder()
+                .order(order.order)
+                .signature(Signature::new(U256::ZERO, U256::ZERO, false))
+                .order_type(order.order_type)
+                .owner(uuid::Uuid::nil())
+                .maybe_post_only(order.post_only)
+                .build())
+        }
+
+        async fn post_order(&self, _order: SignedOrder) -> Result<PostOrderResponse> {
+            self.next_post_response()
+        }
+
+        async fn post_orders(&self, orders: Vec<SignedOrder>) -> Result<Vec<PostOrderResponse>> {
+            orders.iter().map(|_| self.next_post_response()).collect()
+        }
+
+        async fn order(&self, order_id: &str) -> Result<OpenOrderResponse> {
+            self.state
+                .lock()
+                .unwrap()
+                .order_responses
+                .get(order_id)
+                .cloned()
+                .ok_or_else(|| anyhow!("订单 {} 不存在", order_id))
+        }
+
+        async fn orders(&self, _request: &OrdersRequest, _next_cursor: Option<String>) -> Result<Page<OpenOrderResponse>> {
+            let data = self.state.lock().unwrap().open_orders.clone();
+            let count = data.len() as u64;
+            Ok(Page::builder().data(data).next_cursor("LTE=").limit(count).count(count).build())
+        }
+
+        async fn cancel_order(&self, order_id: &str) -> Result<CancelOrdersResponse> {
+            self.cancel_orders(&[order_id]).await
+        }
+
+        async fn cancel_orders(&self, order_ids: &[&str]) -> Result<CancelOrdersResponse> {
+            let ids: Vec<String> = order_ids.iter().map(|id| id.to_string()).collect();
+            self.state.lock().unwrap().canceled.extend(ids.iter().cloned());
+            Ok(CancelOrdersResponse::builder().canceled(ids).build())
+        }
+
+        async fn api_keys(&self) -> Result<ApiKeysResponse> {
+            Err(anyhow!("模拟客户端不支持 api_keys"))
+        }
+    }
+}
@@ -1,23 +1,33 @@
 use anyhow::Result;
-use alloy::signers::Signer;
-use alloy::signers::local::LocalSigner;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use polymarket_client_sdk::clob::{Client, Config};
+use polymarket_client_sdk::clob::types::request::OrdersRequest;
+use polymarket_client_sdk::clob::types::response::PostOrderResponse;
 use polymarket_client_sdk::clob::types::{OrderType, Side, SignatureType};
 use polymarket_client_sdk::types::{Address, Decimal, U256};
-use polymarket_client_sdk::POLYGON;
 use rust_decimal_macros::dec;
+use sha2::{Digest, Sha256};
+use std::fmt;
 use std::str::FromStr;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::monitor::arbitrage::ArbitrageOpportunity;
+use crate::monitor::arbitrage::{ArbitrageOpportunity, SellArbitrageOpportunity};
+use super::clob::{AuthClient, ClobClient, LimitOrderArgs};
+use super::orders::{cancel_in_batches, round_to_tick, CancelSummary, SizePrecision};
+use super::signer::BotSigner;
+use crate::utils::errors::ArbitrageError;
 
+#[derive(Clone)]
 pub struct OrderPairResult {
     pub pair_id: String,
     pub yes_order_id: String,
     pub no_order_id: String,
+    /// 客户端订单 ID（由 pair_id + 腿确定性派生，见 client_order_id）
+    pub yes_client_order_id: String,
+    pub no_client_order_id: String,
     pub yes_filled: Decimal,
     pub no_filled: Decimal,
     pub yes_size: Decimal,
@@ -25,29 +35,137 @@ pub struct OrderPairResult {
     pub success: bool,
 }
 
-pub struct TradingExecutor {
-    client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
-    private_key: String,
+/// ATOMIC_PAIR 平仓结果：两腿剩余挂单已撤销，多出的一腿已按买一价卖出
+#[derive(Clone)]
+pub struct PairUnwind {
+    /// 撤单后按订单实际成交数更新的成交汇总（不扣除卖出份额）
+    pub result: OrderPairResult,
+    /// 卖出的 token（成交较多的一腿）
+    pub token_id: U256,
+    /// 实际卖出份额
+    pub sold: Decimal,
+    /// 卖出所得 USDC
+    pub proceeds: Decimal,
+}
+
+/// 交易执行器：经 ClobClient 下单，生产环境为 SDK 的已认证客户端，测试时可经 with_client 注入模拟实现
+pub struct TradingExecutor<C: ClobClient = AuthClient> {
+    client: C,
+    signer: BotSigner, // 已绑定 Polygon 链 ID，见 signer::build_signer
     max_order_size: Decimal,
-    slippage: [Decimal; 2], // [first, second]，仅下降侧用 second，上涨与持平用 first
+    slippage: [Decimal; 2], // [first, second]，按 slippage_mode 分配到两腿
+    slippage_mode: SlippageMode,
+    server_time_offset: chrono::Duration, // CLOB 服务器时间 - 本地时间，计算 GTD 过期时间时加上
     gtd_expiration_secs: u64,
     arbitrage_order_type: OrderType,
+    dry_run: bool, // 模拟交易：只记录将要提交的订单，不调用下单/撤单接口
+    cancel_batch_size: usize, // 分批撤单每批的订单数
+    cancel_max_retries: u32,  // 撤单批次失败后的最多重试次数
+    price_tick: Decimal, // 最小价格单位，下单价格按方向取整到该单位
+    post_only: bool, // 套利订单以 post-only 挂单（maker）提交，按机会中的挂价下单、不加滑点
+    size_precision: Arc<SizePrecision>, // 按市场的数量精度，下单数量向下截断到该精度
+}
+
+/// 限价允许范围：加滑点后的价格会被截断到 [MIN_LIMIT_PRICE, MAX_LIMIT_PRICE]
+const MIN_LIMIT_PRICE: Decimal = dec!(0.01);
+const MAX_LIMIT_PRICE: Decimal = dec!(0.99);
+
+/// 将加滑点后的限价截断到有效价格区间，发生截断时输出告警
+fn clamp_limit_price(side: &str, price: Decimal) -> Decimal {
+    let clamped = price.clamp(MIN_LIMIT_PRICE, MAX_LIMIT_PRICE);
+    if clamped != price {
+        warn!(
+            "⚠️ 限价超出有效范围，已截断 | {} | {:.4} → {:.4}（允许 {}~{}）",
+            side, price, clamped, MIN_LIMIT_PRICE, MAX_LIMIT_PRICE
+        );
+    }
+    clamped
+}
+
+/// 从下单响应中按订单方向取出本次成交的 (份额, USDC)。
+/// making_amount / taking_amount 分别为下单方付出 / 收到的资产：买单付出 USDC、收到份额，卖单相反。
+/// 价格不超过 1，成交份额不会少于对应的 USDC；若份额一侧小于 USDC 一侧，说明两字段单位与预期相反，
+/// 按份额取较大者并告警，避免把 USDC 金额当作份额计入持仓。
+pub fn filled_amounts(resp: &PostOrderResponse, side: Side) -> (Decimal, Decimal) {
+    let (shares, usdc) = match side {
+        Side::Buy => (resp.taking_amount, resp.making_amount),
+        _ => (resp.making_amount, resp.taking_amount),
+    };
+    if shares < usdc {
+        warn!(
+            order_id = %resp.order_id,
+            side = ?side,
+            making_amount = %resp.making_amount,
+            taking_amount = %resp.taking_amount,
+            "⚠️ 下单响应的成交数量单位异常（份额小于 USDC），已按份额较大者计入"
+        );
+        return (usdc, shares);
+    }
+    (shares, usdc)
+}
+
+/// 滑点分配方式（SLIPPAGE_MODE）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlippageMode {
+    /// 按涨跌方向：仅下降(↓)用 second，上涨(↑)和持平(−/空)用 first
+    Directional,
+    /// 不看方向，两腿都用 max(first, second)
+    Symmetric,
+}
+
+impl FromStr for SlippageMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "directional" => Ok(Self::Directional),
+            "symmetric" => Ok(Self::Symmetric),
+            other => Err(format!("未知的滑点模式: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for SlippageMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Directional => write!(f, "directional"),
+            Self::Symmetric => write!(f, "symmetric"),
+        }
+    }
+}
+
+/// 按涨跌方向（"↑" "↓" "−" 或 ""）与滑点模式取一腿的滑点，slippage 为 [first, second]
+pub fn slippage_for(direction: &str, mode: SlippageMode, slippage: [Decimal; 2]) -> Decimal {
+    match mode {
+        SlippageMode::Directional if direction == "↓" => slippage[1],
+        SlippageMode::Directional => slippage[0],
+        SlippageMode::Symmetric => slippage[0].max(slippage[1]),
+    }
+}
+
+/// 由订单对 ID 与腿（"YES"/"NO"）确定性派生客户端订单 ID，同时作为订单 salt 签名，
+/// 便于由订单（salt）反查所属订单对与腿。截断到 53 位，保证 salt 作为 JSON 数字时不丢精度
+fn client_order_id(pair_id: &str, leg: &str) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(pair_id.as_bytes())
+        .chain_update(b":")
+        .chain_update(leg.as_bytes())
+        .finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes) & ((1u64 << 53) - 1)
 }
 
 impl TradingExecutor {
     pub async fn new(
-        private_key: String,
-        max_order_size_usdc: f64,
+        signer: BotSigner,
+        max_order_size: Decimal,
         proxy_address: Option<Address>,
-        slippage: [f64; 2],
+        slippage: [Decimal; 2],
         gtd_expiration_secs: u64,
         arbitrage_order_type: OrderType,
+        dry_run: bool,
     ) -> Result<Self> {
-        // 验证私钥格式
-        let signer = LocalSigner::from_str(&private_key)
-            .map_err(|e| anyhow::anyhow!("私钥格式无效: {}. 请确保私钥是64字符的十六进制字符串（不带0x前缀）", e))?
-            .with_chain_id(Some(POLYGON));
-
         let config = Config::builder().use_server_time(false).build();
         let mut auth_builder = Client::new("https://clob.polymarket.com", config)
             .map_err(|e| anyhow::anyhow!("创建CLOB客户端失败: {}", e))?
@@ -70,18 +188,133 @@ impl TradingExecutor {
                 )
             })?;
 
-        Ok(Self {
+        let server_time_offset = measure_server_time_offset(&client).await;
+
+        Ok(Self::with_client(
             client,
-            private_key,
-            max_order_size: Decimal::try_from(max_order_size_usdc)
-                .unwrap_or(rust_decimal_macros::dec!(100.0)),
-            slippage: [
-                Decimal::try_from(slippage[0]).unwrap_or(dec!(0.0)),
-                Decimal::try_from(slippage[1]).unwrap_or(dec!(0.01)),
-            ],
+            signer,
+            max_order_size,
+            slippage,
             gtd_expiration_secs,
             arbitrage_order_type,
-        })
+            dry_run,
+        )
+        .with_server_time_offset(server_time_offset))
+    }
+}
+
+/// 本地时钟与 CLOB 服务器时间的偏差超过此值时告警（GTD 过期时间可能被拒或提前过期）
+const MAX_CLOCK_SKEW: chrono::Duration = chrono::Duration::seconds(2);
+
+/// 测量 CLOB 服务器时间相对本地时钟的偏移（服务器 - 本地），以请求往返的中点作为本地参考时间。
+/// 查询失败时返回 0（按本地时钟计算 GTD 过期时间）
+async fn measure_server_time_offset(client: &AuthClient) -> chrono::Duration {
+    let sent = Utc::now();
+    let server = match client.server_time().await {
+        Ok(ts) => ts,
+        Err(e) => {
+            warn!(error = %e, "⚠️ 获取 CLOB 服务器时间失败，GTD 过期时间按本地时钟计算");
+            return chrono::Duration::zero();
+        }
+    };
+    let received = Utc::now();
+    // /time 返回 Unix 秒；按量级兼容毫秒
+    let server_ms = if server > 100_000_000_000 { server } else { server * 1000 };
+    let local_ms = sent.timestamp_millis() + (received - sent).num_milliseconds() / 2;
+    let offset = chrono::Duration::milliseconds(server_ms - local_ms);
+    if offset.abs() > MAX_CLOCK_SKEW {
+        warn!(
+            skew_ms = offset.num_milliseconds(),
+            rtt_ms = (received - sent).num_milliseconds(),
+            "⚠️ 本地时钟与 CLOB 服务器时间偏差 {} 毫秒（超过 {} 秒），GTD 过期时间已按服务器时间校正，请检查系统时间同步",
+            offset.num_milliseconds(),
+            MAX_CLOCK_SKEW.num_seconds()
+        );
+    } else {
+        info!(
+            skew_ms = offset.num_milliseconds(),
+            rtt_ms = (received - sent).num_milliseconds(),
+            "🕒 本地时钟与 CLOB 服务器时间偏差 {} 毫秒",
+            offset.num_milliseconds()
+        );
+    }
+    offset
+}
+
+impl<C: ClobClient> TradingExecutor<C> {
+    /// 以已有的 CLOB 客户端创建执行器（new 认证后经此创建；测试时可传入模拟客户端）
+    pub fn with_client(
+        client: C,
+        signer: BotSigner,
+        max_order_size: Decimal,
+        slippage: [Decimal; 2],
+        gtd_expiration_secs: u64,
+        arbitrage_order_type: OrderType,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            client,
+            signer,
+            max_order_size,
+            slippage,
+            gtd_expiration_secs,
+            arbitrage_order_type,
+            dry_run,
+            cancel_batch_size: 50,
+            cancel_max_retries: 2,
+            price_tick: dec!(0.01),
+            post_only: false,
+            slippage_mode: SlippageMode::Directional,
+            server_time_offset: chrono::Duration::zero(),
+            size_precision: Arc::new(SizePrecision::default()),
+        }
+    }
+
+    /// 设置分批撤单参数：每批订单数与失败批次的最多重试次数
+    pub fn with_cancel_batching(mut self, batch_size: usize, max_retries: u32) -> Self {
+        self.cancel_batch_size = batch_size;
+        self.cancel_max_retries = max_retries;
+        self
+    }
+
+    /// 设置最小价格单位（PRICE_TICK），下单价格按买入向上、卖出向下取整到该单位
+    pub fn with_price_tick(mut self, tick: Decimal) -> Self {
+        self.price_tick = tick;
+        self
+    }
+
+    /// 设置按市场的数量精度（SIZE_DECIMALS 及按币种覆盖），与主循环、对冲监控共享
+    pub fn with_size_precision(mut self, size_precision: Arc<SizePrecision>) -> Self {
+        self.size_precision = size_precision;
+        self
+    }
+
+    /// 按市场的数量精度（收尾卖出等外部下单路径共用）
+    pub fn size_precision(&self) -> &SizePrecision {
+        &self.size_precision
+    }
+
+    /// 套利订单以 post-only 挂单提交（须为 GTC / GTD），会与卖单成交时交易所直接拒绝
+    pub fn with_post_only(mut self, post_only: bool) -> Self {
+        self.post_only = post_only;
+        self
+    }
+
+    /// 设置滑点分配方式（SLIPPAGE_MODE），默认按涨跌方向
+    pub fn with_slippage_mode(mut self, mode: SlippageMode) -> Self {
+        self.slippage_mode = mode;
+        self
+    }
+
+    /// 设置服务器时间偏移（服务器 - 本地），GTD 过期时间按服务器时间计算
+    pub fn with_server_time_offset(mut self, offset: chrono::Duration) -> Self {
+        self.server_time_offset = offset;
+        self
+    }
+
+    /// 是否为模拟交易模式
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
     }
 
     /// 验证认证是否真的成功 - 按照官方示例使用 api_keys() 来验证
@@ -92,12 +325,36 @@ impl TradingExecutor {
         Ok(())
     }
 
-    /// 取消该账户所有挂单（收尾时使用）
-    pub async fn cancel_all_orders(&self) -> Result<polymarket_client_sdk::clob::types::response::CancelOrdersResponse> {
-        self.client
-            .cancel_all_orders()
-            .await
-            .map_err(|e| anyhow::anyhow!("取消所有挂单失败: {}", e))
+    /// 取消该账户所有挂单（收尾时使用）：分页拉取全部挂单后分批撤单，返回撤单汇总。
+    /// 拉取挂单失败时返回错误；个别批次失败记录在汇总中，不中断其余批次
+    pub async fn cancel_all_orders(&self) -> Result<CancelSummary> {
+        if self.dry_run {
+            info!("🧪 [DRY RUN] 将取消所有挂单（未实际调用）");
+            return Ok(CancelSummary::default());
+        }
+        let mut order_ids = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = self
+                .client
+                .orders(&OrdersRequest::default(), cursor)
+                .await
+                .map_err(|e| anyhow::anyhow!("获取挂单失败: {}", e))?;
+            order_ids.extend(page.data.into_iter().map(|o| o.id));
+            if page.next_cursor.is_empty() || page.next_cursor == "LTE=" {
+                break;
+            }
+            cursor = Some(page.next_cursor);
+        }
+        let summary = cancel_in_batches(&order_ids, self.cancel_batch_size, self.cancel_max_retries, |ids| async move {
+            let refs: Vec<&str> = ids.iter().map(|s| s.as_str()).collect();
+            self.client
+                .cancel_orders(&refs)
+                .await
+                .map_err(|e| anyhow::anyhow!("{}", e))
+        })
+        .await;
+        Ok(summary)
     }
 
     /// 以指定价格下 GTC 卖单（收尾时市价意图卖出单腿持仓）
@@ -107,41 +364,439 @@ impl TradingExecutor {
         price: Decimal,
         size: Decimal,
     ) -> Result<polymarket_client_sdk::clob::types::response::PostOrderResponse> {
-        let signer = LocalSigner::from_str(&self.private_key)?
-            .with_chain_id(Some(POLYGON));
-        let order = self
-            .client
-            .limit_order()
-            .token_id(token_id)
-            .side(Side::Sell)
-            .price(price)
-            .size(size)
-            .order_type(OrderType::GTC)
-            .build()
-            .await?;
-        let signed = self.client.sign(&signer, order).await?;
+        let price = round_to_tick(price, self.price_tick, Side::Sell);
+        if self.dry_run {
+            info!(
+                "🧪 [DRY RUN] 将下卖单 | token_id={:#x} | 价格:{:.4} | 数量:{} | GTC",
+                token_id, price, size
+            );
+            return Ok(polymarket_client_sdk::clob::types::response::PostOrderResponse::builder()
+                .making_amount(size)
+                .taking_amount(price * size)
+                .order_id(format!("dry-run-{}", Uuid::new_v4()))
+                .status(polymarket_client_sdk::clob::types::OrderStatusType::Matched)
+                .success(true)
+                .build());
+        }
+        let order = self.client.limit_order(LimitOrderArgs::new(token_id, Side::Sell, price, size, OrderType::GTC)).await?;
+        let signed = self.client.sign(&self.signer, order).await?;
         self.client
             .post_order(signed)
             .await
             .map_err(|e| anyhow::anyhow!("卖出订单提交失败: {}", e))
     }
 
-    /// 按方向取滑点：仅下降(↓)用 second，上涨(↑)和持平(−/空)用 first
-    fn slippage_for_direction(&self, dir: &str) -> Decimal {
-        if dir == "↓" {
-            self.slippage[1]
+    /// 以 FAK 卖出，返回 (卖出份额, 所得 USDC)
+    async fn sell_fak(&self, token_id: U256, price: Decimal, size: Decimal) -> Result<(Decimal, Decimal)> {
+        let price = round_to_tick(price, self.price_tick, Side::Sell);
+        if self.dry_run {
+            info!(
+                "🧪 [DRY RUN] 将下卖单 | token_id={:#x} | 价格:{:.4} | 数量:{} | FAK",
+                token_id, price, size
+            );
+            return Ok((size, price * size));
+        }
+        let order = self.client.limit_order(LimitOrderArgs::new(token_id, Side::Sell, price, size, OrderType::FAK)).await?;
+        let signed = self.client.sign(&self.signer, order).await?;
+        let resp = self
+            .client
+            .post_order(signed)
+            .await
+            .map_err(|e| anyhow::anyhow!("卖出订单提交失败: {}", e))?;
+        Ok(filled_amounts(&resp, Side::Sell))
+    }
+
+    /// 带重定价重试的卖出（收尾时尽量卖平单腿）：每次以 FAK 提交，未卖完的部分在下一次尝试时
+    /// 按最新买一价（best_bid 返回 None 时沿用上次的基准价）再让价 price_step × 已重试次数重新提交，
+    /// 直到卖完或用尽 max_attempts 次；价格不低于 MIN_LIMIT_PRICE。
+    /// 返回 (实际卖出份额, 所得 USDC)；一份都未卖出且提交均失败时返回最后一次的错误。
+    pub async fn sell_with_reprice(
+        &self,
+        token_id: U256,
+        price: Decimal,
+        size: Decimal,
+        max_attempts: u32,
+        price_step: Decimal,
+        best_bid: impl Fn() -> Option<Decimal>,
+    ) -> Result<(Decimal, Decimal)> {
+        /// 两次卖出之间的等待，给订单簿刷新留时间
+        const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+        let mut sold = dec!(0);
+        let mut proceeds = dec!(0);
+        let mut base_price = price;
+        let mut last_err = None;
+        for attempt in 1..=max_attempts {
+            let remaining = self.size_precision.round(token_id, size - sold);
+            if remaining < self.size_precision.min_size(token_id) {
+                break;
+            }
+            if attempt > 1 {
+                if let Some(bid) = best_bid() {
+                    base_price = bid;
+                }
+            }
+            let attempt_price = (base_price - price_step * Decimal::from(attempt - 1)).max(MIN_LIMIT_PRICE);
+            info!(
+                "🔁 收尾卖出 | token_id={:#x} | {:.4}×{} | FAK ({}/{})",
+                token_id, attempt_price, remaining, attempt, max_attempts
+            );
+            match self.sell_fak(token_id, attempt_price, remaining).await {
+                Ok((filled, usdc)) => {
+                    sold += filled;
+                    proceeds += usdc;
+                }
+                Err(e) => {
+                    warn!(error = %e, "❌ 收尾卖出失败 | token_id={:#x} ({}/{})", token_id, attempt, max_attempts);
+                    last_err = Some(e);
+                }
+            }
+            if sold >= size {
+                break;
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+
+        match last_err {
+            Some(e) if sold == dec!(0) => Err(e),
+            _ => Ok((sold, proceeds)),
+        }
+    }
+
+    /// 卖出侧套利：以买一价同时卖出 YES 与 NO（FAK，未成交部分立即取消，不留挂单）。
+    /// size 须已按实际持仓截断。返回的 yes_filled / no_filled 为实际卖出份额。
+    /// pair_id 由调用方生成，便于把同一订单对的日志挂在同一 span 下。
+    pub async fn execute_sell_pair(
+        &self,
+        opp: &SellArbitrageOpportunity,
+        size: Decimal,
+        pair_id: String,
+    ) -> Result<OrderPairResult> {
+        info!(
+            market_id = %opp.market_id,
+            "📤 卖出下单 | {} | YES {:.4}×{} NO {:.4}×{} | FAK",
+            &pair_id[..8], opp.yes_bid_price, size, opp.no_bid_price, size
+        );
+        let (yes_client_id, no_client_id) = (client_order_id(&pair_id, "YES"), client_order_id(&pair_id, "NO"));
+
+        if self.dry_run {
+            info!(
+                "🧪 [DRY RUN] 将提交卖出套利订单 | {} | YES {:.4}×{} NO {:.4}×{}",
+                &pair_id[..8], opp.yes_bid_price, size, opp.no_bid_price, size
+            );
+            return Ok(OrderPairResult {
+                pair_id,
+                yes_order_id: "dry-run-yes".to_string(),
+                no_order_id: "dry-run-no".to_string(),
+                yes_client_order_id: yes_client_id.to_string(),
+                no_client_order_id: no_client_id.to_string(),
+                yes_filled: size,
+                no_filled: size,
+                yes_size: size,
+                no_size: size,
+                success: true,
+            });
+        }
+
+        let (yes_order, no_order) = tokio::join!(
+            self.client.limit_order(LimitOrderArgs::new(opp.yes_token_id, Side::Sell, opp.yes_bid_price, size, OrderType::FAK)),
+            self.client.limit_order(LimitOrderArgs::new(opp.no_token_id, Side::Sell, opp.no_bid_price, size, OrderType::FAK))
+        );
+        let (mut yes_order, mut no_order) = (yes_order?, no_order?);
+        yes_order.order.salt = U256::from(yes_client_id);
+        no_order.order.salt = U256::from(no_client_id);
+        let (signed_yes, signed_no) = tokio::join!(
+            self.client.sign(&self.signer, yes_order),
+            self.client.sign(&self.signer, no_order)
+        );
+        let results = self
+            .client
+            .post_orders(vec![signed_yes?, signed_no?])
+            .await
+            .map_err(|e| anyhow::anyhow!("卖出侧批量下单失败: {}", e))?;
+        if results.len() != 2 {
+            return Err(anyhow::anyhow!(
+                "卖出侧批量下单返回结果数量不正确 | 期望:2 | 实际:{}",
+                results.len()
+            ));
+        }
+
+        let (yes_result, no_result) = (&results[0], &results[1]);
+        let (yes_filled, _) = filled_amounts(yes_result, Side::Sell);
+        let (no_filled, _) = filled_amounts(no_result, Side::Sell);
+        if yes_filled == no_filled && yes_filled > dec!(0) {
+            info!("✅ 卖出套利成功 | {} | 双边各卖出 {} 份", &pair_id[..8], yes_filled);
+        } else {
+            warn!(
+                "⚠️ 卖出套利成交不一致 | {} | YES卖出:{} NO卖出:{} | YES错误:{} NO错误:{}",
+                &pair_id[..8],
+                yes_filled,
+                no_filled,
+                yes_result.error_msg.as_deref().unwrap_or("-"),
+                no_result.error_msg.as_deref().unwrap_or("-")
+            );
+        }
+
+        Ok(OrderPairResult {
+            pair_id,
+            yes_order_id: yes_result.order_id.clone(),
+            no_order_id: no_result.order_id.clone(),
+            yes_client_order_id: yes_client_id.to_string(),
+            no_client_order_id: no_client_id.to_string(),
+            yes_filled,
+            no_filled,
+            yes_size: size,
+            no_size: size,
+            success: yes_result.success && no_result.success,
+        })
+    }
+
+    /// 以 FAK 限价买入（未成交部分立即取消，不留挂单），返回实际买入份额
+    async fn buy_fak(&self, token_id: U256, price: Decimal, size: Decimal) -> Result<Decimal> {
+        if self.dry_run {
+            info!(
+                "🧪 [DRY RUN] 将下补单 | token_id={:#x} | 价格:{:.4} | 数量:{} | FAK",
+                token_id, price, size
+            );
+            return Ok(size);
+        }
+        let order = self.client.limit_order(LimitOrderArgs::new(token_id, Side::Buy, price, size, OrderType::FAK)).await?;
+        let signed = self.client.sign(&self.signer, order).await?;
+        let resp = self
+            .client
+            .post_order(signed)
+            .await
+            .map_err(|e| anyhow::anyhow!("补单提交失败: {}", e))?;
+        Ok(filled_amounts(&resp, Side::Buy).0)
+    }
+
+    /// 补齐单边 / 部分成交：先撤掉成交较少一腿的剩余挂单（以撤单后的实际成交数为准），
+    /// 再按最新卖一价加滑点以 FAK 补单，最多 max_attempts 次，直到两腿成交数量一致。
+    /// 补单价不超过原最深档价格 + 最大滑点，数量不超过 max_order_size；
+    /// 每次补单前以 exposure_ok 检查相对原成交价多付的成本是否超出敞口限制。
+    /// latest_ask(is_yes) 返回该腿最新缓存的卖一价。返回更新后的成交汇总。
+    pub async fn complete_pair(
+        &self,
+        opp: &ArbitrageOpportunity,
+        result: &OrderPairResult,
+        max_attempts: u32,
+        latest_ask: impl Fn(bool) -> Option<Decimal>,
+        exposure_ok: impl Fn(Decimal) -> bool,
+    ) -> Result<OrderPairResult> {
+        /// 两次补单之间的等待，给订单簿刷新留时间
+        const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+        let mut updated = result.clone();
+        if updated.yes_filled == updated.no_filled {
+            return Ok(updated);
+        }
+        let is_yes = updated.yes_filled < updated.no_filled;
+        let (side, token_id, order_id, ref_price, worst_price) = if is_yes {
+            ("YES", opp.yes_token_id, result.yes_order_id.as_str(), opp.yes_ask_price, opp.yes_worst_price)
         } else {
-            self.slippage[0]
+            ("NO", opp.no_token_id, result.no_order_id.as_str(), opp.no_ask_price, opp.no_worst_price)
+        };
+        let price_cap = (worst_price + self.slippage[0].max(self.slippage[1])).min(MAX_LIMIT_PRICE);
+        let pair_id = &result.pair_id[..8];
+
+        // 撤掉原订单的剩余挂单（GTC/GTD 未成交部分仍挂在簿上），并以订单的实际成交数为准
+        if !self.dry_run && !order_id.is_empty() {
+            if let Err(e) = self.client.cancel_order(order_id).await {
+                debug!(order_id = %order_id, error = %e, "补单前撤单失败（可能已成交或已过期）");
+            }
+            match self.client.order(order_id).await {
+                Ok(order) => {
+                    let filled = if is_yes { &mut updated.yes_filled } else { &mut updated.no_filled };
+                    *filled = (*filled).max(order.size_matched);
+                }
+                Err(e) => debug!(order_id = %order_id, error = %e, "查询原订单成交数失败，按下单返回值计算"),
+            }
         }
+
+        for attempt in 1..=max_attempts {
+            let (lagging, leading) = if is_yes {
+                (updated.yes_filled, updated.no_filled)
+            } else {
+                (updated.no_filled, updated.yes_filled)
+            };
+            let remaining = self.size_precision.round(token_id, (leading - lagging).min(self.max_order_size));
+            if remaining <= dec!(0) {
+                break;
+            }
+            let Some(ask) = latest_ask(is_yes) else {
+                warn!("⚠️ 补单 | {} | {} 无最新卖一价，等待下次尝试 ({}/{})", pair_id, side, attempt, max_attempts);
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            };
+            if ask > price_cap {
+                warn!(
+                    "⚠️ 补单 | {} | {} 卖一价 {:.4} 超出滑点上限 {:.4}，等待下次尝试 ({}/{})",
+                    pair_id, side, ask, price_cap, attempt, max_attempts
+                );
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+            let price = round_to_tick(clamp_limit_price(side, (ask + self.slippage[0]).min(price_cap)), self.price_tick, Side::Buy);
+            if price * remaining <= dec!(1) {
+                warn!("⏭️ 补单 | {} | {} 金额 {:.2} USD 不足 $1，放弃补单", pair_id, side, price * remaining);
+                break;
+            }
+            let extra_cost = ((price - ref_price) * remaining).max(dec!(0));
+            if !exposure_ok(extra_cost) {
+                warn!("⚠️ 补单 | {} | 追加成本 {:.2} USD 将超出敞口限制，放弃补单", pair_id, extra_cost);
+                break;
+            }
+
+            info!(
+                "🔁 补单 | {} | {} {:.4}×{} | FAK ({}/{})",
+                pair_id, side, price, remaining, attempt, max_attempts
+            );
+            match self.buy_fak(token_id, price, remaining).await {
+                Ok(filled) => {
+                    if is_yes {
+                        updated.yes_filled += filled;
+                    } else {
+                        updated.no_filled += filled;
+                    }
+                    if filled > dec!(0) {
+                        info!("✅ 补单成交 | {} | {} 成交 {} 份", pair_id, side, filled);
+                    }
+                }
+                Err(e) => warn!(error = %e, "❌ 补单失败 | {} | {} ({}/{})", pair_id, side, attempt, max_attempts),
+            }
+            if updated.yes_filled == updated.no_filled {
+                break;
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+
+        if updated.yes_filled == updated.no_filled {
+            info!("✅ 订单对已补齐 | {} | 双边各 {} 份", pair_id, updated.yes_filled);
+        } else {
+            warn!(
+                "⚠️ 订单对未能补齐 | {} | YES:{}份 NO:{}份 | 交由风控处理",
+                pair_id, updated.yes_filled, updated.no_filled
+            );
+        }
+        Ok(updated)
     }
 
-    /// 执行套利交易（使用post_orders批量提交YES和NO订单；订单类型由 arbitrage_order_type 配置，GTD 时配合 gtd_expiration_secs）
-    /// yes_dir / no_dir：涨跌方向 "↑" "↓" "−" 或 ""，用于按方向分配滑点（仅下降=second，上涨与持平=first）
+    /// ATOMIC_PAIR：双腿要么都持有要么都不持有。两腿成交不一致时，在 window 内轮询两腿订单的成交数，
+    /// 期间补齐则直接返回；到期仍不一致时撤掉两腿的剩余挂单，以撤单后的实际成交数为准，
+    /// 把成交较多一腿多出的份额按最新买一价以 FAK 卖出（最多 UNWIND_SELL_ATTEMPTS 次，每次多让一个 tick）。
+    /// 卖出须支付 taker 手续费并承担买卖价差，平仓必然亏损，换取不留单边方向性风险。
+    /// best_bid(token_id) 返回该 token 最新缓存的买一价。等待期内补齐（含模拟交易）时返回 None；
+    /// 已撤单时返回撤单后的成交汇总与卖出结果，卖出失败或无买一价时 sold 为 0
+    pub async fn unwind_unpaired(
+        &self,
+        opp: &ArbitrageOpportunity,
+        result: &OrderPairResult,
+        window: Duration,
+        best_bid: impl Fn(U256) -> Option<Decimal>,
+    ) -> Option<PairUnwind> {
+        /// 轮询两腿成交数的间隔
+        const POLL_INTERVAL: Duration = Duration::from_millis(500);
+        /// 卖出多出份额的最多尝试次数
+        const UNWIND_SELL_ATTEMPTS: u32 = 3;
+
+        let mut updated = result.clone();
+        if updated.yes_filled == updated.no_filled || self.dry_run {
+            return None;
+        }
+        let pair_id = &result.pair_id[..8];
+
+        let deadline = Instant::now() + window;
+        while Instant::now() < deadline {
+            tokio::time::sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))).await;
+            (updated.yes_filled, updated.no_filled) = tokio::join!(
+                self.order_filled(&result.yes_order_id, updated.yes_filled),
+                self.order_filled(&result.no_order_id, updated.no_filled)
+            );
+            if updated.yes_filled == updated.no_filled {
+                info!("✅ 订单对已在等待期内补齐 | {} | 双边各 {} 份", pair_id, updated.yes_filled);
+                return None;
+            }
+        }
+
+        // 撤掉两腿剩余挂单，再以撤单后的实际成交数为准
+        let live: Vec<&str> = [result.yes_order_id.as_str(), result.no_order_id.as_str()]
+            .into_iter().filter(|id| !id.is_empty()).collect();
+        if let Err(e) = self.client.cancel_orders(&live).await {
+            warn!(error = %e, "⚠️ ATOMIC_PAIR 撤单失败 | {}（可能已成交或已过期）", pair_id);
+        }
+        (updated.yes_filled, updated.no_filled) = tokio::join!(
+            self.order_filled(&result.yes_order_id, updated.yes_filled),
+            self.order_filled(&result.no_order_id, updated.no_filled)
+        );
+        if updated.yes_filled == updated.no_filled {
+            info!("✅ 订单对撤单后已一致 | {} | 双边各 {} 份", pair_id, updated.yes_filled);
+            return Some(PairUnwind { result: updated, token_id: opp.yes_token_id, sold: dec!(0), proceeds: dec!(0) });
+        }
+
+        let (side, token_id, excess) = if updated.yes_filled > updated.no_filled {
+            ("YES", opp.yes_token_id, updated.yes_filled - updated.no_filled)
+        } else {
+            ("NO", opp.no_token_id, updated.no_filled - updated.yes_filled)
+        };
+        let excess = self.size_precision.round(token_id, excess);
+        if excess < self.size_precision.min_size(token_id) {
+            return Some(PairUnwind { result: updated, token_id, sold: dec!(0), proceeds: dec!(0) });
+        }
+        let Some(bid) = best_bid(token_id) else {
+            warn!("⚠️ ATOMIC_PAIR | {} | {} 无买一价，无法卖出多出的 {} 份，交由风控处理", pair_id, side, excess);
+            return Some(PairUnwind { result: updated, token_id, sold: dec!(0), proceeds: dec!(0) });
+        };
+        warn!(
+            "↩️ ATOMIC_PAIR 平仓 | {} | YES成交:{}份 NO成交:{}份 | 卖出 {} 多出的 {} 份 @ 买一 {:.4}（含 taker 手续费与价差损耗）",
+            pair_id, updated.yes_filled, updated.no_filled, side, excess, bid
+        );
+        let (sold, proceeds) = match self
+            .sell_with_reprice(token_id, bid, excess, UNWIND_SELL_ATTEMPTS, self.price_tick, || best_bid(token_id))
+            .await
+        {
+            Ok(sold) => sold,
+            Err(e) => {
+                warn!(error = %e, "❌ ATOMIC_PAIR 卖出失败 | {} | {}", pair_id, side);
+                (dec!(0), dec!(0))
+            }
+        };
+        if sold < excess {
+            warn!(
+                "⚠️ ATOMIC_PAIR 未能完全平仓 | {} | {} 卖出 {}/{} 份，剩余交由风控处理",
+                pair_id, side, sold, excess
+            );
+        } else {
+            info!("✅ ATOMIC_PAIR 已平仓 | {} | {} 卖出 {} 份，所得 {:.4} USDC", pair_id, side, sold, proceeds);
+        }
+        Some(PairUnwind { result: updated, token_id, sold, proceeds })
+    }
+
+    /// 查询订单的实际成交数（只增不减：用户频道或下单返回值可能已更新），订单 ID 为空或查询失败时沿用已知值
+    async fn order_filled(&self, order_id: &str, known_filled: Decimal) -> Decimal {
+        if order_id.is_empty() {
+            return known_filled;
+        }
+        match self.client.order(order_id).await {
+            Ok(order) => order.size_matched.max(known_filled),
+            Err(e) => {
+                debug!(order_id = %order_id, error = %e, "查询订单成交数失败，沿用已知成交数");
+                known_filled
+            }
+        }
+    }
+
+    /// 执行套利交易（使用post_orders批量提交YES和NO订单；订单类型由 arbitrage_order_type 配置，GTD 时配合 gtd_expiration_secs）。
+    /// expire_by 为 GTD 订单的最晚过期时间（如当前窗口结束前），过期时间取 min(now + gtd_expiration_secs, expire_by)；
+    /// expire_by 已过时不下单并返回错误。非 GTD 订单忽略该参数。
+    /// yes_dir / no_dir：涨跌方向 "↑" "↓" "−" 或 ""，按 SLIPPAGE_MODE 分配滑点（见 slippage_for）
+    /// pair_id 由调用方生成，便于把同一订单对的日志挂在同一 span 下。
     pub async fn execute_arbitrage_pair(
         &self,
         opp: &ArbitrageOpportunity,
         yes_dir: &str,
         no_dir: &str,
+        pair_id: String,
+        expire_by: Option<DateTime<Utc>>,
     ) -> Result<OrderPairResult> {
         // 性能计时：总开始时间
         let total_start = Instant::now();
@@ -165,19 +820,40 @@ impl TradingExecutor {
         let yes_token_id = U256::from_str(&opp.yes_token_id.to_string())?;
         let no_token_id = U256::from_str(&opp.no_token_id.to_string())?;
 
-        let order_size = opp.yes_size.min(opp.no_size).min(self.max_order_size);
-
-        // 生成订单对ID
-        let pair_id = Uuid::new_v4().to_string();
+        let order_size = self.size_precision.round(yes_token_id, opp.yes_size.min(opp.no_size).min(self.max_order_size));
 
-        // 计算过期时间：当前时间 + 配置的过期时间
-        let expiration = Utc::now() + chrono::Duration::seconds(self.gtd_expiration_secs as i64);
+        // 计算过期时间：当前服务器时间（本地时间 + 启动时测得的偏移）+ 配置的过期时间，不晚于 expire_by
+        let now = Utc::now() + self.server_time_offset;
+        let mut expiration = now + chrono::Duration::seconds(self.gtd_expiration_secs as i64);
+        if let (OrderType::GTD, Some(limit)) = (&self.arbitrage_order_type, expire_by) {
+            if limit <= now {
+                return Err(ArbitrageError::WindowExpired { limit: limit.format("%H:%M:%S").to_string() }.into());
+            }
+            expiration = expiration.min(limit);
+        }
 
-        // 滑点按涨跌方向分配：上涨=first，下降/持平=second
-        let yes_slippage_apply = self.slippage_for_direction(yes_dir);
-        let no_slippage_apply = self.slippage_for_direction(no_dir);
-        let yes_price_with_slippage = (opp.yes_ask_price + yes_slippage_apply).min(dec!(1.0));
-        let no_price_with_slippage = (opp.no_ask_price + no_slippage_apply).min(dec!(1.0));
+        // 滑点按 SLIPPAGE_MODE 分配（见 slippage_for）
+        // 限价以吃到的最深一档为基准（多档聚合时 VWAP 低于最深档，按 VWAP 下单无法吃满）
+        // 挂单模式：直接以机会中的挂价（买一 + 一个 tick）下单，不加滑点，向下取整避免触及卖一
+        let (yes_slippage_apply, no_slippage_apply, limit_rounding) = if self.post_only {
+            (dec!(0), dec!(0), Side::Sell)
+        } else {
+            (
+                slippage_for(yes_dir, self.slippage_mode, self.slippage),
+                slippage_for(no_dir, self.slippage_mode, self.slippage),
+                Side::Buy,
+            )
+        };
+        let yes_price_with_slippage = round_to_tick(
+            clamp_limit_price("YES", opp.yes_worst_price + yes_slippage_apply),
+            self.price_tick,
+            limit_rounding,
+        );
+        let no_price_with_slippage = round_to_tick(
+            clamp_limit_price("NO", opp.no_worst_price + no_slippage_apply),
+            self.price_tick,
+            limit_rounding,
+        );
         
         // 打印选档信息（加滑点后的价格）
         info!(
@@ -187,7 +863,7 @@ impl TradingExecutor {
         );
         
         let expiry_suffix = if matches!(self.arbitrage_order_type, OrderType::GTD) {
-            format!(" | GTD {}s", self.gtd_expiration_secs)
+            format!(" | GTD {}s", (expiration - now).num_seconds())
         } else {
             String::new()
         };
@@ -212,56 +888,61 @@ impl TradingExecutor {
             ));
         }
 
+        let (yes_client_id, no_client_id) = (client_order_id(&pair_id, "YES"), client_order_id(&pair_id, "NO"));
+
+        // 模拟交易：不构建/签名/提交订单，按机会数量视为双边全部成交，供风控与持仓跟踪照常更新
+        if self.dry_run {
+            info!(
+                "🧪 [DRY RUN] 将提交套利订单 | {} | YES {:.4}×{} NO {:.4}×{} | {}{}",
+                &pair_id[..8],
+                yes_price_with_slippage, order_size,
+                no_price_with_slippage, order_size,
+                self.arbitrage_order_type, expiry_suffix
+            );
+            return Ok(OrderPairResult {
+                pair_id,
+                yes_order_id: "dry-run-yes".to_string(),
+                no_order_id: "dry-run-no".to_string(),
+                yes_client_order_id: yes_client_id.to_string(),
+                no_client_order_id: no_client_id.to_string(),
+                yes_filled: order_size,
+                no_filled: order_size,
+                yes_size: order_size,
+                no_size: order_size,
+                success: true,
+            });
+        }
+
         // 性能计时：并行构建YES和NO订单开始
         let build_start = Instant::now();
         
         // 并行构建YES和NO订单；仅 GTD 时设置 expiration（SDK 规定非 GTD 不可设过期）
+        let arb_order = |token_id, price| LimitOrderArgs {
+            post_only: self.post_only,
+            expiration: matches!(&self.arbitrage_order_type, OrderType::GTD).then_some(expiration),
+            ..LimitOrderArgs::new(token_id, Side::Buy, price, order_size, self.arbitrage_order_type.clone())
+        };
         let (yes_order, no_order) = tokio::join!(
-            async {
-                let b = self.client
-                    .limit_order()
-                    .token_id(yes_token_id)
-                    .side(Side::Buy)
-                    .price(yes_price_with_slippage)
-                    .size(order_size)
-                    .order_type(self.arbitrage_order_type.clone());
-                if matches!(&self.arbitrage_order_type, OrderType::GTD) {
-                    b.expiration(expiration).build().await
-                } else {
-                    b.build().await
-                }
-            },
-            async {
-                let b = self.client
-                    .limit_order()
-                    .token_id(no_token_id)
-                    .side(Side::Buy)
-                    .price(no_price_with_slippage)
-                    .size(order_size)
-                    .order_type(self.arbitrage_order_type.clone());
-                if matches!(&self.arbitrage_order_type, OrderType::GTD) {
-                    b.expiration(expiration).build().await
-                } else {
-                    b.build().await
-                }
-            }
+            self.client.limit_order(arb_order(yes_token_id, yes_price_with_slippage)),
+            self.client.limit_order(arb_order(no_token_id, no_price_with_slippage))
         );
         
-        let yes_order = yes_order?;
-        let no_order = no_order?;
+        // 以确定性的客户端订单 ID 作为 salt，由订单可反查所属订单对与腿
+        let mut yes_order = yes_order?;
+        let mut no_order = no_order?;
+        yes_order.order.salt = U256::from(yes_client_id);
+        no_order.order.salt = U256::from(no_client_id);
         let build_elapsed = build_start.elapsed().as_millis();
 
         // 性能计时：并行签名开始
         let sign_start = Instant::now();
         
         // 创建signer
-        let signer = LocalSigner::from_str(&self.private_key)?
-            .with_chain_id(Some(POLYGON));
         
         // 并行签名YES和NO订单
         let (signed_yes_result, signed_no_result) = tokio::join!(
-            self.client.sign(&signer, yes_order),
-            self.client.sign(&signer, no_order)
+            self.client.sign(&self.signer, yes_order),
+            self.client.sign(&self.signer, no_order)
         );
         
         let signed_yes = signed_yes_result?;
@@ -306,7 +987,9 @@ impl TradingExecutor {
                     total_elapsed,
                     e
                 );
-                return Err(anyhow::anyhow!("批量下单API调用失败: {}", e));
+                // 保留 SDK 错误，调用方据此区分端点故障与业务错误
+                let msg = format!("批量下单API调用失败: {}", e);
+                return Err(e.context(msg));
             }
         };
         
@@ -332,9 +1015,10 @@ impl TradingExecutor {
 
         // 订单返回结果详情已移除，只保留关键信息在后续日志中
 
-        // 检查成交数量（GTD订单的关键指标）
-        let yes_filled = yes_result.taking_amount;
-        let no_filled = no_result.taking_amount;
+        // 检查成交份额（GTD订单的关键指标）：买单成交份额取自 taking_amount，making_amount 为付出的 USDC，
+        // 持仓按份额记账，敞口与买入成本由 register_order_pair 按份额 × 买入价计算
+        let (yes_filled, _) = filled_amounts(yes_result, Side::Buy);
+        let (no_filled, _) = filled_amounts(no_result, Side::Buy);
 
         // 对于GTD订单，如果无法在90秒内全部成交，订单会在过期后取消
         // 我们应该检查实际的成交数量，而不是 success 字段
@@ -386,11 +1070,11 @@ impl TradingExecutor {
                 "两个订单都未成交（详细信息）"
             );
 
-            return Err(anyhow::anyhow!(
-                "套利失败: YES和NO订单都未成交 | YES: {}, NO: {}",
-                yes_error_simple,
-                no_error_simple
-            ));
+            return Err(ArbitrageError::BothUnfilled {
+                yes: yes_error_simple.to_string(),
+                no: no_error_simple.to_string(),
+            }
+            .into());
         }
 
         // 如果至少有一个订单成交了，记录警告但不返回错误
@@ -444,8 +1128,13 @@ impl TradingExecutor {
             );
         }
 
-        // 根据成交情况打印不同的日志
-        if yes_filled > dec!(0) && no_filled > dec!(0) {
+        // 根据成交情况打印不同的日志；挂单模式下未成交部分仍挂在订单簿上，不视为失败
+        if self.post_only && (yes_filled < order_size || no_filled < order_size) {
+            info!(
+                "📌 挂单已提交 | {} | YES成交:{}/{}份 | NO成交:{}/{}份 | 未成交部分挂单等待，由订单对刷新与用户频道跟踪成交，单边成交交风控",
+                &pair_id[..8], yes_filled, order_size, no_filled, order_size
+            );
+        } else if yes_filled > dec!(0) && no_filled > dec!(0) {
             info!(
                 "✅ 套利交易成功 | 订单对ID:{} | YES成交:{}份 | NO成交:{}份 | 总成交:{}份",
                 &pair_id[..8],
@@ -472,6 +1161,8 @@ impl TradingExecutor {
             pair_id,
             yes_order_id: yes_result.order_id.clone(),
             no_order_id: no_result.order_id.clone(),
+            yes_client_order_id: yes_client_id.to_string(),
+            no_client_order_id: no_client_id.to_string(),
             yes_filled,
             no_filled,
             yes_size: order_size,
@@ -480,3 +1171,109 @@ impl TradingExecutor {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::clob::mock::{open_order, MockClobClient};
+    use polymarket_client_sdk::clob::types::OrderStatusType;
+
+    fn executor(mock: &MockClobClient) -> TradingExecutor<MockClobClient> {
+        TradingExecutor::with_client(
+            mock.clone(),
+            BotSigner::random(),
+            dec!(100),
+            [dec!(0), dec!(0.01)],
+            300,
+            OrderType::GTC,
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn cancel_all_orders_cancels_every_open_order() {
+        let mock = MockClobClient::default();
+        mock.set_open_orders(vec![
+            open_order("a", U256::from(1), OrderStatusType::Live, dec!(0.45), dec!(10), dec!(0)),
+            open_order("b", U256::from(2), OrderStatusType::Live, dec!(0.50), dec!(10), dec!(3)),
+        ]);
+        let summary = executor(&mock).cancel_all_orders().await.unwrap();
+        assert_eq!(summary.canceled, vec!["a", "b"]);
+        assert_eq!(mock.canceled(), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn sell_at_price_returns_scripted_post_response() {
+        let mock = MockClobClient::default();
+        mock.push_post_response(
+            PostOrderResponse::builder()
+                .making_amount(dec!(10))
+                .taking_amount(dec!(4.5))
+                .order_id("sell-1")
+                .status(OrderStatusType::Matched)
+                .success(true)
+                .build(),
+        );
+        let resp = executor(&mock).sell_at_price(U256::from(1), dec!(0.45), dec!(10)).await.unwrap();
+        assert_eq!(resp.order_id, "sell-1");
+        assert_eq!(filled_amounts(&resp, Side::Sell), (dec!(10), dec!(4.5)));
+    }
+
+    #[test]
+    fn client_order_id_is_deterministic_per_leg() {
+        let pair_id = "6f1c2d3e-0000-4000-8000-000000000001";
+        assert_eq!(client_order_id(pair_id, "YES"), client_order_id(pair_id, "YES"));
+        assert_ne!(client_order_id(pair_id, "YES"), client_order_id(pair_id, "NO"));
+        assert_ne!(client_order_id(pair_id, "YES"), client_order_id("another-pair", "YES"));
+        assert!(client_order_id(pair_id, "NO") < 1u64 << 53);
+    }
+
+    /// SDK 形状的下单响应：making_amount 为下单方付出、taking_amount 为收到
+    fn post_response(making_amount: Decimal, taking_amount: Decimal) -> PostOrderResponse {
+        PostOrderResponse::builder()
+            .making_amount(making_amount)
+            .taking_amount(taking_amount)
+            .order_id("order-1")
+            .status(OrderStatusType::Matched)
+            .success(true)
+            .build()
+    }
+
+    #[test]
+    fn filled_amounts_by_side() {
+        // 买单以 0.45 买入 10 份：付出 4.5 USDC，收到 10 份
+        assert_eq!(filled_amounts(&post_response(dec!(4.5), dec!(10)), Side::Buy), (dec!(10), dec!(4.5)));
+        // 卖单以 0.45 卖出 10 份：付出 10 份，收到 4.5 USDC
+        assert_eq!(filled_amounts(&post_response(dec!(10), dec!(4.5)), Side::Sell), (dec!(10), dec!(4.5)));
+        // 未成交
+        assert_eq!(filled_amounts(&post_response(dec!(0), dec!(0)), Side::Buy), (dec!(0), dec!(0)));
+    }
+
+    #[test]
+    fn filled_amounts_with_swapped_units_takes_larger_as_shares() {
+        // 两字段单位与预期相反时，份额取较大者，不会把 USDC 金额计为份额
+        assert_eq!(filled_amounts(&post_response(dec!(10), dec!(4.5)), Side::Buy), (dec!(10), dec!(4.5)));
+        assert_eq!(filled_amounts(&post_response(dec!(4.5), dec!(10)), Side::Sell), (dec!(10), dec!(4.5)));
+    }
+
+    #[test]
+    fn slippage_for_table() {
+        let slippage = [dec!(0.01), dec!(0.03)];
+        let cases = [
+            ("↑", SlippageMode::Directional, dec!(0.01)),
+            ("↓", SlippageMode::Directional, dec!(0.03)),
+            ("−", SlippageMode::Directional, dec!(0.01)),
+            ("", SlippageMode::Directional, dec!(0.01)),
+            ("↑", SlippageMode::Symmetric, dec!(0.03)),
+            ("↓", SlippageMode::Symmetric, dec!(0.03)),
+            ("−", SlippageMode::Symmetric, dec!(0.03)),
+            ("", SlippageMode::Symmetric, dec!(0.03)),
+        ];
+        for (direction, mode, expected) in cases {
+            assert_eq!(slippage_for(direction, mode, slippage), expected, "{:?} {}", direction, mode);
+        }
+        // second 小于 first 时，symmetric 仍取较大者
+        assert_eq!(slippage_for("↓", SlippageMode::Symmetric, [dec!(0.02), dec!(0)]), dec!(0.02));
+        assert_eq!(slippage_for("↓", SlippageMode::Directional, [dec!(0.02), dec!(0)]), dec!(0));
+    }
+}
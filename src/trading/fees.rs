@@ -0,0 +1,44 @@
+//! 交易手续费估算：fee = c * fee_rate * (p * (1-p))^exponent
+//! 结果为成交份额的扣减比例（百分比值，如 1.56 表示扣 1.56% 份额）
+
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal::MathematicalOps;
+use rust_decimal_macros::dec;
+
+/// 手续费公式参数
+#[derive(Debug, Clone, Copy)]
+pub struct FeeParams {
+    pub c: Decimal,
+    pub fee_rate: Decimal,
+    pub exponent: Decimal,
+}
+
+impl Default for FeeParams {
+    fn default() -> Self {
+        Self {
+            c: dec!(100.0),
+            fee_rate: dec!(0.25),
+            exponent: dec!(2.0),
+        }
+    }
+}
+
+impl FeeParams {
+    pub fn new(c: Decimal, fee_rate: Decimal, exponent: Decimal) -> Self {
+        Self { c, fee_rate, exponent }
+    }
+
+    /// 按成交价计算手续费百分比（0-100），p 越接近 0.5 手续费越高。
+    /// 整数指数按 Decimal 精确计算；p(1-p) 不超过 0.25，指数非负（由 validate 保证）时幂不超过 1，
+    /// 仅在指数极大时溢出，此时幂趋于 0
+    pub fn fee_pct(&self, price: Decimal) -> Decimal {
+        let base = price * (dec!(1) - price);
+        let power = base.checked_powd(self.exponent).unwrap_or(Decimal::ZERO);
+        self.c * self.fee_rate * power
+    }
+
+    /// 扣除手续费后实际到手的份额比例（0-1）
+    pub fn net_share_ratio(&self, price: Decimal) -> Decimal {
+        ((dec!(100.0) - self.fee_pct(price)) / dec!(100.0)).max(dec!(0))
+    }
+}
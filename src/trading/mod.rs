@@ -1,4 +1,7 @@
+pub mod clob;
 pub mod executor;
+pub mod fees;
 pub mod orders;
+pub mod signer;
 
 pub use executor::TradingExecutor;
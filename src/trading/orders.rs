@@ -1,2 +1,287 @@
 // 订单相关的辅助类型和函数
-// 目前为空，可以后续扩展
+
+use anyhow::Result;
+use dashmap::DashMap;
+use polymarket_client_sdk::clob::types::response::CancelOrdersResponse;
+use polymarket_client_sdk::clob::types::Side;
+use polymarket_client_sdk::types::{Decimal, U256};
+use rust_decimal::RoundingStrategy;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 批次撤单失败后的首次重试等待，之后每次翻倍
+const CANCEL_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// 按最小价格单位（tick）取整：买单向上、卖单向下取到 tick 的整数倍，避免交易所拒绝不在 tick 上的价格。
+/// 买单向上取整保证仍能吃到原价位，卖单向下取整保证不高于买一价。tick <= 0 时原样返回
+pub fn round_to_tick(price: Decimal, tick: Decimal, side: Side) -> Decimal {
+    if tick <= Decimal::ZERO {
+        return price;
+    }
+    let ticks = price / tick;
+    let ticks = match side {
+        Side::Buy => ticks.ceil(),
+        _ => ticks.floor(),
+    };
+    (ticks * tick).normalize()
+}
+
+/// 默认数量精度：2 位小数（0.01 份）
+pub const DEFAULT_SIZE_DECIMALS: u32 = 2;
+
+/// 按数量精度（小数位数）向下截断下单数量，保证不超过实际可用份额；结果可能为 0，由调用方决定跳过或取最小单位
+pub fn round_size(size: Decimal, decimals: u32) -> Decimal {
+    size.round_dp_with_strategy(decimals, RoundingStrategy::ToZero).normalize()
+}
+
+/// 该精度下的最小下单数量（一个数量单位），如 2 位小数为 0.01 份
+pub fn min_size(decimals: u32) -> Decimal {
+    Decimal::new(1, decimals)
+}
+
+/// 按市场的数量精度：窗口开始时按币种登记各市场 YES/NO token 的小数位数，未登记的 token 使用默认精度
+pub struct SizePrecision {
+    default: u32,
+    tokens: DashMap<U256, u32>,
+}
+
+impl SizePrecision {
+    pub fn new(default: u32) -> Self {
+        Self { default, tokens: DashMap::new() }
+    }
+
+    /// 登记一个市场 YES/NO token 的数量精度
+    pub fn set_market(&self, yes_token_id: U256, no_token_id: U256, decimals: u32) {
+        self.tokens.insert(yes_token_id, decimals);
+        self.tokens.insert(no_token_id, decimals);
+    }
+
+    pub fn decimals_for(&self, token_id: U256) -> u32 {
+        self.tokens.get(&token_id).map(|d| *d).unwrap_or(self.default)
+    }
+
+    /// 按该 token 的精度向下截断数量，见 round_size
+    pub fn round(&self, token_id: U256, size: Decimal) -> Decimal {
+        round_size(size, self.decimals_for(token_id))
+    }
+
+    /// 该 token 的最小下单数量
+    pub fn min_size(&self, token_id: U256) -> Decimal {
+        min_size(self.decimals_for(token_id))
+    }
+}
+
+impl Default for SizePrecision {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIZE_DECIMALS)
+    }
+}
+
+/// 分批撤单汇总
+#[derive(Debug, Clone, Default)]
+pub struct CancelSummary {
+    /// 已撤销的订单 ID
+    pub canceled: Vec<String>,
+    /// 未撤销的订单：(订单 ID, 原因)。原因为交易所返回的 not_canceled 说明，或批次重试耗尽后的错误
+    pub failed: Vec<(String, String)>,
+}
+
+impl CancelSummary {
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// 输出撤单结果：全部成功时 info，有失败时 warn 并列出失败的订单 ID。context 为日志前缀，如「收尾」
+    pub fn log(&self, context: &str) {
+        if self.is_complete() {
+            info!("✅ {}：已取消 {} 个订单", context, self.canceled.len());
+        } else {
+            let failed_ids: Vec<&str> = self.failed.iter().map(|(id, _)| id.as_str()).collect();
+            warn!(
+                failed = ?self.failed,
+                "⚠️ {}：部分订单取消失败 | 成功:{} 失败:{} | 失败订单:{}",
+                context,
+                self.canceled.len(),
+                self.failed.len(),
+                failed_ids.join(",")
+            );
+        }
+    }
+}
+
+/// 按 batch_size 分批撤单：每批调用一次 cancel，出错的批次按指数退避最多重试 max_retries 次，
+/// 重试耗尽后该批次所有订单记为失败并继续处理后续批次。交易所明确拒绝（not_canceled）的订单不重试。
+/// cancel 为实际的撤单调用（如 CLOB 客户端的 cancel_orders），便于替换为其他实现。
+pub async fn cancel_in_batches<F, Fut>(
+    order_ids: &[String],
+    batch_size: usize,
+    max_retries: u32,
+    mut cancel: F,
+) -> CancelSummary
+where
+    F: FnMut(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<CancelOrdersResponse>>,
+{
+    let mut summary = CancelSummary::default();
+    for batch in order_ids.chunks(batch_size.max(1)) {
+        let mut attempt = 0u32;
+        loop {
+            match cancel(batch.to_vec()).await {
+                Ok(resp) => {
+                    summary.canceled.extend(resp.canceled);
+                    summary.failed.extend(resp.not_canceled);
+                    break;
+                }
+                Err(e) if attempt < max_retries => {
+                    let delay = CANCEL_RETRY_BASE_DELAY.saturating_mul(1u32 << attempt.min(16));
+                    attempt += 1;
+                    warn!(
+                        error = %e,
+                        batch_len = batch.len(),
+                        "⏳ 批次撤单失败，{}ms 后重试 ({}/{})",
+                        delay.as_millis(),
+                        attempt,
+                        max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    let msg = e.to_string();
+                    summary.failed.extend(batch.iter().map(|id| (id.clone(), msg.clone())));
+                    break;
+                }
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+
+    fn ids(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("o{}", i)).collect()
+    }
+
+    /// 记录每次调用的批次；failures 为每个批次（按首个订单 ID）仍需失败的次数，rejected 为交易所拒绝的订单
+    async fn run(
+        order_ids: &[String],
+        max_retries: u32,
+        failures: &[(&str, u32)],
+        rejected: &[&str],
+    ) -> (CancelSummary, Vec<Vec<String>>) {
+        let mut failures: HashMap<String, u32> = failures.iter().map(|(id, n)| (id.to_string(), *n)).collect();
+        let rejected: Vec<String> = rejected.iter().map(|id| id.to_string()).collect();
+        let mut calls = Vec::new();
+        let summary = cancel_in_batches(order_ids, 3, max_retries, |batch| {
+            calls.push(batch.clone());
+            let fail = failures.get_mut(&batch[0]).is_some_and(|n| {
+                let fail = *n > 0;
+                *n = n.saturating_sub(1);
+                fail
+            });
+            let rejected = rejected.clone();
+            async move {
+                if fail {
+                    return Err(anyhow!("batch error"));
+                }
+                let (not_canceled, canceled): (Vec<String>, Vec<String>) =
+                    batch.into_iter().partition(|id| rejected.contains(id));
+                Ok(CancelOrdersResponse::builder()
+                    .canceled(canceled)
+                    .not_canceled(not_canceled.into_iter().map(|id| (id, "matched".to_string())).collect())
+                    .build())
+            }
+        })
+        .await;
+        (summary, calls)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_middle_batch_does_not_stop_later_batches() {
+        let order_ids = ids(7);
+        let (summary, calls) = run(&order_ids, 1, &[("o3", u32::MAX)], &["o6"]).await;
+
+        // 批次边界：3 + 3 + 1，失败的中间批次重试 1 次
+        let batch_lens: Vec<usize> = calls.iter().map(|b| b.len()).collect();
+        assert_eq!(batch_lens, vec![3, 3, 3, 1]);
+        assert_eq!(calls[1], calls[2]);
+
+        assert_eq!(summary.canceled, vec!["o0", "o1", "o2"]);
+        let failed: Vec<(&str, &str)> = summary.failed.iter().map(|(id, r)| (id.as_str(), r.as_str())).collect();
+        assert_eq!(
+            failed,
+            vec![("o3", "batch error"), ("o4", "batch error"), ("o5", "batch error"), ("o6", "matched")]
+        );
+        assert!(!summary.is_complete());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn transient_batch_failure_is_retried() {
+        let order_ids = ids(6);
+        let (summary, calls) = run(&order_ids, 2, &[("o3", 1)], &[]).await;
+
+        assert_eq!(calls.len(), 3);
+        assert_eq!(summary.canceled, order_ids);
+        assert!(summary.is_complete());
+    }
+
+    #[tokio::test]
+    async fn exact_multiple_of_batch_size() {
+        let order_ids = ids(6);
+        let (summary, calls) = run(&order_ids, 0, &[], &[]).await;
+
+        assert_eq!(calls, vec![order_ids[..3].to_vec(), order_ids[3..].to_vec()]);
+        assert_eq!(summary.canceled, order_ids);
+    }
+
+    #[test]
+    fn buy_rounds_up_and_sell_rounds_down_to_tick() {
+        let tick = dec!(0.01);
+        assert_eq!(round_to_tick(dec!(0.453), tick, Side::Buy), dec!(0.46));
+        assert_eq!(round_to_tick(dec!(0.453), tick, Side::Sell), dec!(0.45));
+        assert_eq!(round_to_tick(dec!(0.4501), dec!(0.001), Side::Buy), dec!(0.451));
+        assert_eq!(round_to_tick(dec!(0.4509), dec!(0.001), Side::Sell), dec!(0.45));
+    }
+
+    #[test]
+    fn price_on_tick_is_unchanged() {
+        for side in [Side::Buy, Side::Sell] {
+            assert_eq!(round_to_tick(dec!(0.45), dec!(0.01), side), dec!(0.45));
+            assert_eq!(round_to_tick(dec!(0.450), dec!(0.001), side), dec!(0.45));
+            assert_eq!(round_to_tick(dec!(0.5), dec!(0.1), side), dec!(0.5));
+        }
+        // tick 无效时原样返回
+        assert_eq!(round_to_tick(dec!(0.453), dec!(0), Side::Buy), dec!(0.453));
+    }
+
+    #[test]
+    fn size_rounds_down_to_market_precision() {
+        let precision = SizePrecision::default();
+        let (yes, no, other) = (U256::from(1), U256::from(2), U256::from(3));
+        precision.set_market(yes, no, 0);
+
+        assert_eq!(precision.round(yes, dec!(12.99)), dec!(12));
+        assert_eq!(precision.round(no, dec!(12.99)), dec!(12));
+        // 未登记的 token 使用默认 2 位小数
+        assert_eq!(precision.round(other, dec!(12.999)), dec!(12.99));
+        assert_eq!(precision.round(other, dec!(12.5)), dec!(12.5));
+    }
+
+    #[test]
+    fn size_below_one_unit_rounds_to_zero() {
+        let precision = SizePrecision::new(2);
+        let token = U256::from(1);
+        // 截断为 0 由调用方处理，最小单位为一个数量单位
+        assert_eq!(precision.round(token, dec!(0.009)), dec!(0));
+        assert_eq!(precision.min_size(token), dec!(0.01));
+        precision.set_market(token, U256::from(2), 0);
+        assert_eq!(precision.round(token, dec!(0.99)), dec!(0));
+        assert_eq!(precision.min_size(token), dec!(1));
+    }
+}
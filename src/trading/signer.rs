@@ -0,0 +1,69 @@
+//! 签名器：下单、API 认证与 Merge 使用的签名器统一经 build_signer 由私钥来源创建。
+//! 私钥来源由 KeySource 抽象：默认为 POLYMARKET_PRIVATE_KEY 原始私钥；设置 KEYSTORE_PATH 时改为
+//! 从加密的 JSON keystore 解密（密码取自 KEYSTORE_PASSWORD），需以 `--features keystore` 编译。
+//! 私钥加载后只以签名器形式保存在 Config 中，不再转回十六进制字符串。
+
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::Signer;
+use anyhow::{Context, Result};
+use polymarket_client_sdk::POLYGON;
+use std::str::FromStr;
+
+/// 机器人使用的签名器类型：各私钥来源最终都得到本地私钥签名器
+pub type BotSigner = PrivateKeySigner;
+
+/// 私钥来源
+pub trait KeySource {
+    /// 用于日志的来源说明（不含敏感信息）
+    fn describe(&self) -> String;
+    /// 加载私钥，得到尚未绑定链 ID 的签名器
+    fn load(&self) -> Result<BotSigner>;
+}
+
+/// 原始十六进制私钥（POLYMARKET_PRIVATE_KEY）
+pub struct RawKey(pub String);
+
+impl KeySource for RawKey {
+    fn describe(&self) -> String {
+        "POLYMARKET_PRIVATE_KEY".to_string()
+    }
+
+    fn load(&self) -> Result<BotSigner> {
+        PrivateKeySigner::from_str(self.0.trim()).map_err(|e| {
+            anyhow::anyhow!("私钥格式无效: {}. 请确保私钥是64字符的十六进制字符串（不带0x前缀）", e)
+        })
+    }
+}
+
+/// 加密的 JSON keystore（KEYSTORE_PATH + KEYSTORE_PASSWORD）
+pub struct Keystore {
+    pub path: String,
+    #[cfg_attr(not(feature = "keystore"), allow(dead_code))]
+    pub password: String,
+}
+
+impl KeySource for Keystore {
+    fn describe(&self) -> String {
+        format!("keystore {}", self.path)
+    }
+
+    #[cfg(feature = "keystore")]
+    fn load(&self) -> Result<BotSigner> {
+        PrivateKeySigner::decrypt_keystore(&self.path, &self.password)
+            .with_context(|| format!("解密 keystore {} 失败（检查 KEYSTORE_PASSWORD）", self.path))
+    }
+
+    #[cfg(not(feature = "keystore"))]
+    fn load(&self) -> Result<BotSigner> {
+        anyhow::bail!(
+            "已设置 KEYSTORE_PATH={}，但当前版本未启用 keystore 支持，请以 `cargo build --features keystore` 重新编译",
+            self.path
+        )
+    }
+}
+
+/// 从私钥来源创建绑定 Polygon 链 ID 的签名器
+pub fn build_signer(source: &dyn KeySource) -> Result<BotSigner> {
+    let signer = source.load().with_context(|| format!("加载私钥失败（来源: {}）", source.describe()))?;
+    Ok(signer.with_chain_id(Some(POLYGON)))
+}
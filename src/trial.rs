@@ -1,5 +1,13 @@
 //! 许可证文件授权：程序仅在有有效许可证时运行。
 //! 许可证由作者签发，内容为加密的过期时间戳，删除许可证将无法运行，无法通过删文件重置试用。
+//! 可选绑定机器：许可证中附带机器指纹（机器标识的哈希），校验时与本机重新计算的指纹比对。
+//!
+//! 明文格式：
+//! - v0（旧格式，仅时间戳）：8 字节过期时间戳（小端）
+//! - v1：1 字节版本号 + 8 字节过期时间戳（小端）+ 1 字节是否绑定机器 + 32 字节机器指纹（未绑定时全 0）
+//! - v2：在 v1 的时间戳之后插入 8 字节宽限期秒数（小端）；v0/v1 视为无宽限期
+//!
+//! 过期后的宽限期内程序继续运行但定期告警，宽限期结束后硬性过期。
 
 use aes_gcm::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
@@ -9,7 +17,7 @@ use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// 默认许可证文件名（放在程序当前工作目录或由环境变量指定路径）
 const LICENSE_FILENAME: &str = "license.key";
@@ -20,9 +28,38 @@ const LICENSE_PATH_ENV: &str = "POLY_15MIN_BOT_LICENSE";
 /// 密钥派生种子（仅用于派生加密密钥；生成许可证时使用相同种子）
 const TRIAL_KEY_SEED: &[u8] = b"poly_15min_bot_trial_seed_2025";
 
+/// 环境变量：机器标识（可选），未设置时读取系统的 machine-id
+const MACHINE_ID_ENV: &str = "POLY_15MIN_BOT_MACHINE_ID";
+
+/// 系统 machine-id 文件（Linux / systemd）
+const MACHINE_ID_FILES: &[&str] = &["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
 /// AES-GCM nonce 长度（12 字节）
 const NONCE_LEN: usize = 12;
 
+/// 带版本号的许可证格式版本
+const PAYLOAD_V1: u8 = 1;
+const PAYLOAD_V2: u8 = 2;
+/// v1 明文长度：版本号 + 时间戳 + 绑定标记 + 指纹
+const PAYLOAD_V1_LEN: usize = 1 + 8 + 1 + 32;
+/// v2 明文长度：版本号 + 时间戳 + 宽限期 + 绑定标记 + 指纹
+const PAYLOAD_V2_LEN: usize = 1 + 8 + 8 + 1 + 32;
+
+/// 剩余时间少于此值时启动即醒目告警，并在运行中每小时提醒
+const EXPIRY_WARN_BEFORE: Duration = Duration::from_secs(24 * 3600);
+/// 过期前的提醒间隔
+const EXPIRY_WARN_INTERVAL: Duration = Duration::from_secs(3600);
+/// 宽限期内的提醒间隔
+const GRACE_WARN_INTERVAL: Duration = Duration::from_secs(300);
+
+/// 解密后的许可证内容
+struct LicensePayload {
+    expiry_secs: u64,
+    grace_secs: u64,
+    /// 绑定的机器指纹，None 表示不绑定机器
+    fingerprint: Option<[u8; 32]>,
+}
+
 /// 解析许可证文件路径：优先使用环境变量，否则为当前目录下的 license.key
 fn license_file_path() -> PathBuf {
     std::env::var(LICENSE_PATH_ENV)
@@ -38,6 +75,31 @@ fn derive_key() -> [u8; 32] {
     digest.into()
 }
 
+/// 机器指纹：SHA-256(种子 || 机器标识)，许可证中只保存指纹，不保存原始标识
+fn machine_fingerprint(machine_id: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(TRIAL_KEY_SEED);
+    hasher.update(b"machine:");
+    hasher.update(machine_id.trim().as_bytes());
+    hasher.finalize().into()
+}
+
+/// 本机标识：优先使用环境变量 POLY_15MIN_BOT_MACHINE_ID，否则读取系统 machine-id。
+/// 用户将此值发给作者，作者用 `gen_license --machine-id <ID>` 签发绑定本机的许可证。
+pub fn local_machine_id() -> Result<String> {
+    if let Ok(id) = std::env::var(MACHINE_ID_ENV) {
+        if !id.trim().is_empty() {
+            return Ok(id.trim().to_string());
+        }
+    }
+    MACHINE_ID_FILES
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+        .with_context(|| format!("无法获取本机标识，请设置环境变量 {}", MACHINE_ID_ENV))
+}
+
 /// 当前时间的 Unix 时间戳（秒）
 fn now_secs() -> Result<u64> {
     SystemTime::now()
@@ -46,12 +108,17 @@ fn now_secs() -> Result<u64> {
         .context("系统时间异常")
 }
 
-/// 加密一个 u64 时间戳：输出 base64(nonce || ciphertext)，密文含认证标签防篡改。
-fn encrypt_timestamp(ts_secs: u64) -> Result<String> {
+/// 加密许可证内容（v2 格式）：输出 base64(nonce || ciphertext)，密文含认证标签防篡改。
+fn encrypt_payload(payload: &LicensePayload) -> Result<String> {
     let key = derive_key();
     let cipher = Aes256Gcm::new_from_slice(&key).context("初始化加密失败")?;
     let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
-    let plaintext = ts_secs.to_le_bytes();
+    let mut plaintext = Vec::with_capacity(PAYLOAD_V2_LEN);
+    plaintext.push(PAYLOAD_V2);
+    plaintext.extend_from_slice(&payload.expiry_secs.to_le_bytes());
+    plaintext.extend_from_slice(&payload.grace_secs.to_le_bytes());
+    plaintext.push(payload.fingerprint.is_some() as u8);
+    plaintext.extend_from_slice(&payload.fingerprint.unwrap_or([0; 32]));
     let ciphertext = cipher
         .encrypt(&nonce, plaintext.as_ref())
         .map_err(|e| anyhow::anyhow!("加密失败: {}", e))?;
@@ -63,8 +130,8 @@ fn encrypt_timestamp(ts_secs: u64) -> Result<String> {
     ))
 }
 
-/// 解密许可证/试用状态内容，返回 u64 时间戳；解密失败或篡改则返回错误。
-fn decrypt_timestamp(encoded: &str) -> Result<u64> {
+/// 解密许可证内容，兼容 v0（仅时间戳）、v1 与 v2 格式；解密失败或篡改则返回错误。
+fn decrypt_payload(encoded: &str) -> Result<LicensePayload> {
     let payload = base64::Engine::decode(
         &base64::engine::general_purpose::STANDARD,
         encoded.trim(),
@@ -83,23 +150,124 @@ fn decrypt_timestamp(encoded: &str) -> Result<u64> {
     let plaintext = cipher
         .decrypt(&nonce, ciphertext)
         .map_err(|_| anyhow::anyhow!("许可证无效或已篡改（解密或校验失败）"))?;
-    if plaintext.len() != 8 {
-        anyhow::bail!("许可证无效或已篡改（内容长度异常）");
+    let read_u64 = |bytes: &[u8]| {
+        let mut buf: [u8; 8] = [0; 8];
+        buf.copy_from_slice(bytes);
+        u64::from_le_bytes(buf)
+    };
+    // 绑定标记 + 指纹（v1/v2 末尾 33 字节）
+    let read_fingerprint = |bytes: &[u8]| match bytes[0] {
+        0 => None,
+        _ => {
+            let mut fp = [0u8; 32];
+            fp.copy_from_slice(&bytes[1..]);
+            Some(fp)
+        }
+    };
+    match plaintext.len() {
+        // v0：仅过期时间戳
+        8 => Ok(LicensePayload {
+            expiry_secs: read_u64(&plaintext),
+            grace_secs: 0,
+            fingerprint: None,
+        }),
+        PAYLOAD_V1_LEN if plaintext[0] == PAYLOAD_V1 => Ok(LicensePayload {
+            expiry_secs: read_u64(&plaintext[1..9]),
+            grace_secs: 0,
+            fingerprint: read_fingerprint(&plaintext[9..]),
+        }),
+        PAYLOAD_V2_LEN if plaintext[0] == PAYLOAD_V2 => Ok(LicensePayload {
+            expiry_secs: read_u64(&plaintext[1..9]),
+            grace_secs: read_u64(&plaintext[9..17]),
+            fingerprint: read_fingerprint(&plaintext[17..]),
+        }),
+        _ => anyhow::bail!("许可证无效或已篡改（内容长度或版本异常）"),
     }
-    let mut bytes: [u8; 8] = [0; 8];
-    bytes.copy_from_slice(&plaintext[..8]);
-    Ok(u64::from_le_bytes(bytes))
 }
 
-/// 生成许可证字符串（过期时间戳加密后的 base64）。
+/// 生成许可证字符串（过期时间戳、宽限期与可选机器指纹加密后的 base64）。
+/// grace_secs 为过期后仍允许运行（持续告警）的秒数；machine_id 为目标机器的标识（见 `local_machine_id`），None 表示不绑定机器。
 /// 供作者使用：用 `gen_license` 二进制或调用此函数生成许可证，将结果写入文件发给试用用户。
-pub fn create_license(expiry_secs: u64) -> Result<String> {
-    encrypt_timestamp(expiry_secs)
+pub fn create_license(expiry_secs: u64, grace_secs: u64, machine_id: Option<&str>) -> Result<String> {
+    encrypt_payload(&LicensePayload {
+        expiry_secs,
+        grace_secs,
+        fingerprint: machine_id.map(machine_fingerprint),
+    })
 }
 
-/// 校验许可证文件：文件必须存在且未过期，否则返回错误。
+/// 已通过校验的许可证有效期
+#[derive(Debug, Clone, Copy)]
+pub struct LicenseInfo {
+    /// 过期时间戳（秒）
+    pub expiry_secs: u64,
+    /// 过期后的宽限期（秒），0 表示无宽限期
+    pub grace_secs: u64,
+}
+
+impl LicenseInfo {
+    /// 硬性过期时间戳：过期时间 + 宽限期
+    pub fn hard_expiry_secs(&self) -> u64 {
+        self.expiry_secs.saturating_add(self.grace_secs)
+    }
+
+    /// 按当前时间输出一次提醒：宽限期内 warn，距过期不足 24 小时 warn，否则 info。
+    /// 返回距下次提醒的时长；已硬性过期时返回 None
+    fn log_status(&self, now: u64) -> Option<Duration> {
+        if now >= self.hard_expiry_secs() {
+            return None;
+        }
+        if now >= self.expiry_secs {
+            let grace_left = self.hard_expiry_secs() - now;
+            tracing::warn!(
+                grace_remaining_mins = grace_left / 60,
+                "⚠️⚠️ 许可证已过期，宽限期剩余约 {} 分钟，届时程序将停止。请尽快联系作者获取新许可证。",
+                grace_left / 60
+            );
+            return Some(GRACE_WARN_INTERVAL.min(Duration::from_secs(grace_left)));
+        }
+        let remaining = self.expiry_secs - now;
+        let hours = (remaining as f64) / 3600.0;
+        if remaining <= EXPIRY_WARN_BEFORE.as_secs() {
+            tracing::warn!(
+                remaining_hours = hours,
+                grace_hours = (self.grace_secs as f64) / 3600.0,
+                "⚠️ 许可证将在约 {:.1} 小时后过期，请及时联系作者续期",
+                hours
+            );
+            Some(EXPIRY_WARN_INTERVAL.min(Duration::from_secs(remaining)))
+        } else {
+            tracing::info!(remaining_hours = hours, "许可证有效，剩余约 {:.1} 小时", hours);
+            // 睡到进入 24 小时告警区间为止
+            Some(Duration::from_secs(remaining - EXPIRY_WARN_BEFORE.as_secs()))
+        }
+    }
+}
+
+/// 运行期间监视许可证：过期前 24 小时起每小时提醒，宽限期内每 5 分钟提醒；
+/// 宽限期结束（硬性过期）时返回，调用方据此停止运行。
+pub async fn watch_license(info: LicenseInfo) {
+    loop {
+        let now = match now_secs() {
+            Ok(now) => now,
+            Err(_) => {
+                tokio::time::sleep(GRACE_WARN_INTERVAL).await;
+                continue;
+            }
+        };
+        match info.log_status(now) {
+            Some(next) => tokio::time::sleep(next.max(Duration::from_secs(1))).await,
+            None => {
+                tracing::error!("许可证宽限期已结束，程序将停止。如需继续使用请联系作者获取新许可证。");
+                return;
+            }
+        }
+    }
+}
+
+/// 校验许可证文件：文件必须存在且未超过宽限期，否则返回错误；返回有效期供运行中监视（见 `watch_license`）。
 /// 删除许可证将无法运行，无法通过删文件重置试用。
-pub fn check_license() -> Result<()> {
+pub fn check_license() -> Result<LicenseInfo> {
     let path = license_file_path();
     let now = now_secs()?;
 
@@ -112,19 +280,24 @@ pub fn check_license() -> Result<()> {
     }
 
     let content = fs::read_to_string(&path).context("读取许可证文件失败")?;
-    let expiry_secs = decrypt_timestamp(&content)?;
+    let LicensePayload { expiry_secs, grace_secs, fingerprint } = decrypt_payload(&content)?;
 
-    if now >= expiry_secs {
+    if let Some(expected) = fingerprint {
+        let machine_id = local_machine_id()?;
+        if machine_fingerprint(&machine_id) != expected {
+            anyhow::bail!(
+                "许可证与本机不匹配（本机标识: {}）。请将该标识发给作者获取绑定本机的许可证。",
+                machine_id
+            );
+        }
+    }
+
+    let info = LicenseInfo { expiry_secs, grace_secs };
+    if now >= info.hard_expiry_secs() {
         anyhow::bail!(
             "许可证已过期。如需继续使用请联系作者获取新许可证。"
         );
     }
-
-    let remaining_secs = expiry_secs - now;
-    tracing::info!(
-        remaining_hours = (remaining_secs as f64) / 3600.0,
-        "许可证有效，剩余约 {:.1} 小时",
-        (remaining_secs as f64) / 3600.0
-    );
-    Ok(())
+    info.log_status(now);
+    Ok(info)
 }
@@ -1,11 +1,52 @@
 use anyhow::Result;
 use chrono::Utc;
+use rust_decimal_macros::dec;
 use serde::Serialize;
+use std::fmt;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::str::FromStr;
+use std::sync::Mutex;
 use tracing::error;
 
 use crate::monitor::ArbitrageOpportunity;
+use crate::trading::executor::OrderPairResult;
+
+/// 成交记录格式（TRADE_LOG_FORMAT）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeLogFormat {
+    /// 每笔一个格式化 JSON 块，以 `---` 分隔
+    Json,
+    /// 每笔一行 CSV，文件为空时先写表头
+    Csv,
+}
+
+impl FromStr for TradeLogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!("未知的成交记录格式: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for TradeLogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// CSV 表头，与 log_trade_csv 写入的列一一对应
+const CSV_HEADER: &str = "timestamp,market,symbol,yes_price,no_price,size,total_cost,estimated_profit,yes_order_id,no_order_id";
+
+/// 串行化 CSV 写入：多个成交任务并发追加时保证表头只写一次、行不交错
+static CSV_WRITE_LOCK: Mutex<()> = Mutex::new(());
 
 #[derive(Serialize)]
 struct ArbitrageRecord {
@@ -68,3 +109,73 @@ pub async fn log_arbitrage_opportunity_async(
         error!(error = %e, "写入套利机会文件失败");
     }
 }
+
+/// CSV 字段转义：含逗号、引号或换行时用双引号包裹，内部引号加倍
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 将一笔已执行的套利交易追加为 CSV 的一行（文件为空时先写表头）。
+/// 下单数量取 YES/NO 下单量的较小值，预估收益 = 利润率 × 下单数量。
+pub fn log_trade_csv(
+    opp: &ArbitrageOpportunity,
+    result: &OrderPairResult,
+    market_name: &str,
+    symbol: &str,
+    file_path: &str,
+) -> Result<()> {
+    let size = result.yes_size.min(result.no_size);
+    let total_cost = (opp.yes_ask_price + opp.no_ask_price) * size;
+    let estimated_profit = opp.profit_percentage / dec!(100) * size;
+    let row = [
+        Utc::now().to_rfc3339(),
+        market_name.to_string(),
+        symbol.to_string(),
+        opp.yes_ask_price.to_string(),
+        opp.no_ask_price.to_string(),
+        size.to_string(),
+        total_cost.round_dp(6).to_string(),
+        estimated_profit.round_dp(6).to_string(),
+        result.yes_order_id.clone(),
+        result.no_order_id.clone(),
+    ]
+    .iter()
+    .map(|v| csv_field(v))
+    .collect::<Vec<_>>()
+    .join(",");
+
+    let _guard = CSV_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{}", CSV_HEADER)?;
+    }
+    writeln!(file, "{}", row)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// 按配置的格式记录一笔已执行的套利交易，写入失败只记录错误
+pub fn log_trade(
+    format: TradeLogFormat,
+    opp: &ArbitrageOpportunity,
+    result: &OrderPairResult,
+    market_name: &str,
+    symbol: &str,
+    file_path: &str,
+) {
+    let written = match format {
+        TradeLogFormat::Json => log_arbitrage_opportunity(opp, market_name, file_path),
+        TradeLogFormat::Csv => log_trade_csv(opp, result, market_name, symbol, file_path),
+    };
+    if let Err(e) = written {
+        error!(error = %e, path = file_path, "写入成交记录失败");
+    }
+}
@@ -0,0 +1,25 @@
+//! 日志时间显示：DISPLAY_TZ 设置的 IANA 时区仅用于格式化日志中的时间（窗口起止、收尾触发、Merge 时间等），
+//! 窗口计算、定时与持久化等内部逻辑一律使用 UTC。
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+/// 日志显示时区，未设置时为 UTC
+static DISPLAY_TZ: OnceLock<Tz> = OnceLock::new();
+
+/// 启动时设置显示时区（仅首次调用生效）
+pub fn init(tz: Tz) {
+    let _ = DISPLAY_TZ.set(tz);
+}
+
+/// 按显示时区格式化时间，例如 `2025-01-01 08:05:00 CST`
+pub fn fmt(time: DateTime<Utc>) -> String {
+    let tz = DISPLAY_TZ.get().copied().unwrap_or(Tz::UTC);
+    time.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S %Z").to_string()
+}
+
+/// 按显示时区格式化 Unix 秒时间戳（窗口时间戳等）
+pub fn fmt_ts(secs: i64) -> String {
+    DateTime::from_timestamp(secs, 0).map(fmt).unwrap_or_else(|| secs.to_string())
+}
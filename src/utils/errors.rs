@@ -1,2 +1,43 @@
 // 简化错误处理，直接使用 anyhow::Error
-// 如果需要更细粒度的错误类型，可以后续扩展
+// 需要按类别分支处理的错误在此定义结构化类型，经 anyhow 传递后由调用方 downcast_ref 匹配
+
+use polymarket_client_sdk::error::{Error as SdkError, Kind, Status, StatusCode};
+use std::fmt;
+
+/// 套利下单的业务失败（区别于下单 API 调用失败等其他错误）
+#[derive(Debug)]
+pub enum ArbitrageError {
+    /// YES 和 NO 订单都未成交，附简化后的失败原因
+    BothUnfilled { yes: String, no: String },
+    /// GTD 最晚过期时间（窗口结束）已过，不再下单
+    WindowExpired { limit: String },
+}
+
+impl fmt::Display for ArbitrageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArbitrageError::BothUnfilled { yes, no } => {
+                write!(f, "套利失败: YES和NO订单都未成交 | YES: {}, NO: {}", yes, no)
+            }
+            ArbitrageError::WindowExpired { limit } => {
+                write!(f, "GTD 最晚过期时间 {} 已过，不再下单", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArbitrageError {}
+
+/// 错误链中是否有 CLOB 端点本身的问题：限速（429）、5xx，或请求未能完成（SDK 归为 Internal 的传输错误）。
+/// 按 SDK 的错误类型判断；业务错误（余额不足、价格变动、参数校验等 4xx）说明端点可达，不计入
+pub fn is_clob_endpoint_error(e: &anyhow::Error) -> bool {
+    e.chain()
+        .filter_map(|cause| cause.downcast_ref::<SdkError>())
+        .any(|sdk| match sdk.kind() {
+            Kind::Status => sdk.downcast_ref::<Status>().is_some_and(|s| {
+                s.status_code == StatusCode::TOO_MANY_REQUESTS || s.status_code.is_server_error()
+            }),
+            Kind::Internal => true,
+            _ => false,
+        })
+}
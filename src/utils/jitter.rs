@@ -0,0 +1,98 @@
+//! 下单抖动：按 ORDER_JITTER_PCT 随机缩放下单数量，并在两次交易的固定间隔上叠加随机延迟，
+//! 避免同一机会持续存在时以完全相同的数量和节奏重复下单。
+//! 设置 ORDER_JITTER_SEED 时随机序列可复现（便于回放与排查），否则以启动时间为种子。
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// SplitMix64 的步长
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+pub struct OrderJitter {
+    pct: f64,
+    state: AtomicU64,
+}
+
+impl OrderJitter {
+    /// pct 为 0 时不启用：数量不变、额外延迟为 0
+    pub fn new(pct: f64, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0)
+        });
+        Self {
+            pct,
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.pct > 0.0
+    }
+
+    /// [0, 1) 均匀分布（SplitMix64，无锁，同一种子下序列固定）
+    fn next_unit(&self) -> f64 {
+        let mut z = self.state.fetch_add(GOLDEN_GAMMA, Ordering::Relaxed).wrapping_add(GOLDEN_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// 数量乘以 1 + U(-pct, pct)，不超过 max，向下取 2 位小数
+    pub fn jitter_size(&self, size: Decimal, max: Decimal) -> Decimal {
+        if !self.is_enabled() {
+            return size;
+        }
+        let factor = 1.0 + self.pct * (2.0 * self.next_unit() - 1.0);
+        let factor = Decimal::from_f64(factor).unwrap_or(dec!(1));
+        ((size * factor).min(max) * dec!(100)).floor() / dec!(100)
+    }
+
+    /// [0, max) 之间的随机延迟，叠加在固定交易间隔之上
+    pub fn extra_delay(&self, max: Duration) -> Duration {
+        if !self.is_enabled() {
+            return Duration::ZERO;
+        }
+        max.mul_f64(self.next_unit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sizes() {
+        let a = OrderJitter::new(0.2, Some(42));
+        let b = OrderJitter::new(0.2, Some(42));
+        let sizes_a: Vec<Decimal> = (0..20).map(|_| a.jitter_size(dec!(100), dec!(1000))).collect();
+        let sizes_b: Vec<Decimal> = (0..20).map(|_| b.jitter_size(dec!(100), dec!(1000))).collect();
+        assert_eq!(sizes_a, sizes_b);
+        // 不是每次都返回同一个值
+        assert!(sizes_a.iter().any(|s| *s != sizes_a[0]));
+    }
+
+    #[test]
+    fn jittered_size_stays_within_bounds() {
+        let jitter = OrderJitter::new(0.1, Some(7));
+        for _ in 0..1000 {
+            let size = jitter.jitter_size(dec!(100), dec!(105));
+            assert!(size >= dec!(90) && size <= dec!(105), "{}", size);
+            assert_eq!(size, size.round_dp(2));
+            assert!(jitter.extra_delay(Duration::from_millis(500)) < Duration::from_millis(500));
+        }
+    }
+
+    #[test]
+    fn disabled_jitter_is_identity() {
+        let jitter = OrderJitter::new(0.0, Some(1));
+        assert_eq!(jitter.jitter_size(dec!(12.345), dec!(5)), dec!(12.345));
+        assert_eq!(jitter.extra_delay(Duration::from_secs(1)), Duration::ZERO);
+    }
+}
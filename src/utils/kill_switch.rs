@@ -0,0 +1,74 @@
+//! 交易暂停开关（kill switch）：用于事故处理时立即停止开新仓，进程继续运行，Merge、仓位平衡与收尾照常执行。
+//! 两种触发方式，任一生效即暂停，全部清除后自动恢复：
+//! - TRADING_HALT_FILE 指定的文件存在（按 HALT_FILE_CHECK_INTERVAL 节流检查，不在每次订单簿更新时访问文件系统）
+//! - 状态服务 POST /halt（POST /resume 解除）
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// 两次检查暂停文件的最小间隔
+const HALT_FILE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 经状态服务设置的暂停标志
+static HALTED_BY_API: AtomicBool = AtomicBool::new(false);
+
+/// 经状态服务暂停 / 恢复交易
+pub fn set_halted_by_api(halted: bool) {
+    if HALTED_BY_API.swap(halted, Ordering::Relaxed) != halted {
+        if halted {
+            warn!("⛔ 已通过状态服务暂停交易");
+        } else {
+            info!("▶️ 已通过状态服务解除交易暂停");
+        }
+    }
+}
+
+/// 是否经状态服务暂停了交易
+pub fn halted_by_api() -> bool {
+    HALTED_BY_API.load(Ordering::Relaxed)
+}
+
+/// 主循环持有的暂停开关：合并暂停文件与状态服务两种来源，状态变化时记录日志
+pub struct HaltSwitch {
+    path: Option<PathBuf>,
+    last_check: Option<Instant>,
+    file_present: bool,
+    halted: bool,
+}
+
+impl HaltSwitch {
+    pub fn new(path: Option<String>) -> Self {
+        Self {
+            path: path.map(PathBuf::from),
+            last_check: None,
+            file_present: false,
+            halted: false,
+        }
+    }
+
+    /// 当前是否暂停交易
+    pub fn is_halted(&mut self) -> bool {
+        if let Some(path) = &self.path {
+            if self.last_check.is_none_or(|t| t.elapsed() >= HALT_FILE_CHECK_INTERVAL) {
+                self.last_check = Some(Instant::now());
+                self.file_present = path.exists();
+            }
+        }
+        let halted = self.file_present || halted_by_api();
+        if halted != self.halted {
+            self.halted = halted;
+            if halted {
+                warn!(
+                    halt_file = self.file_present,
+                    api = halted_by_api(),
+                    "⛔ 交易已暂停（kill switch），不再执行新的套利；Merge、仓位平衡与收尾继续"
+                );
+            } else {
+                info!("▶️ 交易暂停已解除，恢复套利执行");
+            }
+        }
+        halted
+    }
+}
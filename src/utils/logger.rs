@@ -1,27 +1,91 @@
 use anyhow::Result;
 use std::fs::File;
+use std::sync::OnceLock;
+use tracing::Span;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+/// 本次运行的唯一 ID（JSON 日志中用于关联同一次运行的所有事件）
+static RUN_ID: OnceLock<String> = OnceLock::new();
+/// 是否以 JSON 格式输出（LOG_FORMAT=json）
+static JSON_FORMAT: OnceLock<bool> = OnceLock::new();
+
+/// 初始化日志：默认人类可读格式；LOG_FORMAT=json 时每行输出一个 JSON 对象（含所在 span 的字段）。
+/// 设置 LOG_FILE 时写入该文件（不带 ANSI 颜色），否则输出到标准输出。
 pub fn init_logger() -> Result<()> {
     // 设置默认日志级别为 info，如果没有设置 RUST_LOG 环境变量
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
-    
-    if let Ok(path) = std::env::var("LOG_FILE") {
-        let file = File::create(path)?;
-        tracing_subscriber::registry()
-            .with(env_filter)
+
+    let json = std::env::var("LOG_FORMAT")
+        .map(|v| v.trim().eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+    let _ = JSON_FORMAT.set(json);
+
+    let (writer, ansi) = match std::env::var("LOG_FILE") {
+        Ok(path) => (BoxMakeWriter::new(File::create(path)?), false),
+        Err(_) => (BoxMakeWriter::new(std::io::stdout), !json),
+    };
+
+    let registry = tracing_subscriber::registry().with(env_filter);
+    if json {
+        registry
             .with(
                 tracing_subscriber::fmt::layer()
-                    .with_writer(file)
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true)
+                    .with_writer(writer)
                     .with_ansi(false),
             )
             .init();
     } else {
-        tracing_subscriber::fmt()
-            .with_env_filter(env_filter)
+        registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(writer)
+                    .with_ansi(ansi),
+            )
             .init();
     }
 
     Ok(())
 }
+
+/// 本次运行的唯一 ID（首次调用时生成）
+pub fn run_id() -> &'static str {
+    RUN_ID.get_or_init(|| uuid::Uuid::new_v4().to_string())
+}
+
+fn json_enabled() -> bool {
+    JSON_FORMAT.get().copied().unwrap_or(false)
+}
+
+/// 整个运行期的根 span（携带 run_id）。仅 JSON 格式下创建，保持默认格式的输出不变。
+pub fn run_span() -> Span {
+    if json_enabled() {
+        tracing::info_span!("run", run_id = %run_id())
+    } else {
+        Span::none()
+    }
+}
+
+/// 单个套利订单对的 span（携带 pair_id），下单、注册与风险处理的日志都挂在其下。
+/// 仅 JSON 格式下创建；其他格式沿用当前 span（多账户时为账户 span，见 account_span）。
+pub fn pair_span(pair_id: &str) -> Span {
+    if json_enabled() {
+        tracing::info_span!("arbitrage_pair", pair_id = %pair_id)
+    } else {
+        Span::current()
+    }
+}
+
+/// 交易账户的 span（携带账户 ID），该账户的下单、记账、Merge 与收尾日志都挂在其下。
+/// 仅在配置了附加账户（EXTRA_ACCOUNTS）时创建，单账户运行时保持日志输出不变。
+pub fn account_span(account_id: &str, multi_account: bool) -> Span {
+    if multi_account {
+        tracing::info_span!("account", id = %account_id)
+    } else {
+        Span::none()
+    }
+}
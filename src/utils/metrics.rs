@@ -0,0 +1,63 @@
+//! Prometheus 指标：METRICS_PORT 设置时启动 HTTP 导出器，未设置时所有记录调用为空操作。
+
+use anyhow::Result;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::net::SocketAddr;
+use tracing::{error, info};
+
+const OPPORTUNITIES_DETECTED: &str = "poly_bot_arbitrage_opportunities_detected_total";
+const TRADES_EXECUTED: &str = "poly_bot_arbitrage_trades_executed_total";
+const EXPOSURE_USDC: &str = "poly_bot_exposure_usdc";
+const MERGE_SUCCESS: &str = "poly_bot_merge_success_total";
+const MERGE_FAILURE: &str = "poly_bot_merge_failure_total";
+const WS_RECONNECTS: &str = "poly_bot_orderbook_ws_reconnects_total";
+
+/// 安装全局 recorder，并在独立 tokio 任务中运行 HTTP 导出器（GET /metrics）
+pub fn spawn_exporter(port: u16) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let (recorder, exporter) = PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .build()
+        .map_err(|e| anyhow::anyhow!("创建 Prometheus 导出器失败: {}", e))?;
+    metrics::set_global_recorder(recorder)
+        .map_err(|e| anyhow::anyhow!("注册 metrics recorder 失败: {}", e))?;
+
+    tokio::spawn(async move {
+        if let Err(e) = exporter.await {
+            error!(error = ?e, "Prometheus 导出器退出");
+        }
+    });
+    info!(port, "已启动 Prometheus 指标服务");
+    Ok(())
+}
+
+/// 检测到套利机会
+pub fn record_opportunity() {
+    metrics::counter!(OPPORTUNITIES_DETECTED).increment(1);
+}
+
+/// 已提交执行的套利交易
+pub fn record_trade_executed() {
+    metrics::counter!(TRADES_EXECUTED).increment(1);
+}
+
+/// 当前风险敞口（USD）
+pub fn set_exposure(exposure: Decimal) {
+    metrics::gauge!(EXPOSURE_USDC).set(exposure.to_f64().unwrap_or(0.0));
+}
+
+/// Merge 结果
+pub fn record_merge(success: bool) {
+    if success {
+        metrics::counter!(MERGE_SUCCESS).increment(1);
+    } else {
+        metrics::counter!(MERGE_FAILURE).increment(1);
+    }
+}
+
+/// 订单簿 WebSocket 重连
+pub fn record_ws_reconnect() {
+    metrics::counter!(WS_RECONNECTS).increment(1);
+}
@@ -1,3 +1,13 @@
 pub mod arbitrage_logger;
+pub mod display_time;
 pub mod errors;
+pub mod jitter;
+pub mod kill_switch;
 pub mod logger;
+pub mod metrics;
+pub mod notifier;
+pub mod rpc_pool;
+pub mod skip_logger;
+pub mod status;
+pub mod throttle;
+pub mod window_report;
@@ -0,0 +1,180 @@
+//! 事件通知：关键事件（套利成交、Merge、收尾、认证失败等）推送到 Telegram。
+//! 未配置 TELEGRAM_BOT_TOKEN / TELEGRAM_CHAT_ID 时使用空实现，调用方无需判断。
+
+use futures::future::BoxFuture;
+use polymarket_client_sdk::types::{B256, Decimal};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// 合并窗口：窗口内的多条消息合并为一条发送，避免同一秒内多个机会刷屏
+const BATCH_WINDOW: Duration = Duration::from_secs(2);
+/// 单条合并消息最多包含的事件数，超出部分只给出条数
+const MAX_EVENTS_PER_MESSAGE: usize = 20;
+/// 发送队列容量，满时丢弃新事件
+const QUEUE_CAPACITY: usize = 1_000;
+
+/// 需要通知的事件
+#[derive(Debug, Clone)]
+pub enum NotifyEvent {
+    /// 套利已执行
+    ArbitrageExecuted {
+        market: String,
+        profit_pct: Decimal,
+        yes_filled: Decimal,
+        no_filled: Decimal,
+    },
+    /// 套利下单失败
+    ArbitrageFailed { market: String, error: String },
+    MergeSucceeded { condition_id: B256, amount: Option<Decimal> },
+    MergeFailed { condition_id: B256, error: String },
+    WindDownStarted { reason: String },
+    WindDownFinished,
+    AuthFailed { error: String },
+}
+
+impl fmt::Display for NotifyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyEvent::ArbitrageExecuted { market, profit_pct, yes_filled, no_filled } => write!(
+                f,
+                "⚡ 套利成交 | {} | 利润:{:.2}% | YES:{}份 NO:{}份",
+                market, profit_pct, yes_filled, no_filled
+            ),
+            NotifyEvent::ArbitrageFailed { market, error } => {
+                write!(f, "❌ 套利失败 | {} | {}", market, error)
+            }
+            NotifyEvent::MergeSucceeded { condition_id, amount } => match amount {
+                Some(amount) => write!(f, "✅ Merge 完成 | {:#x} | 数量:{}", condition_id, amount),
+                None => write!(f, "✅ Merge 完成 | {:#x}", condition_id),
+            },
+            NotifyEvent::MergeFailed { condition_id, error } => {
+                write!(f, "❌ Merge 失败 | {:#x} | {}", condition_id, error)
+            }
+            NotifyEvent::WindDownStarted { reason } => write!(f, "🛑 开始收尾 | {}", reason),
+            NotifyEvent::WindDownFinished => write!(f, "🛑 收尾完成"),
+            NotifyEvent::AuthFailed { error } => write!(f, "🚫 认证失败，程序退出 | {}", error),
+        }
+    }
+}
+
+/// 通知器。默认实现均为空操作，未配置通知渠道时直接使用 NoopNotifier。
+pub trait Notifier: Send + Sync {
+    /// 投递事件，不阻塞调用方
+    fn notify(&self, _event: NotifyEvent) {}
+
+    /// 立即发送已排队的事件并等待完成（退出前调用，避免丢失最后的通知）
+    fn flush(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async {})
+    }
+}
+
+/// 空通知器
+pub struct NoopNotifier;
+
+impl Notifier for NoopNotifier {}
+
+enum Command {
+    Event(String),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Telegram 通知器：事件经队列交给后台任务，按 BATCH_WINDOW 合并后调用 sendMessage
+pub struct TelegramNotifier {
+    tx: mpsc::Sender<Command>,
+}
+
+impl TelegramNotifier {
+    pub fn spawn(bot_token: String, chat_id: String) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(run_sender(bot_token, chat_id, rx));
+        Self { tx }
+    }
+}
+
+impl Notifier for TelegramNotifier {
+    fn notify(&self, event: NotifyEvent) {
+        if self.tx.try_send(Command::Event(event.to_string())).is_err() {
+            warn!("Telegram 通知队列已满，丢弃事件");
+        }
+    }
+
+    fn flush(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let (done_tx, done_rx) = oneshot::channel();
+            if self.tx.send(Command::Flush(done_tx)).await.is_ok() {
+                let _ = tokio::time::timeout(Duration::from_secs(10), done_rx).await;
+            }
+        })
+    }
+}
+
+/// 后台发送任务：收到第一条事件后等待 BATCH_WINDOW 收集后续事件，再合并发送
+async fn run_sender(bot_token: String, chat_id: String, mut rx: mpsc::Receiver<Command>) {
+    let http = reqwest::Client::new();
+    let url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token);
+    let mut pending: Vec<String> = Vec::new();
+
+    while let Some(cmd) = rx.recv().await {
+        let mut flush_waiters = Vec::new();
+        match cmd {
+            Command::Event(text) => pending.push(text),
+            Command::Flush(done) => flush_waiters.push(done),
+        }
+
+        // 未收到 flush 时等待合并窗口，期间到达的事件一起发送
+        if flush_waiters.is_empty() {
+            let deadline = tokio::time::Instant::now() + BATCH_WINDOW;
+            while let Ok(Some(cmd)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+                match cmd {
+                    Command::Event(text) => pending.push(text),
+                    Command::Flush(done) => {
+                        flush_waiters.push(done);
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            let total = pending.len();
+            let mut text = pending
+                .drain(..)
+                .take(MAX_EVENTS_PER_MESSAGE)
+                .collect::<Vec<_>>()
+                .join("\n");
+            if total > MAX_EVENTS_PER_MESSAGE {
+                text.push_str(&format!("\n…另有 {} 条事件", total - MAX_EVENTS_PER_MESSAGE));
+            }
+            let body = serde_json::json!({
+                "chat_id": chat_id,
+                "text": text,
+                "disable_web_page_preview": true,
+            });
+            match http.post(&url).json(&body).send().await {
+                Ok(resp) if resp.status().is_success() => {}
+                Ok(resp) => warn!(status = %resp.status(), "Telegram 通知发送失败"),
+                Err(e) => warn!(error = %e, "Telegram 通知发送失败"),
+            }
+        }
+
+        for done in flush_waiters {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// 按配置创建通知器：同时设置 TELEGRAM_BOT_TOKEN 与 TELEGRAM_CHAT_ID 时启用 Telegram，否则为空实现
+pub fn from_config(config: &Config) -> Arc<dyn Notifier> {
+    match (&config.telegram_bot_token, &config.telegram_chat_id) {
+        (Some(token), Some(chat_id)) => {
+            info!("📨 Telegram 通知已启用");
+            Arc::new(TelegramNotifier::spawn(token.clone(), chat_id.clone()))
+        }
+        _ => Arc::new(NoopNotifier),
+    }
+}
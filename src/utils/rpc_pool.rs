@@ -0,0 +1,171 @@
+//! Polygon RPC 端点池：Merge 按顺序尝试 POLYGON_RPC_URLS 中的端点，
+//! 连接失败或限速时切换到下一个；每个端点有独立的熔断器，近期失败的端点会被跳过。
+//! EndpointHealth 同时用于 CLOB 下单前的健康闸门：熔断后定期半开探测，恢复后自动放行。
+
+use anyhow::Result;
+use poly_5min_bot::merge::{self, MergeError};
+use polymarket_client_sdk::types::{Address, B256};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::trading::signer::BotSigner;
+
+/// 熔断后两次半开探测之间的最小间隔
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 单个端点的健康状态：熔断器 + 健康指标，熔断期间按 PROBE_INTERVAL 半开探测
+pub struct EndpointHealth {
+    url: String,
+    checker: Arc<rpc_check::RpcChecker>,
+    circuit: rpc_check::CircuitBreaker,
+    metrics: rpc_check::Metrics,
+    last_probe: Mutex<Option<Instant>>,
+}
+
+impl EndpointHealth {
+    pub fn new(url: impl Into<String>, checker: Arc<rpc_check::RpcChecker>) -> Self {
+        Self {
+            url: url.into(),
+            checker,
+            circuit: rpc_check::CircuitBreaker::new(),
+            metrics: rpc_check::Metrics::new(),
+            last_probe: Mutex::new(None),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// 熔断器是否打开（只读，不触发探测）
+    pub fn is_open(&self) -> bool {
+        self.circuit.is_open()
+    }
+
+    /// 记录一次真实调用的结果，驱动熔断器打开 / 恢复
+    pub fn record(&self, ok: bool) {
+        if ok {
+            self.circuit.record_success();
+        } else {
+            self.circuit.record_failure();
+        }
+        self.metrics.record_check(ok);
+    }
+
+    /// 主动健康检查：结果交给熔断器判断是否放行并记录。检查为同步调用，放到阻塞线程池执行
+    pub async fn probe(&self) -> bool {
+        *self.last_probe.lock().unwrap() = Some(Instant::now());
+        let checker = self.checker.clone();
+        let url = self.url.clone();
+        let ok = match tokio::task::spawn_blocking(move || checker.perform_health_check_sync(&url)).await {
+            Ok(status) => self.circuit.status_allows_request(status),
+            Err(_) => false,
+        };
+        self.record(ok);
+        if ok {
+            debug!(url = %self.url, "RPC 端点健康检查通过");
+        } else {
+            warn!(url = %self.url, "RPC 端点健康检查未通过");
+        }
+        ok
+    }
+
+    /// 距上次探测是否已超过 PROBE_INTERVAL
+    fn probe_due(&self) -> bool {
+        self.last_probe
+            .lock()
+            .unwrap()
+            .is_none_or(|t| t.elapsed() >= PROBE_INTERVAL)
+    }
+
+    /// 是否允许发出请求：熔断器关闭时放行；打开时若到期则就地半开探测，通过即放行
+    pub async fn allows_request(&self) -> bool {
+        if !self.circuit.is_open() {
+            return true;
+        }
+        if !self.probe_due() {
+            return false;
+        }
+        let ok = self.probe().await;
+        if ok {
+            info!(url = %self.url, "✅ RPC 端点已恢复，熔断解除");
+        }
+        ok
+    }
+
+    /// 非阻塞版本（订单簿热路径使用）：熔断器打开时本次直接拒绝，到期则在后台探测，
+    /// 探测通过后熔断解除，之后的请求自动放行
+    pub fn try_acquire(self: &Arc<Self>) -> bool {
+        if !self.circuit.is_open() {
+            return true;
+        }
+        if self.probe_due() {
+            // 先占住探测时间，避免并发触发多个探测
+            *self.last_probe.lock().unwrap() = Some(Instant::now());
+            let this = self.clone();
+            tokio::spawn(async move {
+                if this.probe().await {
+                    info!(url = %this.url, "✅ RPC 端点已恢复，熔断解除");
+                }
+            });
+        }
+        false
+    }
+}
+
+pub struct RpcPool {
+    endpoints: Vec<EndpointHealth>,
+}
+
+impl RpcPool {
+    /// 按给定顺序创建端点池；未配置时使用 merge 的默认 RPC
+    pub fn new(urls: &[String], checker: Arc<rpc_check::RpcChecker>) -> Self {
+        let urls: Vec<String> = if urls.is_empty() {
+            vec![merge::RPC_URL_DEFAULT.to_string()]
+        } else {
+            urls.to_vec()
+        };
+        let endpoints = urls
+            .into_iter()
+            .filter(|url| {
+                let valid = checker.validate_endpoint(url);
+                if !valid {
+                    warn!(url = %url, "RPC 端点格式无效，已忽略");
+                }
+                valid
+            })
+            .map(|url| EndpointHealth::new(url, checker.clone()))
+            .collect();
+        Self { endpoints }
+    }
+
+    /// 依次在各端点上执行 merge_max：熔断中的端点先半开探测，未恢复则跳过；
+    /// 端点错误时记录失败并切换到下一个，其他错误（如无可用份额、合约回滚）直接返回。
+    pub async fn merge_max(&self, condition_id: B256, proxy: Address, signer: &BotSigner) -> Result<String, MergeError> {
+        let mut last_err = None;
+        for endpoint in &self.endpoints {
+            if !endpoint.allows_request().await {
+                warn!(url = %endpoint.url(), "RPC 端点熔断中，跳过");
+                continue;
+            }
+            match merge::merge_max(condition_id, proxy, signer, Some(endpoint.url())).await {
+                Ok(tx) => {
+                    endpoint.record(true);
+                    return Ok(tx);
+                }
+                Err(e) if e.is_endpoint_error() => {
+                    endpoint.record(false);
+                    warn!(url = %endpoint.url(), error = %e, "RPC 端点不可用，切换到下一个端点");
+                    last_err = Some(e);
+                }
+                Err(e) => {
+                    // 业务错误说明端点本身可达
+                    endpoint.record(true);
+                    return Err(e);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| MergeError::Rpc(anyhow::anyhow!("所有 RPC 端点熔断中，跳过 Merge"))))
+    }
+}
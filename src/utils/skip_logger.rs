@@ -0,0 +1,112 @@
+//! 跳过记录：设置 SKIPPED_LOG_PATH 时，每个被执行前检查拦下的买入侧套利机会追加一行 JSON（NDJSON），
+//! 与成交记录（TRADE_LOG_PATH）分开，便于统计各项风控检查拦下的机会与潜在利润。
+//! 热路径只做序列化并 try_send，写盘由独立任务完成；队列满时丢弃记录，不阻塞订单簿处理。
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::window_report::SkipReason;
+use crate::monitor::ArbitrageOpportunity;
+use polymarket_client_sdk::types::Decimal;
+
+/// 写入队列容量：写盘跟不上时丢弃新记录
+const SKIP_CHANNEL_CAPACITY: usize = 10_000;
+
+#[derive(Serialize)]
+struct SkippedRecord<'a> {
+    timestamp: String,
+    market: &'a str,
+    symbol: &'a str,
+    market_id: String,
+    reason: SkipReason,
+    yes_price: String,
+    no_price: String,
+    size: String,
+    profit_percentage: String,
+    exposure: String,
+}
+
+/// 跳过记录器：可 clone，各窗口与各市场任务共用同一文件
+#[derive(Clone)]
+pub struct SkipLogger {
+    tx: mpsc::Sender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl SkipLogger {
+    /// 以追加方式打开记录文件并启动写盘任务
+    pub async fn spawn(path: &Path) -> Result<Self> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("打开跳过记录文件失败: {}", path.display()))?;
+        let (tx, mut rx) = mpsc::channel::<String>(SKIP_CHANNEL_CAPACITY);
+        let path_display = path.display().to_string();
+
+        tokio::spawn(async move {
+            let mut writer = tokio::io::BufWriter::new(file);
+            while let Some(line) = rx.recv().await {
+                let result = async {
+                    writer.write_all(line.as_bytes()).await?;
+                    writer.write_all(b"\n").await?;
+                    // 队列已空时刷盘，突发时批量写入
+                    if rx.is_empty() {
+                        writer.flush().await?;
+                    }
+                    std::io::Result::Ok(())
+                }
+                .await;
+                if let Err(e) = result {
+                    warn!(error = %e, path = %path_display, "跳过记录写入失败，停止记录");
+                    return;
+                }
+            }
+            let _ = writer.flush().await;
+        });
+
+        info!(path = %path.display(), "📝 跳过记录已启用");
+        Ok(Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// 记录一个被跳过的机会：价格为双边成交均价，size 为机会当时的可下单数量，exposure 为当时的总敞口（USD）
+    pub fn record(&self, opp: &ArbitrageOpportunity, market: &str, symbol: &str, reason: SkipReason, exposure: Decimal) {
+        let record = SkippedRecord {
+            timestamp: Utc::now().to_rfc3339(),
+            market,
+            symbol,
+            market_id: format!("{:?}", opp.market_id),
+            reason,
+            yes_price: opp.yes_ask_price.to_string(),
+            no_price: opp.no_ask_price.to_string(),
+            size: opp.yes_size.min(opp.no_size).to_string(),
+            profit_percentage: opp.profit_percentage.to_string(),
+            exposure: exposure.to_string(),
+        };
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(error = %e, "跳过记录序列化失败");
+                return;
+            }
+        };
+        if self.tx.try_send(line).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            // 避免刷屏：每丢弃 1000 条提示一次
+            if dropped % 1000 == 1 {
+                warn!(dropped, "跳过记录队列已满或写盘任务已停止，丢弃记录");
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! 状态服务：STATUS_PORT 设置时启动轻量 HTTP 服务，供编排系统探活与人工查看。
+//! - GET /healthz：订单簿流存活且最近有订单簿更新时返回 200，否则 503
+//! - GET /status：当前窗口、订阅市场数、风险敞口与上限、各 token 持仓与成本、未完成订单对数、连续失败次数、当日已实现盈亏与每日亏损上限状态、最近 Merge 时间等（JSON）
+//! - GET /markets：各订阅市场的 YES/NO 买一卖一、卖一总价与是否存在套利（JSON，见 OrderBookMonitor::snapshot_source）
+//! - POST /halt、POST /resume：暂停 / 恢复新的套利执行（见 kill_switch）
+//!
+//! 主循环与 Merge 任务通过下方的记录函数写入原子变量，服务端除暂停开关外只读；
+//! 行情快照的数据来源在切换窗口时替换，仅在 /markets 请求时短暂加锁读取。
+
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+use crate::monitor::MarketSnapshotSource;
+use crate::risk::RiskManager;
+
+/// 超过该时长没有订单簿更新时 /healthz 视为不健康
+const MAX_BOOK_AGE: Duration = Duration::from_secs(60);
+/// 读取请求的超时
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct BotStatus {
+    window_timestamp: AtomicI64,
+    market_count: AtomicUsize,
+    stream_alive: AtomicBool,
+    last_book_update_ms: AtomicI64, // Unix 毫秒，0 表示尚未收到
+    last_merge_ms: AtomicI64,       // Unix 毫秒，0 表示本次运行尚未 Merge
+    stream_stalls: AtomicUsize,     // 看门狗因订单簿流静默而重建流的次数
+}
+
+static STATUS: BotStatus = BotStatus {
+    window_timestamp: AtomicI64::new(0),
+    market_count: AtomicUsize::new(0),
+    stream_alive: AtomicBool::new(false),
+    last_book_update_ms: AtomicI64::new(0),
+    last_merge_ms: AtomicI64::new(0),
+    stream_stalls: AtomicUsize::new(0),
+};
+
+/// 当前窗口订阅市场的行情快照来源，进入新窗口时替换
+static MARKETS: Mutex<Option<MarketSnapshotSource>> = Mutex::new(None);
+
+/// 进入新窗口：替换 /markets 的行情快照来源
+pub fn set_markets(source: MarketSnapshotSource) {
+    *MARKETS.lock().unwrap() = Some(source);
+}
+
+fn markets_json() -> serde_json::Value {
+    let source = MARKETS.lock().unwrap().clone();
+    serde_json::json!(source.map(|s| s.snapshots()).unwrap_or_default())
+}
+
+/// 进入新窗口：记录窗口时间戳与订阅市场数
+pub fn set_window(window_timestamp: i64, market_count: usize) {
+    STATUS.window_timestamp.store(window_timestamp, Ordering::Relaxed);
+    STATUS.market_count.store(market_count, Ordering::Relaxed);
+}
+
+/// 订单簿流建立 / 结束
+pub fn set_stream_alive(alive: bool) {
+    STATUS.stream_alive.store(alive, Ordering::Relaxed);
+}
+
+/// 收到一次订单簿更新
+pub fn record_book_update() {
+    STATUS
+        .last_book_update_ms
+        .store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+}
+
+/// 最近一次订单簿更新时间（Unix 毫秒），尚未收到时为 None
+pub fn last_book_update_ms() -> Option<i64> {
+    match STATUS.last_book_update_ms.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(ms),
+    }
+}
+
+/// 看门狗检测到订单簿流静默并重建
+pub fn record_stream_stall() {
+    STATUS.stream_stalls.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Merge 成功
+pub fn record_merge() {
+    STATUS
+        .last_merge_ms
+        .store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+}
+
+fn is_healthy() -> bool {
+    let last = STATUS.last_book_update_ms.load(Ordering::Relaxed);
+    STATUS.stream_alive.load(Ordering::Relaxed)
+        && last > 0
+        && chrono::Utc::now().timestamp_millis() - last <= MAX_BOOK_AGE.as_millis() as i64
+}
+
+fn status_json(risk_manager: &RiskManager) -> serde_json::Value {
+    // 0 表示尚无记录，输出 null
+    let rfc3339 = |ms: &AtomicI64| match ms.load(Ordering::Relaxed) {
+        0 => None,
+        ms => chrono::DateTime::from_timestamp_millis(ms).map(|d| d.to_rfc3339()),
+    };
+    let positions = risk_manager.position_tracker().snapshot();
+    serde_json::json!({
+        "run_id": super::logger::run_id(),
+        "healthy": is_healthy(),
+        "window_timestamp": STATUS.window_timestamp.load(Ordering::Relaxed),
+        "market_count": STATUS.market_count.load(Ordering::Relaxed),
+        "stream_alive": STATUS.stream_alive.load(Ordering::Relaxed),
+        "last_book_update": rfc3339(&STATUS.last_book_update_ms),
+        "stream_stalls": STATUS.stream_stalls.load(Ordering::Relaxed),
+        "exposure_usdc": positions.exposure.to_string(),
+        "max_exposure_usdc": positions.max_exposure.to_string(),
+        "positions": positions
+            .positions
+            .iter()
+            .map(|(token_id, size)| serde_json::json!({
+                "token_id": token_id.to_string(),
+                "size": size.to_string(),
+                "cost_usdc": positions.exposure_costs.get(token_id).map(|c| c.to_string()),
+            }))
+            .collect::<Vec<_>>(),
+        "pending_pairs": risk_manager.pending_pair_count(),
+        "trade_failure_streak": risk_manager.failure_streak(),
+        "trading_paused_secs": risk_manager.trading_paused().map(|d| d.as_secs()),
+        "daily_realized_pnl": risk_manager.position_tracker().pnl().daily_realized().to_string(),
+        "daily_loss_halted": risk_manager.daily_loss_halted(),
+        "last_merge": rfc3339(&STATUS.last_merge_ms),
+        "trading_halted_by_api": super::kill_switch::halted_by_api(),
+    })
+}
+
+/// 在独立 tokio 任务中运行状态服务
+pub async fn spawn_server(port: u16, risk_manager: Arc<RiskManager>) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("状态服务监听 {} 失败: {}", addr, e))?;
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((socket, _)) => {
+                    let risk_manager = risk_manager.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(socket, &risk_manager).await {
+                            debug!(error = %e, "状态服务请求处理失败");
+                        }
+                    });
+                }
+                Err(e) => warn!(error = %e, "状态服务接受连接失败"),
+            }
+        }
+    });
+    info!(port, "已启动状态服务（/healthz、/status、/markets、/halt、/resume）");
+    Ok(())
+}
+
+async fn handle_connection(mut socket: TcpStream, risk_manager: &RiskManager) -> Result<()> {
+    let mut buf = [0u8; 1024];
+    let n = tokio::time::timeout(READ_TIMEOUT, socket.read(&mut buf)).await??;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut parts = request.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    // 忽略查询参数
+    let path = path.split('?').next().unwrap_or("");
+
+    let (status, body) = match (method, path) {
+        ("GET", "/healthz") if is_healthy() => ("200 OK", "ok".to_string()),
+        ("GET", "/healthz") => ("503 Service Unavailable", "unhealthy".to_string()),
+        ("GET", "/status") => ("200 OK", status_json(risk_manager).to_string()),
+        ("GET", "/markets") => ("200 OK", markets_json().to_string()),
+        ("POST", "/halt") => {
+            super::kill_switch::set_halted_by_api(true);
+            ("200 OK", "halted".to_string())
+        }
+        ("POST", "/resume") => {
+            super::kill_switch::set_halted_by_api(false);
+            ("200 OK", "resumed".to_string())
+        }
+        ("GET", _) | ("POST", _) => ("404 Not Found", "not found".to_string()),
+        _ => ("405 Method Not Allowed", "method not allowed".to_string()),
+    };
+    let content_type = if matches!(path, "/status" | "/markets") && status.starts_with("200") {
+        "application/json"
+    } else {
+        "text/plain; charset=utf-8"
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await?;
+    Ok(())
+}
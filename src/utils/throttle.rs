@@ -0,0 +1,79 @@
+//! 交易节流：每个市场独立的最小交易间隔（MIN_TRADE_INTERVAL_SECS），外加较宽松的全局速率上限
+//! （GLOBAL_TRADE_RATE_PER_SEC）保护 API。某个市场的连续机会不会阻塞其他市场的交易。
+//! 默认只启用全局上限（每 3 秒 1 笔），与原先的全局 3 秒间隔一致。
+
+use dashmap::DashMap;
+use polymarket_client_sdk::types::B256;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub struct TradeThrottle {
+    per_market: Duration, // 同一市场两次交易的最小间隔，ZERO 表示不限制
+    global: Duration,     // 全局两次交易的最小间隔（由每秒速率换算），ZERO 表示不限制
+    last_by_market: DashMap<B256, (Instant, Duration)>,
+    last_global: Mutex<Option<(Instant, Duration)>>,
+}
+
+impl TradeThrottle {
+    /// min_interval_secs 为 0 时不做按市场限制；rate_per_sec 为 0 时不做全局限制
+    pub fn new(min_interval_secs: f64, rate_per_sec: f64) -> Self {
+        let global = if rate_per_sec > 0.0 {
+            Duration::from_secs_f64(1.0 / rate_per_sec)
+        } else {
+            Duration::ZERO
+        };
+        Self {
+            per_market: Duration::from_secs_f64(min_interval_secs.max(0.0)),
+            global,
+            last_by_market: DashMap::new(),
+            last_global: Mutex::new(None),
+        }
+    }
+
+    pub fn per_market_interval(&self) -> Duration {
+        self.per_market
+    }
+
+    pub fn global_interval(&self) -> Duration {
+        self.global
+    }
+
+    /// 尝试占用一次交易名额：市场间隔与全局间隔都满足时记录本次交易并返回 Ok；
+    /// 否则返回 Err(还需等待的时长)，不记录。extra 为叠加在两个间隔上的随机延迟（下单抖动）
+    pub fn try_acquire(&self, market_id: B256, extra: Duration) -> Result<(), Duration> {
+        let now = Instant::now();
+        let remaining = |last: Option<(Instant, Duration)>| {
+            last.map(|(t, interval)| interval.saturating_sub(now.saturating_duration_since(t)))
+                .unwrap_or(Duration::ZERO)
+        };
+        // 先持有全局锁，保证「检查 + 记录」对两个间隔整体原子
+        let mut global = self.last_global.lock().unwrap();
+        let wait = remaining(*global).max(remaining(self.last_by_market.get(&market_id).map(|e| *e)));
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        if !self.global.is_zero() {
+            *global = Some((now, self.global + extra));
+        }
+        if !self.per_market.is_zero() {
+            self.last_by_market.insert(market_id, (now, self.per_market + extra));
+        }
+        Ok(())
+    }
+
+    /// 距该市场可再次交易还需等待的时长（市场间隔与全局间隔取较大者），只读不记录；ZERO 表示现在即可交易
+    pub fn remaining(&self, market_id: B256) -> Duration {
+        let now = Instant::now();
+        let remaining = |last: Option<(Instant, Duration)>| {
+            last.map(|(t, interval)| interval.saturating_sub(now.saturating_duration_since(t)))
+                .unwrap_or(Duration::ZERO)
+        };
+        let global = *self.last_global.lock().unwrap();
+        remaining(global).max(remaining(self.last_by_market.get(&market_id).map(|e| *e)))
+    }
+
+    /// 进入新窗口时清空按市场的记录（市场随窗口更换）
+    pub fn clear_markets(&self) {
+        self.last_by_market.clear();
+    }
+}
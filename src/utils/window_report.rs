@@ -0,0 +1,128 @@
+//! 窗口报告：按币种统计本窗口检测到、执行与跳过（按原因）的套利机会，窗口结束时输出汇总。
+//! 不跨任务共享，无需加锁：串行模式下由主循环更新；启用 PER_MARKET_TASKS 时各市场任务各持一份，窗口结束时经 merge 汇总。
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// 套利机会被跳过的原因（跳过记录中以 snake_case 输出，如 exposure_limit）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    MonitorOnly,
+    PriceThreshold,
+    ShuttingDown,
+    NearEnd,
+    BelowMinSize,
+    ExposureLimit,
+    MarketExposureLimit,
+    Imbalance,
+    Duplicate,
+    FailurePause,
+    CircuitOpen,
+    Saturated,
+    TradeInterval,
+    TooYoung,
+    Halted,
+    WarmUp,
+    DailyLossLimit,
+}
+
+impl SkipReason {
+    fn label(self) -> &'static str {
+        match self {
+            SkipReason::MonitorOnly => "仅监控",
+            SkipReason::PriceThreshold => "价格阈值",
+            SkipReason::ShuttingDown => "退出中",
+            SkipReason::NearEnd => "临近结束",
+            SkipReason::BelowMinSize => "数量过小",
+            SkipReason::ExposureLimit => "敞口上限",
+            SkipReason::MarketExposureLimit => "单市场敞口",
+            SkipReason::Imbalance => "持仓不平衡",
+            SkipReason::Duplicate => "重复信号",
+            SkipReason::FailurePause => "失败暂停",
+            SkipReason::CircuitOpen => "CLOB熔断",
+            SkipReason::Saturated => "并发上限",
+            SkipReason::TradeInterval => "交易间隔",
+            SkipReason::TooYoung => "机会过新",
+            SkipReason::Halted => "交易暂停",
+            SkipReason::WarmUp => "窗口预热",
+            SkipReason::DailyLossLimit => "每日亏损",
+        }
+    }
+}
+
+#[derive(Default)]
+struct SymbolCounts {
+    detected: u64,
+    executed: u64,
+    skipped: BTreeMap<SkipReason, u64>,
+}
+
+#[derive(Default)]
+pub struct WindowReport {
+    by_symbol: BTreeMap<String, SymbolCounts>,
+}
+
+impl WindowReport {
+    fn counts(&mut self, symbol: &str) -> &mut SymbolCounts {
+        let symbol = if symbol.is_empty() { "未知" } else { symbol };
+        self.by_symbol.entry(symbol.to_string()).or_default()
+    }
+
+    /// 检测到一次套利机会
+    pub fn detected(&mut self, symbol: &str) {
+        self.counts(symbol).detected += 1;
+    }
+
+    /// 执行了一次套利
+    pub fn executed(&mut self, symbol: &str) {
+        self.counts(symbol).executed += 1;
+    }
+
+    /// 一次套利机会因 reason 被跳过
+    pub fn skipped(&mut self, symbol: &str, reason: SkipReason) {
+        *self.counts(symbol).skipped.entry(reason).or_default() += 1;
+    }
+
+    /// 合并另一份报告（各市场任务的统计）
+    pub fn merge(&mut self, other: WindowReport) {
+        for (symbol, counts) in other.by_symbol {
+            let mine = self.by_symbol.entry(symbol).or_default();
+            mine.detected += counts.detected;
+            mine.executed += counts.executed;
+            for (reason, n) in counts.skipped {
+                *mine.skipped.entry(reason).or_default() += n;
+            }
+        }
+    }
+
+    /// 输出本窗口汇总：合计一行，各币种明细各一行；无机会时只输出合计
+    pub fn log_summary(&self, window_timestamp: i64) {
+        let detected: u64 = self.by_symbol.values().map(|c| c.detected).sum();
+        let executed: u64 = self.by_symbol.values().map(|c| c.executed).sum();
+        info!(
+            window_timestamp,
+            detected,
+            executed,
+            "📋 窗口报告 | 检测到 {} 次机会，执行 {} 次，跳过 {} 次",
+            detected,
+            executed,
+            detected.saturating_sub(executed)
+        );
+        for (symbol, counts) in &self.by_symbol {
+            let skipped: Vec<String> = counts
+                .skipped
+                .iter()
+                .map(|(reason, n)| format!("{}:{}", reason.label(), n))
+                .collect();
+            info!(
+                "  📋 {} | 检测:{} | 执行:{} | 跳过:{}",
+                symbol,
+                counts.detected,
+                counts.executed,
+                if skipped.is_empty() { "无".to_string() } else { skipped.join(" ") }
+            );
+        }
+    }
+}